@@ -15,8 +15,7 @@ use ferrum_snapshot::{
     generate_structure_definition_snapshot, SnapshotExpander,
 };
 use ferrum_fhirpath::value::{Collection, ValueData};
-use ferrum_fhirpath::vm::Plan;
-use ferrum_fhirpath::{Context, Engine, Value as FhirValue};
+use ferrum_fhirpath::{ferrum_fhirpath_value_to_string, Context, Engine, Value as FhirValue};
 
 #[derive(Parser)]
 #[command(
@@ -36,8 +35,13 @@ enum Commands {
     FP {
         /// FHIRPath expression to evaluate.
         expr: String,
-        /// Path to a resource JSON file (or "-" for stdin). Omit to evaluate against an empty context.
+        /// Path to a JSON file (or "-" for stdin) bound as the root; any JSON value works, not only
+        /// resources. Omit to evaluate against an empty context.
         resource: Option<PathBuf>,
+        /// Bind `%NAME` to a JSON value (e.g. `x=41`, `s='"text"'`). Values that are not valid JSON
+        /// bind as strings. Repeatable.
+        #[arg(long = "var", value_name = "NAME=JSON", value_parser = parse_var)]
+        vars: Vec<(String, Value)>,
         /// FHIR version (R4, R4B, R5).
         #[arg(short = 'v', long, default_value = "R5")]
         fhir_version: String,
@@ -102,6 +106,12 @@ enum Commands {
         /// Optional module path prefix for generated modules.
         #[arg(long)]
         module_prefix: Option<String>,
+        /// Generate `_field` companions preserving primitive ids and extensions.
+        #[arg(long, default_value_t = false, action = ArgAction::Set)]
+        primitive_extensions: bool,
+        /// Generate repeating elements as plain `Vec`s instead of `Option<Vec<_>>`.
+        #[arg(long, default_value_t = false, action = ArgAction::Set)]
+        plain_vecs: bool,
     },
 
     /// Generate FHIR type metadata for the format crate (array cardinality info).
@@ -192,6 +202,7 @@ async fn main() -> Result<()> {
         Commands::FP {
             expr,
             resource,
+            vars,
             fhir_version,
             strict,
             base_type,
@@ -201,6 +212,7 @@ async fn main() -> Result<()> {
             run_fhirpath(
                 &expr,
                 resource.as_deref(),
+                &vars,
                 &fhir_version,
                 strict,
                 base_type.as_deref(),
@@ -274,25 +286,57 @@ async fn main() -> Result<()> {
             docs,
             serde,
             module_prefix,
+            primitive_extensions,
+            plain_vecs,
         } => {
-            run_codegen(
-                &output,
-                &fhir_version,
-                &packages,
-                docs,
-                serde,
+            let config = GeneratorConfig {
+                generate_docs: docs,
+                generate_serde: serde,
                 module_prefix,
-            )
-            .await?;
+                primitive_extensions,
+                plain_vecs,
+            };
+            run_codegen(&output, &fhir_version, &packages, config).await?;
         }
     }
 
     Ok(())
 }
 
+/// Parse a `--var NAME=JSON` flag; non-JSON values are taken as strings.
+fn parse_var(raw: &str) -> Result<(String, Value)> {
+    let (name, value) = raw
+        .split_once('=')
+        .with_context(|| format!("Expected NAME=JSON, got '{}'", raw))?;
+    let name = name.trim().trim_start_matches('%');
+    if name.is_empty() {
+        anyhow::bail!("Variable name is empty in '{}'", raw);
+    }
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((name.to_string(), value))
+}
+
+/// Evaluation context rooted at `root`, with `vars` bound as external constants.
+fn fhirpath_context(root: Option<&Value>, vars: &[(String, Value)], strict: bool) -> Context {
+    let root_value = root
+        .map(|v| FhirValue::from_json(v.clone()))
+        .unwrap_or_else(FhirValue::empty);
+
+    let mut ctx = Context::new(root_value);
+    for (name, value) in vars {
+        ctx = ctx.with_variable(name.as_str(), FhirValue::from_json(value.clone()));
+    }
+    if strict {
+        ctx = ctx.with_strict_semantics();
+    }
+    ctx
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn run_fhirpath(
     expr: &str,
     resource_path: Option<&Path>,
+    vars: &[(String, Value)],
     fhir_version: &str,
     strict: bool,
     base_type_override: Option<&str>,
@@ -319,15 +363,7 @@ async fn run_fhirpath(
         }
     };
 
-    let resource_value = json
-        .as_ref()
-        .map(|v| FhirValue::from_json(v.clone()))
-        .unwrap_or_else(FhirValue::empty);
-
-    let mut ctx = Context::new(resource_value);
-    if strict {
-        ctx = ctx.with_strict_semantics();
-    }
+    let ctx = fhirpath_context(json.as_ref(), vars, strict);
 
     // Use explicit base type if provided, else infer from resourceType.
     let inferred_base_type = json
@@ -380,8 +416,7 @@ async fn run_fhirpath(
 
     match output.to_ascii_lowercase().as_str() {
         "json" => {
-            let stringify_plan = engine.compile("$this.toString()", None).ok();
-            let json_out = collection_to_json(&engine, stringify_plan.as_ref(), &result);
+            let json_out = collection_to_json(&result);
             if pretty {
                 println!("{}", serde_json::to_string_pretty(&json_out)?);
             } else {
@@ -389,16 +424,12 @@ async fn run_fhirpath(
             }
         }
         "fhirpath" | "lines" => {
-            let stringify_plan = engine.compile("$this.toString()", None).ok();
+            // Primitives print as toString() would; complex values as compact JSON
             for item in result.iter() {
-                if let Some(plan) = stringify_plan.as_ref() {
-                    let (engine, plan) = (&engine, plan);
-                    if let Some(s) = stringify_value(engine, plan, item) {
-                        println!("{}", s);
-                        continue;
-                    }
+                match ferrum_fhirpath_value_to_string(item) {
+                    Some(s) => println!("{}", s),
+                    None => println!("{}", serde_json::to_string(&value_to_json(item))?),
                 }
-                println!("{:?}", item);
             }
         }
         other => anyhow::bail!(
@@ -497,6 +528,12 @@ async fn run_gen_format_metadata(output: &Path, fhir_version: &str) -> Result<()
             Err(_) => continue,
         };
 
+        // Profiles constrain base definitions (e.g. narrowing cardinality) and must not
+        // override the base element shapes the conversion relies on.
+        if sd.is_profile() {
+            continue;
+        }
+
         let snapshot = match &sd.snapshot {
             Some(s) => s,
             None => continue,
@@ -541,9 +578,14 @@ async fn run_gen_format_metadata(output: &Path, fhir_version: &str) -> Result<()
                 } else {
                     "string".to_string()
                 }
-            } else if element.content_reference.is_some() {
-                // Content references point to another element's structure.
-                "BackboneElement".to_string()
+            } else if let Some(reference) = &element.content_reference {
+                // Content references reuse another element's structure, e.g.
+                // "#Questionnaire.item"; use that element's synthetic type name so its
+                // children keep their precise types.
+                reference
+                    .rsplit_once('#')
+                    .map_or(reference.as_str(), |(_, path)| path)
+                    .to_string()
             } else {
                 "string".to_string()
             };
@@ -577,18 +619,10 @@ async fn run_codegen(
     output: &Path,
     fhir_version: &str,
     packages: &[String],
-    docs: bool,
-    serde: bool,
-    module_prefix: Option<String>,
+    config: GeneratorConfig,
 ) -> Result<()> {
     let context = create_context(fhir_version, packages).await?;
 
-    let config = GeneratorConfig {
-        generate_docs: docs,
-        generate_serde: serde,
-        module_prefix,
-    };
-
     let generated = ferrum_codegen::generate_rust_from_context(&context, output, config)
         .with_context(|| "Failed to generate Rust models from context".to_string())?;
 
@@ -696,28 +730,14 @@ fn write_json_output(value: &Value, output: Option<&Path>, pretty: bool) -> Resu
     Ok(())
 }
 
-fn stringify_value(engine: &Engine, plan: &Arc<Plan>, value: &FhirValue) -> Option<String> {
-    let ctx = Context::new(FhirValue::empty()).push_this(value.clone());
-    engine
-        .evaluate(plan, &ctx)
-        .ok()
-        .and_then(|c| c.as_string().ok())
-        .map(|s| s.to_string())
-}
-
-fn value_to_json(engine: &Engine, stringify_plan: Option<&Arc<Plan>>, value: &FhirValue) -> Value {
+fn value_to_json(value: &FhirValue) -> Value {
     match value.data() {
         ValueData::Boolean(b) => Value::Bool(*b),
         ValueData::Integer(i) => Value::Number((*i).into()),
         ValueData::Decimal(d) => Value::String(d.to_string()),
         ValueData::String(s) => Value::String(s.to_string()),
         ValueData::Date { .. } | ValueData::DateTime { .. } | ValueData::Time { .. } => {
-            if let Some(plan) = stringify_plan {
-                if let Some(s) = stringify_value(engine, plan, value) {
-                    return Value::String(s);
-                }
-            }
-            Value::String(format!("{:?}", value))
+            Value::String(ferrum_fhirpath_value_to_string(value).unwrap_or_default())
         }
         ValueData::Quantity { value, unit } => Value::Object({
             let mut map = Map::new();
@@ -728,31 +748,21 @@ fn value_to_json(engine: &Engine, stringify_plan: Option<&Arc<Plan>>, value: &Fh
         ValueData::Object(map) => {
             let mut obj = Map::new();
             for (k, coll) in map.iter() {
-                obj.insert(
-                    k.to_string(),
-                    collection_to_json(engine, stringify_plan, coll),
-                );
+                obj.insert(k.to_string(), collection_to_json(coll));
             }
             Value::Object(obj)
         }
         ValueData::LazyJson { .. } => {
             // Materialize lazy JSON first and recursively convert
             let materialized = value.materialize();
-            value_to_json(engine, stringify_plan, &materialized)
+            value_to_json(&materialized)
         }
         ValueData::Empty => Value::Null,
     }
 }
 
-fn collection_to_json(
-    engine: &Engine,
-    stringify_plan: Option<&Arc<Plan>>,
-    coll: &Collection,
-) -> Value {
-    let items: Vec<Value> = coll
-        .iter()
-        .map(|v| value_to_json(engine, stringify_plan, v))
-        .collect();
+fn collection_to_json(coll: &Collection) -> Value {
+    let items: Vec<Value> = coll.iter().map(value_to_json).collect();
     Value::Array(items)
 }
 
@@ -824,3 +834,31 @@ async fn run_visualize(expr: &str, format: &str, stage: &str, output: Option<&Pa
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn var_flags_bind_external_constants() {
+        let vars = vec![parse_var("x=41").unwrap(), parse_var("%s=text").unwrap()];
+        assert_eq!(vars[1], ("s".to_string(), Value::String("text".into())));
+        assert!(parse_var("x").is_err());
+        assert!(parse_var("=1").is_err());
+
+        let engine = Engine::new(Arc::new(DefaultFhirContext::from_packages(vec![])), None);
+        let ctx = fhirpath_context(None, &vars, false);
+        let result = engine.evaluate_expr("%x + 1", &ctx, None).unwrap();
+        assert_eq!(result.as_integer().unwrap(), 42);
+
+        let root = serde_json::json!({ "given": ["Ada"] });
+        let ctx = fhirpath_context(Some(&root), &vars, false);
+        let result = engine
+            .evaluate_expr("given.first() + %s", &ctx, None)
+            .unwrap();
+        assert_eq!(
+            ferrum_fhirpath_value_to_string(result.iter().next().unwrap()).unwrap(),
+            "Adatext"
+        );
+    }
+}
@@ -44,9 +44,10 @@ impl ContentFormat {
     /// Note: Generic MIME types (application/json, application/xml, text/xml) are also accepted
     /// per the spec for client convenience.
     pub fn parse(s: &str) -> Option<Self> {
-        // Strip charset and other parameters from mime type
+        // Strip charset and other parameters from mime type. An unencoded `+` in a
+        // `_format` query value arrives as a space (`application/fhir xml`).
         let mime_type = s.split(';').next().unwrap_or(s).trim();
-        let s_lower = mime_type.to_ascii_lowercase();
+        let s_lower = mime_type.to_ascii_lowercase().replace(' ', "+");
 
         match s_lower.as_str() {
             "json" | "application/json" | "application/fhir+json" => Some(Self::Json),
@@ -207,7 +208,7 @@ impl ContentNegotiation {
         let explicit_fhir_format_requested = query_params
             .get("_format")
             .map(|s| {
-                let s_lower = s.to_lowercase();
+                let s_lower = s.to_lowercase().replace(' ', "+");
                 s_lower.contains("fhir+json") || s_lower.contains("fhir+xml")
             })
             .unwrap_or(false)
@@ -285,21 +286,35 @@ impl ContentNegotiation {
     }
 
     /// Extract format from Accept header
+    ///
+    /// Picks the supported format with the highest q-value; on ties the first listed
+    /// media type wins. Media types with `q=0` are not acceptable.
     fn extract_format_from_accept(headers: &HeaderMap) -> Option<ContentFormat> {
         let accept = headers.get("accept")?.to_str().ok()?;
 
-        // Parse Accept header and find highest priority supported format
-        // Simple implementation - doesn't handle full q-values
+        let mut best: Option<(ContentFormat, f32)> = None;
         for part in accept.split(',') {
-            let media_type = part.split(';').next()?.trim();
-            if let Some(format) =
-                ContentFormat::parse(media_type).filter(ContentFormat::is_supported)
-            {
-                return Some(format);
+            let mut params = part.split(';');
+            let media_type = params.next().unwrap_or("").trim();
+            let Some(format) = ContentFormat::parse(media_type).filter(ContentFormat::is_supported)
+            else {
+                continue;
+            };
+
+            let quality = params
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .and_then(|(_, value)| value.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality <= 0.0 {
+                continue;
+            }
+            if best.is_none_or(|(_, best_quality)| quality > best_quality) {
+                best = Some((format, quality));
             }
         }
 
-        None
+        best.map(|(format, _)| format)
     }
 
     /// Check if Accept header explicitly requests a FHIR format
@@ -384,6 +399,19 @@ mod tests {
         assert_eq!(ContentFormat::parse("invalid"), None);
     }
 
+    #[test]
+    fn test_content_format_from_unencoded_query_value() {
+        // `_format=application/fhir+xml` sent without percent-encoding the `+`
+        assert_eq!(
+            ContentFormat::parse("application/fhir xml"),
+            Some(ContentFormat::Xml)
+        );
+        assert_eq!(
+            ContentFormat::parse("application/fhir json"),
+            Some(ContentFormat::Json)
+        );
+    }
+
     #[test]
     fn test_summary_mode_from_str() {
         assert_eq!(SummaryMode::parse("true"), Some(SummaryMode::True));
@@ -420,6 +448,40 @@ mod tests {
         assert_eq!(cn.format, ContentFormat::Xml);
     }
 
+    #[test]
+    fn test_accept_header_quality_values() {
+        let negotiate = |accept: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("accept", accept.parse().unwrap());
+            ContentNegotiation::from_request(&HashMap::new(), &headers, "json").format
+        };
+
+        assert_eq!(
+            negotiate("application/fhir+json;q=0.5, application/fhir+xml"),
+            ContentFormat::Xml
+        );
+        assert_eq!(
+            negotiate("application/fhir+xml;q=0.8, application/fhir+json;q=0.8"),
+            ContentFormat::Xml
+        );
+        assert_eq!(
+            negotiate("application/fhir+xml;q=0, text/html"),
+            ContentFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_format_param_takes_precedence_over_accept_header() {
+        let mut params = HashMap::new();
+        params.insert("_format".to_string(), "json".to_string());
+        let mut headers = HeaderMap::new();
+        headers.insert("accept", "application/fhir+xml".parse().unwrap());
+
+        let cn = ContentNegotiation::from_request(&params, &headers, "json");
+
+        assert_eq!(cn.format, ContentFormat::Json);
+    }
+
     #[test]
     fn test_content_negotiation_default() {
         let params = HashMap::new();
@@ -2,7 +2,7 @@
 
 use axum::{
     async_trait,
-    body::Bytes,
+    body::{Body, Bytes},
     extract::{FromRequest, Request},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
@@ -59,9 +59,10 @@ where
             "application/fhir+xml" | "application/xml" | "text/xml"
         );
 
+        // Keeps 413 for bodies over the size limit
         let bytes = Bytes::from_request(req, state).await.map_err(|e| {
             FhirBodyRejection {
-                status: StatusCode::BAD_REQUEST,
+                status: e.status(),
                 message: format!("Failed to read request body: {}", e),
             }
         })?;
@@ -137,3 +138,31 @@ pub fn parse_fhir_body(bytes: &[u8], headers: &HeaderMap) -> crate::Result<JsonV
         })
     }
 }
+
+/// Read a request body into memory, refusing bodies larger than `limit` bytes.
+///
+/// Use this in handlers that manually read the request body instead of using
+/// the [`FhirBody`] extractor, with `server.max_request_body_size` as the limit.
+pub async fn read_body(body: Body, limit: usize) -> crate::Result<Bytes> {
+    axum::body::to_bytes(body, limit).await.map_err(|e| {
+        if is_length_limit_error(&e) {
+            crate::Error::PayloadTooLarge(format!(
+                "the request body exceeds the maximum of {} bytes",
+                limit
+            ))
+        } else {
+            crate::Error::Validation(format!("Failed to read request body: {}", e))
+        }
+    })
+}
+
+fn is_length_limit_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if e.is::<http_body_util::LengthLimitError>() {
+            return true;
+        }
+        current = e.source();
+    }
+    false
+}
@@ -52,6 +52,23 @@ pub async fn get_search_hash_collisions(State(state): State<AppState>) -> Result
     Ok((StatusCode::OK, Json(status)).into_response())
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMaintenanceQuery {
+    pub vacuum: Option<bool>,
+}
+
+pub async fn run_search_index_maintenance(
+    State(state): State<AppState>,
+    Query(q): Query<SearchMaintenanceQuery>,
+) -> Result<Response> {
+    let report = state
+        .admin_service
+        .run_search_index_maintenance(q.vacuum.unwrap_or(false))
+        .await?;
+    Ok((StatusCode::OK, Json(report)).into_response())
+}
+
 pub async fn list_search_parameters(
     State(state): State<AppState>,
     Query(query): Query<SearchParameterListQuery>,
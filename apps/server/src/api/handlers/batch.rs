@@ -4,7 +4,7 @@ use crate::{
     api::{
         content_negotiation::ContentNegotiation,
         extractors::FhirBody,
-        headers::extract_prefer_return,
+        headers::{extract_prefer_return, extract_validate_only},
         resource_formatter::ResourceFormatter,
     },
     runtime_config::ConfigKey,
@@ -43,6 +43,9 @@ fn build_base_url_from_headers(headers: &HeaderMap) -> String {
 /// - Transaction: Entries processed atomically with specific ordering
 /// - History: Entries processed sequentially for replication (idempotent, non-atomic)
 ///
+/// With `X-Validate-Only: true`, a transaction is processed and rolled back instead of
+/// committed.
+///
 /// After processing, queues background indexing jobs for affected resources.
 pub async fn batch_transaction(
     State(state): State<AppState>,
@@ -72,9 +75,17 @@ pub async fn batch_transaction(
 
     validate_bundle_entry_access(&state, &bundle).await?;
 
+    let validate_only = extract_validate_only(&headers);
+    if validate_only && bundle_type != "transaction" {
+        return Err(crate::Error::Validation(
+            "X-Validate-Only is only supported for transaction bundles".to_string(),
+        ));
+    }
+
     let options = BundleRequestOptions {
         prefer_return,
         base_url: Some(build_base_url_from_headers(&headers)),
+        validate_only,
     };
 
     let response_bundle = match bundle_type.as_str() {
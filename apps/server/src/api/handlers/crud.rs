@@ -251,6 +251,14 @@ fn get_effective_prefer_return(headers: &HeaderMap, default_config: &str) -> Pre
     }
 }
 
+/// Append issues found by lenient write-time validation to a success OperationOutcome
+fn with_validation_issues(mut outcome: JsonValue, issues: Vec<JsonValue>) -> JsonValue {
+    if let Some(list) = outcome.get_mut("issue").and_then(JsonValue::as_array_mut) {
+        list.extend(issues);
+    }
+    outcome
+}
+
 /// Format and create response with proper content negotiation
 ///
 /// Returns a Response with:
@@ -349,6 +357,8 @@ pub async fn create_resource(
             "conditional-create",
         )
         .await?;
+        // Reject read-only types before running the conditional search
+        service.ensure_writable(&resource_type)?;
 
         let query = if_none_exist_raw.trim().trim_start_matches('?');
         let query_items = parse_form_urlencoded(query)?;
@@ -474,6 +484,7 @@ pub async fn create_resource(
                     )
                 }]
             });
+            let operation_outcome = with_validation_issues(operation_outcome, result.issues);
             let base_response = status.into_response();
             let response = format_resource_response(
                 operation_outcome,
@@ -702,6 +713,7 @@ pub async fn update_resource(
                     )
                 }]
             });
+            let operation_outcome = with_validation_issues(operation_outcome, result.issues);
             let base_response = status.into_response();
             let response = format_resource_response(
                 operation_outcome,
@@ -918,7 +930,9 @@ pub async fn resource_history(
         base_response,
     )?;
 
-    let response_headers = FhirResponseHeaders::new().with_cache_control_no_cache();
+    let response_headers = FhirResponseHeaders::new()
+        .with_cache_control_no_cache()
+        .with_vary_accept();
     Ok(response_headers.apply_to_response(response))
 }
 
@@ -942,6 +956,8 @@ pub async fn conditional_update_resource(
     )
     .await?;
     crate::api::fhir_access::ensure_resource_type_supported(&state, &resource_type)?;
+    // Reject read-only types before running the conditional search
+    state.crud_service.ensure_writable(&resource_type)?;
 
     let headers = request.headers().clone();
     let default_format = runtime_default_format(&state).await;
@@ -965,9 +981,11 @@ pub async fn conditional_update_resource(
     let base_url = build_base_url(&headers, &request);
 
     // Parse resource body (JSON or XML).
-    let body_bytes = axum::body::to_bytes(request.into_body(), usize::MAX)
-        .await
-        .map_err(|e| crate::Error::Validation(format!("Failed to read request body: {}", e)))?;
+    let body_bytes = crate::api::extractors::read_body(
+        request.into_body(),
+        state.config.server.max_request_body_size,
+    )
+    .await?;
     let mut resource: JsonValue =
         crate::api::extractors::parse_fhir_body(&body_bytes, &headers)?;
 
@@ -1068,6 +1086,7 @@ pub async fn conditional_update_resource(
                     )
                 }]
             });
+            let operation_outcome = with_validation_issues(operation_outcome, result.issues);
             let base_response = status.into_response();
             let response = format_resource_response(
                 operation_outcome,
@@ -1148,11 +1167,6 @@ pub async fn patch_resource(
 
     // Handle If-Match conditional patch (resource contention)
     let if_match = extract_if_match(&headers);
-    let update_params = if if_match.is_some() {
-        Some(UpdateParams { if_match })
-    } else {
-        None
-    };
 
     let service = &state.crud_service;
     let current = service.read_resource(&resource_type, &id).await?;
@@ -1187,8 +1201,17 @@ pub async fn patch_resource(
         .resolve(&mut patched, Some(&base_url))
         .await?;
 
+    // Write against the version the patch was applied to, so a concurrent update made
+    // after the read is reported as a conflict instead of being silently overwritten.
     let result = service
-        .update_resource(&resource_type, &id, patched, update_params)
+        .update_resource(
+            &resource_type,
+            &id,
+            patched,
+            Some(UpdateParams {
+                if_match: Some(current.version_id),
+            }),
+        )
         .await?;
 
     let response_headers = FhirResponseHeaders::for_create_update(
@@ -1214,6 +1237,7 @@ pub async fn patch_resource(
                     "diagnostics": format!("Resource patched successfully with ID {}", result.resource.id)
                 }]
             });
+            let operation_outcome = with_validation_issues(operation_outcome, result.issues);
             let base_response = StatusCode::OK.into_response();
             let response = format_resource_response(
                 operation_outcome,
@@ -1257,6 +1281,8 @@ pub async fn conditional_patch_resource(
     )
     .await?;
     crate::api::fhir_access::ensure_resource_type_supported(&state, &resource_type)?;
+    // Reject read-only types before running the conditional search
+    state.crud_service.ensure_writable(&resource_type)?;
 
     let headers = request.headers().clone();
     let default_format = runtime_default_format(&state).await;
@@ -1302,9 +1328,11 @@ pub async fn conditional_patch_resource(
         )));
     }
 
-    let body_bytes = axum::body::to_bytes(request.into_body(), usize::MAX)
-        .await
-        .map_err(|e| crate::Error::Validation(format!("Failed to read request body: {}", e)))?;
+    let body_bytes = crate::api::extractors::read_body(
+        request.into_body(),
+        state.config.server.max_request_body_size,
+    )
+    .await?;
 
     let patch: json_patch::Patch = serde_json::from_slice(&body_bytes).map_err(|e| {
         crate::Error::InvalidResource(format!("Invalid JSON Patch document: {}", e))
@@ -1333,11 +1361,6 @@ pub async fn conditional_patch_resource(
 
     // Handle If-Match conditional patch (resource contention)
     let if_match = extract_if_match(&headers);
-    let update_params = if if_match.is_some() {
-        Some(UpdateParams { if_match })
-    } else {
-        None
-    };
 
     let result = state.crud_service;
 
@@ -1372,8 +1395,16 @@ pub async fn conditional_patch_resource(
         .resolve(&mut patched, Some(&base_url))
         .await?;
 
+    // Write against the version the patch was applied to (see `patch_resource`).
     let result = result
-        .update_resource(&resource_type, &id, patched, update_params)
+        .update_resource(
+            &resource_type,
+            &id,
+            patched,
+            Some(UpdateParams {
+                if_match: Some(current.version_id),
+            }),
+        )
         .await?;
 
     let response_headers = FhirResponseHeaders::for_create_update(
@@ -1399,6 +1430,7 @@ pub async fn conditional_patch_resource(
                     "diagnostics": format!("Resource patched successfully with ID {}", result.resource.id)
                 }]
             });
+            let operation_outcome = with_validation_issues(operation_outcome, result.issues);
             let base_response = StatusCode::OK.into_response();
             let response = format_resource_response(
                 operation_outcome,
@@ -1490,6 +1522,8 @@ pub async fn conditional_delete_resource(
     )
     .await?;
     crate::api::fhir_access::ensure_resource_type_supported(&state, &resource_type)?;
+    // Reject read-only types before running the conditional search
+    state.crud_service.ensure_writable(&resource_type)?;
 
     let headers = request.headers().clone();
 
@@ -1509,9 +1543,11 @@ pub async fn conditional_delete_resource(
     let base_url = build_base_url(&headers, &request);
 
     // Per spec: request body SHALL be empty.
-    let body_bytes = axum::body::to_bytes(request.into_body(), usize::MAX)
-        .await
-        .map_err(|e| crate::Error::Validation(format!("Failed to read request body: {}", e)))?;
+    let body_bytes = crate::api::extractors::read_body(
+        request.into_body(),
+        state.config.server.max_request_body_size,
+    )
+    .await?;
     if !body_bytes.is_empty() {
         return Err(crate::Error::InvalidResource(
             "DELETE request body must be empty".to_string(),
@@ -1639,7 +1675,9 @@ pub async fn type_history(
         base_response,
     )?;
 
-    let response_headers = FhirResponseHeaders::new().with_cache_control_no_cache();
+    let response_headers = FhirResponseHeaders::new()
+        .with_cache_control_no_cache()
+        .with_vary_accept();
     Ok(response_headers.apply_to_response(response))
 }
 
@@ -1668,9 +1706,11 @@ pub async fn system_delete(State(state): State<AppState>, request: Request) -> R
     let base_url = build_base_url(&headers, &request);
 
     // Per spec: request body SHALL be empty.
-    let body_bytes = axum::body::to_bytes(request.into_body(), usize::MAX)
-        .await
-        .map_err(|e| crate::Error::Validation(format!("Failed to read request body: {}", e)))?;
+    let body_bytes = crate::api::extractors::read_body(
+        request.into_body(),
+        state.config.server.max_request_body_size,
+    )
+    .await?;
     if !body_bytes.is_empty() {
         return Err(crate::Error::InvalidResource(
             "DELETE request body must be empty".to_string(),
@@ -1764,6 +1804,8 @@ pub async fn system_history(
         base_response,
     )?;
 
-    let response_headers = FhirResponseHeaders::new().with_cache_control_no_cache();
+    let response_headers = FhirResponseHeaders::new()
+        .with_cache_control_no_cache()
+        .with_vary_accept();
     Ok(response_headers.apply_to_response(response))
 }
@@ -1,6 +1,6 @@
 //! Background job management handlers (admin API)
 
-use crate::{state::AppState, Result};
+use crate::{queue::Job, services::admin::JobListQuery, state::AppState, Result};
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
@@ -11,68 +11,45 @@ use serde::Deserialize;
 use serde_json::json;
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ListJobsQuery {
-    pub job_type: Option<String>,
-    pub status: Option<String>,
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
+/// Serialize a job for the admin API
+fn job_json(job: &Job) -> serde_json::Value {
+    json!({
+        "id": job.id,
+        "jobType": job.job_type,
+        "status": job.status,
+        "priority": job.get_priority(),
+        "parameters": job.parameters,
+        "progress": job.progress,
+        "processedItems": job.processed_items,
+        "totalItems": job.total_items,
+        "progressPercent": job.progress_percent(),
+        "errorMessage": job.error_message,
+        "lastErrorAt": job.last_error_at,
+        "scheduledAt": job.scheduled_at,
+        "cancelRequested": job.cancel_requested,
+        "createdAt": job.created_at,
+        "startedAt": job.started_at,
+        "completedAt": job.completed_at,
+        "workerId": job.worker_id,
+        "retryCount": job.retry_count,
+    })
 }
 
 /// List all background jobs with optional filtering
 pub async fn list_jobs(
     State(state): State<AppState>,
-    Query(q): Query<ListJobsQuery>,
+    Query(q): Query<JobListQuery>,
 ) -> Result<Response> {
-    // Get pagination parameters
-    let limit = q.limit.unwrap_or(50).clamp(1, 1000);
-    let offset = q.offset.unwrap_or(0).max(0);
-
-    // Use the helper function
-    let (jobs, total) = crate::queue::list_jobs(
-        &state.db_pool,
-        q.job_type.as_deref(),
-        q.status.as_deref(),
-        limit,
-        offset,
-    )
-    .await?;
-
-    // Convert to JSON-friendly format
-    let jobs_json: Vec<serde_json::Value> = jobs
-        .into_iter()
-        .map(|job| {
-            json!({
-                "id": job.id,
-                "jobType": job.job_type,
-                "status": job.status,
-                "priority": job.get_priority(),
-                "parameters": job.parameters,
-                "progress": job.progress,
-                "processedItems": job.processed_items,
-                "totalItems": job.total_items,
-                "progressPercent": job.progress_percent(),
-                "errorMessage": job.error_message,
-                "lastErrorAt": job.last_error_at,
-                "scheduledAt": job.scheduled_at,
-                "cancelRequested": job.cancel_requested,
-                "createdAt": job.created_at,
-                "startedAt": job.started_at,
-                "completedAt": job.completed_at,
-                "workerId": job.worker_id,
-                "retryCount": job.retry_count,
-            })
-        })
-        .collect();
+    let result = state.admin_service.list_jobs(q).await?;
+    let jobs_json: Vec<serde_json::Value> = result.jobs.iter().map(job_json).collect();
 
     Ok((
         StatusCode::OK,
         Json(json!({
             "jobs": jobs_json,
-            "total": total,
-            "limit": limit,
-            "offset": offset
+            "total": result.total,
+            "limit": result.limit,
+            "offset": result.offset
         })),
     )
         .into_response())
@@ -83,31 +60,11 @@ pub async fn get_job(State(state): State<AppState>, Path(job_id): Path<Uuid>) ->
     let job = state.job_queue.get_job(job_id).await?;
 
     match job {
-        Some(job) => Ok((
-            StatusCode::OK,
-            Json(json!({
-                "id": job.id,
-                "jobType": job.job_type,
-                "status": job.status,
-                "priority": job.get_priority(),
-                "parameters": job.parameters,
-                "progress": job.progress,
-                "processedItems": job.processed_items,
-                "totalItems": job.total_items,
-                "progressPercent": job.progress_percent(),
-                "errorMessage": job.error_message,
-                "lastErrorAt": job.last_error_at,
-                "scheduledAt": job.scheduled_at,
-                "cancelRequested": job.cancel_requested,
-                "createdAt": job.created_at,
-                "startedAt": job.started_at,
-                "completedAt": job.completed_at,
-                "workerId": job.worker_id,
-                "retryCount": job.retry_count,
-                "retryPolicy": job.retry_policy,
-            })),
-        )
-            .into_response()),
+        Some(job) => {
+            let mut body = job_json(&job);
+            body["retryPolicy"] = job.retry_policy.clone();
+            Ok((StatusCode::OK, Json(body)).into_response())
+        }
         None => Err(crate::Error::ResourceNotFound {
             resource_type: "Job".to_string(),
             id: job_id.to_string(),
@@ -115,27 +72,30 @@ pub async fn get_job(State(state): State<AppState>, Path(job_id): Path<Uuid>) ->
     }
 }
 
-/// Cancel a running or pending job
+/// Cancel a pending, failed, or running job
 pub async fn cancel_job(
     State(state): State<AppState>,
     Path(job_id): Path<Uuid>,
 ) -> Result<Response> {
-    let cancelled = state.job_queue.cancel_job(job_id).await?;
+    state.admin_service.cancel_job(job_id).await?;
 
-    if cancelled {
-        Ok((
-            StatusCode::OK,
-            Json(json!({
-                "cancelled": true,
-                "jobId": job_id
-            })),
-        )
-            .into_response())
-    } else {
-        Err(crate::Error::Validation(
-            "Job not found or already completed".to_string(),
-        ))
-    }
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "cancelled": true,
+            "jobId": job_id
+        })),
+    )
+        .into_response())
+}
+
+/// Retry a failed or cancelled job (resets it to pending and clears its error)
+pub async fn retry_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Response> {
+    let job = state.admin_service.retry_job(job_id).await?;
+    Ok((StatusCode::OK, Json(job_json(&job))).into_response())
 }
 
 /// Delete a single job (must be in a terminal state)
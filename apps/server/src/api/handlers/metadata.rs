@@ -1,6 +1,7 @@
 //! Metadata endpoint handler
 //!
-//! Handles the FHIR capabilities interaction (GET /metadata)
+//! Handles the FHIR capabilities interaction (GET /metadata) and per-type search parameter
+//! discovery (GET /metadata/{type})
 
 use crate::{
     api::{content_negotiation::ContentNegotiation, resource_formatter::ResourceFormatter},
@@ -11,7 +12,7 @@ use crate::{
 };
 use axum::{
     body::Body,
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
@@ -91,6 +92,50 @@ pub async fn capability_statement(
     Ok(Response::from_parts(parts, body))
 }
 
+/// List the search parameters of a resource type (GET /metadata/{type})
+///
+/// Returns a `collection` Bundle of the active SearchParameter definitions for the type,
+/// including each parameter's code, type and expression.
+pub async fn resource_search_parameters(
+    State(state): State<AppState>,
+    Path(resource_type): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    crate::api::fhir_access::ensure_interaction_enabled_runtime(
+        &state,
+        ConfigKey::InteractionsSystemCapabilities,
+        "capabilities",
+    )
+    .await?;
+
+    if !crate::models::is_known_resource_type(&resource_type) {
+        return Err(crate::Error::Validation(format!(
+            "Invalid resource type: {}",
+            resource_type
+        )));
+    }
+    crate::api::fhir_access::ensure_resource_type_supported(&state, &resource_type)?;
+
+    let bundle = state
+        .metadata_service
+        .get_resource_search_parameters(&resource_type)
+        .await?;
+
+    let default_format: String = state
+        .runtime_config_cache
+        .get(ConfigKey::FormatDefault)
+        .await;
+
+    format_resource_response(
+        bundle,
+        &params,
+        &headers,
+        &default_format,
+        StatusCode::OK.into_response(),
+    )
+}
+
 /// Helper function to format resource response with content negotiation
 fn format_resource_response(
     resource: serde_json::Value,
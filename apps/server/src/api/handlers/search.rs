@@ -21,7 +21,7 @@ use crate::{
 use axum::{
     body::Body,
     extract::{Path, Request, State},
-    http::{HeaderMap, Method, StatusCode},
+    http::{HeaderMap, HeaderValue, Method, StatusCode},
     response::{IntoResponse, Response},
 };
 use serde::Deserialize;
@@ -36,7 +36,7 @@ use std::collections::HashMap;
 /// - Checking for unknown parameters
 /// - Formatting the response with content negotiation
 async fn handle_search<F, Fut>(
-    _state: &AppState,
+    state: &AppState,
     headers: &HeaderMap,
     request: Request,
     resource_context: &str,
@@ -54,9 +54,11 @@ where
 
     // Extract method and body
     let method = request.method().clone();
-    let body_bytes = axum::body::to_bytes(request.into_body(), usize::MAX)
-        .await
-        .map_err(|e| crate::Error::Validation(format!("Failed to read request body: {}", e)))?;
+    let body_bytes = crate::api::extractors::read_body(
+        request.into_body(),
+        state.config.server.max_request_body_size,
+    )
+    .await?;
 
     // Extract and merge parameters from query string and POST body
     let items = extract_search_items(&method, raw_query.as_deref(), headers, &body_bytes).await?;
@@ -87,6 +89,7 @@ where
 /// Returns a Response with:
 /// - Correct Content-Type header for the negotiated format
 /// - Formatted body (JSON, XML, etc.)
+/// - `Cache-Control: no-cache` and `Vary: Accept`
 /// - 406 Not Acceptable if requested format is not supported
 fn format_search_response(
     bundle: serde_json::Value,
@@ -127,6 +130,10 @@ fn format_search_response(
             .parse()
             .map_err(|e| crate::Error::Internal(format!("Invalid cache-control: {}", e)))?,
     );
+    // The body depends on the negotiated format, so shared caches must key on Accept
+    parts
+        .headers
+        .insert(axum::http::header::VARY, HeaderValue::from_static("Accept"));
 
     Ok(Response::from_parts(parts, Body::from(formatted_body)))
 }
@@ -12,6 +12,7 @@
 //! - `If-None-Exist` - Conditional create (HL7 extension)
 //! - `If-None-Match` - ETag-based conditional requests
 //! - `Prefer` - Request behaviors (return preference, processing preference, etc.)
+//! - `X-Validate-Only` - Dry-run a transaction without committing it (server extension)
 //!
 //! ## Response Headers
 //! - `ETag` - Version ID as weak ETag (W/"versionId")
@@ -296,6 +297,31 @@ pub fn extract_if_none_exist(headers: &HeaderMap) -> Option<String> {
         .map(|s| s.to_string())
 }
 
+// ============================================================================
+// Dry-Run Header
+// ============================================================================
+
+/// Extract the X-Validate-Only header
+///
+/// Server extension: when `true`, a transaction is processed in full and then rolled
+/// back, so the client gets the response Bundle it would have produced.
+///
+/// # Examples
+/// ```
+/// use axum::http::HeaderMap;
+/// use ferrum::api::headers::extract_validate_only;
+/// let mut headers = HeaderMap::new();
+/// assert!(!extract_validate_only(&headers));
+/// headers.insert("x-validate-only", "true".parse().unwrap());
+/// assert!(extract_validate_only(&headers));
+/// ```
+pub fn extract_validate_only(headers: &HeaderMap) -> bool {
+    headers
+        .get("x-validate-only")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|s| s.trim().eq_ignore_ascii_case("true"))
+}
+
 // ============================================================================
 // Response Header Building
 // ============================================================================
@@ -310,6 +336,7 @@ pub struct FhirResponseHeaders {
     pub last_modified: Option<String>,
     pub content_location: Option<String>,
     pub cache_control: Option<String>,
+    pub vary: Option<String>,
 }
 
 impl FhirResponseHeaders {
@@ -321,6 +348,7 @@ impl FhirResponseHeaders {
             last_modified: None,
             content_location: None,
             cache_control: None,
+            vary: None,
         }
     }
 
@@ -366,6 +394,17 @@ impl FhirResponseHeaders {
         self.with_cache_control(format!("max-age={}, immutable", seconds))
     }
 
+    /// Set Vary header
+    pub fn with_vary(mut self, value: impl Into<String>) -> Self {
+        self.vary = Some(value.into());
+        self
+    }
+
+    /// Mark the response as negotiated on `Accept` (JSON vs XML bodies)
+    pub fn with_vary_accept(self) -> Self {
+        self.with_vary("Accept")
+    }
+
     /// Build headers for create/update response
     ///
     /// Sets Location, ETag, and Last-Modified headers.
@@ -386,11 +425,12 @@ impl FhirResponseHeaders {
 
     /// Build headers for read response
     ///
-    /// Sets ETag and Last-Modified headers.
+    /// Sets ETag, Last-Modified and `Vary: Accept` headers.
     pub fn for_read(version_id: i32, last_updated: &DateTime<Utc>) -> Self {
         Self::new()
             .with_etag(version_id)
             .with_last_modified(last_updated)
+            .with_vary_accept()
     }
 
     /// Convert to Axum header array for use in responses
@@ -431,6 +471,12 @@ impl FhirResponseHeaders {
             }
         }
 
+        if let Some(ref vary) = self.vary {
+            if let Ok(value) = HeaderValue::from_str(vary) {
+                headers.push((header::VARY, value));
+            }
+        }
+
         headers
     }
 
@@ -474,6 +520,12 @@ impl FhirResponseHeaders {
             }
         }
 
+        if let Some(ref vary) = self.vary {
+            if let Ok(value) = HeaderValue::from_str(vary) {
+                headers.insert(header::VARY, value);
+            }
+        }
+
         response
     }
 }
@@ -505,6 +557,17 @@ mod tests {
         assert_eq!(format_etag(1), "W/\"1\"");
     }
 
+    #[test]
+    fn test_read_headers_vary_on_accept() {
+        let headers = FhirResponseHeaders::for_read(3, &Utc::now()).to_header_array();
+        assert!(headers
+            .iter()
+            .any(|(name, value)| name == header::ETAG && value == "W/\"3\""));
+        assert!(headers
+            .iter()
+            .any(|(name, value)| name == header::VARY && value == "Accept"));
+    }
+
     #[test]
     fn test_extract_prefer_return() {
         let mut headers = HeaderMap::new();
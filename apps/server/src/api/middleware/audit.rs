@@ -518,3 +518,75 @@ pub async fn audit_middleware(State(state): State<AppState>, req: Request, next:
 
     response
 }
+
+/// Read access ("break-the-glass") audit middleware.
+///
+/// Stores an `AuditEvent` resource for each successful read or vread of a resource type
+/// configured under `logging.audit.read_access`, recording the authenticated subject, the
+/// resource read and the purpose of use sent in the configured request header.
+pub async fn read_access_audit_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(interaction) = parse_fhir_interaction(req.method(), req.uri())
+        .filter(|i| i.interaction == "read" || i.interaction == "vread")
+    else {
+        return next.run(req).await;
+    };
+    let Some(target) = interaction
+        .resource_type
+        .clone()
+        .zip(interaction.resource_id.clone())
+        .filter(|(resource_type, _)| state.audit_service.audits_read_access(resource_type))
+    else {
+        return next.run(req).await;
+    };
+
+    let purpose_of_use: Vec<String> = state
+        .audit_service
+        .purpose_of_use_header()
+        .and_then(|name| req.headers().get(name))
+        .and_then(|v| v.to_str().ok())
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|code| !code.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    let input = crate::services::audit::HttpAuditInput {
+        method: req.method().as_str().to_string(),
+        interaction: interaction.interaction,
+        action: interaction.action,
+        status: 0,
+        request_id: req
+            .extensions()
+            .get::<RequestContext>()
+            .map(|c| c.request_id.clone()),
+        principal: req.extensions().get::<Principal>().cloned(),
+        client_ip: extract_client_ip(req.headers()),
+        user_agent: extract_user_agent(req.headers()),
+        target: Some(target),
+        patient_id: None,
+        query_base64: None,
+        query_harmonized: None,
+        operation_outcome: None,
+    };
+
+    let response = next.run(req).await;
+
+    // Only access that actually disclosed the resource is recorded
+    if response.status().is_success() {
+        let input = crate::services::audit::HttpAuditInput {
+            status: response.status().as_u16(),
+            ..input
+        };
+        state
+            .audit_service
+            .record_read_access(input, purpose_of_use);
+    }
+
+    response
+}
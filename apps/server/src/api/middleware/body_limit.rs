@@ -0,0 +1,157 @@
+//! Maximum request body size for all API routes
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Rejects request bodies larger than `server.max_request_body_size` with 413 Payload Too Large
+/// and an OperationOutcome.
+///
+/// A declared `Content-Length` over the limit is refused before the body is read. Bodies without
+/// one (chunked uploads, decompressed bodies) are cut off by the `RequestBodyLimitLayer` directly
+/// inside this middleware as they are buffered; the 413 the body readers answer with is replaced
+/// here with the same OperationOutcome. Both run inside request decompression, so the limit
+/// applies to the decoded size.
+pub async fn request_body_limit_middleware(
+    State(limit): State<usize>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let declared_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared_length.is_some_and(|length| length > limit as u64) {
+        return payload_too_large(limit);
+    }
+
+    let response = next.run(req).await;
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        return payload_too_large(limit);
+    }
+    response
+}
+
+fn payload_too_large(limit: usize) -> Response {
+    crate::Error::PayloadTooLarge(format!(
+        "the request body exceeds the maximum of {} bytes",
+        limit
+    ))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::post, Router};
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+    use tower::ServiceExt;
+    use tower_http::{decompression::RequestDecompressionLayer, limit::RequestBodyLimitLayer};
+
+    const LIMIT: usize = 16;
+
+    async fn echo(request: Request) -> crate::Result<Vec<u8>> {
+        let body = crate::api::extractors::read_body(request.into_body(), LIMIT).await?;
+        Ok(body.to_vec())
+    }
+
+    fn router() -> Router {
+        Router::new()
+            .route("/echo", post(echo))
+            .layer(RequestBodyLimitLayer::new(LIMIT))
+            .layer(axum::middleware::from_fn_with_state(
+                LIMIT,
+                request_body_limit_middleware,
+            ))
+            .layer(RequestDecompressionLayer::new())
+    }
+
+    async fn send_with(
+        body: Body,
+        content_length: Option<usize>,
+        content_encoding: Option<&str>,
+    ) -> Response {
+        let mut request = Request::post("/echo");
+        if let Some(length) = content_length {
+            request = request.header(header::CONTENT_LENGTH, length);
+        }
+        if let Some(encoding) = content_encoding {
+            request = request.header(header::CONTENT_ENCODING, encoding);
+        }
+        router().oneshot(request.body(body).unwrap()).await.unwrap()
+    }
+
+    async fn send(body: Body, content_length: Option<usize>) -> Response {
+        send_with(body, content_length, None).await
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    async fn assert_rejected(response: Response) {
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let outcome: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(outcome["resourceType"], "OperationOutcome");
+        assert_eq!(outcome["issue"][0]["code"], "too-costly");
+        assert!(outcome["issue"][0]["diagnostics"]
+            .as_str()
+            .unwrap()
+            .contains("maximum of 16 bytes"));
+    }
+
+    #[tokio::test]
+    async fn body_within_limit_is_accepted() {
+        let response = send(Body::from("a".repeat(LIMIT)), Some(LIMIT)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn declared_length_over_limit_is_rejected() {
+        let response = send(Body::from("a".repeat(LIMIT + 1)), Some(LIMIT + 1)).await;
+
+        assert_rejected(response).await;
+    }
+
+    #[tokio::test]
+    async fn streamed_body_over_limit_is_rejected() {
+        let chunks = futures::stream::iter(
+            ["a".repeat(LIMIT), "a".repeat(LIMIT)].map(Ok::<_, std::io::Error>),
+        );
+        let response = send(Body::from_stream(chunks), None).await;
+
+        assert_rejected(response).await;
+    }
+
+    #[tokio::test]
+    async fn decompressed_body_over_limit_is_rejected() {
+        let compressed = gzip("a".repeat(LIMIT * 64).as_bytes());
+        assert!(compressed.len() <= LIMIT * 4);
+        let length = compressed.len();
+
+        let response = send_with(Body::from(compressed), Some(length), Some("gzip")).await;
+
+        assert_rejected(response).await;
+    }
+
+    #[tokio::test]
+    async fn decompressed_body_within_limit_is_accepted() {
+        let compressed = gzip("a".repeat(LIMIT).as_bytes());
+        let length = compressed.len();
+
+        let response = send_with(Body::from(compressed), Some(length), Some("gzip")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
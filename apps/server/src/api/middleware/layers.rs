@@ -1,10 +1,20 @@
 //! Layer factories for middleware
 
+use std::str::FromStr;
+use std::time::Duration;
+
+use axum::http::{HeaderName, HeaderValue, Method};
 use tower_http::{
-    compression::CompressionLayer,
-    cors::{AllowOrigin, Any, CorsLayer},
+    compression::{
+        predicate::{NotForContentType, SizeAbove},
+        CompressionLayer, Predicate,
+    },
+    cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer, ExposeHeaders},
+    decompression::RequestDecompressionLayer,
 };
 
+use crate::config::{CompressionCodec, CompressionConfig, ServerConfig};
+
 /// Tracing/logging middleware
 ///
 /// With OpenTelemetry integration, we handle tracing via #[instrument] attributes
@@ -14,31 +24,105 @@ pub fn trace() -> tower::layer::util::Identity {
 }
 
 /// CORS middleware
-pub fn cors(origins: &[String]) -> CorsLayer {
-    if origins.is_empty() {
-        // Secure default: do not emit permissive CORS headers unless explicitly configured.
-        return CorsLayer::new();
-    }
+///
+/// Without configured origins no CORS headers are emitted, so browsers only allow
+/// same-origin requests. Requests from origins not in the list get no
+/// `Access-Control-Allow-Origin` header and are blocked by the browser.
+pub fn cors(config: &ServerConfig) -> CorsLayer {
+    let is_wildcard = |values: &[String]| values.iter().any(|v| v.trim() == "*");
 
-    let mut header_values = Vec::with_capacity(origins.len());
-    for origin in origins {
-        if let Ok(value) = axum::http::HeaderValue::from_str(origin) {
-            header_values.push(value);
+    let allow_origin = if is_wildcard(&config.cors_origins) {
+        AllowOrigin::any()
+    } else {
+        let origins: Vec<HeaderValue> = parse_values(&config.cors_origins, "origin");
+        // Secure default: do not emit CORS headers unless valid origins are configured.
+        if origins.is_empty() {
+            return CorsLayer::new();
         }
-    }
+        AllowOrigin::list(origins)
+    };
+
+    let allow_methods = if is_wildcard(&config.cors_methods) {
+        AllowMethods::any()
+    } else {
+        AllowMethods::list(parse_values::<Method>(&config.cors_methods, "method"))
+    };
+
+    let allow_headers = if is_wildcard(&config.cors_headers) {
+        AllowHeaders::any()
+    } else {
+        AllowHeaders::list(parse_values::<HeaderName>(&config.cors_headers, "header"))
+    };
+
+    let expose_headers = if is_wildcard(&config.cors_expose_headers) {
+        ExposeHeaders::any()
+    } else {
+        ExposeHeaders::list(parse_values::<HeaderName>(
+            &config.cors_expose_headers,
+            "expose header",
+        ))
+    };
 
-    // If all configured origins were invalid, fall back to no CORS.
-    if header_values.is_empty() {
-        return CorsLayer::new();
+    // Credentials with wildcards is rejected by `Config::validate`; never emit the invalid combination.
+    let wildcard = is_wildcard(&config.cors_origins)
+        || is_wildcard(&config.cors_methods)
+        || is_wildcard(&config.cors_headers)
+        || is_wildcard(&config.cors_expose_headers);
+
+    let layer = CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(allow_methods)
+        .allow_headers(allow_headers)
+        .expose_headers(expose_headers)
+        .allow_credentials(config.cors_allow_credentials && !wildcard);
+
+    match config.cors_max_age_seconds {
+        Some(seconds) => layer.max_age(Duration::from_secs(seconds)),
+        None => layer,
     }
+}
 
-    CorsLayer::new()
-        .allow_origin(AllowOrigin::list(header_values))
-        .allow_methods(Any)
-        .allow_headers(Any)
+/// Parse configured CORS values, skipping (and logging) invalid ones.
+fn parse_values<T: FromStr>(values: &[String], kind: &str) -> Vec<T> {
+    values
+        .iter()
+        .filter_map(|value| match value.trim().parse() {
+            Ok(parsed) => Some(parsed),
+            Err(_) => {
+                tracing::warn!(value = %value, "Ignoring invalid CORS {}", kind);
+                None
+            }
+        })
+        .collect()
 }
 
 /// Compression middleware
-pub fn compression() -> CompressionLayer {
+///
+/// Negotiates `Accept-Encoding` and compresses responses above the configured size,
+/// skipping content that is already compressed or streamed to the client incrementally.
+pub fn compression(config: &CompressionConfig) -> CompressionLayer<impl Predicate> {
+    let predicate = SizeAbove::new(config.min_size)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE);
+
     CompressionLayer::new()
+        .gzip(config.codec_enabled(CompressionCodec::Gzip))
+        .deflate(config.codec_enabled(CompressionCodec::Deflate))
+        .br(config.codec_enabled(CompressionCodec::Br))
+        .zstd(config.codec_enabled(CompressionCodec::Zstd))
+        .compress_when(predicate)
+}
+
+/// Request decompression middleware
+///
+/// Decodes request bodies sent with a `Content-Encoding` of an enabled codec and rejects
+/// other encodings with 415. With compression disabled, bodies are passed through as-is.
+pub fn request_decompression(config: &CompressionConfig) -> RequestDecompressionLayer {
+    RequestDecompressionLayer::new()
+        .gzip(config.codec_enabled(CompressionCodec::Gzip))
+        .deflate(config.codec_enabled(CompressionCodec::Deflate))
+        .br(config.codec_enabled(CompressionCodec::Br))
+        .zstd(config.codec_enabled(CompressionCodec::Zstd))
+        .pass_through_unaccepted(!config.enabled)
 }
@@ -1,14 +1,20 @@
 //! Middleware stack for the API
 
 pub mod audit;
+pub mod body_limit;
 pub mod layers;
 pub mod metrics;
+pub mod rate_limit;
 pub mod request_id;
 pub mod security;
+pub mod timeout;
 
 // Re-export public API
-pub use audit::audit_middleware;
-pub use layers::{compression, cors, trace};
+pub use audit::{audit_middleware, read_access_audit_middleware};
+pub use body_limit::request_body_limit_middleware;
+pub use layers::{compression, cors, request_decompression, trace};
 pub use metrics::metrics_middleware;
+pub use rate_limit::{rate_limit_middleware, RateLimiter};
 pub use request_id::request_id_middleware;
 pub use security::security_headers_middleware;
+pub use timeout::request_timeout_middleware;
@@ -0,0 +1,295 @@
+//! Per-client rate limiting for FHIR REST interactions
+
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::auth::Principal;
+use crate::config::{RateLimitConfig, RateLimitRule};
+
+/// How often idle (full) buckets are dropped. Sweeping is O(n) under the lock, so it runs
+/// at most once per interval rather than on every request.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Interaction classes with separate limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum InteractionClass {
+    Read,
+    Write,
+}
+
+impl InteractionClass {
+    /// GET/HEAD and searches via `POST .../_search` are reads; everything else writes.
+    fn of(method: &Method, path: &str) -> Self {
+        if method == Method::GET
+            || method == Method::HEAD
+            || (method == Method::POST && path.ends_with("/_search"))
+        {
+            Self::Read
+        } else {
+            Self::Write
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    entries: HashMap<(String, InteractionClass), Bucket>,
+    next_sweep: Instant,
+}
+
+/// Token-bucket limiter keyed by client and interaction class.
+pub struct RateLimiter {
+    read: RateLimitRule,
+    write: RateLimitRule,
+    trusted_proxies: HashSet<IpAddr>,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            read: config.read,
+            write: config.write,
+            trusted_proxies: config
+                .trusted_proxies
+                .iter()
+                .filter_map(|proxy| proxy.trim().parse().ok())
+                .collect(),
+            buckets: Mutex::new(Buckets {
+                entries: HashMap::new(),
+                next_sweep: Instant::now() + SWEEP_INTERVAL,
+            }),
+        }
+    }
+
+    fn rule(&self, class: InteractionClass) -> RateLimitRule {
+        match class {
+            InteractionClass::Read => self.read,
+            InteractionClass::Write => self.write,
+        }
+    }
+
+    /// Take a token for `client`. Returns the seconds to wait when the bucket is empty.
+    fn check(&self, client: &str, class: InteractionClass, now: Instant) -> Result<(), u64> {
+        let rule = self.rule(class);
+        let capacity = f64::from(rule.burst.max(1));
+        let per_second = f64::from(rule.requests_per_minute.max(1)) / 60.0;
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if now >= buckets.next_sweep {
+            buckets.next_sweep = now + SWEEP_INTERVAL;
+            buckets.entries.retain(|(_, class), bucket| {
+                let rule = self.rule(*class);
+                let refilled = bucket.tokens
+                    + now.duration_since(bucket.updated).as_secs_f64()
+                        * f64::from(rule.requests_per_minute.max(1))
+                        / 60.0;
+                refilled < f64::from(rule.burst.max(1))
+            });
+        }
+
+        let bucket = buckets
+            .entries
+            .entry((client.to_string(), class))
+            .or_insert(Bucket {
+                tokens: capacity,
+                updated: now,
+            });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = (1.0 - bucket.tokens) / per_second;
+            Err((wait.ceil() as u64).max(1))
+        }
+    }
+}
+
+/// Identify the client: the authenticated subject, else the client IP.
+fn client_key(req: &Request, trusted_proxies: &HashSet<IpAddr>) -> String {
+    if let Some(principal) = req.extensions().get::<Principal>() {
+        return format!("sub:{}", principal.subject);
+    }
+
+    match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => {
+            let ip = client_ip(req.headers(), addr.ip(), trusted_proxies);
+            format!("ip:{}", ip)
+        }
+        None => "anonymous".to_string(),
+    }
+}
+
+/// The client address of a request from `peer`.
+///
+/// Forwarded headers are only honoured when the peer is a trusted proxy. The client is then the
+/// last `X-Forwarded-For` hop that is not itself a trusted proxy (earlier hops are set by the
+/// client and cannot be trusted), else `X-Real-IP`.
+fn client_ip(headers: &HeaderMap, peer: IpAddr, trusted_proxies: &HashSet<IpAddr>) -> IpAddr {
+    if !trusted_proxies.contains(&peer) {
+        return peer;
+    }
+
+    let forwarded: Vec<IpAddr> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|hop| hop.trim().parse().ok())
+        .collect();
+    if let Some(ip) = forwarded
+        .iter()
+        .rev()
+        .find(|ip| !trusted_proxies.contains(ip))
+    {
+        return *ip;
+    }
+
+    headers
+        .get("x-real-ip")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(peer)
+}
+
+/// Rejects requests over the client's limit with 429 and `Retry-After`.
+///
+/// Runs after authentication so that authenticated clients are limited per subject.
+pub async fn rate_limit_middleware(
+    State(limiter): State<Arc<RateLimiter>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let class = InteractionClass::of(req.method(), req.uri().path());
+    let client = client_key(&req, &limiter.trusted_proxies);
+
+    if let Err(retry_after_seconds) = limiter.check(&client, class, Instant::now()) {
+        tracing::debug!(client = %client, ?class, retry_after_seconds, "Rate limit exceeded");
+        return crate::Error::TooManyRequests {
+            retry_after_seconds,
+        }
+        .into_response();
+    }
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(requests_per_minute: u32, burst: u32) -> RateLimiter {
+        let rule = RateLimitRule {
+            requests_per_minute,
+            burst,
+        };
+        RateLimiter::new(&RateLimitConfig {
+            enabled: true,
+            read: rule,
+            write: rule,
+            trusted_proxies: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn bucket_allows_burst_then_refills() {
+        let limiter = limiter(60, 2);
+        let start = Instant::now();
+
+        assert!(limiter.check("a", InteractionClass::Read, start).is_ok());
+        assert!(limiter.check("a", InteractionClass::Read, start).is_ok());
+        assert_eq!(limiter.check("a", InteractionClass::Read, start), Err(1));
+
+        // Other clients and classes have their own buckets
+        assert!(limiter.check("b", InteractionClass::Read, start).is_ok());
+        assert!(limiter.check("a", InteractionClass::Write, start).is_ok());
+
+        // One request per second refills one token
+        let later = start + Duration::from_secs(1);
+        assert!(limiter.check("a", InteractionClass::Read, later).is_ok());
+        assert!(limiter.check("a", InteractionClass::Read, later).is_err());
+    }
+
+    #[test]
+    fn retry_after_reflects_refill_rate() {
+        let limiter = limiter(6, 1);
+        let start = Instant::now();
+
+        assert!(limiter.check("a", InteractionClass::Write, start).is_ok());
+        assert_eq!(limiter.check("a", InteractionClass::Write, start), Err(10));
+    }
+
+    #[test]
+    fn idle_buckets_are_swept_periodically() {
+        let limiter = limiter(60, 1);
+        let start = Instant::now();
+
+        assert!(limiter.check("a", InteractionClass::Read, start).is_ok());
+        assert!(limiter.check("b", InteractionClass::Read, start).is_ok());
+
+        // Both buckets have refilled by the next sweep and are dropped; "b" starts a new one
+        let sweep = start + SWEEP_INTERVAL;
+        assert!(limiter.check("b", InteractionClass::Read, sweep).is_ok());
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.entries.len(), 1);
+        assert!(buckets.next_sweep > sweep);
+    }
+
+    #[test]
+    fn forwarded_headers_are_only_trusted_from_trusted_proxies() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let trusted = HashSet::from([proxy]);
+        let mut headers = HeaderMap::new();
+        let forwarded = "198.51.100.1, 203.0.113.7";
+        headers.insert("x-forwarded-for", forwarded.parse().unwrap());
+
+        // A direct client cannot choose its key
+        assert_eq!(client_ip(&headers, client, &trusted), client);
+        assert_eq!(client_ip(&headers, client, &HashSet::new()), client);
+
+        // Behind a trusted proxy the last untrusted hop is the client; spoofed hops are ignored
+        assert_eq!(client_ip(&headers, proxy, &trusted), client);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", "203.0.113.7".parse().unwrap());
+        assert_eq!(client_ip(&headers, proxy, &trusted), client);
+        assert_eq!(client_ip(&HeaderMap::new(), proxy, &trusted), proxy);
+    }
+
+    #[test]
+    fn searches_via_post_are_reads() {
+        assert_eq!(
+            InteractionClass::of(&Method::POST, "/Patient/_search"),
+            InteractionClass::Read
+        );
+        assert_eq!(
+            InteractionClass::of(&Method::POST, "/Patient"),
+            InteractionClass::Write
+        );
+        assert_eq!(
+            InteractionClass::of(&Method::HEAD, "/Patient/1"),
+            InteractionClass::Read
+        );
+    }
+}
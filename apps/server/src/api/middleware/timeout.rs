@@ -0,0 +1,103 @@
+//! Per-request timeout for FHIR REST interactions
+
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Cancels requests that run longer than the configured timeout with 504 Gateway Timeout.
+///
+/// The handler future is dropped on timeout, which stops any further work for the request.
+/// Queries already sent to Postgres are bounded separately by the pool's statement timeout.
+pub async fn request_timeout_middleware(
+    State(timeout): State<Duration>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+
+    match tokio::time::timeout(timeout, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!(
+                method = %method,
+                path = %path,
+                timeout_ms = timeout.as_millis() as u64,
+                "Request timed out"
+            );
+            crate::Error::Timeout(format!(
+                "request exceeded the {}s limit",
+                timeout.as_secs_f64()
+            ))
+            .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, routing::get, Router};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn router(timeout: Duration, finished: Arc<AtomicBool>) -> Router {
+        Router::new()
+            .route(
+                "/slow",
+                get(move || async move {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    finished.store(true, Ordering::SeqCst);
+                    "done"
+                }),
+            )
+            .route("/fast", get(|| async { "done" }))
+            .layer(axum::middleware::from_fn_with_state(
+                timeout,
+                request_timeout_middleware,
+            ))
+    }
+
+    #[tokio::test]
+    async fn slow_request_is_cut_off_with_gateway_timeout() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let app = router(Duration::from_millis(50), finished.clone());
+
+        let started = std::time::Instant::now();
+        let response = app
+            .oneshot(Request::get("/slow").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let outcome: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(outcome["resourceType"], "OperationOutcome");
+        assert_eq!(outcome["issue"][0]["code"], "timeout");
+
+        // The handler was cancelled rather than left running in the background
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn fast_request_is_unaffected() {
+        let app = router(Duration::from_secs(1), Arc::new(AtomicBool::new(false)));
+
+        let response = app
+            .oneshot(Request::get("/fast").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
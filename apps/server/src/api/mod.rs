@@ -19,7 +19,11 @@ use axum::{
     Router,
 };
 use serde_json::json;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
 use tower::Layer;
+use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::normalize_path::{NormalizePath, NormalizePathLayer};
 use tower_http::services::{ServeDir, ServeFile};
 
@@ -30,12 +34,36 @@ use tower_http::services::{ServeDir, ServeFile};
 pub fn create_router(state: AppState) -> NormalizePath<Router> {
     // Get request body size limit from config
     let max_body_size = state.config.server.max_request_body_size;
-    let cors_origins = state.config.server.cors_origins.clone();
+    let cors = middleware::cors(&state.config.server);
+    let compression = state.config.server.compression.clone();
     let fhir_auth_state = state.clone();
     let fhir_audit_state = state.clone();
     let admin_auth_state = state.clone();
 
-    let fhir_router = routes::fhir::fhir_routes()
+    let mut fhir_router = routes::fhir::fhir_routes();
+    if let Some(seconds) = state.config.server.request_timeout_seconds {
+        fhir_router = fhir_router.layer(axum::middleware::from_fn_with_state(
+            Duration::from_secs(seconds),
+            middleware::request_timeout_middleware,
+        ));
+    }
+    // Rate limiting runs after authentication so clients are keyed by subject when known
+    if state.config.server.rate_limit.enabled {
+        let limiter = Arc::new(middleware::RateLimiter::new(
+            &state.config.server.rate_limit,
+        ));
+        fhir_router = fhir_router.layer(axum::middleware::from_fn_with_state(
+            limiter,
+            middleware::rate_limit_middleware,
+        ));
+    }
+    if state.config.logging.audit.read_access.enabled {
+        fhir_router = fhir_router.layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::read_access_audit_middleware,
+        ));
+    }
+    let fhir_router = fhir_router
         .layer(axum::middleware::from_fn_with_state(
             fhir_audit_state,
             middleware::audit_middleware,
@@ -50,8 +78,9 @@ pub fn create_router(state: AppState) -> NormalizePath<Router> {
     ));
 
     let mut router = Router::new()
-        // Health check
+        // Liveness and readiness checks
         .route("/health", get(health_check))
+        .route("/ready", get(readiness_check))
         // Root endpoint — redirect to UI if serving static files, otherwise JSON info
         .route("/", get(root_redirect))
         // Favicon handler (returns 204 to prevent 404 logs)
@@ -81,10 +110,17 @@ pub fn create_router(state: AppState) -> NormalizePath<Router> {
         ))
         .layer(axum::middleware::from_fn(middleware::request_id_middleware))
         .layer(axum::middleware::from_fn(middleware::metrics_middleware))
-        .layer(middleware::compression())
-        .layer(middleware::cors(&cors_origins))
+        .layer(middleware::compression(&compression))
+        // Limit request body size to prevent DoS via large payloads. Both sit inside request
+        // decompression so the limit applies to the decoded body, not the compressed one.
+        .layer(RequestBodyLimitLayer::new(max_body_size))
+        .layer(axum::middleware::from_fn_with_state(
+            max_body_size,
+            middleware::request_body_limit_middleware,
+        ))
+        .layer(middleware::request_decompression(&compression))
+        .layer(cors)
         .layer(middleware::trace())
-        // Limit request body size to prevent DoS via large payloads
         .layer(DefaultBodyLimit::max(max_body_size));
 
     // NormalizePath wraps the entire service so trailing slashes are stripped
@@ -100,6 +136,40 @@ async fn health_check() -> impl IntoResponse {
     }))
 }
 
+/// Upper bound for the readiness database checks so probes never hang.
+const READINESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+async fn readiness_check(State(state): State<AppState>) -> impl IntoResponse {
+    let (database, migrations) = tokio::time::timeout(READINESS_TIMEOUT, async {
+        let database = state.metrics_service.database_reachable().await;
+        let migrations = database && state.metrics_service.migrations_applied().await;
+        (database, migrations)
+    })
+    .await
+    .unwrap_or((false, false));
+    let packages = state.packages_loaded.load(Ordering::Acquire);
+
+    let ready = database && migrations && packages;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": if ready { "ready" } else { "not_ready" },
+            "service": "fhir-server",
+            "checks": {
+                "database": database,
+                "migrations": migrations,
+                "packages": packages
+            }
+        })),
+    )
+}
+
 async fn root_redirect(State(state): State<AppState>) -> impl IntoResponse {
     // If UI static files are configured, redirect to the UI
     if state.config.ui.static_dir.is_some() {
@@ -26,6 +26,7 @@ pub fn admin_routes() -> Router<AppState> {
         .route("/jobs/cleanup", post(jobs::cleanup_old_jobs))
         .route("/jobs/:id", get(jobs::get_job).delete(jobs::delete_job))
         .route("/jobs/:id/cancel", post(jobs::cancel_job))
+        .route("/jobs/:id/retry", post(jobs::retry_job))
         // Operations
         .route("/operations", get(admin::list_operations))
         // Terminology
@@ -65,6 +66,10 @@ pub fn admin_routes() -> Router<AppState> {
             "/search/hash-collisions",
             get(admin::get_search_hash_collisions),
         )
+        .route(
+            "/search/maintenance",
+            post(admin::run_search_index_maintenance),
+        )
         // Compartment memberships
         .route(
             "/compartments/memberships",
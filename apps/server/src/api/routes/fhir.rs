@@ -38,6 +38,10 @@ pub fn fhir_routes() -> Router<AppState> {
         )
         // Metadata
         .route("/metadata", get(metadata::capability_statement))
+        .route(
+            "/metadata/:resource_type",
+            get(metadata::resource_search_parameters),
+        )
         // System-level search (must come before /_history to match exactly)
         .route("/_search", post(search::search_system))
         // System-level operations (before /_history)
@@ -1,9 +1,11 @@
 //! Admin repository - server statistics queries.
 
+use crate::queue::Job;
 use crate::services::admin::{
     AuditEventAdminDetail, AuditEventAdminListItem, SearchHashCollisionStatus,
-    SearchIndexTableStatus, SearchParameterAdminListItem, SearchParameterIndexingStatus,
-    TransactionAdminDetail, TransactionAdminListItem, TransactionEntryItem,
+    SearchIndexMaintenanceTable, SearchIndexTableStatus, SearchParameterAdminListItem,
+    SearchParameterIndexingStatus, TransactionAdminDetail, TransactionAdminListItem,
+    TransactionEntryItem,
 };
 use crate::Result;
 use chrono::{DateTime, Utc};
@@ -96,6 +98,50 @@ impl AdminRepository {
         Ok(status)
     }
 
+    /// Run `ANALYZE` (or `VACUUM (ANALYZE)`) on each search index table in turn.
+    ///
+    /// Neither takes locks that block reads or writes, and `SKIP_LOCKED` skips a table whose
+    /// lock is held (e.g. by a concurrent reindex) instead of waiting for it.
+    pub async fn run_search_index_maintenance(
+        &self,
+        vacuum: bool,
+    ) -> Result<Vec<SearchIndexMaintenanceTable>> {
+        // Read the catalog directly: check_search_index_status() counts every table's rows and
+        // only looks at `public`, while the pool may be pointed at another schema
+        let tables: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT c.relname::text
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE n.nspname = current_schema()
+              AND c.relname LIKE 'search\_%'
+              AND c.relkind = 'r'
+            ORDER BY c.relname
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let command = if vacuum {
+            "VACUUM (ANALYZE, SKIP_LOCKED)"
+        } else {
+            "ANALYZE (SKIP_LOCKED)"
+        };
+
+        let mut results = Vec::with_capacity(tables.len());
+        for table_name in tables {
+            let started = std::time::Instant::now();
+            // VACUUM cannot run inside a transaction block, so use the simple query protocol
+            let sql = format!("{} \"{}\"", command, table_name.replace('"', "\"\""));
+            sqlx::raw_sql(&sql).execute(&self.pool).await?;
+            results.push(SearchIndexMaintenanceTable {
+                table_name,
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+        Ok(results)
+    }
+
     pub async fn list_search_parameters(
         &self,
         q: Option<&str>,
@@ -821,6 +867,96 @@ impl AdminRepository {
 
         Ok(rows)
     }
+
+    // =========================================================================
+    // Job queue introspection
+    // =========================================================================
+
+    pub async fn list_jobs(
+        &self,
+        job_type: Option<&str>,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Job>, i64)> {
+        crate::queue::list_jobs(&self.pool, job_type, status, limit, offset).await
+    }
+
+    pub async fn job_exists(&self, id: Uuid) -> Result<bool> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM jobs WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(crate::Error::Database)?;
+
+        Ok(exists)
+    }
+
+    /// Reset a failed or cancelled job to `pending`, clearing its error state, retry count and
+    /// progress.
+    ///
+    /// Returns `None` if the job does not exist or is not in a retryable state.
+    pub async fn retry_job(&self, id: Uuid) -> Result<Option<Job>> {
+        let job = sqlx::query_as::<_, Job>(
+            r#"
+            UPDATE jobs
+            SET status = 'pending',
+                retry_count = 0,
+                progress = NULL,
+                processed_items = 0,
+                total_items = NULL,
+                error_message = NULL,
+                last_error_at = NULL,
+                scheduled_at = NULL,
+                started_at = NULL,
+                completed_at = NULL,
+                worker_id = NULL,
+                cancel_requested = FALSE
+            WHERE id = $1 AND status IN ('failed', 'cancelled')
+            RETURNING id, job_type, status, priority, parameters, progress,
+                      retry_policy, retry_count, processed_items, total_items,
+                      error_message, last_error_at, scheduled_at, cancel_requested,
+                      created_at, started_at, completed_at, worker_id
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(crate::Error::Database)?;
+
+        if let Some(ref job) = job {
+            // Wake up workers listening for this job type
+            sqlx::query("SELECT pg_notify('job_queue', $1)")
+                .bind(&job.job_type)
+                .execute(&self.pool)
+                .await
+                .map_err(crate::Error::Database)?;
+        }
+
+        Ok(job)
+    }
+
+    /// Cancel a job: pending and failed jobs are cancelled immediately, running jobs are asked
+    /// to stop via `cancel_requested`.
+    ///
+    /// Returns `false` if the job does not exist or is already completed or cancelled.
+    pub async fn cancel_job(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE jobs
+            SET status = CASE WHEN status = 'running' THEN status ELSE 'cancelled' END,
+                completed_at = CASE WHEN status = 'running' THEN completed_at ELSE NOW() END,
+                cancel_requested = TRUE
+            WHERE id = $1 AND status IN ('pending', 'failed', 'running')
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::Error::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, sqlx::FromRow)]
@@ -10,6 +10,8 @@ pub struct SearchParameterInfo {
     pub resource_type: String,
     pub code: String,
     pub param_type: String,
+    pub expression: Option<String>,
+    pub url: Option<String>,
     pub description: Option<String>,
     pub targets: Option<Vec<String>>,
 }
@@ -32,6 +34,8 @@ impl MetadataRepository {
                 resource_type,
                 code,
                 type,
+                expression,
+                url,
                 description,
                 targets
             FROM search_parameters
@@ -44,18 +48,35 @@ impl MetadataRepository {
             .await
             .map_err(crate::Error::Database)?;
 
-        let mut params = Vec::new();
-        for row in rows {
-            params.push(SearchParameterInfo {
-                resource_type: row.get("resource_type"),
-                code: row.get("code"),
-                param_type: row.get("type"),
-                description: row.get("description"),
-                targets: row.get("targets"),
-            });
-        }
+        Ok(rows.iter().map(search_parameter_info_from_row).collect())
+    }
+
+    /// Get the active search parameters of a single resource type
+    pub async fn get_search_parameters_for_resource(
+        &self,
+        resource_type: &str,
+    ) -> Result<Vec<SearchParameterInfo>> {
+        let query = r#"
+            SELECT
+                resource_type,
+                code,
+                type,
+                expression,
+                url,
+                description,
+                targets
+            FROM search_parameters
+            WHERE resource_type = $1 AND active = true
+            ORDER BY code
+        "#;
 
-        Ok(params)
+        let rows = sqlx::query(query)
+            .bind(resource_type)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::Error::Database)?;
+
+        Ok(rows.iter().map(search_parameter_info_from_row).collect())
     }
 
     /// Get search parameters grouped by resource type
@@ -103,3 +124,15 @@ impl MetadataRepository {
         Ok(resource_types)
     }
 }
+
+fn search_parameter_info_from_row(row: &sqlx::postgres::PgRow) -> SearchParameterInfo {
+    SearchParameterInfo {
+        resource_type: row.get("resource_type"),
+        code: row.get("code"),
+        param_type: row.get("type"),
+        expression: row.get("expression"),
+        url: row.get("url"),
+        description: row.get("description"),
+        targets: row.get("targets"),
+    }
+}
@@ -23,6 +23,19 @@ impl MetricsRepository {
         Ok(result.0)
     }
 
+    /// Cheap connectivity probe (`SELECT 1`)
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Get versions of all successfully applied migrations
+    pub async fn get_applied_migration_versions(&self) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success ORDER BY version")
+            .fetch_all(&self.pool)
+            .await
+    }
+
     /// Get connection pool size (for metrics)
     pub fn get_pool_size(&self) -> u32 {
         self.pool.size()
@@ -32,4 +45,9 @@ impl MetricsRepository {
     pub fn get_num_idle(&self) -> usize {
         self.pool.num_idle()
     }
+
+    /// Get maximum pool size (for metrics)
+    pub fn get_max_pool_size(&self) -> u32 {
+        self.pool.options().get_max_connections()
+    }
 }
@@ -5,6 +5,7 @@ pub mod indexing;
 pub mod metadata;
 pub mod metrics;
 pub mod packages;
+pub mod pool;
 pub mod resolver;
 pub mod runtime_config;
 pub mod search;
@@ -23,3 +24,6 @@ pub use store::PostgresResourceStore;
 pub use terminology::TerminologyRepository;
 pub use traits::{ResourceStore, ResourceTransaction, TransactionContext};
 pub use transaction::PostgresTransactionContext;
+
+/// Database migrations embedded at compile time.
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
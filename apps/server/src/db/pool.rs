@@ -0,0 +1,53 @@
+//! Connection pool helpers
+
+use sqlx::{PgPool, Postgres, Transaction};
+use std::time::{Duration, Instant};
+
+/// Open `connections` pooled connections up front and return them to the pool as idle.
+///
+/// sqlx only opens connections on demand, so without warmup the first requests after startup
+/// pay for connection setup (TLS, auth, `after_connect` statements).
+pub async fn warm_up(pool: &PgPool, connections: u32) -> Result<(), sqlx::Error> {
+    let connections = connections.min(pool.options().get_max_connections());
+    let started = Instant::now();
+
+    let held = futures::future::try_join_all((0..connections).map(|_| pool.acquire())).await?;
+    drop(held);
+
+    tracing::info!(
+        "Database pool warmed up with {} connections in {:?}",
+        connections,
+        started.elapsed()
+    );
+    Ok(())
+}
+
+/// Begin a transaction, waiting at most `timeout` for a pooled connection.
+///
+/// An exhausted pool (e.g. more concurrent jobs than connections) fails with
+/// [`crate::Error::Timeout`] instead of blocking the caller. The wait is recorded in
+/// `fhir_db_pool_acquire_wait_seconds` under `operation`.
+pub async fn begin_with_timeout(
+    pool: &PgPool,
+    timeout: Duration,
+    operation: &str,
+) -> crate::Result<Transaction<'static, Postgres>> {
+    let started = Instant::now();
+    let result = tokio::time::timeout(timeout, pool.begin()).await;
+    crate::metrics::DB_POOL_ACQUIRE_WAIT_SECONDS
+        .with_label_values(&[operation])
+        .observe(started.elapsed().as_secs_f64());
+
+    match result {
+        Ok(Ok(tx)) => Ok(tx),
+        Ok(Err(sqlx::Error::PoolTimedOut)) | Err(_) => Err(crate::Error::Timeout(format!(
+            "no database connection available for {} within {:?} (pool max {}, in use {}); \
+             increase the pool size or lower workers.max_concurrent_jobs",
+            operation,
+            timeout,
+            pool.options().get_max_connections(),
+            (pool.size() as usize).saturating_sub(pool.num_idle()),
+        ))),
+        Ok(Err(e)) => Err(crate::Error::Database(e)),
+    }
+}
@@ -5,6 +5,7 @@
 //! - Executing searches against the database
 //! - Handling _include and _revinclude
 //! - Managing pagination and result limits
+//! - Observation/$lastn grouping over the token/date indexes
 
 use crate::db::search::{params, query_builder};
 use crate::runtime_config::RuntimeConfigCache;
@@ -12,6 +13,7 @@ use serde_json::Value as JsonValue;
 use sqlx::PgPool;
 use std::sync::Arc;
 
+pub use lastn::LastNCategory;
 pub use params::SearchParameters;
 pub use query_builder::QueryBuilder;
 
@@ -20,6 +22,7 @@ mod compartments;
 mod execute;
 mod filter;
 mod includes;
+mod lastn;
 mod normalize;
 mod resolve;
 mod sort;
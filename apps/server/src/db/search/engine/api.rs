@@ -88,6 +88,9 @@ impl SearchEngine {
                 )
             };
 
+        // The default page size is capped like an explicit `_count`
+        let default_count = default_count.min(max_count);
+
         // Validate search parameters against configured limits
         params.validate_limits(
             max_count,
@@ -0,0 +1,100 @@
+use super::SearchEngine;
+use crate::Result;
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+
+/// Token filter for `$lastn` (`[system|]code`).
+#[derive(Debug, Clone)]
+pub struct LastNCategory {
+    pub system: Option<String>,
+    pub code: String,
+}
+
+impl SearchEngine {
+    /// Observation/$lastn: the `max` most recent Observations per subject and code.
+    ///
+    /// Groups are keyed on the indexed `subject` reference and `code` token (system + code),
+    /// and each group is ordered by the indexed `date` (effective[x]), newest first.
+    /// An Observation with several codings appears once, in the first group it qualifies for.
+    pub async fn lastn_observations(
+        &self,
+        max: i64,
+        patient_id: Option<&str>,
+        category: Option<&LastNCategory>,
+    ) -> Result<Vec<JsonValue>> {
+        let rows: Vec<(String, JsonValue)> = sqlx::query_as(
+            r#"
+            WITH ranked AS (
+                SELECT
+                    r.id,
+                    r.resource,
+                    COALESCE(code.system, '') AS code_system,
+                    code.code AS code,
+                    eff.effective,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY
+                            COALESCE(subj.target_type || '/' || subj.target_id, ''),
+                            COALESCE(code.system, ''),
+                            code.code
+                        ORDER BY eff.effective DESC NULLS LAST, r.last_updated DESC
+                    ) AS rn
+                FROM resources r
+                JOIN search_token code
+                  ON code.resource_type = r.resource_type
+                 AND code.resource_id = r.id
+                 AND code.version_id = r.version_id
+                 AND code.parameter_name = 'code'
+                LEFT JOIN LATERAL (
+                    SELECT MAX(sd.start_date) AS effective
+                    FROM search_date sd
+                    WHERE sd.resource_type = r.resource_type
+                      AND sd.resource_id = r.id
+                      AND sd.version_id = r.version_id
+                      AND sd.parameter_name = 'date'
+                ) eff ON TRUE
+                LEFT JOIN LATERAL (
+                    SELECT sr.target_type, sr.target_id
+                    FROM search_reference sr
+                    WHERE sr.resource_type = r.resource_type
+                      AND sr.resource_id = r.id
+                      AND sr.version_id = r.version_id
+                      AND sr.parameter_name = 'subject'
+                    LIMIT 1
+                ) subj ON TRUE
+                WHERE r.resource_type = 'Observation'
+                  AND r.is_current = TRUE
+                  AND r.deleted = FALSE
+                  AND ($2::text IS NULL OR (subj.target_type = 'Patient' AND subj.target_id = $2))
+                  AND ($3::text IS NULL OR EXISTS (
+                      SELECT 1
+                      FROM search_token cat
+                      WHERE cat.resource_type = r.resource_type
+                        AND cat.resource_id = r.id
+                        AND cat.version_id = r.version_id
+                        AND cat.parameter_name = 'category'
+                        AND cat.code = $3
+                        AND ($4::text IS NULL OR cat.system = $4)
+                  ))
+            )
+            SELECT id, resource
+            FROM ranked
+            WHERE rn <= $1
+            ORDER BY code_system, code, effective DESC NULLS LAST
+            "#,
+        )
+        .bind(max)
+        .bind(patient_id)
+        .bind(category.map(|c| c.code.as_str()))
+        .bind(category.and_then(|c| c.system.as_deref()))
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(crate::Error::Database)?;
+
+        let mut seen = HashSet::new();
+        Ok(rows
+            .into_iter()
+            .filter(|(id, _)| seen.insert(id.clone()))
+            .map(|(_, resource)| resource)
+            .collect())
+    }
+}
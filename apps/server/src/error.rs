@@ -5,7 +5,7 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde_json::{json, Value as JsonValue};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -86,64 +86,130 @@ pub enum Error {
     #[error("Operation too costly: {0}")]
     TooCostly(String),
 
+    #[error("Too many requests; retry after {retry_after_seconds} seconds")]
+    TooManyRequests { retry_after_seconds: u64 },
+
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+
+    #[error("Request body too large: {0}")]
+    PayloadTooLarge(String),
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
-impl IntoResponse for Error {
-    fn into_response(self) -> Response {
-        let (status, error_message, etag) = match &self {
-            Error::ResourceNotFound { .. } => (StatusCode::NOT_FOUND, self.to_string(), None),
-            Error::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string(), None),
-            Error::ResourceDeleted { version_id, .. } => {
-                (StatusCode::GONE, self.to_string(), *version_id)
-            }
-            Error::VersionNotFound { .. } => (StatusCode::NOT_FOUND, self.to_string(), None),
-            Error::InvalidResource(_) | Error::Validation(_) | Error::InvalidReference(_) => {
-                (StatusCode::BAD_REQUEST, self.to_string(), None)
+impl Error {
+    /// HTTP status returned for this error.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Error::ResourceNotFound { .. } | Error::NotFound(_) | Error::VersionNotFound { .. } => {
+                StatusCode::NOT_FOUND
             }
-            Error::BusinessRule(_) => (StatusCode::CONFLICT, self.to_string(), None),
-            Error::MethodNotAllowed(_) => (StatusCode::METHOD_NOT_ALLOWED, self.to_string(), None),
-            Error::VersionConflict { .. } => {
-                (StatusCode::PRECONDITION_FAILED, self.to_string(), None)
+            Error::ResourceDeleted { .. } => StatusCode::GONE,
+            Error::InvalidResource(_)
+            | Error::Validation(_)
+            | Error::InvalidReference(_)
+            | Error::Search(_) => StatusCode::BAD_REQUEST,
+            Error::BusinessRule(_) => StatusCode::CONFLICT,
+            Error::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
+            Error::VersionConflict { .. } | Error::PreconditionFailed(_) => {
+                StatusCode::PRECONDITION_FAILED
             }
-            Error::PreconditionFailed(_) => {
-                (StatusCode::PRECONDITION_FAILED, self.to_string(), None)
-            }
-            Error::Search(_) => (StatusCode::BAD_REQUEST, self.to_string(), None),
-            Error::UnsupportedMediaType(_) => {
-                (StatusCode::UNSUPPORTED_MEDIA_TYPE, self.to_string(), None)
-            }
-            Error::UnprocessableEntity(_) => {
-                (StatusCode::UNPROCESSABLE_ENTITY, self.to_string(), None)
+            Error::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Error::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
+            Error::TooCostly(_) => StatusCode::FORBIDDEN,
+            Error::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Error::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            Error::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            // Cancelled by the statement timeout
+            Error::Database(e) if is_query_canceled(e) => StatusCode::GATEWAY_TIMEOUT,
+            Error::Database(_)
+            | Error::JobQueue(_)
+            | Error::FhirContext(_)
+            | Error::FhirPath(_)
+            | Error::ExternalReference(_)
+            | Error::Internal(_)
+            | Error::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// OperationOutcome `issue.code` for this error.
+    pub fn issue_code(&self) -> &'static str {
+        match self {
+            Error::ResourceNotFound { .. } | Error::NotFound(_) | Error::VersionNotFound { .. } => {
+                "not-found"
             }
-            Error::NotImplemented(_) => (StatusCode::NOT_IMPLEMENTED, self.to_string(), None),
-            Error::TooCostly(_) => (StatusCode::FORBIDDEN, self.to_string(), None),
+            Error::ResourceDeleted { .. } => "deleted",
+            Error::InvalidResource(_)
+            | Error::Validation(_)
+            | Error::InvalidReference(_)
+            | Error::Search(_) => "invalid",
+            Error::BusinessRule(_)
+            | Error::VersionConflict { .. }
+            | Error::PreconditionFailed(_) => "conflict",
+            Error::MethodNotAllowed(_)
+            | Error::UnsupportedMediaType(_)
+            | Error::NotImplemented(_) => "not-supported",
+            Error::UnprocessableEntity(_) | Error::FhirPath(_) => "processing",
+            Error::TooCostly(_) | Error::PayloadTooLarge(_) => "too-costly",
+            Error::TooManyRequests { .. } => "throttled",
+            Error::Timeout(_) => "timeout",
+            Error::Database(e) if is_query_canceled(e) => "timeout",
             Error::Database(_)
             | Error::JobQueue(_)
+            | Error::FhirContext(_)
+            | Error::ExternalReference(_)
+            | Error::Internal(_)
+            | Error::Other(_) => "exception",
+        }
+    }
+
+    /// Whether the error is an internal failure whose details must not reach the client.
+    pub fn is_internal(&self) -> bool {
+        match self {
+            Error::Database(e) => !is_query_canceled(e),
+            Error::JobQueue(_)
             | Error::Internal(_)
             | Error::ExternalReference(_)
-            | Error::Other(_) => {
-                tracing::error!("Internal error: {}", self);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error".to_string(),
-                    None,
-                )
-            }
-            Error::FhirContext(_) | Error::FhirPath(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string(), None)
+            | Error::Other(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Diagnostics returned to the client; internal failures get a generic message.
+    pub fn diagnostics(&self) -> String {
+        match self {
+            Error::Database(e) if is_query_canceled(e) => {
+                "Request timed out: database query cancelled".to_string()
             }
-        };
+            _ if self.is_internal() => "Internal server error".to_string(),
+            _ => self.to_string(),
+        }
+    }
 
-        let body = Json(json!({
+    /// OperationOutcome describing this error.
+    pub fn to_operation_outcome(&self) -> JsonValue {
+        json!({
             "resourceType": "OperationOutcome",
             "issue": [{
                 "severity": "error",
-                "code": status_to_fhir_code(status),
-                "diagnostics": error_message
+                "code": self.issue_code(),
+                "diagnostics": self.diagnostics()
             }]
-        }));
+        })
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        if self.is_internal() {
+            tracing::error!("Internal error: {}", self);
+        }
+
+        let status = self.status();
+        let body = Json(self.to_operation_outcome());
 
         let mut response = (status, body).into_response();
 
@@ -155,29 +221,221 @@ impl IntoResponse for Error {
         );
 
         // Per FHIR spec: MAY include ETag on deleted resource errors
-        if let Some(version_id) = etag {
+        if let Error::ResourceDeleted {
+            version_id: Some(version_id),
+            ..
+        } = &self
+        {
             let etag_value = format!("W/\"{}\"", version_id);
             if let Ok(header_value) = etag_value.parse() {
                 response.headers_mut().insert(header::ETAG, header_value);
             }
         }
 
+        if let Error::TooManyRequests {
+            retry_after_seconds,
+        } = self
+        {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_seconds));
+        }
+
         response
     }
 }
 
-fn status_to_fhir_code(status: StatusCode) -> &'static str {
-    match status {
-        StatusCode::BAD_REQUEST => "invalid",
-        StatusCode::NOT_FOUND => "not-found",
-        StatusCode::GONE => "deleted",
-        StatusCode::UNSUPPORTED_MEDIA_TYPE => "not-supported",
-        StatusCode::METHOD_NOT_ALLOWED => "not-supported",
-        StatusCode::CONFLICT => "conflict",
-        StatusCode::PRECONDITION_FAILED => "conflict",
-        StatusCode::UNPROCESSABLE_ENTITY => "processing",
-        StatusCode::NOT_IMPLEMENTED => "not-supported",
-        StatusCode::FORBIDDEN => "too-costly",
-        _ => "exception",
+/// Postgres `query_canceled` (SQLSTATE 57014), raised when `statement_timeout` expires.
+fn is_query_canceled(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "57014")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_each_error_to_status_and_issue_code() {
+        let cases = [
+            (
+                Error::ResourceNotFound {
+                    resource_type: "Patient".into(),
+                    id: "1".into(),
+                },
+                StatusCode::NOT_FOUND,
+                "not-found",
+            ),
+            (
+                Error::ResourceDeleted {
+                    resource_type: "Patient".into(),
+                    id: "1".into(),
+                    version_id: Some(2),
+                },
+                StatusCode::GONE,
+                "deleted",
+            ),
+            (
+                Error::InvalidResource("x".into()),
+                StatusCode::BAD_REQUEST,
+                "invalid",
+            ),
+            (
+                Error::Validation("x".into()),
+                StatusCode::BAD_REQUEST,
+                "invalid",
+            ),
+            (
+                Error::BusinessRule("x".into()),
+                StatusCode::CONFLICT,
+                "conflict",
+            ),
+            (
+                Error::MethodNotAllowed("x".into()),
+                StatusCode::METHOD_NOT_ALLOWED,
+                "not-supported",
+            ),
+            (
+                Error::VersionConflict {
+                    expected: 1,
+                    actual: 2,
+                },
+                StatusCode::PRECONDITION_FAILED,
+                "conflict",
+            ),
+            (
+                Error::PreconditionFailed("x".into()),
+                StatusCode::PRECONDITION_FAILED,
+                "conflict",
+            ),
+            (
+                Error::VersionNotFound {
+                    resource_type: "Patient".into(),
+                    id: "1".into(),
+                    version_id: 3,
+                },
+                StatusCode::NOT_FOUND,
+                "not-found",
+            ),
+            (
+                Error::Search("x".into()),
+                StatusCode::BAD_REQUEST,
+                "invalid",
+            ),
+            (
+                Error::NotFound("x".into()),
+                StatusCode::NOT_FOUND,
+                "not-found",
+            ),
+            (
+                Error::UnsupportedMediaType("x".into()),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "not-supported",
+            ),
+            (
+                Error::UnprocessableEntity("x".into()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "processing",
+            ),
+            (
+                Error::NotImplemented("x".into()),
+                StatusCode::NOT_IMPLEMENTED,
+                "not-supported",
+            ),
+            (
+                Error::JobQueue("x".into()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "exception",
+            ),
+            (
+                Error::FhirContext("x".into()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "exception",
+            ),
+            (
+                Error::FhirPath("x".into()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "processing",
+            ),
+            (
+                Error::InvalidReference("x".into()),
+                StatusCode::BAD_REQUEST,
+                "invalid",
+            ),
+            (
+                Error::ExternalReference("x".into()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "exception",
+            ),
+            (
+                Error::Internal("x".into()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "exception",
+            ),
+            (
+                Error::TooCostly("x".into()),
+                StatusCode::FORBIDDEN,
+                "too-costly",
+            ),
+            (
+                Error::TooManyRequests {
+                    retry_after_seconds: 1,
+                },
+                StatusCode::TOO_MANY_REQUESTS,
+                "throttled",
+            ),
+            (
+                Error::Timeout("x".into()),
+                StatusCode::GATEWAY_TIMEOUT,
+                "timeout",
+            ),
+            (
+                Error::Database(sqlx::Error::PoolTimedOut),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "exception",
+            ),
+            (
+                Error::Other(anyhow::anyhow!("x")),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "exception",
+            ),
+        ];
+
+        for (error, status, code) in cases {
+            assert_eq!(error.status(), status, "{:?}", error);
+            let outcome = error.to_operation_outcome();
+            assert_eq!(outcome["issue"][0]["code"], code, "{:?}", error);
+            assert_eq!(outcome["issue"][0]["severity"], "error");
+        }
+    }
+
+    #[test]
+    fn fhirpath_errors_keep_their_diagnostics() {
+        let error = Error::FhirPath("Unknown function 'foo'".into());
+        assert_eq!(
+            error.to_operation_outcome()["issue"][0]["diagnostics"],
+            "FHIRPath error: Unknown function 'foo'"
+        );
+    }
+
+    #[test]
+    fn internal_errors_do_not_leak_details() {
+        let errors = [
+            Error::Database(sqlx::Error::Protocol(
+                "relation \"resources\" does not exist".into(),
+            )),
+            Error::Internal("connection string postgres://secret".into()),
+            Error::Other(anyhow::anyhow!("secret")),
+        ];
+        for error in errors {
+            let outcome = error.to_operation_outcome();
+            assert_eq!(
+                outcome["issue"][0]["diagnostics"], "Internal server error",
+                "{:?}",
+                error
+            );
+        }
     }
 }
@@ -66,11 +66,14 @@ async fn main() -> anyhow::Result<()> {
         .with_context(|| format!("Failed to bind TCP listener on {addr}"))?;
 
     // Run server with graceful shutdown.
-    // NormalizePath wraps the Router so we use ServiceExt::into_make_service().
+    // NormalizePath wraps the Router so we use ServiceExt::into_make_service_with_connect_info().
+    // The peer address identifies anonymous clients for rate limiting.
     use axum::ServiceExt;
     if let Err(e) = axum::serve(
         listener,
-        <_ as ServiceExt<axum::extract::Request>>::into_make_service(app),
+        <_ as ServiceExt<axum::extract::Request>>::into_make_service_with_connect_info::<
+            std::net::SocketAddr,
+        >(app),
     )
         .with_graceful_shutdown(shutdown_signal())
         .await
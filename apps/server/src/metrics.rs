@@ -140,6 +140,22 @@ lazy_static! {
     )
     .expect("Failed to register DB_CONNECTIONS_IDLE");
 
+    /// Maximum size of the database connection pool
+    pub static ref DB_POOL_MAX_CONNECTIONS: IntGauge = register_int_gauge!(
+        "fhir_db_pool_max_connections",
+        "Maximum number of connections in the database pool"
+    )
+    .expect("Failed to register DB_POOL_MAX_CONNECTIONS");
+
+    /// Time spent waiting for a pooled database connection
+    pub static ref DB_POOL_ACQUIRE_WAIT_SECONDS: HistogramVec = register_histogram_vec!(
+        "fhir_db_pool_acquire_wait_seconds",
+        "Time spent waiting for a database connection from the pool in seconds",
+        &["operation"],
+        vec![0.0001, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0]
+    )
+    .expect("Failed to register DB_POOL_ACQUIRE_WAIT_SECONDS");
+
     // Indexing Metrics
 
     /// Resources indexed
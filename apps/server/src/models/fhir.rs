@@ -34,6 +34,9 @@ pub struct ResourceResult {
 
     /// Operation that was performed
     pub operation: ResourceOperation,
+
+    /// OperationOutcome issues found by lenient write-time validation
+    pub issues: Vec<JsonValue>,
 }
 
 /// Type of operation performed
@@ -7,7 +7,14 @@
 //! be completed before a response is observed.
 
 use super::{Job, JobPriority, JobQueue, JobStatus, RetryPolicy};
-use crate::{db::PostgresResourceStore, services::IndexingService, Result};
+use crate::{
+    db::PostgresResourceStore,
+    services::{
+        bulk_import::{ImportJobParams, BULK_IMPORT_JOB_TYPE},
+        ImportService, IndexingService,
+    },
+    Result,
+};
 use async_trait::async_trait;
 use chrono::Utc;
 use futures::stream::BoxStream;
@@ -25,14 +32,20 @@ struct IndexSearchParams {
 pub struct InlineJobQueue {
     pool: PgPool,
     indexing_service: std::sync::Arc<IndexingService>,
+    import_service: std::sync::Arc<ImportService>,
     jobs: Mutex<HashMap<Uuid, Job>>,
 }
 
 impl InlineJobQueue {
-    pub fn new(pool: PgPool, indexing_service: std::sync::Arc<IndexingService>) -> Self {
+    pub fn new(
+        pool: PgPool,
+        indexing_service: std::sync::Arc<IndexingService>,
+        import_service: std::sync::Arc<ImportService>,
+    ) -> Self {
         Self {
             pool,
             indexing_service,
+            import_service,
             jobs: Mutex::new(HashMap::new()),
         }
     }
@@ -67,6 +80,20 @@ impl InlineJobQueue {
         Ok(())
     }
 
+    async fn run_bulk_import(&self, job_id: Uuid, parameters: serde_json::Value) -> Result<()> {
+        let params: ImportJobParams = serde_json::from_value(parameters).map_err(|e| {
+            crate::Error::Internal(format!("Failed to parse job parameters: {}", e))
+        })?;
+
+        let summary = self.import_service.run(self, job_id, &params).await?;
+        let results = serde_json::to_value(&summary).map_err(|e| {
+            crate::Error::Internal(format!("Failed to serialize import summary: {}", e))
+        })?;
+        self.complete_job(job_id, Some(results)).await?;
+
+        Ok(())
+    }
+
     fn insert_job(&self, job: Job) {
         let mut jobs = self.jobs.lock().unwrap();
         jobs.insert(job.id, job);
@@ -125,6 +152,7 @@ impl JobQueue for InlineJobQueue {
 
         let result = match job_type.as_str() {
             "index_search" => self.run_index_search(job_id, parameters).await,
+            BULK_IMPORT_JOB_TYPE => self.run_bulk_import(job_id, parameters).await,
             // Unsupported jobs are treated as no-ops in inline mode.
             _ => {
                 self.complete_job(job_id, None).await?;
@@ -205,16 +233,22 @@ impl JobQueue for InlineJobQueue {
 
     async fn cancel_job(&self, job_id: Uuid) -> Result<bool> {
         let mut jobs = self.jobs.lock().unwrap();
-        if let Some(job) = jobs.get_mut(&job_id) {
-            job.cancel_requested = true;
-            // Immediately cancel pending jobs
-            if job.status == JobStatus::Pending {
+        let Some(job) = jobs.get_mut(&job_id) else {
+            return Ok(false);
+        };
+        match job.status {
+            // Immediately cancel pending and failed jobs
+            JobStatus::Pending | JobStatus::Failed => {
+                job.cancel_requested = true;
                 job.status = JobStatus::Cancelled;
                 job.completed_at = Some(chrono::Utc::now());
+                Ok(true)
+            }
+            JobStatus::Running | JobStatus::Retrying => {
+                job.cancel_requested = true;
+                Ok(true)
             }
-            Ok(true)
-        } else {
-            Ok(false)
+            JobStatus::Completed | JobStatus::Cancelled => Ok(false),
         }
     }
 
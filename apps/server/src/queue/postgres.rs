@@ -362,14 +362,15 @@ impl JobQueue for PostgresJobQueue {
     async fn cancel_job(&self, job_id: Uuid) -> Result<bool> {
         let now = chrono::Utc::now();
 
-        // Immediately cancel pending jobs (they haven't started)
+        // Immediately cancel pending jobs (they haven't started) and failed jobs (so they are
+        // no longer offered for retry)
         let result = sqlx::query(
             r#"
             UPDATE jobs
             SET status = 'cancelled',
                 cancel_requested = TRUE,
                 completed_at = $1
-            WHERE id = $2 AND status = 'pending'
+            WHERE id = $2 AND status IN ('pending', 'failed')
             "#,
         )
         .bind(now)
@@ -379,7 +380,7 @@ impl JobQueue for PostgresJobQueue {
         .map_err(crate::Error::Database)?;
 
         if result.rows_affected() > 0 {
-            tracing::info!("Job {} cancelled (was pending)", job_id);
+            tracing::info!("Job {} cancelled (was pending or failed)", job_id);
             return Ok(true);
         }
 
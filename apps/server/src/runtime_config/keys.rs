@@ -350,7 +350,7 @@ impl ConfigKey {
 
             // Search
             ConfigKey::SearchDefaultCount => "Default page size when _count is not specified",
-            ConfigKey::SearchMaxCount => "Maximum allowed _count value",
+            ConfigKey::SearchMaxCount => "Maximum page size (larger _count values are clamped)",
             ConfigKey::SearchMaxTotalResults => "Maximum total results across all pages",
             ConfigKey::SearchMaxIncludeDepth => {
                 "Maximum depth for _include:iterate and _revinclude:iterate"
@@ -5,6 +5,7 @@ use crate::{
         AdminRepository, CompartmentMembershipRecord, ReferenceEdge, ResourceTypeStats,
         TerminologySummary,
     },
+    queue::Job,
     Result,
 };
 use chrono::{DateTime, Utc};
@@ -153,6 +154,22 @@ pub struct SearchIndexTableStatus {
     pub size_pretty: String,
 }
 
+/// Outcome of running `ANALYZE` (optionally with `VACUUM`) over the search index tables.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndexMaintenanceReport {
+    pub vacuum: bool,
+    pub tables: Vec<SearchIndexMaintenanceTable>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndexMaintenanceTable {
+    pub table_name: String,
+    pub duration_ms: u64,
+}
+
 #[derive(Debug, Clone, Serialize, sqlx::FromRow)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchHashCollisionStatus {
@@ -241,6 +258,27 @@ pub struct TransactionEntryItem {
     pub error_message: Option<String>,
 }
 
+// =============================================================================
+// Job queue types
+// =============================================================================
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobListQuery {
+    pub job_type: Option<String>,
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct JobListResponse {
+    pub jobs: Vec<Job>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceReferencesResponse {
@@ -379,6 +417,20 @@ impl AdminService {
         self.repo.fetch_search_hash_collisions().await
     }
 
+    /// Refresh planner statistics on the search index tables, e.g. after a large import.
+    pub async fn run_search_index_maintenance(
+        &self,
+        vacuum: bool,
+    ) -> Result<SearchIndexMaintenanceReport> {
+        let started = std::time::Instant::now();
+        let tables = self.repo.run_search_index_maintenance(vacuum).await?;
+        Ok(SearchIndexMaintenanceReport {
+            vacuum,
+            tables,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
     pub async fn list_search_parameters(
         &self,
         query: SearchParameterListQuery,
@@ -447,6 +499,66 @@ impl AdminService {
     pub async fn get_transaction(&self, id: Uuid) -> Result<TransactionAdminDetail> {
         self.repo.get_transaction(id).await
     }
+
+    pub async fn list_jobs(&self, query: JobListQuery) -> Result<JobListResponse> {
+        let limit = query.limit.unwrap_or(50).clamp(1, 1000);
+        let offset = query.offset.unwrap_or(0).max(0);
+
+        let job_type = query
+            .job_type
+            .as_ref()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty());
+        let status = query
+            .status
+            .as_ref()
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty());
+
+        let (jobs, total) = self
+            .repo
+            .list_jobs(job_type, status.as_deref(), limit, offset)
+            .await?;
+
+        Ok(JobListResponse {
+            jobs,
+            total,
+            limit,
+            offset,
+        })
+    }
+
+    /// Cancel a pending, failed or running job. Running jobs stop at their next cancellation
+    /// check.
+    pub async fn cancel_job(&self, id: Uuid) -> Result<()> {
+        if self.repo.cancel_job(id).await? {
+            tracing::info!("Job {} cancelled", id);
+            Ok(())
+        } else {
+            Err(crate::Error::Validation(
+                "Job not found or already completed".to_string(),
+            ))
+        }
+    }
+
+    /// Reset a failed or cancelled job to pending so a worker picks it up again.
+    pub async fn retry_job(&self, id: Uuid) -> Result<Job> {
+        if let Some(job) = self.repo.retry_job(id).await? {
+            tracing::info!("Job {} reset to pending for retry", id);
+            return Ok(job);
+        }
+
+        if self.repo.job_exists(id).await? {
+            Err(crate::Error::Validation(
+                "Only failed or cancelled jobs can be retried".to_string(),
+            ))
+        } else {
+            Err(crate::Error::ResourceNotFound {
+                resource_type: "Job".to_string(),
+                id: id.to_string(),
+            })
+        }
+    }
 }
 
 impl ResourceTypeStatsTotals {
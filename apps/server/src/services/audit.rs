@@ -3,15 +3,23 @@
 //! Emits FHIR `AuditEvent` resources for RESTful operations. These AuditEvents are stored in the
 //! internal `audit_log` table (independent of the clinical `resources` store).
 //!
+//! When read access auditing is configured (`logging.audit.read_access`), reads of sensitive
+//! resource types are additionally stored as `AuditEvent` resources in the `resources` store so
+//! they can be searched through the FHIR API.
+//!
 //! Notes:
 //! - Works with both FHIR R4/R4B and R5 (the AuditEvent shape differs).
 //! - Emission is best-effort and must not fail the primary request path.
 
 use crate::auth::Principal;
+use crate::config::ReadAccessAuditConfig;
 use crate::runtime_config::{ConfigKey, RuntimeConfigCache};
+use crate::services::CrudService;
 use chrono::{SecondsFormat, Utc};
 use serde_json::{json, Value as JsonValue};
 use sqlx::PgPool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 
 #[derive(Debug, Clone)]
@@ -31,12 +39,25 @@ pub struct HttpAuditInput {
     pub operation_outcome: Option<JsonValue>,
 }
 
+/// `AuditEvent.purposeOfEvent` (R4) / `AuditEvent.authorization` (R5) code system.
+const ACT_REASON_SYSTEM: &str = "http://terminology.hl7.org/CodeSystem/v3-ActReason";
+
 #[derive(Clone)]
 pub struct AuditService {
     runtime_config_cache: std::sync::Arc<RuntimeConfigCache>,
     fhir_version: String,
     observer_display: String,
     sender: mpsc::Sender<AuditLogInsert>,
+    read_access: Option<ReadAccessAudit>,
+}
+
+/// Read access auditing settings and the queue of `AuditEvent` resources to store.
+#[derive(Clone)]
+struct ReadAccessAudit {
+    config: ReadAccessAuditConfig,
+    sender: mpsc::Sender<JsonValue>,
+    /// Events dropped because the queue was full
+    dropped: Arc<AtomicU64>,
 }
 
 impl AuditService {
@@ -63,6 +84,83 @@ impl AuditService {
             fhir_version,
             observer_display,
             sender,
+            read_access: None,
+        }
+    }
+
+    /// Enable read access auditing when `config.enabled`; events are stored via `crud_service`
+    /// from a background task.
+    pub fn set_read_access_auditing(
+        &mut self,
+        config: &ReadAccessAuditConfig,
+        crud_service: Arc<CrudService>,
+    ) {
+        if !config.enabled {
+            self.read_access = None;
+            return;
+        }
+
+        let (sender, mut receiver) = mpsc::channel::<JsonValue>(2048);
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                if let Err(e) = crud_service
+                    .create_resource("AuditEvent", event, None)
+                    .await
+                {
+                    tracing::warn!("Failed to store read access AuditEvent: {}", e);
+                }
+            }
+        });
+
+        self.read_access = Some(ReadAccessAudit {
+            config: config.clone(),
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        });
+    }
+
+    /// Whether reads of `resource_type` are stored as `AuditEvent` resources.
+    pub fn audits_read_access(&self, resource_type: &str) -> bool {
+        // Reading the audit trail itself is not audited, so it does not grow on every review
+        self.read_access.as_ref().is_some_and(|read_access| {
+            resource_type != "AuditEvent"
+                && (read_access.config.resource_types.is_empty()
+                    || read_access
+                        .config
+                        .resource_types
+                        .iter()
+                        .any(|t| t == resource_type))
+        })
+    }
+
+    /// Request header carrying the purpose of use of audited reads.
+    pub fn purpose_of_use_header(&self) -> Option<&str> {
+        self.read_access
+            .as_ref()
+            .map(|read_access| read_access.config.purpose_of_use_header.as_str())
+    }
+
+    /// Queue an `AuditEvent` resource for a read of `input.target`.
+    ///
+    /// The event is stored in the background; failures are logged and never affect the read.
+    /// When the queue is full the event is dropped rather than buffered without bound.
+    pub fn record_read_access(&self, input: HttpAuditInput, purpose_of_use: Vec<String>) {
+        let Some(read_access) = &self.read_access else {
+            return;
+        };
+        let event = self.build_read_access_event(input, &purpose_of_use);
+        match read_access.sender.try_send(event) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_event)) => {
+                // Warn on the first drop and then every 1000th to keep the log readable
+                let dropped = read_access.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped == 1 || dropped.is_multiple_of(1000) {
+                    tracing::warn!(dropped, "Read access audit queue full; dropping AuditEvent");
+                }
+            }
+            Err(mpsc::error::TrySendError::Closed(_event)) => {
+                tracing::warn!("Read access audit queue closed; dropping event");
+            }
         }
     }
 
@@ -400,6 +498,31 @@ impl AuditService {
         event
     }
 
+    fn build_read_access_event(
+        &self,
+        input: HttpAuditInput,
+        purpose_of_use: &[String],
+    ) -> JsonValue {
+        let mut event = if self.is_r5() {
+            self.build_r5_http_event(input)
+        } else {
+            self.build_r4_http_event(input)
+        };
+        if !purpose_of_use.is_empty() {
+            let purposes: Vec<JsonValue> = purpose_of_use
+                .iter()
+                .map(|code| json!({ "coding": [{ "system": ACT_REASON_SYSTEM, "code": code }] }))
+                .collect();
+            let key = if self.is_r5() {
+                "authorization"
+            } else {
+                "purposeOfEvent"
+            };
+            event[key] = json!(purposes);
+        }
+        event
+    }
+
     fn build_r4_http_event(&self, input: HttpAuditInput) -> JsonValue {
         let recorded = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
 
@@ -727,4 +850,53 @@ mod tests {
         assert!(evt.get("outcome").is_some());
         assert!(evt.get("entity").is_some());
     }
+
+    #[tokio::test]
+    async fn builds_read_access_event_with_subject_and_purpose() {
+        let config = crate::config::Config::load().unwrap();
+        let cache = std::sync::Arc::new(crate::runtime_config::RuntimeConfigCache::new(
+            std::sync::Arc::new(config),
+        ));
+        let svc = AuditService::new(
+            cache,
+            "R4".to_string(),
+            "FHIR Server".to_string(),
+            dummy_pool(),
+        );
+
+        let evt = svc.build_read_access_event(
+            HttpAuditInput {
+                method: "GET".to_string(),
+                interaction: "read".to_string(),
+                action: "R".to_string(),
+                status: 200,
+                request_id: None,
+                principal: Some(Principal {
+                    subject: "dr-jones".to_string(),
+                    scopes: vec![],
+                    issuer: None,
+                    audience: None,
+                    client_id: None,
+                    patient: None,
+                }),
+                client_ip: None,
+                user_agent: None,
+                target: Some(("Condition".to_string(), "c1".to_string())),
+                patient_id: None,
+                query_base64: None,
+                query_harmonized: None,
+                operation_outcome: None,
+            },
+            &["ETREAT".to_string()],
+        );
+
+        assert_eq!(evt["entity"][0]["what"]["reference"], "Condition/c1");
+        assert_eq!(evt["agent"][0]["who"]["identifier"]["value"], "dr-jones");
+        assert_eq!(evt["agent"][0]["requestor"], true);
+        assert_eq!(evt["purposeOfEvent"][0]["coding"][0]["code"], "ETREAT");
+        assert_eq!(
+            evt["purposeOfEvent"][0]["coding"][0]["system"],
+            ACT_REASON_SYSTEM
+        );
+    }
 }
@@ -12,7 +12,7 @@ use crate::{
     models::UpdateParams,
     queue::{JobPriority, JobQueue},
     runtime_config::RuntimeConfigCache,
-    services::CrudService,
+    services::{CodeTranslator, CrudService, NarrativeGenerator, WriteValidator},
     Result,
 };
 use axum::http::StatusCode;
@@ -47,6 +47,8 @@ pub enum PreferReturn {
 pub struct BundleRequestOptions {
     pub prefer_return: PreferReturn,
     pub base_url: Option<String>,
+    /// Run a transaction to completion and roll it back instead of committing
+    pub validate_only: bool,
 }
 
 use crate::db::admin::{TransactionEntryRecord, TransactionRecorder};
@@ -62,6 +64,10 @@ pub struct BatchService {
     hard_delete: bool,
     runtime_config_cache: Option<Arc<RuntimeConfigCache>>,
     referential_integrity_mode: String,
+    read_only_types: HashSet<String>,
+    write_validator: Option<Arc<WriteValidator>>,
+    narrative_generator: Option<Arc<NarrativeGenerator>>,
+    code_translator: Option<Arc<CodeTranslator>>,
     transaction_recorder: Option<TransactionRecorder>,
 }
 
@@ -84,6 +90,10 @@ impl BatchService {
             hard_delete,
             runtime_config_cache: None,
             referential_integrity_mode: "lenient".to_string(),
+            read_only_types: HashSet::new(),
+            write_validator: None,
+            narrative_generator: None,
+            code_translator: None,
             transaction_recorder: None,
         }
     }
@@ -96,6 +106,22 @@ impl BatchService {
         self.referential_integrity_mode = mode;
     }
 
+    pub fn set_read_only_types(&mut self, types: impl IntoIterator<Item = String>) {
+        self.read_only_types = types.into_iter().collect();
+    }
+
+    pub fn set_write_validator(&mut self, validator: Option<Arc<WriteValidator>>) {
+        self.write_validator = validator;
+    }
+
+    pub fn set_narrative_generator(&mut self, generator: Option<Arc<NarrativeGenerator>>) {
+        self.narrative_generator = generator;
+    }
+
+    pub fn set_code_translator(&mut self, translator: Option<Arc<CodeTranslator>>) {
+        self.code_translator = translator;
+    }
+
     pub fn new_with_runtime_config(
         store: PostgresResourceStore,
         hooks: Vec<Arc<dyn ResourceHook>>,
@@ -266,6 +292,10 @@ impl BatchService {
             )
         };
         crud.set_referential_integrity_mode(self.referential_integrity_mode.clone());
+        crud.set_read_only_types(self.read_only_types.iter().cloned());
+        crud.set_write_validator(self.write_validator.clone());
+        crud.set_narrative_generator(self.narrative_generator.clone());
+        crud.set_code_translator(self.code_translator.clone());
 
         for index in ordered {
             if let Some(err) = pre_errors.get(&index) {
@@ -428,25 +458,28 @@ impl BatchService {
                         )),
                         etag: Some(format!("W/\"{}\"", result.resource.version_id)),
                         last_modified: Some(result.resource.last_updated.to_rfc3339()),
-                        outcome: match prefer_return {
-                            PreferReturn::OperationOutcome => Some(serde_json::json!({
-                                "resourceType": "OperationOutcome",
-                                "issue": [{
-                                    "severity": "information",
-                                    "code": "informational",
-                                    "diagnostics": format!(
-                                        "Resource {} successfully with ID {}",
-                                        match result.operation {
-                                            ResourceOperation::Created => "created",
-                                            ResourceOperation::NoOp => "matched existing resource",
-                                            _ => "processed"
-                                        },
-                                        result.resource.id
-                                    )
-                                }]
-                            })),
-                            _ => None,
-                        },
+                        outcome: outcome_with_issues(
+                            match prefer_return {
+                                PreferReturn::OperationOutcome => Some(serde_json::json!({
+                                    "resourceType": "OperationOutcome",
+                                    "issue": [{
+                                        "severity": "information",
+                                        "code": "informational",
+                                        "diagnostics": format!(
+                                            "Resource {} successfully with ID {}",
+                                            match result.operation {
+                                                ResourceOperation::Created => "created",
+                                                ResourceOperation::NoOp => "matched existing resource",
+                                                _ => "processed"
+                                            },
+                                            result.resource.id
+                                        )
+                                    }]
+                                })),
+                                _ => None,
+                            },
+                            result.issues,
+                        ),
                         extensions: HashMap::new(),
                     }),
                     resource: match prefer_return {
@@ -563,25 +596,28 @@ impl BatchService {
                         )),
                         etag: Some(format!("W/\"{}\"", result.resource.version_id)),
                         last_modified: Some(result.resource.last_updated.to_rfc3339()),
-                        outcome: match prefer_return {
-                            PreferReturn::OperationOutcome => Some(serde_json::json!({
-                                "resourceType": "OperationOutcome",
-                                "issue": [{
-                                    "severity": "information",
-                                    "code": "informational",
-                                    "diagnostics": format!(
-                                        "Resource {} successfully with ID {}",
-                                        match result.operation {
-                                            ResourceOperation::Created => "created",
-                                            ResourceOperation::Updated => "updated",
-                                            _ => "processed"
-                                        },
-                                        result.resource.id
-                                    )
-                                }]
-                            })),
-                            _ => None,
-                        },
+                        outcome: outcome_with_issues(
+                            match prefer_return {
+                                PreferReturn::OperationOutcome => Some(serde_json::json!({
+                                    "resourceType": "OperationOutcome",
+                                    "issue": [{
+                                        "severity": "information",
+                                        "code": "informational",
+                                        "diagnostics": format!(
+                                            "Resource {} successfully with ID {}",
+                                            match result.operation {
+                                                ResourceOperation::Created => "created",
+                                                ResourceOperation::Updated => "updated",
+                                                _ => "processed"
+                                            },
+                                            result.resource.id
+                                        )
+                                    }]
+                                })),
+                                _ => None,
+                            },
+                            result.issues,
+                        ),
                         extensions: HashMap::new(),
                     }),
                     resource: match prefer_return {
@@ -609,11 +645,6 @@ impl BatchService {
                 let patch = parse_json_patch_from_binary(&binary)?;
 
                 let if_match = request.if_match.as_deref().and_then(parse_etag);
-                let update_params = if if_match.is_some() {
-                    Some(UpdateParams { if_match })
-                } else {
-                    None
-                };
 
                 let resource_id = if let Some(resource_id) = parsed_url.resource_id {
                     resource_id
@@ -682,8 +713,17 @@ impl BatchService {
                 )
                 .await?;
 
+                // Write against the version the patch was applied to, so a concurrent
+                // update made after the read is reported as a conflict.
                 let result = crud
-                    .update_resource(&resource_type, &resource_id, patched, update_params)
+                    .update_resource(
+                        &resource_type,
+                        &resource_id,
+                        patched,
+                        Some(UpdateParams {
+                            if_match: Some(current.version_id),
+                        }),
+                    )
                     .await?;
 
                 Ok(BundleEntry {
@@ -697,20 +737,23 @@ impl BatchService {
                         )),
                         etag: Some(format!("W/\"{}\"", result.resource.version_id)),
                         last_modified: Some(result.resource.last_updated.to_rfc3339()),
-                        outcome: match prefer_return {
-                            PreferReturn::OperationOutcome => Some(serde_json::json!({
-                                "resourceType": "OperationOutcome",
-                                "issue": [{
-                                    "severity": "information",
-                                    "code": "informational",
-                                    "diagnostics": format!(
-                                        "Resource patched successfully with ID {}",
-                                        result.resource.id
-                                    )
-                                }]
-                            })),
-                            _ => None,
-                        },
+                        outcome: outcome_with_issues(
+                            match prefer_return {
+                                PreferReturn::OperationOutcome => Some(serde_json::json!({
+                                    "resourceType": "OperationOutcome",
+                                    "issue": [{
+                                        "severity": "information",
+                                        "code": "informational",
+                                        "diagnostics": format!(
+                                            "Resource patched successfully with ID {}",
+                                            result.resource.id
+                                        )
+                                    }]
+                                })),
+                                _ => None,
+                            },
+                            result.issues,
+                        ),
                         extensions: HashMap::new(),
                     }),
                     resource: match prefer_return {
@@ -1117,65 +1160,37 @@ fn partition_entries_for_processing(
 // Response helpers
 // =============================================================================
 
-fn status_line(status: StatusCode) -> String {
-    match status.canonical_reason() {
-        Some(reason) => format!("{} {}", status.as_u16(), reason),
-        None => status.as_u16().to_string(),
+/// Add write-time validation issues to an entry's `response.outcome`, creating an
+/// OperationOutcome when the Prefer setting did not ask for one.
+pub(crate) fn outcome_with_issues(
+    outcome: Option<JsonValue>,
+    issues: Vec<JsonValue>,
+) -> Option<JsonValue> {
+    if issues.is_empty() {
+        return outcome;
     }
-}
-
-fn status_to_fhir_code(status: StatusCode) -> &'static str {
-    match status {
-        StatusCode::BAD_REQUEST => "invalid",
-        StatusCode::NOT_FOUND => "not-found",
-        StatusCode::GONE => "deleted",
-        StatusCode::CONFLICT => "conflict",
-        StatusCode::PRECONDITION_FAILED => "conflict",
-        StatusCode::UNPROCESSABLE_ENTITY => "processing",
-        StatusCode::UNSUPPORTED_MEDIA_TYPE => "not-supported",
-        _ => "exception",
+    let mut outcome = outcome.unwrap_or_else(|| {
+        json!({
+            "resourceType": "OperationOutcome",
+            "issue": []
+        })
+    });
+    if let Some(list) = outcome.get_mut("issue").and_then(JsonValue::as_array_mut) {
+        list.extend(issues);
     }
+    Some(outcome)
 }
 
-fn error_status(err: &crate::Error) -> StatusCode {
-    match err {
-        crate::Error::ResourceNotFound { .. } => StatusCode::NOT_FOUND,
-        crate::Error::VersionNotFound { .. } => StatusCode::NOT_FOUND,
-        crate::Error::NotFound(_) => StatusCode::NOT_FOUND,
-        crate::Error::ResourceDeleted { .. } => StatusCode::GONE,
-        crate::Error::InvalidResource(_)
-        | crate::Error::Validation(_)
-        | crate::Error::InvalidReference(_) => StatusCode::BAD_REQUEST,
-        crate::Error::BusinessRule(_) => StatusCode::CONFLICT,
-        crate::Error::VersionConflict { .. } | crate::Error::PreconditionFailed(_) => {
-            StatusCode::PRECONDITION_FAILED
-        }
-        crate::Error::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
-        crate::Error::Search(_) => StatusCode::BAD_REQUEST,
-        crate::Error::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
-        crate::Error::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
-        crate::Error::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
-        crate::Error::TooCostly(_) => StatusCode::FORBIDDEN,
-        crate::Error::Database(_)
-        | crate::Error::JobQueue(_)
-        | crate::Error::FhirContext(_)
-        | crate::Error::FhirPath(_)
-        | crate::Error::ExternalReference(_)
-        | crate::Error::Internal(_)
-        | crate::Error::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+fn status_line(status: StatusCode) -> String {
+    match status.canonical_reason() {
+        Some(reason) => format!("{} {}", status.as_u16(), reason),
+        None => status.as_u16().to_string(),
     }
 }
 
 fn create_error_entry(full_url: Option<&str>, err: &crate::Error) -> BundleEntry {
-    let status = error_status(err);
-    let outcome = json!({
-        "resourceType": "OperationOutcome",
-        "issue": [{
-            "severity": "error",
-            "code": status_to_fhir_code(status),
-            "diagnostics": err.to_string()
-        }]
-    });
+    let status = err.status();
+    let outcome = err.to_operation_outcome();
 
     BundleEntry {
         full_url: full_url.map(|s| s.to_string()),
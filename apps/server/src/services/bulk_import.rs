@@ -0,0 +1,621 @@
+//! Bulk data import (`$import`)
+//!
+//! Streams NDJSON resources from remote files or inline payloads and loads them in bounded
+//! batches. Each batch is written in a single database transaction (create-or-update, keeping
+//! client ids) and then indexed with `IndexingService::index_resources_auto`, which switches to
+//! the COPY path once a batch reaches the configured bulk threshold.
+//!
+//! Resource hooks are not run for imported resources; conformance resources should be loaded
+//! through package installation instead. Read-only resource types
+//! (`fhir.resource_policies.read_only`) are rejected like any other write.
+//!
+//! `url` inputs are fetched by the server, so only hosts listed in
+//! `fhir.bulk_import.allowed_hosts` are accepted (redirects included), and each download stops
+//! at `fhir.bulk_import.max_download_bytes`.
+
+use crate::{
+    config::BulkImportConfig,
+    db::{PostgresResourceStore, ResourceTransaction, TransactionContext},
+    models::Resource,
+    queue::JobQueue,
+    services::{
+        crud::ensure_type_writable, transaction::populate_meta, IndexingService, WriteValidator,
+    },
+    Error, Result,
+};
+use chrono::Utc;
+use ferrum_context::FhirContext;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
+use uuid::Uuid;
+
+/// Job type name used for `$import` jobs
+pub const BULK_IMPORT_JOB_TYPE: &str = "bulk_import";
+
+/// Maximum number of per-line errors kept in the job results
+const MAX_REPORTED_ERRORS: usize = 100;
+
+/// Parameters of a `bulk_import` job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportJobParams {
+    pub inputs: Vec<ImportInput>,
+    #[serde(default)]
+    pub validation: ImportValidation,
+    /// Resources per database transaction
+    #[serde(default = "ImportJobParams::default_batch_size")]
+    pub batch_size: usize,
+}
+
+impl ImportJobParams {
+    pub fn default_batch_size() -> usize {
+        1000
+    }
+}
+
+/// One NDJSON source: either a URL to fetch or the NDJSON content itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportInput {
+    /// Expected resource type of every line (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ndjson: Option<String>,
+}
+
+/// How much checking is applied to each imported resource
+///
+/// Every line must be a JSON object with a resource type known to the FHIR context and, if it
+/// has one, a valid id.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportValidation {
+    /// No further checks
+    #[default]
+    Skip,
+    /// Also require the resource type to match the input's `type` and run the validator
+    /// (`fhir.validation.preset`, strict); resources with errors are reported as failed lines
+    Validate,
+}
+
+impl ImportValidation {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "skip" => Some(Self::Skip),
+            "validate" => Some(Self::Validate),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of an import job, stored as the job results
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub failed: usize,
+    /// Imported resources per resource type
+    pub counts: BTreeMap<String, usize>,
+    pub errors: Vec<String>,
+    /// The job was cancelled; resources of committed batches stay imported
+    pub cancelled: bool,
+}
+
+impl ImportSummary {
+    fn record_error(&mut self, message: String) {
+        self.failed += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(message);
+        }
+    }
+
+    fn progress(&self) -> JsonValue {
+        json!({
+            "imported": self.imported,
+            "failed": self.failed,
+            "counts": self.counts,
+        })
+    }
+}
+
+pub struct ImportService {
+    store: PostgresResourceStore,
+    indexing_service: Arc<IndexingService>,
+    fhir_context: Arc<dyn FhirContext>,
+    read_only_types: HashSet<String>,
+    allowed_hosts: Arc<Vec<String>>,
+    max_download_bytes: u64,
+    max_batch_size: usize,
+    validator: Option<Arc<WriteValidator>>,
+    http: reqwest::Client,
+}
+
+impl ImportService {
+    pub fn new(
+        store: PostgresResourceStore,
+        indexing_service: Arc<IndexingService>,
+        fhir_context: Arc<dyn FhirContext>,
+        config: &BulkImportConfig,
+    ) -> Result<Self> {
+        let allowed_hosts = Arc::new(config.allowed_hosts.clone());
+        let redirect_hosts = allowed_hosts.clone();
+        let http = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= 10 {
+                    attempt.error("too many redirects")
+                } else if is_allowed_host(&redirect_hosts, attempt.url()) {
+                    attempt.follow()
+                } else {
+                    attempt.error("redirect to a host that is not allowed")
+                }
+            }))
+            .build()
+            .map_err(|e| Error::Internal(format!("Failed to create import HTTP client: {}", e)))?;
+
+        Ok(Self {
+            store,
+            indexing_service,
+            fhir_context,
+            read_only_types: HashSet::new(),
+            allowed_hosts,
+            max_download_bytes: config.max_download_bytes,
+            max_batch_size: config.max_batch_size,
+            validator: None,
+            http,
+        })
+    }
+
+    /// Resource types that cannot be imported (`fhir.resource_policies.read_only`).
+    pub fn set_read_only_types(&mut self, types: impl IntoIterator<Item = String>) {
+        self.read_only_types = types.into_iter().collect();
+    }
+
+    /// Validator run on each resource of jobs with `validation=validate`. Without one, such
+    /// jobs fail instead of importing unvalidated resources.
+    pub fn set_validator(&mut self, validator: Arc<WriteValidator>) {
+        self.validator = Some(validator);
+    }
+
+    /// Whether `name` is a concrete resource type of the FHIR context.
+    fn is_resource_type(&self, name: &str) -> bool {
+        matches!(
+            self.fhir_context.get_core_structure_definition_by_type(name),
+            Ok(Some(sd)) if sd.is_resource() && !sd.is_abstract
+        )
+    }
+
+    /// Run an import job, reporting progress to `job_queue` after every batch.
+    ///
+    /// Invalid lines are counted and reported in the summary; storage and indexing errors
+    /// abort the job. Batches committed before an error stay imported. A cancelled job stops
+    /// after the current batch and returns the summary so far with `cancelled` set.
+    pub async fn run(
+        &self,
+        job_queue: &dyn JobQueue,
+        job_id: Uuid,
+        params: &ImportJobParams,
+    ) -> Result<ImportSummary> {
+        let validator = match params.validation {
+            ImportValidation::Skip => None,
+            ImportValidation::Validate => Some(self.validator.clone().ok_or_else(|| {
+                Error::Internal("Import validation requested but no validator is set".to_string())
+            })?),
+        };
+        let mut run = ImportRun {
+            service: self,
+            job_queue,
+            job_id,
+            validation: params.validation,
+            validator,
+            batch_size: params.batch_size.clamp(1, self.max_batch_size),
+            batch: Vec::new(),
+            known_types: HashMap::new(),
+            summary: ImportSummary::default(),
+        };
+
+        for (idx, input) in params.inputs.iter().enumerate() {
+            if run.summary.cancelled {
+                break;
+            }
+            let label = input
+                .url
+                .clone()
+                .unwrap_or_else(|| format!("input[{}]", idx));
+            let expected_type = input.resource_type.as_deref();
+
+            match (&input.ndjson, &input.url) {
+                (Some(ndjson), _) => {
+                    for (line_no, line) in ndjson.lines().enumerate() {
+                        run.accept_line(&label, line_no + 1, line, expected_type)
+                            .await?;
+                        if run.summary.cancelled {
+                            break;
+                        }
+                    }
+                }
+                (None, Some(url)) => {
+                    self.stream_url(&mut run, &label, url, expected_type)
+                        .await?
+                }
+                (None, None) => {
+                    run.summary
+                        .record_error(format!("{}: input has neither url nor ndjson", label));
+                }
+            }
+        }
+
+        if !run.summary.cancelled {
+            run.flush().await?;
+        }
+        Ok(run.summary)
+    }
+
+    /// Fetch an NDJSON file chunk by chunk so that only the current batch is held in memory.
+    async fn stream_url(
+        &self,
+        run: &mut ImportRun<'_>,
+        label: &str,
+        url: &str,
+        expected_type: Option<&str>,
+    ) -> Result<()> {
+        let parsed = match Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => parsed,
+            _ => {
+                run.summary
+                    .record_error(format!("{}: only http(s) URLs are supported", label));
+                return Ok(());
+            }
+        };
+        if !is_allowed_host(&self.allowed_hosts, &parsed) {
+            run.summary.record_error(format!(
+                "{}: host is not listed in fhir.bulk_import.allowed_hosts",
+                label
+            ));
+            return Ok(());
+        }
+        let too_large = || {
+            format!(
+                "{}: file exceeds fhir.bulk_import.max_download_bytes ({} bytes)",
+                label, self.max_download_bytes
+            )
+        };
+
+        let mut response = match self.http.get(parsed).send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                run.summary
+                    .record_error(format!("{}: HTTP {}", label, response.status()));
+                return Ok(());
+            }
+            Err(e) => {
+                run.summary.record_error(format!("{}: {}", label, e));
+                return Ok(());
+            }
+        };
+        if response
+            .content_length()
+            .is_some_and(|length| length > self.max_download_bytes)
+        {
+            run.summary.record_error(too_large());
+            return Ok(());
+        }
+
+        let mut pending: Vec<u8> = Vec::new();
+        let mut line_no = 0usize;
+        let mut downloaded = 0u64;
+        loop {
+            let chunk = match response.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(e) => {
+                    run.summary.record_error(format!("{}: {}", label, e));
+                    return Ok(());
+                }
+            };
+            downloaded += chunk.len() as u64;
+            if downloaded > self.max_download_bytes {
+                run.summary.record_error(too_large());
+                return Ok(());
+            }
+
+            // Bytes already in `pending` were scanned and hold no newline
+            let mut scan_from = pending.len();
+            pending.extend_from_slice(&chunk);
+            while let Some(offset) = pending[scan_from..].iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=scan_from + offset).collect();
+                scan_from = 0;
+                line_no += 1;
+                let line = String::from_utf8_lossy(&line);
+                run.accept_line(label, line_no, &line, expected_type)
+                    .await?;
+                if run.summary.cancelled {
+                    return Ok(());
+                }
+            }
+        }
+
+        if !pending.is_empty() {
+            line_no += 1;
+            let line = String::from_utf8_lossy(&pending);
+            run.accept_line(label, line_no, &line, expected_type)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Mutable state of a single import job
+struct ImportRun<'a> {
+    service: &'a ImportService,
+    job_queue: &'a dyn JobQueue,
+    job_id: Uuid,
+    validation: ImportValidation,
+    /// Set when `validation` is `Validate`
+    validator: Option<Arc<WriteValidator>>,
+    batch_size: usize,
+    batch: Vec<(String, JsonValue)>,
+    /// Resource type names already looked up in the FHIR context
+    known_types: HashMap<String, bool>,
+    summary: ImportSummary,
+}
+
+impl ImportRun<'_> {
+    async fn accept_line(
+        &mut self,
+        label: &str,
+        line_no: usize,
+        line: &str,
+        expected_type: Option<&str>,
+    ) -> Result<()> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        let service = self.service;
+        let known_types = &mut self.known_types;
+        let is_resource_type = |resource_type: &str| {
+            *known_types
+                .entry(resource_type.to_string())
+                .or_insert_with(|| service.is_resource_type(resource_type))
+        };
+        let parsed = parse_line(line, expected_type, self.validation, is_resource_type).and_then(
+            |(resource_type, resource)| {
+                ensure_type_writable(&service.read_only_types, &resource_type)
+                    .map_err(|_| format!("resource type {} is read-only", resource_type))?;
+                Ok((resource_type, resource))
+            },
+        );
+
+        let parsed = match (parsed, &self.validator) {
+            (Ok((resource_type, resource)), Some(validator)) => {
+                match validator.clone().check_blocking(resource.clone()).await {
+                    Ok(_) => Ok((resource_type, resource)),
+                    Err(Error::UnprocessableEntity(message)) => Err(message),
+                    Err(e) => return Err(e),
+                }
+            }
+            (parsed, _) => parsed,
+        };
+
+        match parsed {
+            Ok(entry) => {
+                self.batch.push(entry);
+                if self.batch.len() >= self.batch_size {
+                    self.flush().await?;
+                }
+            }
+            Err(message) => self
+                .summary
+                .record_error(format!("{} line {}: {}", label, line_no, message)),
+        }
+        Ok(())
+    }
+
+    /// Write the pending batch in one transaction, index it and report progress.
+    async fn flush(&mut self) -> Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+
+        let batch = std::mem::take(&mut self.batch);
+        let mut tx = self.service.store.begin_transaction().await?;
+        let mut written: Vec<Resource> = Vec::with_capacity(batch.len());
+
+        for (resource_type, mut resource) in batch {
+            let id = resource
+                .get("id")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+            let version_id = match tx.read(&resource_type, &id).await? {
+                Some(existing) => existing.version_id + 1,
+                None => 1,
+            };
+            populate_meta(&mut resource, &id, version_id, Utc::now());
+
+            written.push(tx.upsert(&resource_type, &id, resource).await?);
+        }
+
+        tx.commit().await?;
+
+        self.service
+            .indexing_service
+            .index_resources_auto(&written)
+            .await?;
+
+        for resource in &written {
+            *self
+                .summary
+                .counts
+                .entry(resource.resource_type.clone())
+                .or_insert(0) += 1;
+        }
+        self.summary.imported += written.len();
+
+        self.job_queue
+            .update_progress(
+                self.job_id,
+                self.summary.imported as i32,
+                None,
+                Some(self.summary.progress()),
+            )
+            .await?;
+
+        if self.job_queue.is_cancelled(self.job_id).await? {
+            tracing::warn!("Import job {} was cancelled", self.job_id);
+            self.summary.cancelled = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `url`'s host (or `host:port`) is on the allowlist.
+fn is_allowed_host(allowed_hosts: &[String], url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host_port = url
+        .port_or_known_default()
+        .map(|port| format!("{}:{}", host, port));
+    allowed_hosts.iter().any(|allowed| {
+        allowed.eq_ignore_ascii_case(host)
+            || host_port
+                .as_deref()
+                .is_some_and(|host_port| allowed.eq_ignore_ascii_case(host_port))
+    })
+}
+
+/// Parse and check one NDJSON line, returning its resource type and JSON.
+fn parse_line(
+    line: &str,
+    expected_type: Option<&str>,
+    validation: ImportValidation,
+    is_resource_type: impl FnOnce(&str) -> bool,
+) -> std::result::Result<(String, JsonValue), String> {
+    let resource: JsonValue =
+        serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e))?;
+    if !resource.is_object() {
+        return Err("expected a JSON object".to_string());
+    }
+
+    let resource_type = resource
+        .get("resourceType")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "missing resourceType".to_string())?
+        .to_string();
+
+    if !is_resource_type(&resource_type) {
+        return Err(format!("unknown resource type {}", resource_type));
+    }
+    if let Some(id) = resource.get("id") {
+        match id.as_str() {
+            Some(id) if is_valid_id(id) => {}
+            _ => return Err(format!("invalid id {}", id)),
+        }
+    }
+    if validation == ImportValidation::Validate {
+        if let Some(expected) = expected_type {
+            if expected != resource_type {
+                return Err(format!(
+                    "resource type {} does not match input type {}",
+                    resource_type, expected
+                ));
+            }
+        }
+    }
+
+    Ok((resource_type, resource))
+}
+
+/// FHIR id: 1-64 characters from `[A-Za-z0-9\-\.]`
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_resource_type(name: &str) -> bool {
+        matches!(name, "Patient" | "Observation")
+    }
+
+    #[test]
+    fn parse_line_checks_type_and_id_when_skipping_validation() {
+        let skip = ImportValidation::Skip;
+        let (rt, _) = parse_line(
+            r#"{"resourceType":"Observation","id":"o-1"}"#,
+            Some("Patient"),
+            skip,
+            is_resource_type,
+        )
+        .unwrap();
+        assert_eq!(rt, "Observation");
+
+        assert!(parse_line(
+            r#"{"resourceType":"NotAType"}"#,
+            None,
+            skip,
+            is_resource_type
+        )
+        .is_err());
+        assert!(parse_line(
+            r#"{"resourceType":"Patient","id":"bad id"}"#,
+            None,
+            skip,
+            is_resource_type
+        )
+        .is_err());
+        assert!(parse_line(r#"{"id":"p1"}"#, None, skip, is_resource_type).is_err());
+        assert!(parse_line("not json", None, skip, is_resource_type).is_err());
+    }
+
+    #[test]
+    fn parse_line_validates_input_type() {
+        let validate = ImportValidation::Validate;
+        assert!(parse_line(
+            r#"{"resourceType":"Patient","id":"p-1"}"#,
+            Some("Patient"),
+            validate,
+            is_resource_type
+        )
+        .is_ok());
+        assert!(parse_line(
+            r#"{"resourceType":"Observation"}"#,
+            Some("Patient"),
+            validate,
+            is_resource_type
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn only_allowlisted_hosts_are_fetched() {
+        let allowed = [
+            "files.example.org".to_string(),
+            "localhost:8081".to_string(),
+        ];
+        let allows = |url: &str| is_allowed_host(&allowed, &Url::parse(url).unwrap());
+        assert!(allows("https://files.example.org/a.ndjson"));
+        assert!(allows("http://FILES.example.org:8080/a.ndjson"));
+        assert!(allows("http://localhost:8081/a.ndjson"));
+        assert!(!allows("http://localhost/a.ndjson"));
+        assert!(!allows("http://169.254.169.254/latest"));
+
+        let url = Url::parse("https://files.example.org/a.ndjson").unwrap();
+        assert!(!is_allowed_host(&[], &url));
+    }
+}
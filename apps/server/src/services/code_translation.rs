@@ -0,0 +1,255 @@
+//! Write-time code translation with ConceptMaps (`fhir.code_translation`)
+
+use crate::{
+    config::{CodeTranslationConfig, CodeTranslationRule},
+    services::{terminology::TranslationMatch, TerminologyService},
+    Result,
+};
+use serde_json::{json, Value as JsonValue};
+use std::{collections::HashMap, sync::Arc};
+
+/// Translations found for one ConceptMap, keyed by source `(system, code)`.
+type Translations = HashMap<(String, String), Vec<TranslationMatch>>;
+
+/// Translates local codes to standard codes before resources are stored.
+///
+/// For each rule of the resource's type, the codings at the rule's element are looked up in
+/// its ConceptMap. Matching targets are appended to a CodeableConcept's `coding` (the original
+/// coding is kept) or replace a Coding element.
+pub struct CodeTranslator {
+    terminology: Arc<TerminologyService>,
+    rules: HashMap<String, Vec<CodeTranslationRule>>,
+}
+
+impl CodeTranslator {
+    pub fn new(
+        terminology: Arc<TerminologyService>,
+        rules: impl IntoIterator<Item = CodeTranslationRule>,
+    ) -> Self {
+        let mut by_type: HashMap<String, Vec<CodeTranslationRule>> = HashMap::new();
+        for rule in rules {
+            by_type
+                .entry(rule.resource_type.clone())
+                .or_default()
+                .push(rule);
+        }
+        Self {
+            terminology,
+            rules: by_type,
+        }
+    }
+
+    /// Build the translator described by `fhir.code_translation`, or `None` without rules.
+    pub fn from_config(
+        config: &CodeTranslationConfig,
+        terminology: Arc<TerminologyService>,
+    ) -> Option<Self> {
+        if config.rules.is_empty() {
+            return None;
+        }
+        Some(Self::new(terminology, config.rules.iter().cloned()))
+    }
+
+    /// Translate the configured code elements of a resource about to be written.
+    pub async fn apply(&self, resource: &mut JsonValue) -> Result<()> {
+        let Some(rules) = resource
+            .get("resourceType")
+            .and_then(JsonValue::as_str)
+            .and_then(|resource_type| self.rules.get(resource_type))
+        else {
+            return Ok(());
+        };
+
+        for rule in rules {
+            let path: Vec<&str> = rule.element.split('.').collect();
+            let mut codes = Vec::new();
+            collect_codes(resource, &path, &mut codes);
+            if codes.is_empty() {
+                continue;
+            }
+
+            let Some(map) = self.terminology.concept_map(&rule.concept_map).await? else {
+                tracing::warn!(
+                    concept_map = %rule.concept_map,
+                    resource_type = %rule.resource_type,
+                    "Skipping code translation: ConceptMap not found"
+                );
+                continue;
+            };
+
+            let mut translations = Translations::new();
+            for (system, code) in codes {
+                if translations.contains_key(&(system.clone(), code.clone())) {
+                    continue;
+                }
+                let matches = TerminologyService::translate_code(&map, &system, &code)
+                    .into_iter()
+                    .filter(TranslationMatch::is_match);
+                translations.insert((system, code), matches.collect());
+            }
+
+            apply_translations(resource, &path, &translations);
+        }
+        Ok(())
+    }
+}
+
+/// Elements at `path` below `value`, following arrays at every step.
+fn elements_at<'a>(value: &'a JsonValue, path: &[&str], out: &mut Vec<&'a JsonValue>) {
+    match value {
+        JsonValue::Array(items) => {
+            for item in items {
+                elements_at(item, path, out);
+            }
+        }
+        _ => match path.split_first() {
+            None => out.push(value),
+            Some((name, rest)) => {
+                if let Some(child) = value.get(*name) {
+                    elements_at(child, rest, out);
+                }
+            }
+        },
+    }
+}
+
+fn elements_at_mut<'a>(value: &'a mut JsonValue, path: &[&str], out: &mut Vec<&'a mut JsonValue>) {
+    match value {
+        JsonValue::Array(items) => {
+            for item in items {
+                elements_at_mut(item, path, out);
+            }
+        }
+        _ => match path.split_first() {
+            None => out.push(value),
+            Some((name, rest)) => {
+                if let Some(child) = value.get_mut(*name) {
+                    elements_at_mut(child, rest, out);
+                }
+            }
+        },
+    }
+}
+
+/// `(system, code)` of a Coding.
+fn coding_key(coding: &JsonValue) -> Option<(String, String)> {
+    let system = coding.get("system")?.as_str()?;
+    let code = coding.get("code")?.as_str()?;
+    Some((system.to_string(), code.to_string()))
+}
+
+/// Codings held by a CodeableConcept or Coding element.
+fn element_codings(element: &JsonValue) -> Vec<&JsonValue> {
+    match element.get("coding") {
+        Some(JsonValue::Array(codings)) => codings.iter().collect(),
+        _ => vec![element],
+    }
+}
+
+fn collect_codes(resource: &JsonValue, path: &[&str], out: &mut Vec<(String, String)>) {
+    let mut elements = Vec::new();
+    elements_at(resource, path, &mut elements);
+    for element in elements {
+        out.extend(element_codings(element).into_iter().filter_map(coding_key));
+    }
+}
+
+fn target_coding(target: &TranslationMatch) -> JsonValue {
+    let mut coding = json!({ "system": target.system, "code": target.code });
+    if let Some(display) = &target.display {
+        coding["display"] = json!(display);
+    }
+    coding
+}
+
+fn apply_translations(resource: &mut JsonValue, path: &[&str], translations: &Translations) {
+    let mut elements = Vec::new();
+    elements_at_mut(resource, path, &mut elements);
+    for element in elements {
+        if let Some(JsonValue::Array(codings)) = element.get_mut("coding") {
+            let mut added = Vec::new();
+            for target in codings
+                .iter()
+                .filter_map(coding_key)
+                .filter_map(|key| translations.get(&key))
+                .flatten()
+            {
+                let key = (target.system.clone(), target.code.clone());
+                let present = codings.iter().any(|c| coding_key(c).as_ref() == Some(&key));
+                if !present && !added.iter().any(|c| coding_key(c).as_ref() == Some(&key)) {
+                    added.push(target_coding(target));
+                }
+            }
+            codings.extend(added);
+        } else if let Some(target) = coding_key(element)
+            .and_then(|key| translations.get(&key))
+            .and_then(|targets| targets.first())
+        {
+            *element = target_coding(target);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translations() -> Translations {
+        HashMap::from([(
+            ("urn:local".to_string(), "GLU".to_string()),
+            vec![TranslationMatch {
+                system: "http://loinc.org".to_string(),
+                code: "2345-7".to_string(),
+                display: Some("Glucose".to_string()),
+                equivalence: "equivalent".to_string(),
+            }],
+        )])
+    }
+
+    #[test]
+    fn standard_code_is_added_next_to_the_local_coding() {
+        let mut observation = json!({
+            "resourceType": "Observation",
+            "code": { "coding": [{ "system": "urn:local", "code": "GLU" }] },
+            "component": [
+                { "code": { "coding": [{ "system": "urn:local", "code": "GLU" }] } },
+                { "code": { "coding": [{ "system": "urn:local", "code": "NA" }] } }
+            ]
+        });
+
+        let mut codes = Vec::new();
+        collect_codes(&observation, &["component", "code"], &mut codes);
+        assert_eq!(codes.len(), 2);
+
+        apply_translations(&mut observation, &["code"], &translations());
+        apply_translations(&mut observation, &["component", "code"], &translations());
+        let loinc = json!({ "system": "http://loinc.org", "code": "2345-7", "display": "Glucose" });
+        assert_eq!(
+            observation["code"]["coding"],
+            json!([{ "system": "urn:local", "code": "GLU" }, loinc])
+        );
+        assert_eq!(observation["component"][0]["code"]["coding"][1], loinc);
+        assert_eq!(
+            observation["component"][1]["code"]["coding"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Translating again does not duplicate the standard coding
+        apply_translations(&mut observation, &["code"], &translations());
+        assert_eq!(observation["code"]["coding"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn coding_element_is_replaced() {
+        let mut resource = json!({
+            "resourceType": "Basic",
+            "tag": { "system": "urn:local", "code": "GLU" }
+        });
+        apply_translations(&mut resource, &["tag"], &translations());
+        assert_eq!(resource["tag"]["code"], "2345-7");
+        assert_eq!(resource["tag"]["system"], "http://loinc.org");
+    }
+}
@@ -11,13 +11,13 @@ use crate::{
     },
     queue::{JobPriority, JobQueue},
     runtime_config::{ConfigKey, RuntimeConfigCache},
-    services::IndexingService,
+    services::{CodeTranslator, IndexingService, NarrativeGenerator, WriteValidator},
     Error, Result,
 };
 use chrono::Utc;
 use json_patch::PatchErrorKind;
 use serde_json::Value as JsonValue;
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 use uuid::Uuid;
 
 pub struct CrudService {
@@ -29,6 +29,10 @@ pub struct CrudService {
     hard_delete: bool,
     runtime_config_cache: Option<Arc<RuntimeConfigCache>>,
     referential_integrity_mode: String,
+    read_only_types: HashSet<String>,
+    write_validator: Option<Arc<WriteValidator>>,
+    narrative_generator: Option<Arc<NarrativeGenerator>>,
+    code_translator: Option<Arc<CodeTranslator>>,
 }
 
 impl CrudService {
@@ -50,6 +54,10 @@ impl CrudService {
             hard_delete,
             runtime_config_cache: None,
             referential_integrity_mode: "lenient".to_string(),
+            read_only_types: HashSet::new(),
+            write_validator: None,
+            narrative_generator: None,
+            code_translator: None,
         }
     }
 
@@ -83,6 +91,10 @@ impl CrudService {
             hard_delete,
             runtime_config_cache: None,
             referential_integrity_mode: "lenient".to_string(),
+            read_only_types: HashSet::new(),
+            write_validator: None,
+            narrative_generator: None,
+            code_translator: None,
         }
     }
 
@@ -103,6 +115,10 @@ impl CrudService {
             hard_delete,
             runtime_config_cache: None,
             referential_integrity_mode: "lenient".to_string(),
+            read_only_types: HashSet::new(),
+            write_validator: None,
+            narrative_generator: None,
+            code_translator: None,
         }
     }
 
@@ -131,6 +147,27 @@ impl CrudService {
         self.referential_integrity_mode = mode;
     }
 
+    /// Validator run on create/update/patch before storing (see `fhir.validation`).
+    pub fn set_write_validator(&mut self, validator: Option<Arc<WriteValidator>>) {
+        self.write_validator = validator;
+    }
+
+    /// Narrative generator run on create/update/patch before validation (see `fhir.narrative`).
+    pub fn set_narrative_generator(&mut self, generator: Option<Arc<NarrativeGenerator>>) {
+        self.narrative_generator = generator;
+    }
+
+    /// ConceptMap translation run on create/update/patch before narrative generation
+    /// (see `fhir.code_translation`).
+    pub fn set_code_translator(&mut self, translator: Option<Arc<CodeTranslator>>) {
+        self.code_translator = translator;
+    }
+
+    /// Resource types that reject create/update/patch/delete (see `fhir.resource_policies`).
+    pub fn set_read_only_types(&mut self, types: impl IntoIterator<Item = String>) {
+        self.read_only_types = types.into_iter().collect();
+    }
+
     async fn allow_update_create_effective(&self) -> bool {
         if let Some(cache) = &self.runtime_config_cache {
             return cache.get(ConfigKey::BehaviorAllowUpdateCreate).await;
@@ -162,6 +199,7 @@ impl CrudService {
         params: Option<CreateParams>,
     ) -> Result<ResourceResult> {
         self.validate_resource_type_name(resource_type)?;
+        self.ensure_writable(resource_type)?;
 
         // Validate resource type matches
         self.validate_resource_type(&resource, resource_type)?;
@@ -184,6 +222,10 @@ impl CrudService {
         // Populate meta
         self.populate_meta(&mut resource, &id, 1, Utc::now());
 
+        self.translate_codes(&mut resource).await?;
+        self.generate_narrative(&mut resource);
+        let issues = self.validate_on_write(&resource).await?;
+
         // Referential integrity check (strict mode)
         if self.is_strict_referential_integrity() {
             self.validate_references(&resource).await?;
@@ -203,6 +245,7 @@ impl CrudService {
         Ok(ResourceResult {
             resource: created,
             operation: ResourceOperation::Created,
+            issues,
         })
     }
 
@@ -239,6 +282,7 @@ impl CrudService {
     /// This is a destructive "purge" operation and is only allowed when `hard_delete` is enabled.
     pub async fn delete_resource_history(&self, resource_type: &str, id: &str) -> Result<()> {
         self.validate_resource_type_name(resource_type)?;
+        self.ensure_writable(resource_type)?;
 
         if !self.hard_delete_effective().await {
             return Err(Error::MethodNotAllowed(
@@ -267,6 +311,7 @@ impl CrudService {
         version_id: i32,
     ) -> Result<()> {
         self.validate_resource_type_name(resource_type)?;
+        self.ensure_writable(resource_type)?;
 
         if !self.hard_delete_effective().await {
             return Err(Error::MethodNotAllowed(
@@ -295,6 +340,7 @@ impl CrudService {
         params: Option<UpdateParams>,
     ) -> Result<ResourceResult> {
         self.validate_resource_type_name(resource_type)?;
+        self.ensure_writable(resource_type)?;
 
         // Validate ID matches URL (FHIR spec SHALL requirement)
         // "If no id element is provided, or the id disagrees with the id in the URL,
@@ -358,6 +404,10 @@ impl CrudService {
             }
         };
 
+        self.translate_codes(&mut resource).await?;
+        self.generate_narrative(&mut resource);
+        let issues = self.validate_on_write(&resource).await?;
+
         // Referential integrity check (strict mode)
         if self.is_strict_referential_integrity() {
             self.validate_references(&resource).await?;
@@ -382,6 +432,7 @@ impl CrudService {
         Ok(ResourceResult {
             resource: updated,
             operation,
+            issues,
         })
     }
 
@@ -400,6 +451,7 @@ impl CrudService {
         params: Option<UpdateParams>,
     ) -> Result<ResourceResult> {
         self.validate_resource_type_name(resource_type)?;
+        self.ensure_writable(resource_type)?;
 
         let current = self.read_resource(resource_type, id).await?;
 
@@ -431,13 +483,21 @@ impl CrudService {
         let new_version = current.version_id + 1;
         self.populate_meta(&mut patched, id, new_version, Utc::now());
 
+        self.translate_codes(&mut patched).await?;
+        self.generate_narrative(&mut patched);
+        let issues = self.validate_on_write(&patched).await?;
+
         // Referential integrity check (strict mode)
         if self.is_strict_referential_integrity() {
             self.validate_references(&patched).await?;
         }
 
-        // Persist and trigger side effects like a normal update.
-        let updated = self.store.upsert(resource_type, id, patched).await?;
+        // Persist and trigger side effects like a normal update. The write is checked against
+        // the version the patch was applied to so concurrent updates are not overwritten.
+        let updated = self
+            .store
+            .update(resource_type, id, patched, Some(current.version_id))
+            .await?;
 
         for hook in &self.hooks {
             hook.on_updated(&updated).await?;
@@ -449,6 +509,7 @@ impl CrudService {
         Ok(ResourceResult {
             resource: updated,
             operation: ResourceOperation::Updated,
+            issues,
         })
     }
 
@@ -462,6 +523,7 @@ impl CrudService {
     /// - Returns 204 No Content on success
     pub async fn delete_resource(&self, resource_type: &str, id: &str) -> Result<Option<i32>> {
         self.validate_resource_type_name(resource_type)?;
+        self.ensure_writable(resource_type)?;
 
         let current = self.store.read(resource_type, id).await?;
 
@@ -658,6 +720,28 @@ impl CrudService {
         })
     }
 
+    async fn translate_codes(&self, resource: &mut JsonValue) -> Result<()> {
+        match &self.code_translator {
+            Some(translator) => translator.apply(resource).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Add or refresh the narrative of a resource about to be written, when configured.
+    fn generate_narrative(&self, resource: &mut JsonValue) {
+        if let Some(generator) = &self.narrative_generator {
+            generator.apply(resource);
+        }
+    }
+
+    /// Run write-time validation, returning the issues of a resource that may be stored.
+    async fn validate_on_write(&self, resource: &JsonValue) -> Result<Vec<JsonValue>> {
+        match &self.write_validator {
+            Some(validator) => validator.clone().check_blocking(resource.clone()).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
     fn is_strict_referential_integrity(&self) -> bool {
         self.referential_integrity_mode == "strict"
     }
@@ -741,6 +825,11 @@ impl CrudService {
         Ok(())
     }
 
+    /// Reject writes to resource types configured as read-only.
+    pub(crate) fn ensure_writable(&self, resource_type: &str) -> Result<()> {
+        ensure_type_writable(&self.read_only_types, resource_type)
+    }
+
     /// Validate that resource type in JSON matches the endpoint
     fn validate_resource_type(&self, resource: &JsonValue, expected_type: &str) -> Result<()> {
         let resource_type = resource
@@ -861,3 +950,19 @@ fn validate_version_match(resource: &Resource, expected_version: i32) -> Result<
     }
     Ok(())
 }
+
+/// Reject writes to a resource type listed in `read_only_types`.
+///
+/// Shared by CRUD, batch and transaction processing so the message is the same everywhere.
+pub(crate) fn ensure_type_writable(
+    read_only_types: &HashSet<String>,
+    resource_type: &str,
+) -> Result<()> {
+    if read_only_types.contains(resource_type) {
+        return Err(Error::MethodNotAllowed(format!(
+            "Resource type {} is read-only on this server",
+            resource_type
+        )));
+    }
+    Ok(())
+}
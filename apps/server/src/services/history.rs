@@ -21,7 +21,10 @@ use crate::{
 };
 use axum::http::StatusCode;
 use serde_json::{json, Value as JsonValue};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use ferrum_models::{Bundle, BundleEntry, BundleEntryResponse, BundleType};
 use uuid::Uuid;
 
@@ -33,6 +36,7 @@ pub struct HistoryService {
     allow_update_create: bool,
     hard_delete: bool,
     runtime_config_cache: Option<Arc<RuntimeConfigCache>>,
+    read_only_types: HashSet<String>,
 }
 
 impl HistoryService {
@@ -50,6 +54,7 @@ impl HistoryService {
             allow_update_create,
             hard_delete,
             runtime_config_cache: None,
+            read_only_types: HashSet::new(),
         }
     }
 
@@ -66,6 +71,10 @@ impl HistoryService {
         service
     }
 
+    pub fn set_read_only_types(&mut self, types: impl IntoIterator<Item = String>) {
+        self.read_only_types = types.into_iter().collect();
+    }
+
     /// Process a FHIR history bundle (Bundle.type = history).
     pub async fn process_bundle(&self, bundle_json: JsonValue) -> Result<JsonValue> {
         self.process_bundle_with_options(bundle_json, BundleRequestOptions::default())
@@ -130,7 +139,7 @@ impl HistoryService {
             });
         }
 
-        let crud = if let Some(cache) = &self.runtime_config_cache {
+        let mut crud = if let Some(cache) = &self.runtime_config_cache {
             CrudService::new_with_policy_and_runtime_config(
                 self.store.clone(),
                 self.allow_update_create,
@@ -144,6 +153,7 @@ impl HistoryService {
                 self.hard_delete,
             )
         };
+        crud.set_read_only_types(self.read_only_types.iter().cloned());
 
         // Process entries sequentially
         for (index, entry) in entries.iter().enumerate() {
@@ -522,58 +532,9 @@ fn status_line(status: StatusCode) -> String {
     }
 }
 
-fn status_to_fhir_code(status: StatusCode) -> &'static str {
-    match status {
-        StatusCode::BAD_REQUEST => "invalid",
-        StatusCode::NOT_FOUND => "not-found",
-        StatusCode::GONE => "deleted",
-        StatusCode::CONFLICT => "conflict",
-        StatusCode::PRECONDITION_FAILED => "conflict",
-        StatusCode::UNPROCESSABLE_ENTITY => "processing",
-        StatusCode::UNSUPPORTED_MEDIA_TYPE => "not-supported",
-        _ => "exception",
-    }
-}
-
-fn error_status(err: &crate::Error) -> StatusCode {
-    match err {
-        crate::Error::ResourceNotFound { .. } => StatusCode::NOT_FOUND,
-        crate::Error::VersionNotFound { .. } => StatusCode::NOT_FOUND,
-        crate::Error::NotFound(_) => StatusCode::NOT_FOUND,
-        crate::Error::ResourceDeleted { .. } => StatusCode::GONE,
-        crate::Error::InvalidResource(_)
-        | crate::Error::Validation(_)
-        | crate::Error::InvalidReference(_) => StatusCode::BAD_REQUEST,
-        crate::Error::BusinessRule(_) => StatusCode::CONFLICT,
-        crate::Error::VersionConflict { .. } | crate::Error::PreconditionFailed(_) => {
-            StatusCode::PRECONDITION_FAILED
-        }
-        crate::Error::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
-        crate::Error::Search(_) => StatusCode::BAD_REQUEST,
-        crate::Error::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
-        crate::Error::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
-        crate::Error::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
-        crate::Error::TooCostly(_) => StatusCode::FORBIDDEN,
-        crate::Error::Database(_)
-        | crate::Error::JobQueue(_)
-        | crate::Error::FhirContext(_)
-        | crate::Error::FhirPath(_)
-        | crate::Error::ExternalReference(_)
-        | crate::Error::Internal(_)
-        | crate::Error::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
-    }
-}
-
 fn create_error_entry(full_url: Option<&str>, err: &crate::Error) -> BundleEntry {
-    let status = error_status(err);
-    let outcome = json!({
-        "resourceType": "OperationOutcome",
-        "issue": [{
-            "severity": "error",
-            "code": status_to_fhir_code(status),
-            "diagnostics": err.to_string()
-        }]
-    });
+    let status = err.status();
+    let outcome = err.to_operation_outcome();
 
     BundleEntry {
         full_url: full_url.map(|s| s.to_string()),
@@ -37,11 +37,21 @@ use super::{
 /// Bulk indexer using PostgreSQL COPY for maximum throughput
 pub struct BulkIndexer {
     pool: PgPool,
+    acquire_timeout: std::time::Duration,
 }
 
 impl BulkIndexer {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            acquire_timeout: super::DEFAULT_ACQUIRE_TIMEOUT,
+        }
+    }
+
+    /// Set how long to wait for a pooled connection before giving up.
+    pub fn with_acquire_timeout(mut self, acquire_timeout: std::time::Duration) -> Self {
+        self.acquire_timeout = acquire_timeout;
+        self
     }
 
     /// Bulk index using PostgreSQL COPY (100-500x faster than INSERT for large batches)
@@ -127,7 +137,9 @@ impl BulkIndexer {
 
         // 2. Use COPY to load each table
         let tx_start = std::time::Instant::now();
-        let mut tx = self.pool.begin().await.map_err(crate::Error::Database)?;
+        let mut tx =
+            crate::db::pool::begin_with_timeout(&self.pool, self.acquire_timeout, "indexing")
+                .await?;
         let tx_init_time = tx_start.elapsed();
         tracing::debug!("[PERF] Transaction initialization: {:?}", tx_init_time);
 
@@ -272,11 +284,7 @@ impl BulkIndexer {
                 search_params.len()
             );
 
-            let needs_resolve = search_params.iter().any(|p| {
-                p.expression
-                    .as_deref()
-                    .is_some_and(|expr| expr.contains("resolve("))
-            });
+            let needs_resolve = indexing_service.uses_resolve(&search_params);
 
             // Track FHIRPath metrics per resource type
             let mut type_context_build_time = std::time::Duration::ZERO;
@@ -4,7 +4,9 @@ use crate::db::search::string_normalization::normalize_string_for_search;
 use crate::models::Resource;
 use crate::Result;
 use serde_json::Value;
-use ferrum_fhirpath::{conversion::ToJson, Context, EvalOptions, Value as FhirPathValue};
+use ferrum_fhirpath::{
+    conversion::ToJson, Context, Engine as FhirPathEngine, EvalOptions, Value as FhirPathValue,
+};
 
 use super::IndexingService;
 use super::SearchParameter;
@@ -32,93 +34,126 @@ impl IndexingService {
             return Ok(());
         }
 
-        let component_defs = parse_composite_component_defs(components, &resource.resource_type);
+        let base_expr = param
+            .expression
+            .as_deref()
+            .map(str::trim)
+            .filter(|e| !e.is_empty());
+        let component_defs =
+            parse_composite_component_defs(components, &resource.resource_type, base_expr);
         if component_defs.len() < 2 {
             return Ok(());
         }
 
-        let group_root_expr = compute_composite_group_root_expr(&component_defs);
-
-        let root = FhirPathValue::from_json(resource.resource.clone());
-        let ctx = Context::new(root);
-
-        let group_items: Vec<Value> = if group_root_expr.is_empty() {
-            vec![resource.resource.clone()]
-        } else {
-            let collection = self
-                .fhirpath_engine
-                .evaluate_expr_with_options(
-                    &group_root_expr,
-                    &ctx,
-                    EvalOptions {
-                        base_type: Some(resource.resource_type.clone()),
-                        strict: false,
-                        infer_base_type: false,
-                    },
-                )
-                .map_err(|e| crate::Error::FhirPath(e.to_string()))?;
-            collection.iter().filter_map(|v| v.to_json()).collect()
-        };
+        let tuples = composite_tuples(
+            &self.fhirpath_engine,
+            &resource.resource_type,
+            &resource.resource,
+            base_expr,
+            &component_defs,
+        )?;
+
+        for tuple in tuples {
+            let components_value = Value::Array(tuple);
+            sqlx::query(
+                "INSERT INTO search_composite (resource_type, resource_id, version_id, parameter_name, components)
+                 VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING",
+            )
+            .bind(&resource.resource_type)
+            .bind(&resource.id)
+            .bind(resource.version_id)
+            .bind(&param.code)
+            .bind(components_value)
+            .execute(&mut **tx)
+            .await
+            .map_err(crate::Error::Database)?;
+        }
 
-        for group_item in group_items {
-            let group_root = FhirPathValue::from_json(group_item.clone());
-            let group_ctx = Context::new(group_root);
-
-            let mut per_component_values: Vec<Vec<Value>> = Vec::new();
-            let mut missing_component = false;
-
-            for c in &component_defs {
-                let raw_values: Vec<Value> = if c.tail_expr.is_empty() {
-                    vec![group_item.clone()]
-                } else {
-                    let collection = self
-                        .fhirpath_engine
-                        .evaluate_expr_with_options(
-                            &c.tail_expr,
-                            &group_ctx,
-                            EvalOptions {
-                                base_type: None,
-                                strict: false,
-                                infer_base_type: false,
-                            },
-                        )
-                        .map_err(|e| crate::Error::FhirPath(e.to_string()))?;
-                    collection.iter().filter_map(|v| v.to_json()).collect()
-                };
-
-                let indexed_values = index_component_values(&c.component_type, &raw_values);
-                if indexed_values.is_empty() {
-                    missing_component = true;
-                    break;
-                }
-                per_component_values.push(indexed_values);
-            }
+        Ok(())
+    }
+}
 
-            if missing_component || per_component_values.is_empty() {
-                continue;
+/// Compute the index tuples of a composite parameter.
+///
+/// The group root is the composite's own expression (e.g. `Observation.component`), or when
+/// absent the common parent of the component expressions. Components are evaluated in
+/// lockstep against each group element, so a tuple never pairs a code from one
+/// `Observation.component` with a value from another.
+fn composite_tuples(
+    engine: &FhirPathEngine,
+    resource_type: &str,
+    resource: &Value,
+    base_expr: Option<&str>,
+    component_defs: &[CompositeComponentIndexDef],
+) -> Result<Vec<Vec<Value>>> {
+    let group_root_expr = match base_expr {
+        Some(expr) => expr.to_string(),
+        None => compute_composite_group_root_expr(component_defs),
+    };
+
+    let root = FhirPathValue::from_json(resource.clone());
+    let ctx = Context::new(root);
+
+    let group_items: Vec<Value> = if group_root_expr.is_empty() {
+        vec![resource.clone()]
+    } else {
+        let collection = engine
+            .evaluate_expr_with_options(
+                &group_root_expr,
+                &ctx,
+                EvalOptions {
+                    base_type: Some(resource_type.to_string()),
+                    strict: false,
+                    infer_base_type: false,
+                },
+            )
+            .map_err(|e| crate::Error::FhirPath(e.to_string()))?;
+        collection.iter().filter_map(|v| v.to_json()).collect()
+    };
+
+    let mut tuples = Vec::new();
+    for group_item in group_items {
+        let group_root = FhirPathValue::from_json(group_item.clone());
+        let group_ctx = Context::new(group_root);
+
+        let mut per_component_values: Vec<Vec<Value>> = Vec::new();
+        let mut missing_component = false;
+
+        for c in component_defs {
+            let raw_values: Vec<Value> = if c.tail_expr.is_empty() {
+                vec![group_item.clone()]
+            } else {
+                let collection = engine
+                    .evaluate_expr_with_options(
+                        &c.tail_expr,
+                        &group_ctx,
+                        EvalOptions {
+                            base_type: None,
+                            strict: false,
+                            infer_base_type: false,
+                        },
+                    )
+                    .map_err(|e| crate::Error::FhirPath(e.to_string()))?;
+                collection.iter().filter_map(|v| v.to_json()).collect()
+            };
+
+            let indexed_values = index_component_values(&c.component_type, &raw_values);
+            if indexed_values.is_empty() {
+                missing_component = true;
+                break;
             }
+            per_component_values.push(indexed_values);
+        }
 
-            // Generate tuples (cartesian product) within this group item.
-            let tuples = cartesian_product_capped(&per_component_values, 2000);
-            for tuple in tuples {
-                let components_value = Value::Array(tuple);
-                sqlx::query(
-                    "INSERT INTO search_composite (resource_type, resource_id, version_id, parameter_name, components)
-                     VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING",
-                )
-                .bind(&resource.resource_type)
-                .bind(&resource.id)
-                .bind(resource.version_id)
-                .bind(&param.code)
-                .bind(components_value)
-                .execute(&mut **tx)
-                .await
-                .map_err(crate::Error::Database)?;
-            }
+        if missing_component || per_component_values.is_empty() {
+            continue;
         }
 
-        Ok(())
+        // Generate tuples (cartesian product) within this group item.
+        tuples.extend(cartesian_product_capped(&per_component_values, 2000));
     }
+
+    Ok(tuples)
 }
 
 #[derive(Debug, Clone)]
@@ -129,9 +164,15 @@ struct CompositeComponentIndexDef {
     grouping_segments: Vec<String>,
 }
 
+/// Parse the component definitions of a composite parameter.
+///
+/// Component expressions are relative to the composite's expression when it has one (the
+/// FHIR definition); otherwise they are treated as paths from the resource and grouped on
+/// their common parent.
 fn parse_composite_component_defs(
     components: &[serde_json::Value],
     resource_type: &str,
+    base_expr: Option<&str>,
 ) -> Vec<CompositeComponentIndexDef> {
     let mut out = Vec::new();
     for comp in components {
@@ -166,6 +207,13 @@ fn parse_composite_component_defs(
         });
     }
 
+    if base_expr.is_some() {
+        for c in out.iter_mut() {
+            c.tail_expr = c.full_expr.clone();
+        }
+        return out;
+    }
+
     let group_root_segments = compute_composite_group_root_segments(&out);
     let group_root_expr = group_root_segments.join(".");
     for c in out.iter_mut() {
@@ -327,3 +375,55 @@ fn cartesian_product_capped(lists: &[Vec<Value>], max: usize) -> Vec<Vec<Value>>
     }
     results
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrum_context::DefaultFhirContext;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn component_tuples_do_not_cross_components() {
+        let engine = FhirPathEngine::new(Arc::new(DefaultFhirContext::from_packages(vec![])), None);
+        let observation = json!({
+            "resourceType": "Observation",
+            "component": [
+                {
+                    "code": {"coding": [{"system": "http://loinc.org", "code": "8480-6"}]},
+                    "valueQuantity": {"value": 120, "system": "http://unitsofmeasure.org", "code": "mm[Hg]"}
+                },
+                {
+                    "code": {"coding": [{"system": "http://loinc.org", "code": "8462-4"}]},
+                    "valueQuantity": {"value": 80, "system": "http://unitsofmeasure.org", "code": "mm[Hg]"}
+                }
+            ]
+        });
+        let components = vec![
+            json!({"component_code": "component-code", "component_type": "token", "expression": "code"}),
+            json!({"component_code": "component-value-quantity", "component_type": "quantity", "expression": "value.as(Quantity)"}),
+        ];
+        let base_expr = Some("Observation.component");
+        let defs = parse_composite_component_defs(&components, "Observation", base_expr);
+
+        let tuples =
+            composite_tuples(&engine, "Observation", &observation, base_expr, &defs).unwrap();
+        let pairs: Vec<(String, String)> = tuples
+            .iter()
+            .map(|t| {
+                (
+                    t[0]["code"].as_str().unwrap().to_string(),
+                    t[1]["value"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            pairs,
+            vec![
+                ("8480-6".to_string(), "120".to_string()),
+                ("8462-4".to_string(), "80".to_string()),
+            ]
+        );
+    }
+}
@@ -19,6 +19,8 @@ pub struct IndexingService {
     enable_content_search: bool,
     /// Cache of compiled FHIRPath plans by expression (indexing uses stable compile options).
     plan_cache: Arc<RwLock<HashMap<String, Arc<ferrum_fhirpath::vm::Plan>>>>,
+    /// Cache of whether an expression calls `resolve()`, by expression.
+    resolve_usage_cache: Arc<RwLock<HashMap<String, bool>>>,
     /// Cache of search parameters by resource type
     /// Key: resource_type, Value: Vec<SearchParameter>
     search_params_cache: Arc<RwLock<HashMap<String, Vec<SearchParameter>>>>,
@@ -28,8 +30,13 @@ pub struct IndexingService {
     bulk_threshold: usize,
     /// Computed parameter hooks
     computed_hooks: crate::hooks::computed::HookRegistry,
+    /// Maximum time to wait for a pooled connection before failing
+    acquire_timeout: std::time::Duration,
 }
 
+/// Default wait for a pooled connection, see [`IndexingService::with_acquire_timeout`].
+const DEFAULT_ACQUIRE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 impl IndexingService {
     pub fn pool(&self) -> &PgPool {
         &self.pool
@@ -81,13 +88,26 @@ impl IndexingService {
             enable_text_search,
             enable_content_search,
             plan_cache: Arc::new(RwLock::new(HashMap::new())),
+            resolve_usage_cache: Arc::new(RwLock::new(HashMap::new())),
             search_params_cache: Arc::new(RwLock::new(HashMap::new())),
             batch_size,
             bulk_threshold,
             computed_hooks: crate::hooks::computed::HookRegistry::new(),
+            acquire_timeout: DEFAULT_ACQUIRE_TIMEOUT,
         })
     }
 
+    /// Set how long indexing waits for a pooled connection before giving up.
+    pub fn with_acquire_timeout(mut self, acquire_timeout: std::time::Duration) -> Self {
+        self.acquire_timeout = acquire_timeout;
+        self
+    }
+
+    /// Begin an indexing transaction, waiting at most `acquire_timeout` for a connection.
+    pub(super) async fn begin(&self) -> Result<sqlx::Transaction<'static, sqlx::Postgres>> {
+        crate::db::pool::begin_with_timeout(&self.pool, self.acquire_timeout, "indexing").await
+    }
+
     /// Acquire advisory lock for resource to prevent concurrent indexing.
     ///
     /// Uses PostgreSQL transaction-level advisory locks (pg_advisory_xact_lock) to ensure
@@ -137,6 +157,7 @@ impl IndexingService {
                 CompileOptions {
                     base_type: None,
                     strict: false,
+                    optimize: false,
                 },
             )
             .map_err(|e| crate::Error::FhirPath(e.to_string()))?;
@@ -150,6 +171,30 @@ impl IndexingService {
         Ok((plan, false, compile_time))
     }
 
+    /// Whether any of the parameters' expressions call `resolve()`.
+    ///
+    /// Expressions that fail to compile are checked textually so they still get a warm
+    /// reference cache when they are evaluated on a best-effort basis.
+    pub(crate) fn uses_resolve(&self, search_params: &[SearchParameter]) -> bool {
+        search_params.iter().any(|p| {
+            let Some(expr) = p.expression.as_deref() else {
+                return false;
+            };
+            if let Some(uses) = self.resolve_usage_cache.read().unwrap().get(expr) {
+                return *uses;
+            }
+            let uses = match self.fhirpath_engine.analyze(expr, None) {
+                Ok(analysis) => analysis.uses_resolve(),
+                Err(_) => expr.contains("resolve("),
+            };
+            self.resolve_usage_cache
+                .write()
+                .unwrap()
+                .insert(expr.to_string(), uses);
+            uses
+        })
+    }
+
     /// Invalidate the cache for a specific resource type, or clear all if None
     pub fn invalidate_cache(&self, resource_type: Option<&str>) {
         let mut cache = self.search_params_cache.write().unwrap();
@@ -200,11 +245,7 @@ impl IndexingService {
             return Ok(());
         }
 
-        let needs_resolve = search_params.iter().any(|p| {
-            p.expression
-                .as_deref()
-                .is_some_and(|expr| expr.contains("resolve("))
-        });
+        let needs_resolve = self.uses_resolve(&search_params);
 
         // Pre-warm reference resolution cache if any parameters for this type use `resolve()`.
         // This runs outside of the write transaction to avoid deadlocks on small pools.
@@ -223,7 +264,7 @@ impl IndexingService {
             }
         }
 
-        let mut tx = self.pool.begin().await.map_err(crate::Error::Database)?;
+        let mut tx = self.begin().await?;
 
         // Acquire advisory lock to prevent concurrent indexing
         Self::acquire_indexing_lock(&mut tx, &resource.resource_type, &resource.id).await?;
@@ -306,11 +347,7 @@ impl IndexingService {
             if !search_params.is_empty()
                 || matches!(resource_type.as_str(), "CareTeam" | "Group" | "List")
             {
-                let needs_resolve = search_params.iter().any(|p| {
-                    p.expression
-                        .as_deref()
-                        .is_some_and(|expr| expr.contains("resolve("))
-                });
+                let needs_resolve = self.uses_resolve(&search_params);
                 resolve_by_type.insert(resource_type.clone(), needs_resolve);
                 params_by_type.insert(resource_type.clone(), search_params);
             }
@@ -325,13 +362,7 @@ impl IndexingService {
             let needs_resolve = resolve_by_type
                 .get(resource_type)
                 .copied()
-                .unwrap_or_else(|| {
-                    search_params.iter().any(|p| {
-                        p.expression
-                            .as_deref()
-                            .is_some_and(|expr| expr.contains("resolve("))
-                    })
-                });
+                .unwrap_or_else(|| self.uses_resolve(search_params));
             if !needs_resolve {
                 continue;
             }
@@ -353,7 +384,7 @@ impl IndexingService {
 
         // CRITICAL: Single transaction for ENTIRE batch (not per-type)
         let tx_start = std::time::Instant::now();
-        let mut tx = self.pool.begin().await.map_err(crate::Error::Database)?;
+        let mut tx = self.begin().await?;
         tracing::debug!("Started batch transaction in {:?}", tx_start.elapsed());
 
         // Track time spent in different operations
@@ -553,7 +584,8 @@ impl IndexingService {
                 total,
                 self.bulk_threshold
             );
-            let bulk_indexer = BulkIndexer::new(self.pool.clone());
+            let bulk_indexer =
+                BulkIndexer::new(self.pool.clone()).with_acquire_timeout(self.acquire_timeout);
             bulk_indexer.bulk_index_with_copy(resources, self).await
         } else if total > self.batch_size {
             // Split into optimal batches and process in sequence
@@ -1114,7 +1146,7 @@ impl IndexingService {
     }
 
     pub async fn remove_resource_index(&self, resource_type: &str, id: &str) -> Result<()> {
-        let mut tx = self.pool.begin().await.map_err(crate::Error::Database)?;
+        let mut tx = self.begin().await?;
 
         sqlx::query(
             "WITH del_string AS (DELETE FROM search_string WHERE resource_type = $1 AND resource_id = $2),
@@ -1204,6 +1236,7 @@ pub(super) fn get_or_compile_plan(
             CompileOptions {
                 base_type: None,
                 strict: false,
+                optimize: false,
             },
         )
         .map_err(|e| crate::Error::FhirPath(e.to_string()))?;
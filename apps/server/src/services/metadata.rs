@@ -85,6 +85,7 @@ impl MetadataService {
         // Build the capability statement
         let cs_config = &self.config.fhir.capability_statement;
         let now = Utc::now();
+        let cors_enabled = !self.config.server.cors_origins.is_empty();
 
         let mut capability_statement = json!({
             "resourceType": "CapabilityStatement",
@@ -120,8 +121,12 @@ impl MetadataService {
                 "mode": "server",
                 "documentation": "FHIR Server REST API",
                 "security": {
-                    "cors": true,
-                    "description": "CORS is enabled for browser-based applications"
+                    "cors": cors_enabled,
+                    "description": if cors_enabled {
+                        "CORS is enabled for browser-based applications"
+                    } else {
+                        "CORS is disabled; only same-origin browser requests are allowed"
+                    }
                 },
                 "resource": self.build_resource_capabilities(&search_params_by_resource, &supported_resources),
                 "interaction": self.build_system_interactions(),
@@ -144,6 +149,54 @@ impl MetadataService {
         Ok(capability_statement)
     }
 
+    /// Search parameters of one resource type, as a `collection` Bundle of SearchParameter
+    /// resources
+    ///
+    /// A finer-grained view than `CapabilityStatement.rest.resource.searchParam`: each entry
+    /// also carries the parameter's FHIRPath expression and canonical URL.
+    pub async fn get_resource_search_parameters(&self, resource_type: &str) -> Result<JsonValue> {
+        let params = self
+            .repo
+            .get_search_parameters_for_resource(resource_type)
+            .await?;
+
+        let entries: Vec<JsonValue> = params
+            .into_iter()
+            .map(|info| {
+                let mut sp = json!({
+                    "resourceType": "SearchParameter",
+                    "name": info.code,
+                    "status": "active",
+                    "description": info
+                        .description
+                        .unwrap_or_else(|| format!("Search parameter {}", info.code)),
+                    "code": info.code,
+                    "base": [info.resource_type],
+                    "type": info.param_type,
+                });
+                if let Some(url) = info.url {
+                    sp["url"] = json!(url);
+                }
+                if let Some(expression) = info.expression {
+                    sp["expression"] = json!(expression);
+                }
+                if info.param_type == "reference" {
+                    if let Some(targets) = info.targets.filter(|t| !t.is_empty()) {
+                        sp["target"] = json!(targets);
+                    }
+                }
+                json!({ "resource": sp })
+            })
+            .collect();
+
+        Ok(json!({
+            "resourceType": "Bundle",
+            "type": "collection",
+            "timestamp": Utc::now().to_rfc3339(),
+            "entry": entries,
+        }))
+    }
+
     async fn get_terminology_capabilities(&self, base_url: &str) -> Result<JsonValue> {
         let cs_config = &self.config.fhir.capability_statement;
         let now = Utc::now();
@@ -17,6 +17,23 @@ impl MetricsService {
         self.repo.get_job_queue_size(status).await
     }
 
+    /// Check whether the database answers a trivial query
+    pub async fn database_reachable(&self) -> bool {
+        self.repo.ping().await.is_ok()
+    }
+
+    /// Check whether every migration embedded in this binary has been applied
+    pub async fn migrations_applied(&self) -> bool {
+        let Ok(applied) = self.repo.get_applied_migration_versions().await else {
+            return false;
+        };
+
+        crate::db::MIGRATOR
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .all(|m| applied.binary_search(&m.version).is_ok())
+    }
+
     /// Update database connection pool metrics
     pub fn update_db_connection_metrics(&self) {
         let pool_size = self.repo.get_pool_size();
@@ -24,6 +41,7 @@ impl MetricsService {
 
         crate::metrics::DB_CONNECTIONS_ACTIVE.set((pool_size - idle) as i64);
         crate::metrics::DB_CONNECTIONS_IDLE.set(idle as i64);
+        crate::metrics::DB_POOL_MAX_CONNECTIONS.set(self.repo.get_max_pool_size() as i64);
     }
 
     /// Update all job queue metrics
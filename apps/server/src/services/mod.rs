@@ -6,6 +6,8 @@
 pub mod admin;
 pub mod audit;
 pub mod batch;
+pub mod bulk_import;
+pub mod code_translation;
 pub mod conditional;
 pub mod conditional_references;
 pub mod crud;
@@ -13,6 +15,8 @@ pub mod history;
 pub mod indexing;
 pub mod metadata;
 pub mod metrics;
+pub mod named_queries;
+pub mod narrative;
 pub mod operation_executor;
 pub mod operation_registry;
 pub mod package;
@@ -23,16 +27,21 @@ pub mod summary;
 pub mod system;
 pub mod terminology;
 pub mod transaction;
+pub mod write_validation;
 
 pub use admin::AdminService;
 pub use audit::AuditService;
 pub use batch::BatchService;
+pub use bulk_import::ImportService;
+pub use code_translation::CodeTranslator;
 pub use conditional_references::ConditionalReferenceResolver;
 pub use crud::CrudService;
 pub use history::HistoryService;
 pub use indexing::IndexingService;
 pub use metadata::MetadataService;
 pub use metrics::MetricsService;
+pub use named_queries::NamedQueryRegistry;
+pub use narrative::NarrativeGenerator;
 pub use operation_executor::OperationExecutor;
 pub use operation_registry::OperationRegistry;
 pub use package::PackageService;
@@ -42,3 +51,4 @@ pub use summary::SummaryFilter;
 pub use system::SystemService;
 pub use terminology::TerminologyService;
 pub use transaction::TransactionService;
+pub use write_validation::WriteValidator;
@@ -0,0 +1,211 @@
+//! Named queries (`_query`)
+//!
+//! Operators register canned searches in `fhir.search.named_queries`. A request such as
+//! `GET /Patient?_query=by-family&family=Smith` is expanded into the query's criteria with
+//! `{family}` replaced by `Smith` before the search parameters are parsed.
+
+use crate::{config::NamedQueryConfig, Error, Result};
+use std::collections::HashMap;
+
+/// A named query with its criteria pre-parsed into `(name, value template)` pairs
+#[derive(Debug, Clone)]
+struct NamedQuery {
+    resource_type: Option<String>,
+    criteria: Vec<(String, String)>,
+    parameters: Vec<String>,
+}
+
+/// Registry of the named queries configured for this server
+#[derive(Debug, Clone, Default)]
+pub struct NamedQueryRegistry {
+    queries: HashMap<String, NamedQuery>,
+}
+
+impl NamedQueryRegistry {
+    pub fn from_config(configs: &[NamedQueryConfig]) -> Self {
+        let queries = configs
+            .iter()
+            .map(|config| {
+                let criteria = url::form_urlencoded::parse(config.criteria.as_bytes())
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect();
+                (
+                    config.name.clone(),
+                    NamedQuery {
+                        resource_type: config.resource_type.clone(),
+                        criteria,
+                        parameters: config.parameters.clone(),
+                    },
+                )
+            })
+            .collect();
+        Self { queries }
+    }
+
+    /// Replace `_query` and the named query's parameters in `query_items` with its criteria.
+    ///
+    /// Items without `_query` are returned unchanged.
+    pub fn expand(
+        &self,
+        resource_type: Option<&str>,
+        query_items: &[(String, String)],
+    ) -> Result<Vec<(String, String)>> {
+        let mut names = query_items.iter().filter(|(key, _)| key == "_query");
+        let Some((_, name)) = names.next() else {
+            return Ok(query_items.to_vec());
+        };
+        if names.next().is_some() {
+            return Err(Error::Validation(
+                "Search parameter '_query' may only be given once".to_string(),
+            ));
+        }
+
+        let query = self
+            .queries
+            .get(name)
+            .ok_or_else(|| Error::Validation(format!("Unknown named query '{}'", name)))?;
+
+        if let Some(expected) = query.resource_type.as_deref() {
+            if resource_type != Some(expected) {
+                return Err(Error::Validation(format!(
+                    "Named query '{}' can only be used to search {}",
+                    name, expected
+                )));
+            }
+        }
+
+        let mut arguments: HashMap<&str, &str> = HashMap::new();
+        let mut expanded = Vec::with_capacity(query_items.len() + query.criteria.len());
+        for (key, value) in query_items {
+            if key == "_query" {
+                continue;
+            }
+            if query.parameters.iter().any(|p| p == key) {
+                if arguments.insert(key, value).is_some() {
+                    return Err(Error::Validation(format!(
+                        "Parameter '{}' of named query '{}' may only be given once",
+                        key, name
+                    )));
+                }
+                continue;
+            }
+            expanded.push((key.clone(), value.clone()));
+        }
+
+        for parameter in &query.parameters {
+            if !arguments.contains_key(parameter.as_str()) {
+                return Err(Error::Validation(format!(
+                    "Named query '{}' requires parameter '{}'",
+                    name, parameter
+                )));
+            }
+        }
+
+        for (key, template) in &query.criteria {
+            expanded.push((key.clone(), substitute(template, &arguments)));
+        }
+
+        Ok(expanded)
+    }
+}
+
+/// Replace `{parameter}` placeholders in a single pass, so arguments are never re-expanded.
+fn substitute(template: &str, arguments: &HashMap<&str, &str>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after
+            .find('}')
+            .and_then(|end| arguments.get(&after[..end]).map(|arg| (end, arg)))
+        {
+            Some((end, argument)) => {
+                out.push_str(argument);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> NamedQueryRegistry {
+        NamedQueryRegistry::from_config(&[NamedQueryConfig {
+            name: "by-family".to_string(),
+            resource_type: Some("Patient".to_string()),
+            criteria: "active=true&family={family}".to_string(),
+            parameters: vec!["family".to_string()],
+        }])
+    }
+
+    fn items(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn expands_criteria_with_parameters() {
+        let expanded = registry()
+            .expand(
+                Some("Patient"),
+                &items(&[
+                    ("_query", "by-family"),
+                    ("family", "Smith"),
+                    ("_count", "5"),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(
+            expanded,
+            items(&[("_count", "5"), ("active", "true"), ("family", "Smith")])
+        );
+    }
+
+    #[test]
+    fn arguments_cannot_inject_parameters() {
+        let expanded = registry()
+            .expand(
+                Some("Patient"),
+                &items(&[("_query", "by-family"), ("family", "x&active=false")]),
+            )
+            .unwrap();
+        assert_eq!(
+            expanded,
+            items(&[("active", "true"), ("family", "x&active=false")])
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_queries_and_missing_parameters() {
+        let registry = registry();
+        assert!(registry
+            .expand(Some("Patient"), &items(&[("_query", "nope")]))
+            .is_err());
+        assert!(registry
+            .expand(Some("Patient"), &items(&[("_query", "by-family")]))
+            .is_err());
+        assert!(registry
+            .expand(
+                Some("Observation"),
+                &items(&[("_query", "by-family"), ("family", "Smith")])
+            )
+            .is_err());
+        assert_eq!(
+            registry
+                .expand(Some("Patient"), &items(&[("family", "Smith")]))
+                .unwrap(),
+            items(&[("family", "Smith")])
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! Write-time narrative generation (`text.div`) from key resource elements
+
+use crate::config::NarrativeConfig;
+use ferrum_fhirpath::{
+    Context, Engine as FhirPathEngine, EvalOptions, ToJson, Value as FhirPathValue,
+};
+use serde_json::{json, Value as JsonValue};
+use std::{collections::HashSet, sync::Arc};
+
+/// Elements rendered for types without a dedicated list.
+const DEFAULT_ELEMENTS: &[(&str, &str)] = &[
+    ("Identifier", "identifier"),
+    ("Status", "status"),
+    ("Code", "code"),
+    ("Subject", "subject"),
+];
+
+/// Label and FHIRPath expression of the elements shown in a resource type's narrative.
+fn key_elements(resource_type: &str) -> &'static [(&'static str, &'static str)] {
+    match resource_type {
+        "Patient" | "RelatedPerson" | "Person" => &[
+            ("Name", "name"),
+            ("Identifier", "identifier"),
+            ("Gender", "gender"),
+            ("Birth date", "birthDate"),
+            ("Telecom", "telecom"),
+            ("Address", "address"),
+        ],
+        "Practitioner" => &[
+            ("Name", "name"),
+            ("Identifier", "identifier"),
+            ("Qualification", "qualification.code"),
+            ("Telecom", "telecom"),
+        ],
+        "Organization" | "Location" => &[
+            ("Name", "name"),
+            ("Identifier", "identifier"),
+            ("Type", "type"),
+            ("Telecom", "telecom"),
+            ("Address", "address"),
+        ],
+        "Observation" => &[
+            ("Code", "code"),
+            ("Status", "status"),
+            ("Subject", "subject"),
+            ("Effective", "effective"),
+            ("Value", "value"),
+            ("Interpretation", "interpretation"),
+        ],
+        "Condition" => &[
+            ("Code", "code"),
+            ("Clinical status", "clinicalStatus"),
+            ("Subject", "subject"),
+            ("Onset", "onset"),
+        ],
+        "AllergyIntolerance" => &[
+            ("Code", "code"),
+            ("Clinical status", "clinicalStatus"),
+            ("Criticality", "criticality"),
+            ("Patient", "patient"),
+        ],
+        "Procedure" => &[
+            ("Code", "code"),
+            ("Status", "status"),
+            ("Subject", "subject"),
+            ("Performed", "performed"),
+        ],
+        "Encounter" => &[
+            ("Status", "status"),
+            ("Class", "class"),
+            ("Type", "type"),
+            ("Subject", "subject"),
+            ("Period", "period"),
+        ],
+        "MedicationRequest" => &[
+            ("Medication", "medication"),
+            ("Status", "status"),
+            ("Intent", "intent"),
+            ("Subject", "subject"),
+            ("Dosage", "dosageInstruction.text"),
+        ],
+        _ => DEFAULT_ELEMENTS,
+    }
+}
+
+/// Generates a basic XHTML narrative for configured resource types (see `fhir.narrative`).
+///
+/// Resources without `text`, or whose narrative was itself generated (`text.status =
+/// generated`), get a fresh `text.div` listing their key elements. Authored narratives
+/// (`extensions`, `additional`, `empty`) are left untouched.
+pub struct NarrativeGenerator {
+    engine: Arc<FhirPathEngine>,
+    resource_types: HashSet<String>,
+}
+
+impl NarrativeGenerator {
+    pub fn new(
+        engine: Arc<FhirPathEngine>,
+        resource_types: impl IntoIterator<Item = String>,
+    ) -> Self {
+        Self {
+            engine,
+            resource_types: resource_types.into_iter().collect(),
+        }
+    }
+
+    /// Build the generator described by `fhir.narrative`, or `None` when no type is enabled.
+    pub fn from_config(config: &NarrativeConfig, engine: Arc<FhirPathEngine>) -> Option<Self> {
+        if config.resource_types.is_empty() {
+            return None;
+        }
+        Some(Self::new(engine, config.resource_types.iter().cloned()))
+    }
+
+    /// Add or refresh the generated narrative of a resource about to be written.
+    pub fn apply(&self, resource: &mut JsonValue) {
+        let Some(resource_type) = resource
+            .get("resourceType")
+            .and_then(JsonValue::as_str)
+            .filter(|resource_type| self.resource_types.contains(*resource_type))
+            .map(str::to_string)
+        else {
+            return;
+        };
+
+        let regenerate = match resource.get("text") {
+            None => true,
+            Some(text) => text.get("status").and_then(JsonValue::as_str) == Some("generated"),
+        };
+        if !regenerate {
+            return;
+        }
+
+        let div = self.generate_div(&resource_type, resource);
+        if let Some(obj) = resource.as_object_mut() {
+            obj.insert(
+                "text".to_string(),
+                json!({ "status": "generated", "div": div }),
+            );
+        }
+    }
+
+    fn generate_div(&self, resource_type: &str, resource: &JsonValue) -> String {
+        let ctx = Context::new(FhirPathValue::from_json(resource.clone()));
+
+        let mut div = String::from("<div xmlns=\"http://www.w3.org/1999/xhtml\">");
+        div.push_str(&format!("<p><b>{}</b>", escape(resource_type)));
+        if let Some(id) = resource.get("id").and_then(JsonValue::as_str) {
+            div.push_str(&format!(" {}", escape(id)));
+        }
+        div.push_str("</p>");
+
+        for (label, expression) in key_elements(resource_type) {
+            let values = match self.engine.evaluate_expr_with_options(
+                expression,
+                &ctx,
+                EvalOptions {
+                    base_type: Some(resource_type.to_string()),
+                    strict: false,
+                    infer_base_type: false,
+                },
+            ) {
+                Ok(collection) => collection
+                    .iter()
+                    .filter_map(|value| value.to_json())
+                    .filter_map(|value| display(&value))
+                    .collect::<Vec<_>>(),
+                Err(e) => {
+                    tracing::warn!(
+                        resource_type = %resource_type,
+                        expression = %expression,
+                        error = %e,
+                        "Skipping narrative element"
+                    );
+                    continue;
+                }
+            };
+            if values.is_empty() {
+                continue;
+            }
+            div.push_str(&format!(
+                "<p><b>{}</b>: {}</p>",
+                label,
+                escape(&values.join(", "))
+            ));
+        }
+
+        div.push_str("</div>");
+        div
+    }
+}
+
+/// Human readable form of an element value, based on the common data type shapes.
+fn display(value: &JsonValue) -> Option<String> {
+    let text = match value {
+        JsonValue::String(s) => s.clone(),
+        JsonValue::Number(n) => n.to_string(),
+        JsonValue::Bool(b) => b.to_string(),
+        JsonValue::Object(obj) => {
+            let field = |name: &str| obj.get(name).and_then(display);
+            if let Some(text) = field("text").or_else(|| field("display")) {
+                // CodeableConcept, HumanName, Address, Reference, Coding
+                text
+            } else if obj.contains_key("family") || obj.contains_key("given") {
+                // HumanName
+                join_fields(obj, &["prefix", "given", "family", "suffix"], " ")
+            } else if let Some(coding) = obj.get("coding").and_then(JsonValue::as_array) {
+                // CodeableConcept without text
+                coding.iter().filter_map(display).next()?
+            } else if obj.contains_key("line") || obj.contains_key("city") {
+                // Address
+                join_fields(obj, &["line", "city", "postalCode", "country"], ", ")
+            } else if obj.get("value").is_some_and(JsonValue::is_number) {
+                // Quantity
+                let amount = field("value")?;
+                match field("unit").or_else(|| field("code")) {
+                    Some(unit) => format!("{} {}", amount, unit),
+                    None => amount,
+                }
+            } else if obj.contains_key("start") || obj.contains_key("end") {
+                // Period
+                format!(
+                    "{} - {}",
+                    field("start").unwrap_or_default(),
+                    field("end").unwrap_or_default()
+                )
+            } else {
+                // Identifier, ContactPoint, Coding without display, Reference
+                field("value")
+                    .or_else(|| field("code"))
+                    .or_else(|| field("reference"))?
+            }
+        }
+        _ => return None,
+    };
+    let text = text.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Displays of the given fields (repeating or not), in order.
+fn join_fields(
+    obj: &serde_json::Map<String, JsonValue>,
+    names: &[&str],
+    separator: &str,
+) -> String {
+    names
+        .iter()
+        .filter_map(|name| obj.get(*name))
+        .flat_map(|field| match field {
+            JsonValue::Array(items) => items.iter().filter_map(display).collect(),
+            other => display(other).into_iter().collect::<Vec<_>>(),
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrum_context::DefaultFhirContext;
+
+    fn generator(resource_types: &[&str]) -> NarrativeGenerator {
+        let engine = FhirPathEngine::new(Arc::new(DefaultFhirContext::from_packages(vec![])), None);
+        NarrativeGenerator::new(
+            Arc::new(engine),
+            resource_types.iter().map(|t| t.to_string()),
+        )
+    }
+
+    #[test]
+    fn generates_narrative_only_for_configured_types_without_authored_text() {
+        let generator = generator(&["Patient"]);
+
+        let mut patient = json!({
+            "resourceType": "Patient",
+            "id": "p1",
+            "name": [{ "given": ["Ann"], "family": "Smith" }],
+            "gender": "female"
+        });
+        generator.apply(&mut patient);
+        assert_eq!(patient["text"]["status"], "generated");
+        let div = patient["text"]["div"].as_str().unwrap();
+        assert!(div.contains("<b>Name</b>: Ann Smith"), "{div}");
+        assert!(div.contains("<b>Gender</b>: female"), "{div}");
+
+        // A generated narrative is refreshed, an authored one is kept
+        patient["name"][0]["family"] = json!("Jones");
+        generator.apply(&mut patient);
+        assert!(patient["text"]["div"]
+            .as_str()
+            .unwrap()
+            .contains("Ann Jones"));
+        let authored = json!({ "status": "additional", "div": "<div>Authored</div>" });
+        patient["text"] = authored.clone();
+        generator.apply(&mut patient);
+        assert_eq!(patient["text"], authored);
+
+        let mut observation = json!({ "resourceType": "Observation", "status": "final" });
+        generator.apply(&mut observation);
+        assert!(observation.get("text").is_none());
+    }
+
+    #[test]
+    fn displays_common_data_types() {
+        let name = json!({ "prefix": ["Dr"], "given": ["Ann", "Marie"], "family": "Smith" });
+        assert_eq!(display(&name).as_deref(), Some("Dr Ann Marie Smith"));
+
+        let concept = json!({ "coding": [{ "system": "http://loinc.org", "code": "8867-4" }] });
+        assert_eq!(display(&concept).as_deref(), Some("8867-4"));
+
+        let quantity = json!({ "value": 72, "unit": "beats/min" });
+        assert_eq!(display(&quantity).as_deref(), Some("72 beats/min"));
+
+        let identifier = json!({ "system": "urn:mrn", "value": "12345" });
+        assert_eq!(display(&identifier).as_deref(), Some("12345"));
+
+        assert_eq!(display(&json!({})), None);
+    }
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(
+            escape("<b>O'Neil & Co</b>"),
+            "&lt;b&gt;O&#39;Neil &amp; Co&lt;/b&gt;"
+        );
+    }
+}
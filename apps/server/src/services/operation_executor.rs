@@ -1,8 +1,14 @@
+use crate::config::BulkImportConfig;
 use crate::db::search::engine::SearchEngine;
 use crate::db::PostgresResourceStore;
 use crate::error::{Error, Result};
-use crate::models::{OperationContext, OperationRequest, OperationResult, Parameters};
+use crate::models::{
+    OperationContext, OperationRequest, OperationResult, Parameter, ParameterValue, Parameters,
+};
 use crate::queue::{JobPriority, JobQueue};
+use crate::services::bulk_import::{
+    ImportInput, ImportJobParams, ImportValidation, BULK_IMPORT_JOB_TYPE,
+};
 use crate::services::{IndexingService, PackageService, TerminologyService};
 use async_trait::async_trait;
 use serde_json::json;
@@ -21,6 +27,8 @@ pub struct OperationExecutor {
     job_queue: Option<Arc<dyn JobQueue>>,
     search_engine: Option<Arc<SearchEngine>>,
     store: Option<PostgresResourceStore>,
+    /// Largest `$import` `batchSize` accepted (`fhir.bulk_import.max_batch_size`)
+    max_import_batch_size: usize,
 }
 
 impl OperationExecutor {
@@ -32,6 +40,7 @@ impl OperationExecutor {
             job_queue: None,
             search_engine: None,
             store: None,
+            max_import_batch_size: BulkImportConfig::default().max_batch_size,
         }
     }
 
@@ -51,9 +60,15 @@ impl OperationExecutor {
             job_queue: Some(job_queue),
             search_engine: Some(search_engine),
             store: Some(store),
+            max_import_batch_size: BulkImportConfig::default().max_batch_size,
         }
     }
 
+    /// Largest `batchSize` a `$import` request may ask for.
+    pub fn set_max_import_batch_size(&mut self, max_batch_size: usize) {
+        self.max_import_batch_size = max_batch_size;
+    }
+
     pub async fn execute(&self, request: OperationRequest) -> Result<OperationResult> {
         match request.operation_name.as_str() {
             "install-package" => self.execute_install_package(request).await,
@@ -65,6 +80,8 @@ impl OperationExecutor {
             "translate" => self.execute_translate(request).await,
             "closure" => self.execute_closure(request).await,
             "everything" => self.execute_everything(request).await,
+            "lastn" => self.execute_lastn(request).await,
+            "import" => self.execute_import(request).await,
             _ => Err(Error::NotImplemented(format!(
                 "Operation '{}' not yet implemented",
                 request.operation_name
@@ -432,6 +449,234 @@ impl OperationExecutor {
 
         Ok(OperationResult::Resource(bundle))
     }
+
+    /// Observation/$lastn — the `max` most recent Observations per subject and code.
+    async fn execute_lastn(&self, request: OperationRequest) -> Result<OperationResult> {
+        match &request.context {
+            OperationContext::Type(rt) if rt == "Observation" => {}
+            _ => {
+                return Err(Error::Validation(
+                    "$lastn is only supported at type level (Observation/$lastn)".to_string(),
+                ));
+            }
+        }
+
+        let search_engine = self
+            .search_engine
+            .as_ref()
+            .ok_or_else(|| Error::Internal("SearchEngine not available".to_string()))?;
+
+        // max defaults to 1; GET query values arrive as valueInteger, POST may use either
+        let max = match request.parameters.get_value("max") {
+            None => 1,
+            Some(v) => v
+                .as_i64()
+                .or_else(|| v.as_str().and_then(|s| s.trim().parse().ok()))
+                .filter(|n| *n >= 1)
+                .ok_or_else(|| {
+                    Error::Validation("Parameter 'max' must be a positive integer".to_string())
+                })?,
+        };
+
+        // patient accepts "Patient/[id]" or a bare id
+        let patient_id: Option<String> = request
+            .parameters
+            .get_value("patient")
+            .and_then(|v| match v {
+                serde_json::Value::String(s) => Some(s.clone()),
+                serde_json::Value::Number(n) => Some(n.to_string()),
+                serde_json::Value::Object(obj) => obj
+                    .get("reference")
+                    .and_then(|r| r.as_str())
+                    .map(|s| s.to_string()),
+                _ => None,
+            })
+            .map(|s| s.trim_start_matches("Patient/").to_string());
+
+        // category accepts a token ("[system|]code") or a Coding
+        let category = request
+            .parameters
+            .get_value("category")
+            .and_then(parse_lastn_category);
+
+        let resources = search_engine
+            .lastn_observations(max, patient_id.as_deref(), category.as_ref())
+            .await?;
+
+        let entries: Vec<_> = resources
+            .into_iter()
+            .map(|resource| {
+                let id = resource
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown");
+                json!({
+                    "fullUrl": format!("Observation/{}", id),
+                    "resource": resource,
+                    "search": { "mode": "match" }
+                })
+            })
+            .collect();
+
+        let bundle = json!({
+            "resourceType": "Bundle",
+            "type": "searchset",
+            "total": entries.len(),
+            "entry": entries
+        });
+
+        Ok(OperationResult::Resource(bundle))
+    }
+}
+
+impl OperationExecutor {
+    /// $import operation - queue a bulk load of NDJSON resources
+    async fn execute_import(&self, request: OperationRequest) -> Result<OperationResult> {
+        if !matches!(request.context, OperationContext::System) {
+            return Err(Error::InvalidResource(
+                "$import can only be invoked at system level".to_string(),
+            ));
+        }
+
+        let job_queue = self
+            .job_queue
+            .as_ref()
+            .ok_or_else(|| Error::Internal("JobQueue not available".to_string()))?;
+
+        let inputs = request
+            .parameters
+            .all_parameters()
+            .filter(|p| p.name == "input")
+            .map(parse_import_input)
+            .collect::<Result<Vec<_>>>()?;
+        if inputs.is_empty() {
+            return Err(Error::Validation(
+                "Missing required parameter: input".to_string(),
+            ));
+        }
+
+        let validation = match request
+            .parameters
+            .get_value("validation")
+            .and_then(|v| v.as_str())
+        {
+            None => ImportValidation::default(),
+            Some(v) => ImportValidation::parse(v).ok_or_else(|| {
+                Error::Validation(format!(
+                    "Invalid validation mode '{}': expected skip or validate",
+                    v
+                ))
+            })?,
+        };
+
+        let batch_size = match request.parameters.get_value("batchSize") {
+            None => ImportJobParams::default_batch_size(),
+            Some(v) => match v.as_i64() {
+                Some(n) if n >= 1 && n as u64 <= self.max_import_batch_size as u64 => n as usize,
+                _ => {
+                    return Err(Error::Validation(format!(
+                        "Parameter 'batchSize' must be an integer between 1 and {}",
+                        self.max_import_batch_size
+                    )))
+                }
+            },
+        };
+
+        let input_count = inputs.len();
+        let params = serde_json::to_value(ImportJobParams {
+            inputs,
+            validation,
+            batch_size,
+        })
+        .map_err(|e| Error::Internal(format!("Failed to serialize import parameters: {}", e)))?;
+
+        let job_id = job_queue
+            .enqueue(
+                BULK_IMPORT_JOB_TYPE.to_string(),
+                params,
+                JobPriority::Normal,
+                None,
+            )
+            .await?;
+
+        let mut response = Parameters::new();
+        response.add_resource(
+            "outcome".to_string(),
+            json!({
+                "resourceType": "OperationOutcome",
+                "issue": [{
+                    "severity": "information",
+                    "code": "informational",
+                    "diagnostics": format!("Import job queued for {} input(s)", input_count)
+                }]
+            }),
+        );
+        response.add_value_string("jobId".to_string(), job_id.to_string());
+
+        Ok(OperationResult::Parameters(response))
+    }
+}
+
+/// Parse an `input` parameter: `type`, and either `url` or inline `ndjson`.
+fn parse_import_input(param: &Parameter) -> Result<ImportInput> {
+    let ParameterValue::Parts { part } = &param.value else {
+        return Err(Error::Validation(
+            "Parameter 'input' must have parts".to_string(),
+        ));
+    };
+
+    let part_value = |name: &str| {
+        part.iter()
+            .find(|p| p.name == name)
+            .and_then(|p| match &p.value {
+                ParameterValue::Value(map) if map.len() == 1 => map.values().next(),
+                _ => None,
+            })
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    };
+
+    let input = ImportInput {
+        resource_type: part_value("type"),
+        url: part_value("url"),
+        ndjson: part_value("ndjson"),
+    };
+    if input.url.is_none() && input.ndjson.is_none() {
+        return Err(Error::Validation(
+            "Each input requires a 'url' or 'ndjson' part".to_string(),
+        ));
+    }
+    Ok(input)
+}
+
+fn parse_lastn_category(
+    value: &serde_json::Value,
+) -> Option<crate::db::search::engine::LastNCategory> {
+    use crate::db::search::engine::LastNCategory;
+
+    match value {
+        serde_json::Value::String(token) => Some(match token.split_once('|') {
+            Some((system, code)) => LastNCategory {
+                system: (!system.is_empty()).then(|| system.to_string()),
+                code: code.to_string(),
+            },
+            None => LastNCategory {
+                system: None,
+                code: token.clone(),
+            },
+        }),
+        serde_json::Value::Object(coding) => {
+            let code = coding.get("code").and_then(|c| c.as_str())?;
+            Some(LastNCategory {
+                system: coding
+                    .get("system")
+                    .and_then(|s| s.as_str())
+                    .map(|s| s.to_string()),
+                code: code.to_string(),
+            })
+        }
+        _ => None,
+    }
 }
 
 impl Default for OperationExecutor {
@@ -10,7 +10,7 @@ use crate::{
     db::search::params::{CursorDirection, SearchParameters},
     models::is_known_resource_type,
     runtime_config::{ConfigKey, RuntimeConfigCache},
-    services::SummaryFilter,
+    services::{NamedQueryRegistry, SummaryFilter},
     Result,
 };
 use serde::{Deserialize, Serialize};
@@ -36,6 +36,7 @@ pub struct SearchService {
     search_engine: Arc<SearchEngine>,
     summary_filter: Option<Arc<SummaryFilter>>,
     runtime_config_cache: Arc<RuntimeConfigCache>,
+    named_queries: NamedQueryRegistry,
 }
 
 impl SearchService {
@@ -48,6 +49,7 @@ impl SearchService {
             search_engine,
             summary_filter: None,
             runtime_config_cache,
+            named_queries: NamedQueryRegistry::default(),
         }
     }
 
@@ -61,9 +63,15 @@ impl SearchService {
             search_engine,
             summary_filter: Some(summary_filter),
             runtime_config_cache,
+            named_queries: NamedQueryRegistry::default(),
         }
     }
 
+    /// Register the named queries available through `_query`
+    pub fn set_named_queries(&mut self, named_queries: NamedQueryRegistry) {
+        self.named_queries = named_queries;
+    }
+
     /// Search for resources of a specific type
     ///
     /// GET/POST [base]/{resource_type}?params
@@ -76,11 +84,10 @@ impl SearchService {
     ) -> Result<JsonValue> {
         self.validate_resource_type_name(resource_type)?;
 
-        let params = SearchParameters::from_items(query_items)?;
-        let default_count: usize = self
-            .runtime_config_cache
-            .get(ConfigKey::SearchDefaultCount)
-            .await;
+        let (params, query_items, query_string) = self
+            .parse_search_params(Some(resource_type), query_items, query_string)
+            .await?;
+        let default_count = self.default_count().await;
 
         // Execute search via database engine
         let result = self
@@ -92,11 +99,11 @@ impl SearchService {
         self.build_searchset_bundle(
             result,
             resource_type,
-            query_string,
+            &query_string,
             base_url,
             &params,
             default_count,
-            query_items,
+            &query_items,
         )
     }
 
@@ -110,11 +117,10 @@ impl SearchService {
         query_string: &str,
         base_url: &str,
     ) -> Result<JsonValue> {
-        let params = SearchParameters::from_items(query_items)?;
-        let default_count: usize = self
-            .runtime_config_cache
-            .get(ConfigKey::SearchDefaultCount)
-            .await;
+        let (params, query_items, query_string) = self
+            .parse_search_params(None, query_items, query_string)
+            .await?;
+        let default_count = self.default_count().await;
 
         // Extract _type parameter to determine which resource types to search
         // If not specified, this is an error per FHIR spec
@@ -137,11 +143,11 @@ impl SearchService {
         self.build_searchset_bundle(
             result,
             "",
-            query_string,
+            &query_string,
             base_url,
             &params,
             default_count,
-            query_items,
+            &query_items,
         )
     }
 
@@ -162,11 +168,10 @@ impl SearchService {
             self.validate_resource_type_name(resource_type)?;
         }
 
-        let params = SearchParameters::from_items(query_items)?;
-        let default_count: usize = self
-            .runtime_config_cache
-            .get(ConfigKey::SearchDefaultCount)
-            .await;
+        let (params, query_items, query_string) = self
+            .parse_search_params(resource_type, query_items, query_string)
+            .await?;
+        let default_count = self.default_count().await;
 
         // Execute compartment search
         let result = self
@@ -190,11 +195,11 @@ impl SearchService {
         self.build_searchset_bundle(
             result,
             &search_path,
-            query_string,
+            &query_string,
             base_url,
             &params,
             default_count,
-            query_items,
+            &query_items,
         )
     }
 
@@ -434,6 +439,71 @@ impl SearchService {
         Ok(bundle)
     }
 
+    /// Parse search parameters, expanding a named query (`_query`) and clamping `_count` to
+    /// the configured maximum page size.
+    ///
+    /// Returns the query items and query string to use for bundle links; these keep `_query`
+    /// as requested and carry the page size actually used: the clamped `_count`, or the
+    /// configured default when the client gave none.
+    async fn parse_search_params(
+        &self,
+        resource_type: Option<&str>,
+        query_items: &[(String, String)],
+        query_string: &str,
+    ) -> Result<(SearchParameters, Vec<(String, String)>, String)> {
+        let expanded = self.named_queries.expand(resource_type, query_items)?;
+        let mut params = SearchParameters::from_items(&expanded)?;
+        let max_count: usize = self
+            .runtime_config_cache
+            .get(ConfigKey::SearchMaxCount)
+            .await;
+
+        let query_items: Vec<(String, String)> = match params.count {
+            Some(count) if count > max_count => {
+                params.count = Some(max_count);
+                query_items
+                    .iter()
+                    .map(|(key, value)| {
+                        if key == "_count" {
+                            (key.clone(), max_count.to_string())
+                        } else {
+                            (key.clone(), value.clone())
+                        }
+                    })
+                    .collect()
+            }
+            None if !matches!(
+                params.summary,
+                Some(crate::db::search::params::SummaryMode::Count)
+            ) =>
+            {
+                let default_count = self.default_count().await;
+                let mut items = query_items.to_vec();
+                items.push(("_count".to_string(), default_count.to_string()));
+                items
+            }
+            _ => return Ok((params, query_items.to_vec(), query_string.to_string())),
+        };
+
+        let query_string = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(&query_items)
+            .finish();
+        Ok((params, query_items, query_string))
+    }
+
+    /// The configured default page size, capped at the maximum like an explicit `_count`.
+    async fn default_count(&self) -> usize {
+        let default_count: usize = self
+            .runtime_config_cache
+            .get(ConfigKey::SearchDefaultCount)
+            .await;
+        let max_count: usize = self
+            .runtime_config_cache
+            .get(ConfigKey::SearchMaxCount)
+            .await;
+        default_count.min(max_count)
+    }
+
     /// Build a pagination URL with cursor
     /// Per FHIR spec: preserves _count and _maxresults in pagination links
     fn build_paging_url(
@@ -462,9 +462,7 @@ impl TerminologyService {
         let matches = translate_with_map(&map, &system, &code, reverse);
 
         let mut out = Parameters::new();
-        let result = matches
-            .iter()
-            .any(|m| m.equivalence != "unmatched" && m.equivalence != "disjoint");
+        let result = matches.iter().any(TranslationMatch::is_match);
         out.add_value_boolean("result".to_string(), result);
         for m in matches {
             let mut parts = Vec::new();
@@ -495,6 +493,19 @@ impl TerminologyService {
         Ok(out)
     }
 
+    /// The current ConceptMap whose canonical url is `url`, for translating codes with
+    /// [`Self::translate_code`].
+    pub async fn concept_map(&self, url: &str) -> Result<Option<JsonValue>> {
+        self.repo
+            .find_resource_by_canonical_url("ConceptMap", url, None)
+            .await
+    }
+
+    /// Translate `system|code` with a loaded ConceptMap, as `$translate` does.
+    pub fn translate_code(map: &JsonValue, system: &str, code: &str) -> Vec<TranslationMatch> {
+        translate_with_map(map, system, code, false)
+    }
+
     pub async fn closure(&self, params: &Parameters) -> Result<JsonValue> {
         let name = params
             .get_value("name")
@@ -1336,12 +1347,20 @@ struct Concept {
     designations: Option<JsonValue>,
 }
 
+/// A target concept of a ConceptMap translation (`$translate` `match`).
 #[derive(Debug, Clone)]
-struct TranslationMatch {
-    system: String,
-    code: String,
-    display: Option<String>,
-    equivalence: String,
+pub struct TranslationMatch {
+    pub system: String,
+    pub code: String,
+    pub display: Option<String>,
+    pub equivalence: String,
+}
+
+impl TranslationMatch {
+    /// Whether the target counts as a translation (`unmatched` and `disjoint` do not).
+    pub fn is_match(&self) -> bool {
+        self.equivalence != "unmatched" && self.equivalence != "disjoint"
+    }
 }
 
 fn extract_valueset_expansion_contains(value: &JsonValue, out: &mut HashMap<String, Concept>) {
@@ -11,7 +11,7 @@ use crate::{
     },
     hooks::ResourceHook,
     runtime_config::{ConfigKey, RuntimeConfigCache},
-    services::IndexingService,
+    services::{CodeTranslator, IndexingService, NarrativeGenerator, WriteValidator},
     Result,
 };
 use axum::http::StatusCode;
@@ -26,7 +26,8 @@ use ferrum_context::FhirContext;
 use ferrum_models::{Bundle, BundleEntry, BundleEntryResponse, BundleType, StructureDefinition};
 use uuid::Uuid;
 
-use super::batch::{BundleRequestOptions, PreferReturn};
+use super::batch::{outcome_with_issues, BundleRequestOptions, PreferReturn};
+use super::crud::ensure_type_writable;
 use crate::db::search::engine::SearchEngine;
 use crate::services::conditional::{
     build_conditional_search_params_from_items, parse_form_urlencoded,
@@ -86,6 +87,10 @@ pub struct TransactionService {
     hard_delete: bool,
     runtime_config_cache: Option<Arc<RuntimeConfigCache>>,
     referential_integrity_mode: String,
+    read_only_types: HashSet<String>,
+    write_validator: Option<Arc<WriteValidator>>,
+    narrative_generator: Option<Arc<NarrativeGenerator>>,
+    code_translator: Option<Arc<CodeTranslator>>,
     transaction_recorder: Option<TransactionRecorder>,
 }
 
@@ -110,6 +115,10 @@ impl TransactionService {
             hard_delete,
             runtime_config_cache: None,
             referential_integrity_mode: "lenient".to_string(),
+            read_only_types: HashSet::new(),
+            write_validator: None,
+            narrative_generator: None,
+            code_translator: None,
             transaction_recorder: None,
         }
     }
@@ -118,6 +127,22 @@ impl TransactionService {
         self.referential_integrity_mode = mode;
     }
 
+    pub fn set_read_only_types(&mut self, types: impl IntoIterator<Item = String>) {
+        self.read_only_types = types.into_iter().collect();
+    }
+
+    pub fn set_write_validator(&mut self, validator: Option<Arc<WriteValidator>>) {
+        self.write_validator = validator;
+    }
+
+    pub fn set_narrative_generator(&mut self, generator: Option<Arc<NarrativeGenerator>>) {
+        self.narrative_generator = generator;
+    }
+
+    pub fn set_code_translator(&mut self, translator: Option<Arc<CodeTranslator>>) {
+        self.code_translator = translator;
+    }
+
     pub fn set_transaction_recorder(&mut self, recorder: TransactionRecorder) {
         self.transaction_recorder = Some(recorder);
     }
@@ -177,8 +202,13 @@ impl TransactionService {
 
         let entry_count = bundle.entry.as_ref().map(|e| e.len() as i32).unwrap_or(0);
         let tracking_id = Uuid::new_v4();
+        // Dry runs leave no trace: they are neither tracked nor indexed
+        let recorder = self
+            .transaction_recorder
+            .as_ref()
+            .filter(|_| !options.validate_only);
 
-        if let Some(recorder) = &self.transaction_recorder {
+        if let Some(recorder) = recorder {
             if let Err(e) = recorder
                 .record_start(tracking_id, "transaction", entry_count, None)
                 .await
@@ -209,7 +239,7 @@ impl TransactionService {
             match self.process_transaction(bundle, &options).await {
                 Ok(result) => result,
                 Err(err) => {
-                    if let Some(recorder) = &self.transaction_recorder {
+                    if let Some(recorder) = recorder {
                         if let Err(e) = recorder
                             .record_complete(tracking_id, "failed", Some(&err.to_string()))
                             .await
@@ -221,7 +251,7 @@ impl TransactionService {
                 }
             };
 
-        if let Some(recorder) = &self.transaction_recorder {
+        if let Some(recorder) = recorder {
             let entry_records =
                 extract_entry_records(&response_bundle, &original_requests);
             if let Err(e) = recorder
@@ -239,15 +269,17 @@ impl TransactionService {
         }
 
         // Only after successful commit: trigger hooks + inline indexing
-        if let Err(e) = self.trigger_conformance_hooks(&response_bundle).await {
-            tracing::warn!("Failed to trigger conformance hooks: {}", e);
-        }
+        if !options.validate_only {
+            if let Err(e) = self.trigger_conformance_hooks(&response_bundle).await {
+                tracing::warn!("Failed to trigger conformance hooks: {}", e);
+            }
 
-        if let Err(e) = self
-            .apply_inline_indexing(&response_bundle, indexing_actions)
-            .await
-        {
-            tracing::warn!("Failed to apply inline transaction indexing: {}", e);
+            if let Err(e) = self
+                .apply_inline_indexing(&response_bundle, indexing_actions)
+                .await
+            {
+                tracing::warn!("Failed to apply inline transaction indexing: {}", e);
+            }
         }
 
         serde_json::to_value(response_bundle).map_err(|e| {
@@ -413,7 +445,11 @@ impl TransactionService {
             }
         }
 
-        tx.commit().await?;
+        if options.validate_only {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
 
         indexing_actions.add_written(&written_resources);
 
@@ -456,6 +492,11 @@ impl TransactionService {
 
         let method = request.method.to_uppercase();
         let parsed_url = ParsedUrl::parse(&request.url);
+        if !matches!(method.as_str(), "GET" | "HEAD") {
+            if let Some(resource_type) = parsed_url.resource_type.as_deref() {
+                ensure_type_writable(&self.read_only_types, resource_type)?;
+            }
+        }
         let query_items = query_from_url(&request.url)
             .map(parse_form_urlencoded)
             .transpose()?
@@ -736,6 +777,10 @@ impl TransactionService {
                 })?;
                 populate_meta(&mut resource, &id, 1, Utc::now());
 
+                self.translate_codes(&mut resource).await?;
+                self.generate_narrative(&mut resource);
+                let issues = self.validate_on_write(&resource).await?;
+
                 // Referential integrity check (strict mode)
                 if self.is_strict_referential_integrity() {
                     self.validate_references_in_transaction(&resource, &known_ids).await?;
@@ -766,20 +811,23 @@ impl TransactionService {
                         )),
                         etag: Some(format!("W/\"{}\"", created.version_id)),
                         last_modified: Some(created.last_updated.to_rfc3339()),
-                        outcome: match prefer_return {
-                            PreferReturn::OperationOutcome => Some(serde_json::json!({
-                                "resourceType": "OperationOutcome",
-                                "issue": [{
-                                    "severity": "information",
-                                    "code": "informational",
-                                    "diagnostics": format!(
-                                        "Resource created successfully with ID {}",
-                                        created.id
-                                    )
-                                }]
-                            })),
-                            _ => None,
-                        },
+                        outcome: outcome_with_issues(
+                            match prefer_return {
+                                PreferReturn::OperationOutcome => Some(serde_json::json!({
+                                    "resourceType": "OperationOutcome",
+                                    "issue": [{
+                                        "severity": "information",
+                                        "code": "informational",
+                                        "diagnostics": format!(
+                                            "Resource created successfully with ID {}",
+                                            created.id
+                                        )
+                                    }]
+                                })),
+                                _ => None,
+                            },
+                            issues,
+                        ),
                         extensions: HashMap::new(),
                     }),
                     resource: match prefer_return {
@@ -872,6 +920,10 @@ impl TransactionService {
                                     .insert(full_url.clone(), format!("{}/{}", resource_type, &id));
                             }
 
+                            self.translate_codes(&mut resource).await?;
+                            self.generate_narrative(&mut resource);
+                            let issues = self.validate_on_write(&resource).await?;
+
                             // Referential integrity check (strict mode)
                             if self.is_strict_referential_integrity() {
                                 self.validate_references_in_transaction(&resource, &known_ids).await?;
@@ -902,20 +954,25 @@ impl TransactionService {
                                     )),
                                     etag: Some(format!("W/\"{}\"", created.version_id)),
                                     last_modified: Some(created.last_updated.to_rfc3339()),
-                                    outcome: match prefer_return {
-                                        PreferReturn::OperationOutcome => Some(serde_json::json!({
-                                            "resourceType": "OperationOutcome",
-                                            "issue": [{
-                                                "severity": "information",
-                                                "code": "informational",
-                                                "diagnostics": format!(
-                                                    "Resource created successfully with ID {}",
-                                                    created.id
-                                                )
-                                            }]
-                                        })),
-                                        _ => None,
-                                    },
+                                    outcome: outcome_with_issues(
+                                        match prefer_return {
+                                            PreferReturn::OperationOutcome => {
+                                                Some(serde_json::json!({
+                                                    "resourceType": "OperationOutcome",
+                                                    "issue": [{
+                                                        "severity": "information",
+                                                        "code": "informational",
+                                                        "diagnostics": format!(
+                                                            "Resource created successfully with ID {}",
+                                                            created.id
+                                                        )
+                                                    }]
+                                                }))
+                                            }
+                                            _ => None,
+                                        },
+                                        issues,
+                                    ),
                                     extensions: HashMap::new(),
                                 }),
                                 resource: match prefer_return {
@@ -956,6 +1013,10 @@ impl TransactionService {
                                     .insert(full_url.clone(), format!("{}/{}", resource_type, &id));
                             }
 
+                            self.translate_codes(&mut resource).await?;
+                            self.generate_narrative(&mut resource);
+                            let issues = self.validate_on_write(&resource).await?;
+
                             // Referential integrity check (strict mode)
                             if self.is_strict_referential_integrity() {
                                 self.validate_references_in_transaction(&resource, &known_ids).await?;
@@ -986,25 +1047,30 @@ impl TransactionService {
                                     )),
                                     etag: Some(format!("W/\"{}\"", updated.version_id)),
                                     last_modified: Some(updated.last_updated.to_rfc3339()),
-                                    outcome: match prefer_return {
-                                        PreferReturn::OperationOutcome => Some(serde_json::json!({
-                                            "resourceType": "OperationOutcome",
-                                            "issue": [{
-                                                "severity": "information",
-                                                "code": "informational",
-                                                "diagnostics": format!(
-                                                    "Resource {} successfully with ID {}",
-                                                    if status == StatusCode::CREATED {
-                                                        "created"
-                                                    } else {
-                                                        "updated"
-                                                    },
-                                                    updated.id
-                                                )
-                                            }]
-                                        })),
-                                        _ => None,
-                                    },
+                                    outcome: outcome_with_issues(
+                                        match prefer_return {
+                                            PreferReturn::OperationOutcome => {
+                                                Some(serde_json::json!({
+                                                    "resourceType": "OperationOutcome",
+                                                    "issue": [{
+                                                        "severity": "information",
+                                                        "code": "informational",
+                                                        "diagnostics": format!(
+                                                            "Resource {} successfully with ID {}",
+                                                            if status == StatusCode::CREATED {
+                                                                "created"
+                                                            } else {
+                                                                "updated"
+                                                            },
+                                                            updated.id
+                                                        )
+                                                    }]
+                                                }))
+                                            }
+                                            _ => None,
+                                        },
+                                        issues,
+                                    ),
                                     extensions: HashMap::new(),
                                 }),
                                 resource: match prefer_return {
@@ -1062,6 +1128,10 @@ impl TransactionService {
                     obj.insert("id".to_string(), json!(resource_id));
                 }
 
+                self.translate_codes(&mut resource).await?;
+                self.generate_narrative(&mut resource);
+                let issues = self.validate_on_write(&resource).await?;
+
                 // Referential integrity check (strict mode)
                 if self.is_strict_referential_integrity() {
                     self.validate_references_in_transaction(&resource, &known_ids).await?;
@@ -1092,25 +1162,28 @@ impl TransactionService {
                         )),
                         etag: Some(format!("W/\"{}\"", updated.version_id)),
                         last_modified: Some(updated.last_updated.to_rfc3339()),
-                        outcome: match prefer_return {
-                            PreferReturn::OperationOutcome => Some(serde_json::json!({
-                                "resourceType": "OperationOutcome",
-                                "issue": [{
-                                    "severity": "information",
-                                    "code": "informational",
-                                    "diagnostics": format!(
-                                        "Resource {} successfully with ID {}",
-                                        if status == StatusCode::CREATED {
-                                            "created"
-                                        } else {
-                                            "updated"
-                                        },
-                                        updated.id
-                                    )
-                                }]
-                            })),
-                            _ => None,
-                        },
+                        outcome: outcome_with_issues(
+                            match prefer_return {
+                                PreferReturn::OperationOutcome => Some(serde_json::json!({
+                                    "resourceType": "OperationOutcome",
+                                    "issue": [{
+                                        "severity": "information",
+                                        "code": "informational",
+                                        "diagnostics": format!(
+                                            "Resource {} successfully with ID {}",
+                                            if status == StatusCode::CREATED {
+                                                "created"
+                                            } else {
+                                                "updated"
+                                            },
+                                            updated.id
+                                        )
+                                    }]
+                                })),
+                                _ => None,
+                            },
+                            issues,
+                        ),
                         extensions: HashMap::new(),
                     }),
                     resource: match prefer_return {
@@ -1232,6 +1305,10 @@ impl TransactionService {
                     obj.remove("text");
                 }
 
+                self.translate_codes(&mut patched).await?;
+                self.generate_narrative(&mut patched);
+                let issues = self.validate_on_write(&patched).await?;
+
                 // Referential integrity check (strict mode)
                 if self.is_strict_referential_integrity() {
                     self.validate_references_in_transaction(&patched, &known_ids).await?;
@@ -1262,20 +1339,23 @@ impl TransactionService {
                         )),
                         etag: Some(format!("W/\"{}\"", updated.version_id)),
                         last_modified: Some(updated.last_updated.to_rfc3339()),
-                        outcome: match prefer_return {
-                            PreferReturn::OperationOutcome => Some(serde_json::json!({
-                                "resourceType": "OperationOutcome",
-                                "issue": [{
-                                    "severity": "information",
-                                    "code": "informational",
-                                    "diagnostics": format!(
-                                        "Resource patched successfully: {}/{}",
-                                        resource_type, resource_id
-                                    )
-                                }]
-                            })),
-                            _ => None,
-                        },
+                        outcome: outcome_with_issues(
+                            match prefer_return {
+                                PreferReturn::OperationOutcome => Some(serde_json::json!({
+                                    "resourceType": "OperationOutcome",
+                                    "issue": [{
+                                        "severity": "information",
+                                        "code": "informational",
+                                        "diagnostics": format!(
+                                            "Resource patched successfully: {}/{}",
+                                            resource_type, resource_id
+                                        )
+                                    }]
+                                })),
+                                _ => None,
+                            },
+                            issues,
+                        ),
                         extensions: HashMap::new(),
                     }),
                     resource: match prefer_return {
@@ -1362,6 +1442,29 @@ impl TransactionService {
         self.referential_integrity_mode == "strict"
     }
 
+    async fn translate_codes(&self, resource: &mut JsonValue) -> Result<()> {
+        match &self.code_translator {
+            Some(translator) => translator.apply(resource).await,
+            None => Ok(()),
+        }
+    }
+
+    fn generate_narrative(&self, resource: &mut JsonValue) {
+        if let Some(generator) = &self.narrative_generator {
+            generator.apply(resource);
+        }
+    }
+
+    /// Run write-time validation; in strict mode invalid resources fail the transaction.
+    ///
+    /// Returns the issues found, for the entry's `response.outcome`.
+    async fn validate_on_write(&self, resource: &JsonValue) -> Result<Vec<JsonValue>> {
+        match &self.write_validator {
+            Some(validator) => validator.clone().check_blocking(resource.clone()).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Validate references in a resource within a transaction context.
     ///
     /// `known_ids` contains `(resource_type, id)` pairs for resources created earlier
@@ -1743,7 +1846,12 @@ fn empty_searchset_entry() -> BundleEntry {
     }
 }
 
-fn populate_meta(resource: &mut JsonValue, id: &str, version_id: i32, now: chrono::DateTime<Utc>) {
+pub(crate) fn populate_meta(
+    resource: &mut JsonValue,
+    id: &str,
+    version_id: i32,
+    now: chrono::DateTime<Utc>,
+) {
     if let Some(obj) = resource.as_object_mut() {
         obj.insert("id".to_string(), json!(id));
 
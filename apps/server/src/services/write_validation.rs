@@ -0,0 +1,241 @@
+//! Write-time validation of resources using the ferrum validator
+
+use crate::{config::WriteValidationConfig, Error, Result};
+use ferrum_context::{FhirContext, Result as ContextResult};
+use ferrum_models::StructureDefinition;
+use ferrum_snapshot::ExpandedFhirContext;
+use ferrum_validator::{FhirVersion, Preset, Validator, ValidatorConfig};
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+
+/// Maximum number of error diagnostics included in a rejection message.
+const MAX_REPORTED_ERRORS: usize = 10;
+
+/// The server's shared FHIR context, usable where a concrete `FhirContext` is required.
+#[derive(Clone)]
+pub struct SharedFhirContext(pub Arc<dyn FhirContext>);
+
+impl FhirContext for SharedFhirContext {
+    fn get_resource_by_url(
+        &self,
+        canonical_url: &str,
+        version: Option<&str>,
+    ) -> ContextResult<Option<Arc<JsonValue>>> {
+        self.0.get_resource_by_url(canonical_url, version)
+    }
+
+    fn get_latest_resource_by_url(
+        &self,
+        canonical_url: &str,
+    ) -> ContextResult<Option<Arc<JsonValue>>> {
+        self.0.get_latest_resource_by_url(canonical_url)
+    }
+
+    fn get_structure_definition(
+        &self,
+        canonical_url: &str,
+    ) -> ContextResult<Option<Arc<StructureDefinition>>> {
+        self.0.get_structure_definition(canonical_url)
+    }
+
+    fn get_core_structure_definition_by_type(
+        &self,
+        type_name: &str,
+    ) -> ContextResult<Option<Arc<StructureDefinition>>> {
+        self.0.get_core_structure_definition_by_type(type_name)
+    }
+}
+
+/// Validator over the server's FHIR context with expanded snapshots.
+pub type ServerValidator = Validator<ExpandedFhirContext<SharedFhirContext>>;
+
+/// How validation results affect a write (see `fhir.validation.mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteValidationMode {
+    Off,
+    Lenient,
+    Strict,
+}
+
+impl WriteValidationMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "off" => Some(Self::Off),
+            "lenient" => Some(Self::Lenient),
+            "strict" => Some(Self::Strict),
+            _ => None,
+        }
+    }
+}
+
+/// Runs the shared validator on resources before they are stored.
+pub struct WriteValidator {
+    validator: Arc<ServerValidator>,
+    mode: WriteValidationMode,
+}
+
+impl WriteValidator {
+    pub fn new(validator: Arc<ServerValidator>, mode: WriteValidationMode) -> Self {
+        Self { validator, mode }
+    }
+
+    /// Build the write validator described by `fhir.validation`, or `None` when it is off.
+    pub fn from_config(
+        config: &WriteValidationConfig,
+        fhir_version: &str,
+        fhir_context: Arc<dyn FhirContext>,
+    ) -> Result<Option<Self>> {
+        let mode = WriteValidationMode::parse(&config.mode).ok_or_else(|| {
+            Error::Internal(format!("Invalid fhir.validation.mode '{}'", config.mode))
+        })?;
+        if mode == WriteValidationMode::Off {
+            return Ok(None);
+        }
+
+        let preset = match config.preset.as_str() {
+            "ingestion" => Preset::Ingestion,
+            "authoring" => Preset::Authoring,
+            "server" => Preset::Server,
+            other => {
+                return Err(Error::Internal(format!(
+                    "Invalid fhir.validation.preset '{}'",
+                    other
+                )))
+            }
+        };
+
+        let mut validator_config = ValidatorConfig::preset(preset);
+        validator_config.fhir.version = match fhir_version {
+            "R4" | "R4B" => FhirVersion::R4,
+            _ => FhirVersion::R5,
+        };
+
+        let validator = Validator::from_config(&validator_config, SharedFhirContext(fhir_context))
+            .map_err(|e| Error::Internal(format!("Failed to build validator: {}", e)))?
+            .with_expanded_snapshots();
+        for warning in &validator.plan().warnings {
+            tracing::warn!(preset = %config.preset, "fhir.validation: {}", warning);
+        }
+
+        Ok(Some(Self::new(Arc::new(validator), mode)))
+    }
+
+    /// Build a strict validator with the `fhir.validation` preset, regardless of
+    /// `fhir.validation.mode`. Used where validation is requested explicitly (`$import`).
+    pub fn strict_from_config(
+        config: &WriteValidationConfig,
+        fhir_version: &str,
+        fhir_context: Arc<dyn FhirContext>,
+    ) -> Result<Self> {
+        let config = WriteValidationConfig {
+            mode: "strict".to_string(),
+            ..config.clone()
+        };
+        Self::from_config(&config, fhir_version, fhir_context)?
+            .ok_or_else(|| Error::Internal("strict write validator not built".to_string()))
+    }
+
+    pub fn mode(&self) -> WriteValidationMode {
+        self.mode
+    }
+
+    /// Run [`check`](Self::check) on the blocking thread pool, keeping the validator off the
+    /// async request path.
+    pub async fn check_blocking(self: Arc<Self>, resource: JsonValue) -> Result<Vec<JsonValue>> {
+        tokio::task::spawn_blocking(move || self.check(&resource))
+            .await
+            .map_err(|e| Error::Internal(format!("Write validation task failed: {}", e)))?
+    }
+
+    /// Validate a resource about to be written.
+    ///
+    /// In strict mode a resource with errors is rejected with 422 Unprocessable Entity. Otherwise
+    /// the issues found are returned as OperationOutcome issues so they can be reported with the
+    /// stored resource.
+    pub fn check(&self, resource: &JsonValue) -> Result<Vec<JsonValue>> {
+        if self.mode == WriteValidationMode::Off {
+            return Ok(Vec::new());
+        }
+
+        let outcome = self.validator.validate(resource);
+        let resource_type = outcome.resource_type.as_deref().unwrap_or("unknown");
+
+        if self.mode == WriteValidationMode::Strict && outcome.has_errors() {
+            let errors = outcome
+                .issues
+                .iter()
+                .filter(|issue| {
+                    matches!(
+                        issue.severity,
+                        ferrum_validator::IssueSeverity::Error
+                            | ferrum_validator::IssueSeverity::Fatal
+                    )
+                })
+                .take(MAX_REPORTED_ERRORS)
+                .map(|issue| match issue.fhirpath() {
+                    Some(path) => format!("{}: {}", path, issue.diagnostics),
+                    None => issue.diagnostics.clone(),
+                })
+                .collect::<Vec<_>>();
+            return Err(Error::UnprocessableEntity(format!(
+                "{} failed validation with {} error(s): {}",
+                resource_type,
+                outcome.error_count(),
+                errors.join("; ")
+            )));
+        }
+
+        if !outcome.issues.is_empty() {
+            tracing::warn!(
+                resource_type = %resource_type,
+                errors = outcome.error_count(),
+                warnings = outcome.warning_count(),
+                "Storing resource with validation issues"
+            );
+        }
+
+        Ok(outcome
+            .to_operation_outcome()
+            .get_mut("issue")
+            .map(JsonValue::take)
+            .and_then(|issues| match issues {
+                JsonValue::Array(issues) => Some(issues),
+                _ => None,
+            })
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrum_context::DefaultFhirContext;
+
+    #[test]
+    fn presets_build_without_terminology_endpoint() {
+        let fhir_context: Arc<dyn FhirContext> =
+            Arc::new(DefaultFhirContext::from_packages(vec![]));
+        for preset in ["ingestion", "authoring", "server"] {
+            let config = WriteValidationConfig {
+                mode: "strict".to_string(),
+                preset: preset.to_string(),
+            };
+            let validator = WriteValidator::from_config(&config, "R4", fhir_context.clone())
+                .unwrap_or_else(|e| panic!("{preset}: {e}"))
+                .expect("validation enabled");
+            assert_eq!(validator.mode(), WriteValidationMode::Strict);
+        }
+
+        // Publication needs a terminology server and is not a write validation preset
+        let publication = WriteValidationConfig {
+            mode: "strict".to_string(),
+            preset: "publication".to_string(),
+        };
+        assert!(WriteValidator::from_config(&publication, "R4", fhir_context.clone()).is_err());
+
+        let off = WriteValidationConfig::default();
+        assert!(WriteValidator::from_config(&off, "R4", fhir_context)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -14,14 +14,15 @@ use crate::{
     queue::{InlineJobQueue, JobQueue, PostgresJobQueue},
     runtime_config::RuntimeConfigCache,
     services::{
-        AdminService, ConditionalReferenceResolver, CrudService, MetadataService, MetricsService,
-        OperationExecutor, OperationRegistry, PackageService, RuntimeConfigService, SearchService,
-        SystemService, TerminologyService,
+        AdminService, CodeTranslator, ConditionalReferenceResolver, CrudService, ImportService,
+        MetadataService, MetricsService, NamedQueryRegistry, NarrativeGenerator, OperationExecutor,
+        OperationRegistry, PackageService, RuntimeConfigService, SearchService, SystemService,
+        TerminologyService, WriteValidator,
     },
     Result,
 };
 use sqlx::PgPool;
-use std::sync::Arc;
+use std::sync::{atomic::AtomicBool, Arc};
 use ferrum_context::FhirContext;
 use ferrum_fhirpath::Engine as FhirPathEngine;
 
@@ -63,6 +64,8 @@ pub struct AppState {
     pub resource_hooks: Vec<Arc<dyn ResourceHook>>,
     pub fhir_context: Arc<dyn FhirContext>,
     pub fhirpath_engine: Arc<FhirPathEngine>,
+    /// Write-time validator shared by CRUD, batch and transaction (`None` when `fhir.validation.mode` is off)
+    pub write_validator: Option<Arc<WriteValidator>>,
     pub indexing_service: Arc<crate::services::IndexingService>,
     pub search_engine: Arc<SearchEngine>,
     pub crud_service: Arc<crate::services::CrudService>,
@@ -82,6 +85,9 @@ pub struct AppState {
     pub operation_executor: Arc<OperationExecutor>,
     pub runtime_config_cache: Arc<RuntimeConfigCache>,
     pub runtime_config_service: Arc<RuntimeConfigService>,
+    /// Set once FHIR conformance packages are installed and the core context is loaded.
+    /// Reported by the `/ready` endpoint.
+    pub packages_loaded: Arc<AtomicBool>,
 }
 
 impl AppState {
@@ -128,19 +134,23 @@ impl AppState {
 
         let config_arc = Arc::new(config);
 
-        // Create database connection pool
-        let db_pool = create_db_pool(config_arc.as_ref()).await?;
+        // Create database connection pools
+        let (db_pool, api_pool) = create_db_pools(config_arc.as_ref()).await?;
 
         // Run migrations
         if options.run_migrations {
             tracing::info!("Running database migrations...");
-            sqlx::migrate!("./migrations")
+            crate::db::MIGRATOR
                 .run(&db_pool)
                 .await
                 .map_err(|e| crate::Error::Internal(format!("Migration failed: {}", e)))?;
         }
 
-        // Install FHIR packages into database synchronously at startup
+        // Reported by /ready; stays false until the configured packages are installed and loaded
+        let packages_loaded = Arc::new(AtomicBool::new(false));
+
+        // Install FHIR packages into database synchronously at startup. A failed installation
+        // aborts startup so the orchestrator restarts the server.
         if options.install_packages {
             tracing::info!("Installing FHIR packages...");
             crate::startup::install_all_packages(config_arc.as_ref(), &db_pool).await?;
@@ -149,19 +159,44 @@ impl AppState {
         // Load core FHIR package into memory (download if needed)
         tracing::info!("Loading core FHIR package...");
         let fhir_context = load_core_fhir_context(&config_arc.fhir.version).await?;
+        packages_loaded.store(true, std::sync::atomic::Ordering::Release);
 
         // Create FHIRPath engine using the already-loaded context
         let fhirpath_engine = Arc::new(FhirPathEngine::new(fhir_context.clone(), None));
 
-        // Initialize indexing service
-        let indexing_service = Arc::new(crate::services::IndexingService::new(
-            db_pool.clone(),
+        let write_validator = WriteValidator::from_config(
+            &config_arc.fhir.validation,
             &config_arc.fhir.version,
-            config_arc.database.indexing_batch_size,
-            config_arc.database.indexing_bulk_threshold,
-            config_arc.fhir.search.enable_text,
-            config_arc.fhir.search.enable_content,
-        )?);
+            fhir_context.clone(),
+        )?
+        .map(Arc::new);
+
+        let narrative_generator =
+            NarrativeGenerator::from_config(&config_arc.fhir.narrative, fhirpath_engine.clone())
+                .map(Arc::new);
+
+        let terminology_repo = crate::db::TerminologyRepository::new(api_pool.clone());
+        let terminology_service = Arc::new(TerminologyService::new(terminology_repo));
+        let code_translator = CodeTranslator::from_config(
+            &config_arc.fhir.code_translation,
+            terminology_service.clone(),
+        )
+        .map(Arc::new);
+
+        // Initialize indexing service
+        let indexing_service = Arc::new(
+            crate::services::IndexingService::new(
+                api_pool.clone(),
+                &config_arc.fhir.version,
+                config_arc.database.indexing_batch_size,
+                config_arc.database.indexing_bulk_threshold,
+                config_arc.fhir.search.enable_text,
+                config_arc.fhir.search.enable_content,
+            )?
+            .with_acquire_timeout(std::time::Duration::from_secs(
+                config_arc.database.indexing_acquire_timeout_seconds,
+            )),
+        );
 
         // Runtime configuration cache (static defaults come from config.yaml + env).
         let runtime_config_cache = Arc::new(RuntimeConfigCache::new(config_arc.clone()));
@@ -175,21 +210,37 @@ impl AppState {
             spawn_runtime_config_listener(db_pool.clone(), runtime_config_service.clone());
         }
 
+        let store = PostgresResourceStore::new(api_pool.clone());
+        let read_only_types = &config_arc.fhir.resource_policies.read_only;
+
         // Create job queue (may run jobs inline for tests).
         let job_queue: Arc<dyn JobQueue> = match options.job_queue {
             JobQueueKind::Postgres => Arc::new(PostgresJobQueue::new(
                 db_pool.clone(),
                 config_arc.workers.poll_interval_seconds,
             )),
-            JobQueueKind::Inline => Arc::new(InlineJobQueue::new(
-                db_pool.clone(),
-                indexing_service.clone(),
-            )),
+            JobQueueKind::Inline => {
+                let mut import_service = ImportService::new(
+                    store.clone(),
+                    indexing_service.clone(),
+                    fhir_context.clone(),
+                    &config_arc.fhir.bulk_import,
+                )?;
+                import_service.set_read_only_types(read_only_types.iter().cloned());
+                import_service.set_validator(Arc::new(WriteValidator::strict_from_config(
+                    &config_arc.fhir.validation,
+                    &config_arc.fhir.version,
+                    fhir_context.clone(),
+                )?));
+                Arc::new(InlineJobQueue::new(
+                    db_pool.clone(),
+                    indexing_service.clone(),
+                    Arc::new(import_service),
+                ))
+            }
         };
-
-        let store = PostgresResourceStore::new(db_pool.clone());
         let search_engine = Arc::new(SearchEngine::new_with_runtime_config(
-            db_pool.clone(),
+            api_pool.clone(),
             config_arc.fhir.search.clone(),
             runtime_config_cache.clone(),
         ));
@@ -197,7 +248,7 @@ impl AppState {
         // Initialize resource hooks
         let resource_hooks: Vec<Arc<dyn ResourceHook>> = vec![
             Arc::new(SearchParameterHook::new(
-                db_pool.clone(),
+                api_pool.clone(),
                 indexing_service.clone(),
                 search_engine.clone(),
                 config_arc
@@ -206,8 +257,8 @@ impl AppState {
                     .search_parameter_active_statuses
                     .clone(),
             )),
-            Arc::new(TerminologyHook::new(db_pool.clone())),
-            Arc::new(CompartmentDefinitionHook::new(db_pool.clone())),
+            Arc::new(TerminologyHook::new(api_pool.clone())),
+            Arc::new(CompartmentDefinitionHook::new(api_pool.clone())),
         ];
         let mut crud_service_inner = CrudService::with_hooks_and_indexing_and_runtime_config(
             store.clone(),
@@ -221,6 +272,10 @@ impl AppState {
         crud_service_inner.set_referential_integrity_mode(
             config_arc.fhir.referential_integrity.mode.clone(),
         );
+        crud_service_inner.set_read_only_types(read_only_types.iter().cloned());
+        crud_service_inner.set_write_validator(write_validator.clone());
+        crud_service_inner.set_narrative_generator(narrative_generator.clone());
+        crud_service_inner.set_code_translator(code_translator.clone());
         let crud_service = Arc::new(crud_service_inner);
 
         let conditional_service = Arc::new(crate::services::conditional::ConditionalService::new(
@@ -228,14 +283,19 @@ impl AppState {
         ));
         let conditional_reference_resolver =
             Arc::new(ConditionalReferenceResolver::new(search_engine.clone()));
-        let audit_service = Arc::new(crate::services::AuditService::new(
+        let mut audit_service_inner = crate::services::AuditService::new(
             runtime_config_cache.clone(),
             config_arc.fhir.version.clone(),
             config_arc.logging.service_name.clone(),
-            db_pool.clone(),
-        ));
+            api_pool.clone(),
+        );
+        audit_service_inner.set_read_access_auditing(
+            &config_arc.logging.audit.read_access,
+            crud_service.clone(),
+        );
+        let audit_service = Arc::new(audit_service_inner);
         let transaction_recorder =
-            crate::db::admin::TransactionRecorder::new(db_pool.clone());
+            crate::db::admin::TransactionRecorder::new(api_pool.clone());
 
         let mut batch_service_inner = crate::services::BatchService::new_with_runtime_config(
             store.clone(),
@@ -249,6 +309,10 @@ impl AppState {
         batch_service_inner.set_referential_integrity_mode(
             config_arc.fhir.referential_integrity.mode.clone(),
         );
+        batch_service_inner.set_read_only_types(read_only_types.iter().cloned());
+        batch_service_inner.set_write_validator(write_validator.clone());
+        batch_service_inner.set_narrative_generator(narrative_generator.clone());
+        batch_service_inner.set_code_translator(code_translator.clone());
         batch_service_inner.set_transaction_recorder(transaction_recorder.clone());
         let batch_service = Arc::new(batch_service_inner);
         let mut transaction_service_inner =
@@ -265,24 +329,34 @@ impl AppState {
         transaction_service_inner.set_referential_integrity_mode(
             config_arc.fhir.referential_integrity.mode.clone(),
         );
+        transaction_service_inner.set_read_only_types(read_only_types.iter().cloned());
+        transaction_service_inner.set_write_validator(write_validator.clone());
+        transaction_service_inner.set_narrative_generator(narrative_generator.clone());
+        transaction_service_inner.set_code_translator(code_translator.clone());
         transaction_service_inner.set_transaction_recorder(transaction_recorder);
         let transaction_service = Arc::new(transaction_service_inner);
-        let history_service = Arc::new(crate::services::HistoryService::new_with_runtime_config(
+        let mut history_service_inner = crate::services::HistoryService::new_with_runtime_config(
             store.clone(),
             resource_hooks.clone(),
             job_queue.clone(),
             config_arc.fhir.allow_update_create,
             config_arc.fhir.hard_delete,
             runtime_config_cache.clone(),
-        ));
+        );
+        history_service_inner.set_read_only_types(read_only_types.iter().cloned());
+        let history_service = Arc::new(history_service_inner);
 
         // Create search service with summary filtering
         let summary_filter = Arc::new(crate::services::SummaryFilter::new(fhir_context.clone()));
-        let search_service = Arc::new(SearchService::with_summary_filter(
+        let mut search_service_inner = SearchService::with_summary_filter(
             search_engine.clone(),
             summary_filter,
             runtime_config_cache.clone(),
+        );
+        search_service_inner.set_named_queries(NamedQueryRegistry::from_config(
+            &config_arc.fhir.search.named_queries,
         ));
+        let search_service = Arc::new(search_service_inner);
         let system_service = Arc::new(SystemService::new(
             search_engine.clone(),
             crud_service.clone(),
@@ -300,7 +374,7 @@ impl AppState {
             })?,
         );
         let admin_auth = Arc::new(crate::admin_auth::AdminAuthManager::new(config_arc.clone()));
-        let metadata_repo = crate::db::MetadataRepository::new(db_pool.clone());
+        let metadata_repo = crate::db::MetadataRepository::new(api_pool.clone());
         let metadata_service = Arc::new(MetadataService::new(config_arc.clone(), metadata_repo));
 
         let package_service = Arc::new(PackageService::new_admin(PackageRepository::new(
@@ -308,22 +382,22 @@ impl AppState {
         )));
         let admin_service = Arc::new(AdminService::new(AdminRepository::new(db_pool.clone())));
 
-        let metrics_repo = crate::db::MetricsRepository::new(db_pool.clone());
+        // Sample the pool that serves FHIR traffic
+        let metrics_repo = crate::db::MetricsRepository::new(api_pool.clone());
         let metrics_service = Arc::new(MetricsService::new(metrics_repo));
 
-        let terminology_repo = crate::db::TerminologyRepository::new(db_pool.clone());
-        let terminology_service = Arc::new(TerminologyService::new(terminology_repo));
-
         // Create operation services
         let operation_registry = Arc::new(OperationRegistry::new(Arc::new(store.clone())));
-        let operation_executor = Arc::new(OperationExecutor::with_services(
+        let mut operation_executor = OperationExecutor::with_services(
             package_service.clone(),
             indexing_service.clone(),
             terminology_service,
             job_queue.clone(),
             search_engine.clone(),
             store.clone(),
-        ));
+        );
+        operation_executor.set_max_import_batch_size(config_arc.fhir.bulk_import.max_batch_size);
+        let operation_executor = Arc::new(operation_executor);
 
         // Load operation definitions from database (after packages are installed)
         if options.load_operation_definitions {
@@ -342,6 +416,7 @@ impl AppState {
             resource_hooks,
             fhir_context,
             fhirpath_engine,
+            write_validator,
             indexing_service,
             search_engine,
             crud_service,
@@ -361,6 +436,7 @@ impl AppState {
             operation_executor,
             runtime_config_cache,
             runtime_config_service,
+            packages_loaded,
         })
     }
 }
@@ -417,15 +493,68 @@ fn spawn_runtime_config_listener(db_pool: PgPool, service: Arc<RuntimeConfigServ
     });
 }
 
-async fn create_db_pool(config: &Config) -> Result<PgPool> {
-    tracing::info!("Creating database connection pool...");
+/// Connection limits of one pool.
+#[derive(Debug, Clone, Copy)]
+struct PoolSize {
+    min: u32,
+    max: u32,
+}
 
+/// Main pool and the pool for the services behind the /fhir routes.
+///
+/// Queries outlive a cancelled request unless Postgres stops them, so when the request timeout is
+/// shorter than the statement timeout the API gets its own pool capped to the request timeout.
+/// Migrations, package installation and admin routes keep using the main pool. The two pools
+/// share `pool_min_size`/`pool_max_size`, so the total connection budget stays the same; the API
+/// pool gets three quarters of it.
+async fn create_db_pools(config: &Config) -> Result<(PgPool, PgPool)> {
     let statement_timeout = config.database.statement_timeout_seconds;
+    let configured = PoolSize {
+        min: config.database.pool_min_size,
+        max: config.database.pool_max_size,
+    };
+
+    match config.server.request_timeout_seconds {
+        Some(request_timeout) if request_timeout < statement_timeout => {
+            let (main_size, api_size) = split_pool_size(configured);
+            let db_pool = create_db_pool(config, statement_timeout, main_size).await?;
+            let api_pool = create_db_pool(config, request_timeout, api_size).await?;
+            Ok((db_pool, api_pool))
+        }
+        _ => {
+            let db_pool = create_db_pool(config, statement_timeout, configured).await?;
+            Ok((db_pool.clone(), db_pool))
+        }
+    }
+}
+
+/// Split one pool's limits into (main, api). Requires `size.max >= 2`, which config validation
+/// enforces whenever a separate API pool is created.
+fn split_pool_size(size: PoolSize) -> (PoolSize, PoolSize) {
+    let main_max = (size.max / 4).max(1);
+    let api_max = size.max - main_max;
+    let api_min = size.min.min(api_max);
+    let main_min = (size.min - api_min).min(main_max);
+    (
+        PoolSize {
+            min: main_min,
+            max: main_max,
+        },
+        PoolSize {
+            min: api_min,
+            max: api_max,
+        },
+    )
+}
+
+async fn create_db_pool(config: &Config, statement_timeout: u64, size: PoolSize) -> Result<PgPool> {
+    tracing::info!("Creating database connection pool...");
+
     let lock_timeout = config.database.lock_timeout_seconds;
 
     let pool = sqlx::postgres::PgPoolOptions::new()
-        .min_connections(config.database.pool_min_size)
-        .max_connections(config.database.pool_max_size)
+        .min_connections(size.min)
+        .max_connections(size.max)
         .acquire_timeout(std::time::Duration::from_secs(
             config.database.pool_timeout_seconds,
         ))
@@ -450,9 +579,15 @@ async fn create_db_pool(config: &Config) -> Result<PgPool> {
 
     tracing::info!(
         "Database pool created (min: {}, max: {})",
-        config.database.pool_min_size,
-        config.database.pool_max_size
+        size.min,
+        size.max
     );
 
+    if config.database.pool_warmup {
+        crate::db::pool::warm_up(&pool, size.min)
+            .await
+            .map_err(crate::Error::Database)?;
+    }
+
     Ok(pool)
 }
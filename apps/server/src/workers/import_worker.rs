@@ -0,0 +1,99 @@
+//! Bulk import worker
+
+use super::base::{Worker, WorkerConfig};
+use crate::{
+    queue::{Job, JobQueue},
+    services::{
+        bulk_import::{ImportJobParams, BULK_IMPORT_JOB_TYPE},
+        ImportService,
+    },
+    Result,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub struct ImportWorker {
+    job_queue: Arc<dyn JobQueue>,
+    import_service: ImportService,
+    _config: WorkerConfig,
+}
+
+impl ImportWorker {
+    pub fn new(
+        job_queue: Arc<dyn JobQueue>,
+        import_service: ImportService,
+        config: WorkerConfig,
+    ) -> Self {
+        Self {
+            job_queue,
+            import_service,
+            _config: config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for ImportWorker {
+    fn name(&self) -> &str {
+        "ImportWorker"
+    }
+
+    fn supported_job_types(&self) -> &[&str] {
+        &[BULK_IMPORT_JOB_TYPE]
+    }
+
+    async fn start(&self) -> Result<()> {
+        tracing::info!("{} starting...", self.name());
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<()> {
+        tracing::info!("{} stopping...", self.name());
+        Ok(())
+    }
+
+    async fn process_job(&self, job: Job) -> Result<()> {
+        let job_start = std::time::Instant::now();
+        tracing::info!("{} processing job: {}", self.name(), job.id);
+
+        let params: ImportJobParams =
+            serde_json::from_value(job.parameters.clone()).map_err(|e| {
+                crate::Error::Internal(format!("Failed to parse job parameters: {}", e))
+            })?;
+
+        let summary = match self
+            .import_service
+            .run(self.job_queue.as_ref(), job.id, &params)
+            .await
+        {
+            Ok(summary) => summary,
+            Err(e) => {
+                self.job_queue
+                    .fail_job(job.id, format!("Import failed: {}", e), false)
+                    .await?;
+                return Err(e);
+            }
+        };
+
+        let results = serde_json::to_value(&summary).map_err(|e| {
+            crate::Error::Internal(format!("Failed to serialize import summary: {}", e))
+        })?;
+        self.job_queue.complete_job(job.id, Some(results)).await?;
+
+        let outcome = if summary.cancelled {
+            "cancelled"
+        } else {
+            "completed"
+        };
+        tracing::info!(
+            "{} {} job {}: {} imported, {} failed - total time: {:?}",
+            self.name(),
+            outcome,
+            job.id,
+            summary.imported,
+            summary.failed,
+            job_start.elapsed()
+        );
+        Ok(())
+    }
+}
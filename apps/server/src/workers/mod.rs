@@ -4,6 +4,7 @@
 //! Each worker type handles specific job types.
 
 mod base;
+mod import_worker;
 mod indexing_worker;
 mod package_worker;
 mod runner;
@@ -11,6 +12,7 @@ mod state;
 mod terminology_worker;
 
 pub use base::{Worker, WorkerConfig};
+pub use import_worker::ImportWorker;
 pub use indexing_worker::IndexingWorker;
 pub use package_worker::PackageWorker;
 pub use runner::{
@@ -20,11 +22,16 @@ pub use runner::{
 pub use state::WorkerState;
 pub use terminology_worker::TerminologyWorker;
 
-use crate::Result;
+use crate::{
+    db::PostgresResourceStore,
+    services::{ImportService, WriteValidator},
+    Result,
+};
+use std::sync::Arc;
 
 /// Create all configured workers using lightweight WorkerState
 pub fn create_workers(state: &WorkerState, config: WorkerConfig) -> Result<Vec<Box<dyn Worker>>> {
-    let mut workers: Vec<Box<dyn Worker>> = Vec::with_capacity(3);
+    let mut workers: Vec<Box<dyn Worker>> = Vec::with_capacity(4);
 
     // Package installation worker
     // Note: registry_url in config is the package registry URL (e.g., https://packages.fhir.org)
@@ -49,6 +56,34 @@ pub fn create_workers(state: &WorkerState, config: WorkerConfig) -> Result<Vec<B
         config.clone(),
     )));
 
+    // Bulk import worker ($import)
+    let import_context = crate::conformance::core_fhir_context(&state.config.fhir.version)?;
+    let mut import_service = ImportService::new(
+        PostgresResourceStore::new(state.db_pool.clone()),
+        state.indexing_service.clone(),
+        import_context.clone(),
+        &state.config.fhir.bulk_import,
+    )?;
+    import_service.set_read_only_types(
+        state
+            .config
+            .fhir
+            .resource_policies
+            .read_only
+            .iter()
+            .cloned(),
+    );
+    import_service.set_validator(Arc::new(WriteValidator::strict_from_config(
+        &state.config.fhir.validation,
+        &state.config.fhir.version,
+        import_context,
+    )?));
+    workers.push(Box::new(ImportWorker::new(
+        state.job_queue.clone(),
+        import_service,
+        config.clone(),
+    )));
+
     // Terminology indexing worker
     workers.push(Box::new(TerminologyWorker::new(
         state.db_pool.clone(),
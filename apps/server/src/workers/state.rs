@@ -40,7 +40,7 @@ impl WorkerState {
 
         // Run migrations (idempotent)
         tracing::info!("Running database migrations...");
-        sqlx::migrate!("./migrations")
+        crate::db::MIGRATOR
             .run(&db_pool)
             .await
             .map_err(|e| match e {
@@ -63,14 +63,19 @@ impl WorkerState {
         let fhirpath_engine = Arc::new(FhirPathEngine::new(fhir_context.clone(), None));
 
         // Create a shared indexing service (reused across worker jobs).
-        let indexing_service = Arc::new(crate::services::IndexingService::new(
-            db_pool.clone(),
-            &config.fhir.version,
-            config.database.indexing_batch_size,
-            config.database.indexing_bulk_threshold,
-            config.fhir.search.enable_text,
-            config.fhir.search.enable_content,
-        )?);
+        let indexing_service = Arc::new(
+            crate::services::IndexingService::new(
+                db_pool.clone(),
+                &config.fhir.version,
+                config.database.indexing_batch_size,
+                config.database.indexing_bulk_threshold,
+                config.fhir.search.enable_text,
+                config.fhir.search.enable_content,
+            )?
+            .with_acquire_timeout(std::time::Duration::from_secs(
+                config.database.indexing_acquire_timeout_seconds,
+            )),
+        );
 
         tracing::info!("Worker state initialized successfully (no FHIR packages loaded)");
 
@@ -122,5 +127,11 @@ async fn create_db_pool(config: &Config) -> Result<PgPool> {
         config.database.worker_pool_max_size
     );
 
+    if config.database.pool_warmup {
+        crate::db::pool::warm_up(&pool, config.database.worker_pool_min_size)
+            .await
+            .map_err(crate::Error::Database)?;
+    }
+
     Ok(pool)
 }
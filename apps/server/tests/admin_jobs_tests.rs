@@ -0,0 +1,146 @@
+#![allow(unused)]
+//! Integration tests for admin job queue introspection (list / retry / cancel).
+
+mod support;
+
+use axum::http::{Method, StatusCode};
+use serde_json::Value;
+use support::*;
+use uuid::Uuid;
+
+async fn insert_failed_job(app: &TestApp, error_message: &str) -> anyhow::Result<Uuid> {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO jobs (id, job_type, status, parameters, retry_policy, retry_count,
+                          progress, processed_items, total_items,
+                          error_message, last_error_at, completed_at)
+        VALUES ($1, 'index_search', 'failed', '{}'::jsonb, '{}'::jsonb, 3,
+                '{"indexed": 5}'::jsonb, 5, 10, $2, NOW(), NOW())
+        "#,
+    )
+    .bind(id)
+    .bind(error_message)
+    .execute(&app.state.db_pool)
+    .await?;
+    Ok(id)
+}
+
+#[tokio::test]
+async fn failed_job_is_listed_with_error() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let id = insert_failed_job(app, "boom").await?;
+
+            let (status, _headers, body) = app
+                .request(Method::GET, "/admin/jobs?status=failed", None)
+                .await?;
+            assert_status(status, StatusCode::OK, "list failed jobs");
+
+            let json: Value = serde_json::from_slice(&body)?;
+            assert_eq!(json["total"], 1);
+            assert_eq!(json["jobs"][0]["id"], id.to_string());
+            assert_eq!(json["jobs"][0]["status"], "Failed");
+            assert_eq!(json["jobs"][0]["errorMessage"], "boom");
+
+            let (status, _headers, body) = app
+                .request(Method::GET, "/admin/jobs?status=pending", None)
+                .await?;
+            assert_status(status, StatusCode::OK, "list pending jobs");
+            let json: Value = serde_json::from_slice(&body)?;
+            assert_eq!(json["total"], 0);
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn failed_job_can_be_retried() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let id = insert_failed_job(app, "boom").await?;
+
+            let (status, _headers, body) = app
+                .request(Method::POST, &format!("/admin/jobs/{id}/retry"), None)
+                .await?;
+            assert_status(status, StatusCode::OK, "retry job");
+
+            let json: Value = serde_json::from_slice(&body)?;
+            assert_eq!(json["status"], "Pending");
+            assert!(json["errorMessage"].is_null());
+            assert!(json["completedAt"].is_null());
+            assert_eq!(json["retryCount"], 0);
+            assert!(json["progress"].is_null());
+            assert_eq!(json["processedItems"], 0);
+            assert!(json["totalItems"].is_null());
+
+            // A pending job is not retryable.
+            let (status, _headers, _body) = app
+                .request(Method::POST, &format!("/admin/jobs/{id}/retry"), None)
+                .await?;
+            assert_status(status, StatusCode::BAD_REQUEST, "retry pending job");
+
+            let (status, _headers, _body) = app
+                .request(
+                    Method::POST,
+                    &format!("/admin/jobs/{}/retry", Uuid::new_v4()),
+                    None,
+                )
+                .await?;
+            assert_status(status, StatusCode::NOT_FOUND, "retry unknown job");
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn failed_job_can_be_cancelled() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let id = insert_failed_job(app, "boom").await?;
+
+            let (status, _headers, body) = app
+                .request(Method::POST, &format!("/admin/jobs/{id}/cancel"), None)
+                .await?;
+            assert_status(status, StatusCode::OK, "cancel job");
+            let json: Value = serde_json::from_slice(&body)?;
+            assert_eq!(json["cancelled"], true);
+
+            let (status, _headers, body) = app
+                .request(Method::GET, "/admin/jobs?status=cancelled", None)
+                .await?;
+            assert_status(status, StatusCode::OK, "list cancelled jobs");
+            let json: Value = serde_json::from_slice(&body)?;
+            assert_eq!(json["total"], 1);
+            assert_eq!(json["jobs"][0]["id"], id.to_string());
+
+            // Cancelling again is rejected: the job is already in a terminal state.
+            let (status, _headers, _body) = app
+                .request(Method::POST, &format!("/admin/jobs/{id}/cancel"), None)
+                .await?;
+            assert_status(status, StatusCode::BAD_REQUEST, "cancel twice");
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn cancel_unknown_job_is_rejected() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let (status, _headers, _body) = app
+                .request(
+                    Method::POST,
+                    &format!("/admin/jobs/{}/cancel", Uuid::new_v4()),
+                    None,
+                )
+                .await?;
+            assert_status(status, StatusCode::BAD_REQUEST, "cancel unknown job");
+            Ok(())
+        })
+    })
+    .await
+}
@@ -0,0 +1,50 @@
+#![allow(unused)]
+//! Integration tests for the admin search index maintenance operation.
+
+mod support;
+
+use axum::http::{Method, StatusCode};
+use serde_json::Value;
+use support::*;
+
+#[tokio::test]
+async fn analyze_reports_each_search_index_table() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let (status, _headers, body) = app
+                .request(Method::POST, "/admin/search/maintenance", None)
+                .await?;
+            assert_status(status, StatusCode::OK, "analyze search tables");
+
+            let json: Value = serde_json::from_slice(&body)?;
+            assert_eq!(json["vacuum"], false);
+            let tables = json["tables"].as_array().expect("tables array");
+            assert!(!tables.is_empty());
+            assert!(tables
+                .iter()
+                .all(|t| t["tableName"].as_str().unwrap().starts_with("search_")));
+            assert!(tables.iter().any(|t| t["tableName"] == "search_token"));
+            assert!(json["durationMs"].is_u64());
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn vacuum_runs_when_requested() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let (status, _headers, body) = app
+                .request(Method::POST, "/admin/search/maintenance?vacuum=true", None)
+                .await?;
+            assert_status(status, StatusCode::OK, "vacuum search tables");
+
+            let json: Value = serde_json::from_slice(&body)?;
+            assert_eq!(json["vacuum"], true);
+            assert!(!json["tables"].as_array().expect("tables array").is_empty());
+            Ok(())
+        })
+    })
+    .await
+}
@@ -3,7 +3,8 @@
 mod support;
 
 use axum::http::{Method, StatusCode};
-use support::{assert_status, with_test_app_with_config};
+use serde_json::Value;
+use support::{assert_status, minimal_patient, to_json_body, with_test_app_with_config};
 
 #[tokio::test]
 async fn audit_events_are_written_to_audit_log_table() -> anyhow::Result<()> {
@@ -35,3 +36,67 @@ async fn audit_events_are_written_to_audit_log_table() -> anyhow::Result<()> {
     )
     .await
 }
+
+#[tokio::test]
+async fn read_access_is_stored_as_audit_event() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |config| {
+            config.logging.audit.read_access.enabled = true;
+            config.logging.audit.read_access.resource_types = vec!["Patient".to_string()];
+        },
+        |app| {
+            Box::pin(async move {
+                let (status, _headers, body) = app
+                    .request(
+                        Method::POST,
+                        "/fhir/Patient",
+                        Some(to_json_body(&minimal_patient())?),
+                    )
+                    .await?;
+                assert_status(status, StatusCode::CREATED, "create Patient");
+                let created: Value = serde_json::from_slice(&body)?;
+                let id = created["id"].as_str().unwrap();
+
+                let (status, _headers, _body) = app
+                    .request_with_extra_headers(
+                        Method::GET,
+                        &format!("/fhir/Patient/{id}"),
+                        None,
+                        &[("x-purpose-of-use", "ETREAT")],
+                    )
+                    .await?;
+                assert_status(status, StatusCode::OK, "read Patient");
+
+                // The AuditEvent is stored in the background; wait briefly for it.
+                let mut bundle = Value::Null;
+                for _ in 0..50 {
+                    let (status, _headers, body) =
+                        app.request(Method::GET, "/fhir/AuditEvent", None).await?;
+                    assert_status(status, StatusCode::OK, "search AuditEvent");
+                    bundle = serde_json::from_slice(&body)?;
+                    if bundle["entry"].as_array().is_some_and(|e| !e.is_empty()) {
+                        break;
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                }
+                let events: Vec<&Value> = bundle["entry"]
+                    .as_array()
+                    .map(|entries| entries.iter().map(|e| &e["resource"]).collect())
+                    .unwrap_or_default();
+                assert_eq!(events.len(), 1, "expected one AuditEvent: {bundle}");
+
+                let event = events[0];
+                assert_eq!(
+                    event["entity"][0]["what"]["reference"],
+                    format!("Patient/{id}")
+                );
+                assert_eq!(event["agent"][0]["requestor"], true);
+                assert_eq!(event["agent"][0]["who"]["display"], "Anonymous");
+                assert_eq!(event["purposeOfEvent"][0]["coding"][0]["code"], "ETREAT");
+
+                Ok(())
+            })
+        },
+    )
+    .await
+}
@@ -0,0 +1,285 @@
+#![allow(unused)]
+#[allow(unused)]
+mod support;
+
+use axum::http::{Method, StatusCode};
+use ferrum::queue::JobStatus;
+use serde_json::{json, Value};
+use support::*;
+use uuid::Uuid;
+
+fn parse_json(body: &[u8]) -> anyhow::Result<Value> {
+    Ok(serde_json::from_slice(body)?)
+}
+
+/// Register the `family` search parameter and the OperationDefinition for $import.
+async fn setup_import(app: &TestApp) -> anyhow::Result<()> {
+    register_search_parameter(
+        &app.state.db_pool,
+        "family",
+        "Patient",
+        "string",
+        "Patient.name.family",
+        &[],
+    )
+    .await?;
+    app.state.search_engine.invalidate_param_cache();
+
+    let op_def = json!({
+        "resourceType": "OperationDefinition",
+        "id": "import",
+        "url": "http://ferrum.fhir.server/OperationDefinition/import",
+        "status": "active",
+        "kind": "operation",
+        "code": "import",
+        "system": true,
+        "type": false,
+        "instance": false,
+        "affectsState": true,
+        "parameter": [
+            {"name": "input", "use": "in", "min": 1, "max": "*"},
+            {"name": "validation", "use": "in", "min": 0, "max": "1", "type": "code"},
+            {"name": "batchSize", "use": "in", "min": 0, "max": "1", "type": "positiveInt"}
+        ]
+    });
+    let (status, _headers, _body) = app
+        .request(
+            Method::POST,
+            "/fhir/OperationDefinition",
+            Some(to_json_body(&op_def)?),
+        )
+        .await?;
+    assert_status(status, StatusCode::CREATED, "create OperationDefinition");
+
+    app.state.operation_registry.load_definitions().await?;
+    Ok(())
+}
+
+fn job_id(parameters: &Value) -> anyhow::Result<Uuid> {
+    let id = parameters["parameter"]
+        .as_array()
+        .and_then(|ps| ps.iter().find(|p| p["name"] == "jobId"))
+        .and_then(|p| p["valueString"].as_str())
+        .ok_or_else(|| anyhow::anyhow!("missing jobId in {}", parameters))?;
+    Ok(Uuid::parse_str(id)?)
+}
+
+#[tokio::test]
+async fn import_loads_ndjson_and_indexes_resources() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            setup_import(app).await?;
+
+            let patients = [
+                json!({"resourceType": "Patient", "id": "imp-1", "name": [{"family": "Importson"}]}),
+                json!({"resourceType": "Patient", "id": "imp-2", "name": [{"family": "Importson"}]}),
+            ]
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+            let observations = [
+                json!({"resourceType": "Observation", "id": "imp-obs", "status": "final", "code": {"text": "x"}}).to_string(),
+                "not json".to_string(),
+            ]
+            .join("\n");
+
+            let request = json!({
+                "resourceType": "Parameters",
+                "parameter": [
+                    {"name": "input", "part": [
+                        {"name": "type", "valueCode": "Patient"},
+                        {"name": "ndjson", "valueString": patients}
+                    ]},
+                    {"name": "input", "part": [
+                        {"name": "type", "valueCode": "Observation"},
+                        {"name": "ndjson", "valueString": observations}
+                    ]},
+                    {"name": "batchSize", "valueInteger": 1}
+                ]
+            });
+            let (status, _headers, body) = app
+                .request(Method::POST, "/fhir/$import", Some(to_json_body(&request)?))
+                .await?;
+            assert_status(status, StatusCode::OK, "$import");
+            let job_id = job_id(&parse_json(&body)?)?;
+
+            let job = app
+                .state
+                .job_queue
+                .get_job(job_id)
+                .await?
+                .expect("import job exists");
+            assert_eq!(job.status, JobStatus::Completed);
+            let results = job.progress.expect("import results");
+            assert_eq!(results["imported"], 3);
+            assert_eq!(results["failed"], 1);
+            assert_eq!(results["counts"]["Patient"], 2);
+            assert_eq!(results["counts"]["Observation"], 1);
+
+            // Client ids are kept and the resources are searchable
+            let (status, _headers, body) = app
+                .request(Method::GET, "/fhir/Patient?family=Importson", None)
+                .await?;
+            assert_status(status, StatusCode::OK, "search imported patients");
+            let mut ids = extract_resource_ids(&parse_json(&body)?, "Patient")?;
+            ids.sort();
+            assert_eq!(ids, vec!["imp-1".to_string(), "imp-2".to_string()]);
+
+            let (status, _headers, _body) = app
+                .request(Method::GET, "/fhir/Observation/imp-obs", None)
+                .await?;
+            assert_status(status, StatusCode::OK, "read imported observation");
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn import_validation_runs_the_validator() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            setup_import(app).await?;
+
+            let ndjson = [
+                json!({"resourceType": "Patient", "id": "imp-val-1", "birthDate": "1980-01-02"}),
+                json!({"resourceType": "Patient", "id": "imp-val-2", "birthDate": "not a date"}),
+                json!({"resourceType": "Observation", "id": "imp-val-obs", "status": "final", "code": {"text": "x"}}),
+            ]
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+            let request = json!({
+                "resourceType": "Parameters",
+                "parameter": [
+                    {"name": "input", "part": [
+                        {"name": "type", "valueCode": "Patient"},
+                        {"name": "ndjson", "valueString": ndjson}
+                    ]},
+                    {"name": "validation", "valueCode": "validate"}
+                ]
+            });
+            let (status, _headers, body) = app
+                .request(Method::POST, "/fhir/$import", Some(to_json_body(&request)?))
+                .await?;
+            assert_status(status, StatusCode::OK, "$import");
+            let job_id = job_id(&parse_json(&body)?)?;
+
+            let job = app
+                .state
+                .job_queue
+                .get_job(job_id)
+                .await?
+                .expect("import job exists");
+            let results = job.progress.expect("import results");
+            assert_eq!(results["imported"], 1);
+            assert_eq!(results["failed"], 2);
+
+            let (status, _headers, _body) = app
+                .request(Method::GET, "/fhir/Patient/imp-val-2", None)
+                .await?;
+            assert_status(status, StatusCode::NOT_FOUND, "invalid resource not imported");
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn import_rejects_invalid_parameters() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            setup_import(app).await?;
+
+            let cases = [
+                json!({"resourceType": "Parameters", "parameter": []}),
+                json!({"resourceType": "Parameters", "parameter": [
+                    {"name": "input", "part": [{"name": "type", "valueCode": "Patient"}]}
+                ]}),
+                json!({"resourceType": "Parameters", "parameter": [
+                    {"name": "input", "part": [{"name": "ndjson", "valueString": ""}]},
+                    {"name": "validation", "valueCode": "strict"}
+                ]}),
+                json!({"resourceType": "Parameters", "parameter": [
+                    {"name": "input", "part": [{"name": "ndjson", "valueString": ""}]},
+                    {"name": "batchSize", "valueInteger": 10_001}
+                ]}),
+            ];
+            for request in cases {
+                let (status, _headers, _body) = app
+                    .request(Method::POST, "/fhir/$import", Some(to_json_body(&request)?))
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::BAD_REQUEST,
+                    "$import invalid parameters",
+                );
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn import_rejects_read_only_types_and_unlisted_hosts() -> anyhow::Result<()> {
+    let read_only_observations = |config: &mut ferrum::config::Config| {
+        config.fhir.resource_policies.read_only = vec!["Observation".to_string()];
+    };
+    with_test_app_with_config(read_only_observations, |app| {
+        Box::pin(async move {
+            setup_import(app).await?;
+
+            let ndjson = [
+                json!({"resourceType": "Patient", "id": "imp-ro-1"}),
+                json!({"resourceType": "Observation", "id": "imp-ro-obs", "status": "final", "code": {"text": "x"}}),
+                json!({"resourceType": "NotAType", "id": "imp-ro-2"}),
+                json!({"resourceType": "Patient", "id": "bad id"}),
+            ]
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+            let request = json!({
+                "resourceType": "Parameters",
+                "parameter": [
+                    {"name": "input", "part": [{"name": "ndjson", "valueString": ndjson}]},
+                    {"name": "input", "part": [
+                        {"name": "url", "valueUrl": "http://169.254.169.254/latest/meta-data"}
+                    ]}
+                ]
+            });
+            let (status, _headers, body) = app
+                .request(Method::POST, "/fhir/$import", Some(to_json_body(&request)?))
+                .await?;
+            assert_status(status, StatusCode::OK, "$import");
+            let job_id = job_id(&parse_json(&body)?)?;
+
+            let job = app
+                .state
+                .job_queue
+                .get_job(job_id)
+                .await?
+                .expect("import job exists");
+            let results = job.progress.expect("import results");
+            assert_eq!(results["imported"], 1);
+            assert_eq!(results["failed"], 4);
+            assert_eq!(results["counts"]["Patient"], 1);
+
+            let (status, _headers, _body) = app
+                .request(Method::GET, "/fhir/Observation/imp-ro-obs", None)
+                .await?;
+            assert_status(status, StatusCode::NOT_FOUND, "read-only type not imported");
+
+            Ok(())
+        })
+    })
+    .await
+}
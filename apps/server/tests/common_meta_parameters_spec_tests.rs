@@ -248,3 +248,102 @@ async fn tag_and_security_token_semantics_match_system_only_and_code_only() -> a
     })
     .await
 }
+
+#[tokio::test]
+async fn tag_and_security_match_system_and_code() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            create_search_parameter(
+                app,
+                json!({
+                    "resourceType": "SearchParameter",
+                    "status": "active",
+                    "code": "_tag",
+                    "base": ["Resource"],
+                    "type": "token",
+                    "expression": "Resource.meta.tag"
+                }),
+            )
+            .await?;
+            create_search_parameter(
+                app,
+                json!({
+                    "resourceType": "SearchParameter",
+                    "status": "active",
+                    "code": "_security",
+                    "base": ["Resource"],
+                    "type": "token",
+                    "expression": "Resource.meta.security"
+                }),
+            )
+            .await?;
+
+            let restricted = json!({
+                "resourceType": "Patient",
+                "meta": {
+                    "security": [{
+                        "system": "http://terminology.hl7.org/CodeSystem/v3-Confidentiality",
+                        "code": "R"
+                    }],
+                    "tag": [{
+                        "system": "http://example.org/workflow",
+                        "code": "needs-review"
+                    }]
+                }
+            });
+            let reviewed = json!({
+                "resourceType": "Patient",
+                "meta": {
+                    "security": [{
+                        "system": "http://terminology.hl7.org/CodeSystem/v3-Confidentiality",
+                        "code": "N"
+                    }],
+                    "tag": [{
+                        "system": "http://example.org/workflow",
+                        "code": "reviewed"
+                    }]
+                }
+            });
+
+            let mut ids = Vec::new();
+            for patient in [&restricted, &reviewed] {
+                let (status, _headers, body) = app
+                    .request(Method::POST, "/fhir/Patient", Some(to_json_body(patient)?))
+                    .await?;
+                assert_status(status, StatusCode::CREATED, "create Patient");
+                let created = parse_json(&body)?;
+                ids.push(created["id"].as_str().unwrap().to_string());
+            }
+
+            let cases = [
+                (
+                    "_security=http://terminology.hl7.org/CodeSystem/v3-Confidentiality|R",
+                    vec![ids[0].clone()],
+                ),
+                (
+                    "_security=http://terminology.hl7.org/CodeSystem/v3-Confidentiality|N",
+                    vec![ids[1].clone()],
+                ),
+                ("_tag=http://example.org/workflow|needs-review", vec![ids[0].clone()]),
+                ("_tag=http://example.org/workflow|reviewed", vec![ids[1].clone()]),
+                // Same code in another system does not match
+                ("_tag=http://example.org/other|reviewed", vec![]),
+                (
+                    "_security=http://example.org/workflow|R&_tag=http://example.org/workflow|needs-review",
+                    vec![],
+                ),
+            ];
+            for (query, expected) in cases {
+                let (status, _headers, body) = app
+                    .request(Method::GET, &format!("/fhir/Patient?{}", query), None)
+                    .await?;
+                assert_status(status, StatusCode::OK, query);
+                let bundle = parse_json(&body)?;
+                assert_eq!(extract_resource_ids(&bundle, "Patient")?, expected, "{}", query);
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
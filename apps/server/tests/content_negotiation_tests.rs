@@ -183,6 +183,79 @@ async fn create_patient_as_json_get_as_xml() -> anyhow::Result<()> {
     .await
 }
 
+#[tokio::test]
+async fn read_format_resolves_from_format_param_then_accept() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let patient = json!({
+                "resourceType": "Patient",
+                "name": [{"family": "Negotiated"}]
+            });
+            let (status, _headers, body) = app
+                .request(Method::POST, "/fhir/Patient", Some(to_json_body(&patient)?))
+                .await?;
+            assert_status(status, StatusCode::CREATED, "create Patient");
+            let created: Value = serde_json::from_slice(&body)?;
+            let id = created["id"].as_str().unwrap();
+
+            let cases: [(String, &str, &str); 4] = [
+                // `_format` alone (default Accept is application/fhir+json)
+                (
+                    format!("/fhir/Patient/{}?_format=xml", id),
+                    "application/fhir+json",
+                    "application/fhir+xml",
+                ),
+                // Unencoded `+` in the `_format` value
+                (
+                    format!("/fhir/Patient/{}?_format=application/fhir+xml", id),
+                    "application/fhir+json",
+                    "application/fhir+xml",
+                ),
+                // Accept alone
+                (
+                    format!("/fhir/Patient/{}", id),
+                    "application/fhir+xml",
+                    "application/fhir+xml",
+                ),
+                // `_format` takes precedence over Accept
+                (
+                    format!("/fhir/Patient/{}?_format=json", id),
+                    "application/fhir+xml",
+                    "application/fhir+json",
+                ),
+            ];
+
+            for (path, accept, expected) in cases {
+                let (status, headers, body) = app
+                    .request_with_extra_headers(Method::GET, &path, None, &[("accept", accept)])
+                    .await?;
+                assert_status(status, StatusCode::OK, &path);
+
+                let ct = headers
+                    .get("content-type")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("");
+                assert!(
+                    ct.starts_with(expected),
+                    "{path} (Accept: {accept}): expected {expected}, got '{ct}'"
+                );
+                let body_str = String::from_utf8_lossy(&body);
+                if expected == "application/fhir+xml" {
+                    assert!(
+                        body_str.contains("<Patient"),
+                        "expected XML body for {path}"
+                    );
+                } else {
+                    assert_eq!(parse_json(&body)?["resourceType"], "Patient");
+                }
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
+
 #[tokio::test]
 async fn create_patient_as_xml_get_as_json() -> anyhow::Result<()> {
     with_test_app(|app| {
@@ -288,3 +361,141 @@ async fn search_result_as_xml() -> anyhow::Result<()> {
     })
     .await
 }
+
+#[tokio::test]
+async fn search_response_varies_on_accept() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let (status, headers, _body) = app
+                .request(Method::GET, "/fhir/Patient?name=VaryTest", None)
+                .await?;
+            assert_status(status, StatusCode::OK, "search");
+
+            let varies_on_accept = headers
+                .get_all("vary")
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(','))
+                .any(|v| v.trim().eq_ignore_ascii_case("accept"));
+            assert!(varies_on_accept, "expected Vary: Accept, got {headers:?}");
+            assert_eq!(
+                headers.get("cache-control").and_then(|v| v.to_str().ok()),
+                Some("no-cache")
+            );
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+fn gzip(bytes: &[u8]) -> anyhow::Result<Bytes> {
+    use std::io::Write as _;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes)?;
+    Ok(Bytes::from(encoder.finish()?))
+}
+
+fn gunzip(bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    use std::io::Read as _;
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(bytes).read_to_end(&mut decoded)?;
+    Ok(decoded)
+}
+
+#[tokio::test]
+async fn gzipped_bundle_and_gzipped_search_response() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let entries: Vec<Value> = (0..5)
+                .map(|i| {
+                    json!({
+                        "resource": {
+                            "resourceType": "Patient",
+                            "name": [{ "family": "Compressed", "given": [format!("Patient{}", i)] }]
+                        },
+                        "request": { "method": "POST", "url": "Patient" }
+                    })
+                })
+                .collect();
+            let bundle = json!({
+                "resourceType": "Bundle",
+                "type": "transaction",
+                "entry": entries
+            });
+
+            let (status, _headers, body) = app
+                .request_with_extra_headers(
+                    Method::POST,
+                    "/fhir",
+                    Some(gzip(&serde_json::to_vec(&bundle)?)?),
+                    &[("content-encoding", "gzip")],
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "gzipped transaction");
+            let response = parse_json(&body)?;
+            assert_eq!(response["type"], "transaction-response");
+
+            let (status, headers, body) = app
+                .request_with_extra_headers(
+                    Method::GET,
+                    "/fhir/Patient?family=Compressed",
+                    None,
+                    &[("accept-encoding", "gzip")],
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "search");
+            assert_eq!(
+                headers
+                    .get("content-encoding")
+                    .and_then(|v| v.to_str().ok()),
+                Some("gzip")
+            );
+            let searchset = parse_json(&gunzip(&body)?)?;
+            assert_eq!(searchset["entry"].as_array().map(Vec::len), Some(5));
+
+            // Responses below the size threshold are sent uncompressed
+            let (status, headers, _body) = app
+                .request_with_extra_headers(
+                    Method::GET,
+                    "/health",
+                    None,
+                    &[("accept-encoding", "gzip")],
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "health");
+            assert!(headers.get("content-encoding").is_none());
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn unsupported_request_encoding_is_rejected() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |config| {
+            config.server.compression.codecs = vec![ferrum::config::CompressionCodec::Gzip];
+        },
+        |app| {
+            Box::pin(async move {
+                let (status, _headers, _body) = app
+                    .request_with_extra_headers(
+                        Method::POST,
+                        "/fhir/Patient",
+                        Some(to_json_body(&minimal_patient())?),
+                        &[("content-encoding", "br")],
+                    )
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    "brotli disabled",
+                );
+                Ok(())
+            })
+        },
+    )
+    .await
+}
@@ -0,0 +1,155 @@
+//! Write-Time Code Translation Tests
+//!
+//! These tests verify the configurable `fhir.code_translation.rules`:
+//! - a local code with a ConceptMap match is stored with the standard code added
+//! - the translation also applies to transaction entries
+//! - codes without a match are stored unchanged
+
+use crate::support::{assert_status, to_json_body, with_test_app_with_config, TestApp};
+use axum::http::{Method, StatusCode};
+use ferrum::config::CodeTranslationRule;
+use serde_json::{json, Value};
+
+const LOCAL_SYSTEM: &str = "http://lab.example.org/codes";
+const CONCEPT_MAP_URL: &str = "http://lab.example.org/ConceptMap/local-to-loinc";
+
+fn enable_observation_translation(config: &mut ferrum::config::Config) {
+    config.fhir.code_translation.rules = vec![CodeTranslationRule {
+        resource_type: "Observation".to_string(),
+        element: "code".to_string(),
+        concept_map: CONCEPT_MAP_URL.to_string(),
+    }];
+}
+
+async fn create_concept_map(app: &TestApp) -> anyhow::Result<()> {
+    let concept_map = json!({
+        "resourceType": "ConceptMap",
+        "url": CONCEPT_MAP_URL,
+        "status": "active",
+        "group": [{
+            "source": LOCAL_SYSTEM,
+            "target": "http://loinc.org",
+            "element": [{
+                "code": "GLU",
+                "target": [{
+                    "code": "2345-7",
+                    "display": "Glucose [Mass/volume] in Serum or Plasma",
+                    "equivalence": "equivalent"
+                }]
+            }]
+        }]
+    });
+    let (status, _headers, _body) = app
+        .request(
+            Method::POST,
+            "/fhir/ConceptMap",
+            Some(to_json_body(&concept_map)?),
+        )
+        .await?;
+    assert_status(status, StatusCode::CREATED, "create concept map");
+    Ok(())
+}
+
+fn observation(code: &str) -> Value {
+    json!({
+        "resourceType": "Observation",
+        "status": "final",
+        "code": { "coding": [{ "system": LOCAL_SYSTEM, "code": code }] }
+    })
+}
+
+async fn read(app: &TestApp, location: &str) -> anyhow::Result<Value> {
+    let (status, _headers, body) = app.request(Method::GET, location, None).await?;
+    assert_status(status, StatusCode::OK, "read observation");
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[tokio::test]
+async fn create_stores_translated_standard_code() -> anyhow::Result<()> {
+    with_test_app_with_config(enable_observation_translation, |app| {
+        Box::pin(async move {
+            create_concept_map(app).await?;
+
+            let (status, _headers, body) = app
+                .request(
+                    Method::POST,
+                    "/fhir/Observation",
+                    Some(to_json_body(&observation("GLU"))?),
+                )
+                .await?;
+            assert_status(status, StatusCode::CREATED, "create observation");
+            let created: Value = serde_json::from_slice(&body)?;
+            let id = created["id"].as_str().unwrap();
+
+            let stored = read(app, &format!("/fhir/Observation/{id}")).await?;
+            let codings = stored["code"]["coding"].as_array().unwrap();
+            assert_eq!(codings.len(), 2, "{stored}");
+            assert_eq!(codings[0]["code"], "GLU");
+            assert_eq!(codings[1]["system"], "http://loinc.org");
+            assert_eq!(codings[1]["code"], "2345-7");
+
+            // The standard code is searchable
+            let (status, _headers, body) = app
+                .request(
+                    Method::GET,
+                    "/fhir/Observation?code=http://loinc.org|2345-7",
+                    None,
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "search by standard code");
+            let bundle: Value = serde_json::from_slice(&body)?;
+            assert_eq!(bundle["entry"].as_array().map(Vec::len), Some(1));
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn transaction_entries_are_translated() -> anyhow::Result<()> {
+    with_test_app_with_config(enable_observation_translation, |app| {
+        Box::pin(async move {
+            create_concept_map(app).await?;
+
+            let bundle = json!({
+                "resourceType": "Bundle",
+                "type": "transaction",
+                "entry": [
+                    {
+                        "fullUrl": "urn:uuid:3f1c3bde-5b5b-4c0e-9d55-0b2b8b1f2a01",
+                        "resource": observation("GLU"),
+                        "request": { "method": "POST", "url": "Observation" }
+                    },
+                    {
+                        "fullUrl": "urn:uuid:3f1c3bde-5b5b-4c0e-9d55-0b2b8b1f2a02",
+                        "resource": observation("UNMAPPED"),
+                        "request": { "method": "POST", "url": "Observation" }
+                    }
+                ]
+            });
+            let (status, _headers, body) = app
+                .request(Method::POST, "/fhir", Some(to_json_body(&bundle)?))
+                .await?;
+            assert_status(status, StatusCode::OK, "transaction");
+            let response: Value = serde_json::from_slice(&body)?;
+
+            let location = |index: usize| {
+                let location = response["entry"][index]["response"]["location"]
+                    .as_str()
+                    .unwrap();
+                format!("/fhir/{}", location.split("/_history").next().unwrap())
+            };
+
+            let translated = read(app, &location(0)).await?;
+            assert_eq!(translated["code"]["coding"][1]["code"], "2345-7");
+
+            let unmapped = read(app, &location(1)).await?;
+            assert_eq!(
+                unmapped["code"]["coding"],
+                observation("UNMAPPED")["code"]["coding"]
+            );
+            Ok(())
+        })
+    })
+    .await
+}
@@ -82,6 +82,162 @@ async fn supported_resources_are_enforced_for_type_level_writes() -> anyhow::Res
     .await
 }
 
+// ============================================================================
+// Resource Type Policy Tests
+// ============================================================================
+
+#[tokio::test]
+async fn read_only_type_allows_reads_and_rejects_writes() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |config| {
+            config.fhir.resource_policies.read_only = vec!["CodeSystem".to_string()];
+        },
+        |app| {
+            Box::pin(async move {
+                // Seed through the store, as package installation would.
+                let store = PostgresResourceStore::new(app.state.db_pool.clone());
+                let code_system = json!({
+                    "resourceType": "CodeSystem",
+                    "id": "read-only-cs",
+                    "url": "http://example.org/CodeSystem/read-only-cs",
+                    "status": "active",
+                    "content": "complete"
+                });
+                store.create("CodeSystem", code_system.clone()).await?;
+
+                let (status, _headers, _body) = app
+                    .request(Method::GET, "/fhir/CodeSystem/read-only-cs", None)
+                    .await?;
+                assert_status(status, StatusCode::OK, "read read-only type");
+
+                let (status, _headers, _body) =
+                    app.request(Method::GET, "/fhir/CodeSystem", None).await?;
+                assert_status(status, StatusCode::OK, "search read-only type");
+
+                let (status, _headers, body) = app
+                    .request(
+                        Method::POST,
+                        "/fhir/CodeSystem",
+                        Some(to_json_body(&code_system)?),
+                    )
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    "create read-only type",
+                );
+                let outcome: serde_json::Value = serde_json::from_slice(&body)?;
+                assert_eq!(outcome["resourceType"], "OperationOutcome");
+                assert!(
+                    outcome["issue"][0]["diagnostics"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .contains("Resource type CodeSystem is read-only"),
+                    "unexpected outcome: {outcome}"
+                );
+
+                let (status, _headers, _body) = app
+                    .request(
+                        Method::PUT,
+                        "/fhir/CodeSystem/read-only-cs",
+                        Some(to_json_body(&code_system)?),
+                    )
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    "update read-only type",
+                );
+
+                let (status, _headers, _body) = app
+                    .request(Method::DELETE, "/fhir/CodeSystem/read-only-cs", None)
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    "delete read-only type",
+                );
+
+                // Conditional writes are rejected before the search runs, even without a match.
+                let (status, _headers, _body) = app
+                    .request(
+                        Method::DELETE,
+                        "/fhir/CodeSystem?url=http://example.org/CodeSystem/missing",
+                        None,
+                    )
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    "conditional delete of read-only type",
+                );
+
+                let (status, _headers, _body) = app
+                    .request(
+                        Method::PUT,
+                        "/fhir/CodeSystem?url=http://example.org/CodeSystem/read-only-cs",
+                        Some(to_json_body(&code_system)?),
+                    )
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    "conditional update of read-only type",
+                );
+
+                // Transactions cannot bypass the policy.
+                let bundle = json!({
+                    "resourceType": "Bundle",
+                    "type": "transaction",
+                    "entry": [{
+                        "resource": code_system,
+                        "request": {"method": "POST", "url": "CodeSystem"}
+                    }]
+                });
+                let (status, _headers, _body) = app
+                    .request(Method::POST, "/fhir", Some(to_json_body(&bundle)?))
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    "transaction write to read-only type",
+                );
+
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn read_only_policy_does_not_affect_other_types() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |config| {
+            config.fhir.resource_policies.read_only = vec!["CodeSystem".to_string()];
+        },
+        |app| {
+            Box::pin(async move {
+                let patient = minimal_patient();
+                let (status, _headers, body) = app
+                    .request(Method::POST, "/fhir/Patient", Some(to_json_body(&patient)?))
+                    .await?;
+                assert_status(status, StatusCode::CREATED, "create unrestricted type");
+
+                let created: serde_json::Value = serde_json::from_slice(&body)?;
+                let id = created["id"].as_str().unwrap();
+                let (status, _headers, _body) = app
+                    .request(Method::DELETE, &format!("/fhir/Patient/{id}"), None)
+                    .await?;
+                assert_status(status, StatusCode::NO_CONTENT, "delete unrestricted type");
+
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
 // ============================================================================
 // allow_update_create Configuration Tests
 // ============================================================================
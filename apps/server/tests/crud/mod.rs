@@ -1,9 +1,12 @@
+pub mod code_translation;
 pub mod conditional_references;
 pub mod configurable_behaviors;
 pub mod create;
 pub mod delete;
+pub mod narrative;
 pub mod patch;
 pub mod read;
 pub mod referential_integrity;
 pub mod spec_compliance;
 pub mod update;
+pub mod write_validation;
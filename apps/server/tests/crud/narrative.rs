@@ -0,0 +1,94 @@
+//! Write-Time Narrative Generation Tests
+//!
+//! These tests verify the configurable `fhir.narrative.resource_types`:
+//! - listed types written without `text` get a generated `text.div`
+//! - authored narratives are kept as sent
+//! - other types, and all types by default, are stored unchanged
+
+use crate::support::{assert_status, example_patient, to_json_body, with_test_app_with_config};
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+fn enable_patient_narrative(config: &mut ferrum::config::Config) {
+    config.fhir.narrative.resource_types = vec!["Patient".to_string()];
+}
+
+#[tokio::test]
+async fn create_without_narrative_generates_text_div() -> anyhow::Result<()> {
+    with_test_app_with_config(enable_patient_narrative, |app| {
+        Box::pin(async move {
+            let (status, _headers, body) = app
+                .request(
+                    Method::POST,
+                    "/fhir/Patient",
+                    Some(to_json_body(&example_patient("Smith", "Ann"))?),
+                )
+                .await?;
+            assert_status(status, StatusCode::CREATED, "create patient");
+
+            let created: Value = serde_json::from_slice(&body)?;
+            let id = created["id"].as_str().unwrap();
+            let (status, _headers, body) = app
+                .request(Method::GET, &format!("/fhir/Patient/{id}"), None)
+                .await?;
+            assert_status(status, StatusCode::OK, "read patient");
+
+            let stored: Value = serde_json::from_slice(&body)?;
+            assert_eq!(stored["text"]["status"], "generated");
+            let div = stored["text"]["div"].as_str().unwrap();
+            assert!(
+                div.starts_with("<div xmlns=\"http://www.w3.org/1999/xhtml\">"),
+                "{div}"
+            );
+            assert!(div.contains("Ann Smith"), "{div}");
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn authored_narrative_is_kept() -> anyhow::Result<()> {
+    with_test_app_with_config(enable_patient_narrative, |app| {
+        Box::pin(async move {
+            let mut patient = example_patient("Smith", "Ann");
+            patient["text"] = json!({
+                "status": "additional",
+                "div": "<div xmlns=\"http://www.w3.org/1999/xhtml\">Authored</div>"
+            });
+            let (status, _headers, body) = app
+                .request(Method::POST, "/fhir/Patient", Some(to_json_body(&patient)?))
+                .await?;
+            assert_status(status, StatusCode::CREATED, "create patient");
+
+            let created: Value = serde_json::from_slice(&body)?;
+            assert_eq!(created["text"], patient["text"]);
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn narrative_generation_is_off_by_default() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |_| {},
+        |app| {
+            Box::pin(async move {
+                let (status, _headers, body) = app
+                    .request(
+                        Method::POST,
+                        "/fhir/Patient",
+                        Some(to_json_body(&example_patient("Smith", "Ann"))?),
+                    )
+                    .await?;
+                assert_status(status, StatusCode::CREATED, "create patient");
+
+                let created: Value = serde_json::from_slice(&body)?;
+                assert!(created.get("text").is_none());
+                Ok(())
+            })
+        },
+    )
+    .await
+}
@@ -4,6 +4,7 @@
 //!
 //! Tests cover:
 //! - JSON Patch application and version increment
+//! - If-Match version checks (stale versions are rejected without applying the patch)
 //! - Conditional patch resolution (0/1/many matches)
 //! - 422 Unprocessable Entity on failing JSON Patch test op
 //! - Narrative safety behavior (narrative removed after patch)
@@ -56,6 +57,73 @@ async fn patch_removes_narrative_for_safety() -> anyhow::Result<()> {
     .await
 }
 
+#[tokio::test]
+async fn patch_honors_if_match() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let patient = json!({ "resourceType": "Patient", "active": true });
+            let (status, _headers, body) = app
+                .request(Method::POST, "/fhir/Patient", Some(to_json_body(&patient)?))
+                .await?;
+            assert_status(status, StatusCode::CREATED, "create");
+            let created: serde_json::Value = serde_json::from_slice(&body)?;
+            let id = created["id"].as_str().unwrap().to_string();
+
+            // Matching version: applied
+            let patch = json!([{ "op": "replace", "path": "/active", "value": false }]);
+            let (status, headers, body) = app
+                .request_with_extra_headers(
+                    Method::PATCH,
+                    &format!("/fhir/Patient/{id}"),
+                    Some(to_json_body(&patch)?),
+                    &[
+                        ("content-type", "application/json-patch+json"),
+                        ("if-match", "W/\"1\""),
+                    ],
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "patch with current version");
+            let patched: serde_json::Value = serde_json::from_slice(&body)?;
+            assert_version_id(&patched, "2")?;
+            assert_eq!(patched["active"], false);
+            assert_eq!(
+                headers.get("etag").and_then(|v| v.to_str().ok()),
+                Some("W/\"2\"")
+            );
+
+            // Stale version: rejected as a version conflict, resource unchanged
+            let patch = json!([{ "op": "add", "path": "/gender", "value": "female" }]);
+            let (status, _headers, _body) = app
+                .request_with_extra_headers(
+                    Method::PATCH,
+                    &format!("/fhir/Patient/{id}"),
+                    Some(to_json_body(&patch)?),
+                    &[
+                        ("content-type", "application/json-patch+json"),
+                        ("if-match", "W/\"1\""),
+                    ],
+                )
+                .await?;
+            assert_status(
+                status,
+                StatusCode::PRECONDITION_FAILED,
+                "patch with stale version",
+            );
+
+            let (status, _headers, body) = app
+                .request(Method::GET, &format!("/fhir/Patient/{id}"), None)
+                .await?;
+            assert_status(status, StatusCode::OK, "read");
+            let current: serde_json::Value = serde_json::from_slice(&body)?;
+            assert_version_id(&current, "2")?;
+            assert!(current.get("gender").is_none());
+
+            Ok(())
+        })
+    })
+    .await
+}
+
 #[tokio::test]
 async fn conditional_patch_returns_404_when_no_match() -> anyhow::Result<()> {
     with_test_app(|app| {
@@ -7,7 +7,7 @@
 //! - 404 Not Found for non-existent resources
 //! - 410 Gone for deleted resources
 //! - Reading returns current version only
-//! - ETag header with version ID
+//! - ETag, Last-Modified, Cache-Control and Vary headers
 
 use crate::support::{
     assert_resource_id, assert_status, minimal_patient, to_json_body, with_test_app,
@@ -159,15 +159,18 @@ async fn read_returns_etag_header() -> anyhow::Result<()> {
 
             assert_status(status, StatusCode::OK, "read");
 
-            // ETag should contain version ID
-            if let Some(etag) = headers.get("etag") {
-                let etag_str = etag.to_str()?;
-                // ETag format varies, but should reference version 1
-                assert!(
-                    etag_str.contains('1'),
-                    "ETag should reference version 1: {etag_str}"
-                );
-            }
+            // Weak ETag carrying the version ID
+            assert_eq!(
+                headers.get("etag").and_then(|v| v.to_str().ok()),
+                Some("W/\"1\"")
+            );
+            assert!(headers.contains_key("last-modified"));
+            assert!(headers.contains_key("cache-control"));
+            assert!(headers
+                .get_all("vary")
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .any(|v| v.split(',').any(|v| v.trim() == "Accept")));
 
             Ok(())
         })
@@ -0,0 +1,204 @@
+//! Write-Time Validation Tests
+//!
+//! These tests verify the configurable `fhir.validation.mode`:
+//! - "off" (default): resources are stored without validation
+//! - "lenient": resources are stored; issues are returned with `Prefer: return=OperationOutcome`
+//! - "strict": resources with validation errors are rejected with 422; warnings are returned
+//!   without blocking the write
+
+use crate::support::{assert_status, minimal_patient, to_json_body, with_test_app_with_config};
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+/// A Patient whose `active` is a string instead of a boolean.
+fn invalid_patient() -> Value {
+    json!({
+        "resourceType": "Patient",
+        "active": "yes",
+        "name": [{"family": "Doe"}]
+    })
+}
+
+/// A Patient whose `maritalStatus` uses a code outside the extensible binding: a warning only.
+fn patient_with_warning() -> Value {
+    json!({
+        "resourceType": "Patient",
+        "name": [{"family": "Doe"}],
+        "maritalStatus": {
+            "coding": [{"system": "http://example.org/marital-status", "code": "civil-union"}]
+        }
+    })
+}
+
+fn has_issue_with_severity(outcome: &Value, severity: &str) -> bool {
+    outcome["issue"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|issue| issue["severity"] == severity)
+}
+
+fn has_error_issue(outcome: &Value) -> bool {
+    has_issue_with_severity(outcome, "error")
+}
+
+#[tokio::test]
+async fn strict_stores_valid_resource() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |config| {
+            config.fhir.validation.mode = "strict".to_string();
+        },
+        |app| {
+            Box::pin(async move {
+                let (status, _headers, body) = app
+                    .request(
+                        Method::POST,
+                        "/fhir/Patient",
+                        Some(to_json_body(&minimal_patient())?),
+                    )
+                    .await?;
+                assert_status(status, StatusCode::CREATED, "valid resource is stored");
+
+                let created: Value = serde_json::from_slice(&body)?;
+                let id = created["id"].as_str().unwrap();
+                let (status, _headers, _body) = app
+                    .request(Method::GET, &format!("/fhir/Patient/{id}"), None)
+                    .await?;
+                assert_status(status, StatusCode::OK, "read stored resource");
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn strict_rejects_invalid_resource() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |config| {
+            config.fhir.validation.mode = "strict".to_string();
+        },
+        |app| {
+            Box::pin(async move {
+                let (status, _headers, body) = app
+                    .request(
+                        Method::POST,
+                        "/fhir/Patient",
+                        Some(to_json_body(&invalid_patient())?),
+                    )
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "strict rejects invalid create",
+                );
+                let outcome: Value = serde_json::from_slice(&body)?;
+                assert_eq!(outcome["resourceType"], "OperationOutcome");
+
+                let mut patient = invalid_patient();
+                patient["id"] = json!("wv-strict");
+                let (status, _headers, _body) = app
+                    .request(
+                        Method::PUT,
+                        "/fhir/Patient/wv-strict",
+                        Some(to_json_body(&patient)?),
+                    )
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "strict rejects invalid update",
+                );
+
+                let (status, _headers, _body) = app
+                    .request(Method::GET, "/fhir/Patient/wv-strict", None)
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::NOT_FOUND,
+                    "rejected resource not stored",
+                );
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn lenient_stores_invalid_resource_with_warnings() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |config| {
+            config.fhir.validation.mode = "lenient".to_string();
+        },
+        |app| {
+            Box::pin(async move {
+                let mut patient = invalid_patient();
+                patient["id"] = json!("wv-lenient");
+                let (status, _headers, body) = app
+                    .request_with_extra_headers(
+                        Method::PUT,
+                        "/fhir/Patient/wv-lenient",
+                        Some(to_json_body(&patient)?),
+                        &[("prefer", "return=OperationOutcome")],
+                    )
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::CREATED,
+                    "lenient stores invalid resource",
+                );
+
+                let outcome: Value = serde_json::from_slice(&body)?;
+                assert_eq!(outcome["resourceType"], "OperationOutcome");
+                assert!(
+                    has_error_issue(&outcome),
+                    "validation issues reported: {outcome}"
+                );
+
+                let (status, _headers, _body) = app
+                    .request(Method::GET, "/fhir/Patient/wv-lenient", None)
+                    .await?;
+                assert_status(status, StatusCode::OK, "read stored resource");
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn strict_returns_warnings_with_created_resource() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |config| {
+            config.fhir.validation.mode = "strict".to_string();
+        },
+        |app| {
+            Box::pin(async move {
+                let (status, _headers, body) = app
+                    .request_with_extra_headers(
+                        Method::POST,
+                        "/fhir/Patient",
+                        Some(to_json_body(&patient_with_warning())?),
+                        &[("prefer", "return=OperationOutcome")],
+                    )
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::CREATED,
+                    "resource with warnings is stored",
+                );
+
+                let outcome: Value = serde_json::from_slice(&body)?;
+                assert_eq!(outcome["resourceType"], "OperationOutcome");
+                assert!(
+                    has_issue_with_severity(&outcome, "warning"),
+                    "warnings reported: {outcome}"
+                );
+                assert!(!has_error_issue(&outcome), "no errors: {outcome}");
+                Ok(())
+            })
+        },
+    )
+    .await
+}
@@ -0,0 +1,78 @@
+#![allow(unused)]
+//! Integration tests for database pool metrics and pool exhaustion handling.
+
+mod support;
+
+use axum::http::{Method, StatusCode};
+use ferrum::services::IndexingService;
+use sqlx::postgres::PgPoolOptions;
+use std::time::Duration;
+use support::*;
+
+#[tokio::test]
+async fn metrics_expose_pool_usage() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            // Goes through the bounded acquire, which records the wait time
+            app.state
+                .indexing_service
+                .remove_resource_index("Patient", "pool-metrics")
+                .await?;
+
+            let (status, _headers, body) = app.request(Method::GET, "/metrics", None).await?;
+            assert_status(status, StatusCode::OK, "metrics");
+
+            let text = String::from_utf8(body.to_vec())?;
+            for metric in [
+                "fhir_db_connections_active",
+                "fhir_db_connections_idle",
+                "fhir_db_pool_max_connections",
+                "fhir_db_pool_acquire_wait_seconds_count{operation=\"indexing\"}",
+            ] {
+                assert!(text.contains(metric), "missing {metric} in:\n{text}");
+            }
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn exhausted_pool_fails_indexing_instead_of_hanging() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let pool = PgPoolOptions::new()
+                .max_connections(1)
+                .connect(&app.state.config.database.url)
+                .await?;
+            let indexing = IndexingService::new(
+                pool.clone(),
+                &app.state.config.fhir.version,
+                50,
+                200,
+                false,
+                false,
+            )?
+            .with_acquire_timeout(Duration::from_millis(200));
+
+            // Another job holds the only connection
+            let _held = pool.acquire().await?;
+
+            let result = tokio::time::timeout(
+                Duration::from_secs(5),
+                indexing.remove_resource_index("Patient", "pool-exhausted"),
+            )
+            .await
+            .expect("indexing hung on an exhausted pool");
+
+            match result {
+                Err(ferrum::Error::Timeout(message)) => {
+                    assert!(message.contains("pool max 1"), "{message}");
+                }
+                other => panic!("expected a pool timeout, got {other:?}"),
+            }
+            Ok(())
+        })
+    })
+    .await
+}
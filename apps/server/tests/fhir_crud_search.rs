@@ -591,6 +591,77 @@ async fn id_parameter_rejects_modifiers_and_pipes() -> anyhow::Result<()> {
     .await
 }
 
+#[tokio::test]
+async fn id_parameter_matches_any_of_comma_separated_ids() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let mut ids = Vec::new();
+            for family in ["Alpha", "Beta", "Gamma"] {
+                let patient = json!({"resourceType": "Patient", "name": [{"family": family}]});
+                let (status, _headers, body) = app
+                    .request(Method::POST, "/fhir/Patient", Some(to_json_body(&patient)?))
+                    .await?;
+                assert_status(status, StatusCode::CREATED, "create Patient");
+                let created: serde_json::Value = serde_json::from_slice(&body)?;
+                ids.push(created["id"].as_str().context("id")?.to_string());
+            }
+
+            let (status, _headers, body) = app
+                .request(
+                    Method::GET,
+                    &format!("/fhir/Patient?_id={},{}", ids[0], ids[1]),
+                    None,
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "search _id");
+            let bundle: serde_json::Value = serde_json::from_slice(&body)?;
+            let mut found = extract_resource_ids(&bundle, "Patient")?;
+            found.sort();
+            let mut expected = vec![ids[0].clone(), ids[1].clone()];
+            expected.sort();
+            assert_eq!(found, expected);
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn last_updated_parameter_applies_prefixes() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let patient = json!({"resourceType": "Patient", "name": [{"family": "Recent"}]});
+            let (status, _headers, body) = app
+                .request(Method::POST, "/fhir/Patient", Some(to_json_body(&patient)?))
+                .await?;
+            assert_status(status, StatusCode::CREATED, "create Patient");
+            let created: serde_json::Value = serde_json::from_slice(&body)?;
+            let id = created["id"].as_str().context("id")?.to_string();
+
+            let search = |query: &'static str| async move {
+                let (status, _headers, body) = app
+                    .request(Method::GET, &format!("/fhir/Patient?{query}"), None)
+                    .await?;
+                assert_status(status, StatusCode::OK, query);
+                let bundle: serde_json::Value = serde_json::from_slice(&body)?;
+                extract_resource_ids(&bundle, "Patient")
+            };
+
+            assert_eq!(search("_lastUpdated=gt2020-01-01").await?, vec![id.clone()]);
+            assert!(search("_lastUpdated=lt2020-01-01").await?.is_empty());
+            assert!(search("_lastUpdated=gt2999-01-01").await?.is_empty());
+            assert_eq!(
+                search("_lastUpdated=ge2020-01-01&_lastUpdated=lt2999-01-01").await?,
+                vec![id]
+            );
+
+            Ok(())
+        })
+    })
+    .await
+}
+
 #[tokio::test]
 async fn reference_identifier_modifier_matches_reference_identifier_only() -> anyhow::Result<()> {
     with_test_app(|app| {
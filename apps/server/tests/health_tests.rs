@@ -0,0 +1,68 @@
+#![allow(unused)]
+//! Integration tests for the liveness (`/health`) and readiness (`/ready`) endpoints.
+
+mod support;
+
+use axum::http::{Method, StatusCode};
+use std::sync::atomic::Ordering;
+use support::*;
+
+#[tokio::test]
+async fn health_is_ok() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let (status, _headers, body) = app.request(Method::GET, "/health", None).await?;
+            assert_status(status, StatusCode::OK, "health");
+
+            let json: serde_json::Value = serde_json::from_slice(&body)?;
+            assert_eq!(json["status"], "ok");
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn ready_reports_all_checks_when_initialized() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let (status, _headers, body) = app.request(Method::GET, "/ready", None).await?;
+            assert_status(status, StatusCode::OK, "ready");
+
+            let json: serde_json::Value = serde_json::from_slice(&body)?;
+            assert_eq!(json["status"], "ready");
+            assert_eq!(json["checks"]["database"], true);
+            assert_eq!(json["checks"]["migrations"], true);
+            assert_eq!(json["checks"]["packages"], true);
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn ready_fails_until_packages_are_loaded() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            app.state.packages_loaded.store(false, Ordering::Release);
+
+            let (status, _headers, body) = app.request(Method::GET, "/ready", None).await?;
+            assert_status(status, StatusCode::SERVICE_UNAVAILABLE, "ready before load");
+            let json: serde_json::Value = serde_json::from_slice(&body)?;
+            assert_eq!(json["status"], "not_ready");
+            assert_eq!(json["checks"]["packages"], false);
+            assert_eq!(json["checks"]["database"], true);
+
+            // Liveness is unaffected by readiness.
+            let (status, _headers, _body) = app.request(Method::GET, "/health", None).await?;
+            assert_status(status, StatusCode::OK, "health before load");
+
+            app.state.packages_loaded.store(true, Ordering::Release);
+
+            let (status, _headers, _body) = app.request(Method::GET, "/ready", None).await?;
+            assert_status(status, StatusCode::OK, "ready after load");
+            Ok(())
+        })
+    })
+    .await
+}
@@ -0,0 +1,225 @@
+#![allow(unused)]
+#[allow(unused)]
+mod support;
+
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+use support::*;
+
+fn parse_json(body: &[u8]) -> anyhow::Result<Value> {
+    Ok(serde_json::from_slice(body)?)
+}
+
+fn entries(bundle: &Value) -> Vec<Value> {
+    bundle
+        .get("entry")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Register the search parameters $lastn groups on and the OperationDefinition for $lastn.
+async fn setup_lastn(app: &TestApp) -> anyhow::Result<()> {
+    register_search_parameter(
+        &app.state.db_pool,
+        "code",
+        "Observation",
+        "token",
+        "Observation.code",
+        &[],
+    )
+    .await?;
+    register_search_parameter(
+        &app.state.db_pool,
+        "category",
+        "Observation",
+        "token",
+        "Observation.category",
+        &[],
+    )
+    .await?;
+    register_search_parameter(
+        &app.state.db_pool,
+        "date",
+        "Observation",
+        "date",
+        "Observation.effective",
+        &[],
+    )
+    .await?;
+    register_search_parameter(
+        &app.state.db_pool,
+        "subject",
+        "Observation",
+        "reference",
+        "Observation.subject",
+        &[],
+    )
+    .await?;
+    app.state.search_engine.invalidate_param_cache();
+
+    let op_def = json!({
+        "resourceType": "OperationDefinition",
+        "id": "lastn",
+        "url": "http://hl7.org/fhir/OperationDefinition/Observation-lastn",
+        "status": "active",
+        "kind": "operation",
+        "code": "lastn",
+        "resource": ["Observation"],
+        "system": false,
+        "type": true,
+        "instance": false,
+        "affectsState": false,
+        "parameter": [
+            {"name": "max", "use": "in", "min": 0, "max": "1", "type": "positiveInt"},
+            {"name": "patient", "use": "in", "min": 0, "max": "1", "type": "string"},
+            {"name": "category", "use": "in", "min": 0, "max": "1", "type": "string"}
+        ]
+    });
+    let (status, _headers, _body) = app
+        .request(
+            Method::POST,
+            "/fhir/OperationDefinition",
+            Some(to_json_body(&op_def)?),
+        )
+        .await?;
+    assert_status(status, StatusCode::CREATED, "create OperationDefinition");
+
+    app.state.operation_registry.load_definitions().await?;
+    Ok(())
+}
+
+async fn create_observation(
+    app: &TestApp,
+    patient_id: &str,
+    code: &str,
+    category: &str,
+    effective: &str,
+) -> anyhow::Result<String> {
+    let obs = json!({
+        "resourceType": "Observation",
+        "status": "final",
+        "category": [{"coding": [{
+            "system": "http://terminology.hl7.org/CodeSystem/observation-category",
+            "code": category
+        }]}],
+        "code": {"coding": [{"system": "http://loinc.org", "code": code}]},
+        "subject": {"reference": format!("Patient/{}", patient_id)},
+        "effectiveDateTime": effective
+    });
+    let (status, _headers, body) = app
+        .request(Method::POST, "/fhir/Observation", Some(to_json_body(&obs)?))
+        .await?;
+    assert_status(status, StatusCode::CREATED, "create Observation");
+    Ok(parse_json(&body)?["id"].as_str().unwrap().to_string())
+}
+
+fn ids(bundle: &Value) -> Vec<String> {
+    entries(bundle)
+        .iter()
+        .map(|e| e["resource"]["id"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn lastn_returns_latest_max_per_code() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            setup_lastn(app).await?;
+
+            let patient = json!({"resourceType": "Patient", "name": [{"family": "LastN"}]});
+            let (status, _headers, body) = app
+                .request(Method::POST, "/fhir/Patient", Some(to_json_body(&patient)?))
+                .await?;
+            assert_status(status, StatusCode::CREATED, "create Patient");
+            let patient_id = parse_json(&body)?["id"].as_str().unwrap().to_string();
+
+            // Three heart-rate readings (vital signs) and two glucose results (laboratory)
+            let hr_old =
+                create_observation(app, &patient_id, "8867-4", "vital-signs", "2024-01-01").await?;
+            let hr_mid =
+                create_observation(app, &patient_id, "8867-4", "vital-signs", "2024-02-01").await?;
+            let hr_new =
+                create_observation(app, &patient_id, "8867-4", "vital-signs", "2024-03-01").await?;
+            let glu_old =
+                create_observation(app, &patient_id, "2339-0", "laboratory", "2023-06-01").await?;
+            let glu_new =
+                create_observation(app, &patient_id, "2339-0", "laboratory", "2023-07-01").await?;
+
+            // max defaults to 1: the latest of each code
+            let (status, _headers, body) = app
+                .request(
+                    Method::GET,
+                    &format!("/fhir/Observation/$lastn?patient=Patient/{}", patient_id),
+                    None,
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "$lastn");
+            let bundle = parse_json(&body)?;
+            assert_eq!(bundle["resourceType"], "Bundle");
+            assert_eq!(bundle["type"], "searchset");
+            let mut got = ids(&bundle);
+            got.sort();
+            let mut expected = vec![hr_new.clone(), glu_new.clone()];
+            expected.sort();
+            assert_eq!(got, expected);
+
+            // max=2: the two latest of each code, newest first within a code
+            let (status, _headers, body) = app
+                .request(
+                    Method::GET,
+                    &format!(
+                        "/fhir/Observation/$lastn?patient=Patient/{}&max=2",
+                        patient_id
+                    ),
+                    None,
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "$lastn max=2");
+            let got = ids(&parse_json(&body)?);
+            assert_eq!(got.len(), 4, "expected 4 observations, got {:?}", got);
+            assert!(!got.contains(&hr_old), "oldest heart rate must be excluded");
+            let pos = |id: &String| got.iter().position(|g| g == id).unwrap();
+            assert!(pos(&hr_new) < pos(&hr_mid));
+            assert!(pos(&glu_new) < pos(&glu_old));
+
+            // category narrows the groups
+            let (status, _headers, body) = app
+                .request(
+                    Method::GET,
+                    &format!(
+                        "/fhir/Observation/$lastn?patient=Patient/{}&max=5&category=laboratory",
+                        patient_id
+                    ),
+                    None,
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "$lastn category");
+            let mut got = ids(&parse_json(&body)?);
+            got.sort();
+            let mut expected = vec![glu_old, glu_new];
+            expected.sort();
+            assert_eq!(got, expected);
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn lastn_rejects_non_positive_max() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            setup_lastn(app).await?;
+
+            let (status, _headers, _body) = app
+                .request(Method::GET, "/fhir/Observation/$lastn?max=0", None)
+                .await?;
+            assert_status(status, StatusCode::BAD_REQUEST, "$lastn max=0");
+
+            Ok(())
+        })
+    })
+    .await
+}
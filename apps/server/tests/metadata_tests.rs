@@ -0,0 +1,77 @@
+#![allow(unused)]
+//! Integration tests for per-resource-type search parameter discovery (`/metadata/{type}`).
+
+mod support;
+
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+use support::*;
+
+#[tokio::test]
+async fn resource_search_parameters_include_custom_parameter() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let sp = json!({
+                "resourceType": "SearchParameter",
+                "url": "http://example.org/fhir/SearchParameter/patient-nickname",
+                "name": "nickname",
+                "status": "active",
+                "description": "Patient nickname",
+                "code": "nickname",
+                "base": ["Patient"],
+                "type": "string",
+                "expression": "Patient.name.where(use = 'nickname').given"
+            });
+            let (status, _headers, _body) = app
+                .request(
+                    Method::POST,
+                    "/fhir/SearchParameter",
+                    Some(to_json_body(&sp)?),
+                )
+                .await?;
+            assert_status(status, StatusCode::CREATED, "create SearchParameter");
+
+            let (status, _headers, body) = app
+                .request(Method::GET, "/fhir/metadata/Patient", None)
+                .await?;
+            assert_status(status, StatusCode::OK, "patient search parameters");
+
+            let bundle: Value = serde_json::from_slice(&body)?;
+            assert_eq!(bundle["resourceType"], "Bundle");
+            assert_eq!(bundle["type"], "collection");
+
+            let entries = bundle["entry"].as_array().cloned().unwrap_or_default();
+            let nickname = entries
+                .iter()
+                .map(|e| &e["resource"])
+                .find(|r| r["code"] == "nickname")
+                .expect("custom parameter listed for Patient");
+            assert_eq!(nickname["type"], "string");
+            assert_eq!(nickname["base"], json!(["Patient"]));
+            assert_eq!(
+                nickname["expression"],
+                "Patient.name.where(use = 'nickname').given"
+            );
+
+            // Not listed for other resource types
+            let (status, _headers, body) = app
+                .request(Method::GET, "/fhir/metadata/Observation", None)
+                .await?;
+            assert_status(status, StatusCode::OK, "observation search parameters");
+            let bundle: Value = serde_json::from_slice(&body)?;
+            let listed = bundle["entry"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|e| e["resource"]["code"] == "nickname");
+            assert!(!listed);
+
+            let (status, _headers, _body) = app
+                .request(Method::GET, "/fhir/metadata/NotAType", None)
+                .await?;
+            assert_status(status, StatusCode::BAD_REQUEST, "unknown resource type");
+            Ok(())
+        })
+    })
+    .await
+}
@@ -1,6 +1,9 @@
 pub mod chaining;
 pub mod includes;
+pub mod named_queries;
 pub mod paging;
 pub mod parameters;
+pub mod post_search;
+pub mod sorting;
 // pub mod modifiers;
 // pub mod result_params;
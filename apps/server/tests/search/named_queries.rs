@@ -0,0 +1,96 @@
+//! Named query (`_query`) tests
+//!
+//! Named queries are configured in `fhir.search.named_queries`; `_query=<name>` runs the
+//! query's criteria with its parameters substituted from the request.
+
+use crate::support::*;
+use axum::http::{Method, StatusCode};
+use ferrum::config::NamedQueryConfig;
+use serde_json::Value;
+
+fn by_gender_and_family() -> NamedQueryConfig {
+    NamedQueryConfig {
+        name: "female-by-family".to_string(),
+        resource_type: Some("Patient".to_string()),
+        criteria: "gender=female&family={family}".to_string(),
+        parameters: vec!["family".to_string()],
+    }
+}
+
+async fn create_patient(app: &TestApp, family: &str, gender: &str) -> anyhow::Result<String> {
+    let patient = PatientBuilder::new().family(family).gender(gender).build();
+    let (status, _headers, body) = app
+        .request(Method::POST, "/fhir/Patient", Some(to_json_body(&patient)?))
+        .await?;
+    assert_status(status, StatusCode::CREATED, "create patient");
+    let created: Value = serde_json::from_slice(&body)?;
+    Ok(created["id"].as_str().unwrap_or_default().to_string())
+}
+
+#[tokio::test]
+async fn named_query_substitutes_parameters() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |config| config.fhir.search.named_queries = vec![by_gender_and_family()],
+        |app| {
+            Box::pin(async move {
+                register_search_parameter(
+                    &app.state.db_pool,
+                    "family",
+                    "Patient",
+                    "string",
+                    "Patient.name.family",
+                    &[],
+                )
+                .await?;
+                register_search_parameter(
+                    &app.state.db_pool,
+                    "gender",
+                    "Patient",
+                    "token",
+                    "Patient.gender",
+                    &[],
+                )
+                .await?;
+
+                let expected = create_patient(app, "Smith", "female").await?;
+                create_patient(app, "Smith", "male").await?;
+                create_patient(app, "Jones", "female").await?;
+
+                let (status, _headers, body) = app
+                    .request(
+                        Method::GET,
+                        "/fhir/Patient?_query=female-by-family&family=Smith",
+                        None,
+                    )
+                    .await?;
+                assert_status(status, StatusCode::OK, "named query search");
+                let bundle: Value = serde_json::from_slice(&body)?;
+                assert_bundle_type(&bundle, "searchset")?;
+                assert_eq!(extract_resource_ids(&bundle, "Patient")?, vec![expected]);
+
+                // Missing parameter and unknown query names are rejected
+                let (status, _headers, _body) = app
+                    .request(Method::GET, "/fhir/Patient?_query=female-by-family", None)
+                    .await?;
+                assert_status(status, StatusCode::BAD_REQUEST, "missing parameter");
+                let (status, _headers, _body) = app
+                    .request(Method::GET, "/fhir/Patient?_query=unknown", None)
+                    .await?;
+                assert_status(status, StatusCode::BAD_REQUEST, "unknown named query");
+
+                // Queries are restricted to their resource type
+                let (status, _headers, _body) = app
+                    .request(
+                        Method::GET,
+                        "/fhir/Observation?_query=female-by-family&family=Smith",
+                        None,
+                    )
+                    .await?;
+                assert_status(status, StatusCode::BAD_REQUEST, "wrong resource type");
+
+                Ok(())
+            })
+        },
+    )
+    .await
+}
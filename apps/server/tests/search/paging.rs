@@ -131,3 +131,156 @@ async fn paging_links_include_prev_first_last() -> anyhow::Result<()> {
     })
     .await
 }
+
+#[tokio::test]
+async fn count_within_max_is_applied_as_requested() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |cfg| cfg.fhir.search.max_count = 2,
+        |app| {
+            Box::pin(async move {
+                create_patient(app, "Alpha").await?;
+                create_patient(app, "Beta").await?;
+                create_patient(app, "Gamma").await?;
+
+                let (status, _headers, body) = app
+                    .request(Method::GET, "/fhir/Patient?_count=2", None)
+                    .await?;
+                assert_status(status, StatusCode::OK, "_count within max");
+                let bundle: Value = serde_json::from_slice(&body)?;
+                let ids = extract_resource_ids_by_mode(&bundle, "Patient", "match")?;
+                assert_eq!(ids.len(), 2);
+
+                let self_url = link_url(&bundle, "self").context("self link")?;
+                assert_eq!(query_param(&self_url, "_count").as_deref(), Some("2"));
+
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn count_over_max_is_clamped() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |cfg| cfg.fhir.search.max_count = 2,
+        |app| {
+            Box::pin(async move {
+                create_patient(app, "Alpha").await?;
+                create_patient(app, "Beta").await?;
+                create_patient(app, "Gamma").await?;
+
+                let (status, _headers, body) = app
+                    .request(Method::GET, "/fhir/Patient?_count=500", None)
+                    .await?;
+                assert_status(status, StatusCode::OK, "_count over max");
+                let bundle: Value = serde_json::from_slice(&body)?;
+                let ids = extract_resource_ids_by_mode(&bundle, "Patient", "match")?;
+                assert_eq!(ids.len(), 2, "page size clamped to max_count");
+
+                let self_url = link_url(&bundle, "self").context("self link")?;
+                assert_eq!(query_param(&self_url, "_count").as_deref(), Some("2"));
+                let next_url = link_url(&bundle, "next").context("next link")?;
+                assert_eq!(query_param(&next_url, "_count").as_deref(), Some("2"));
+
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn missing_count_uses_configured_default() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |cfg| cfg.fhir.search.default_count = 2,
+        |app| {
+            Box::pin(async move {
+                create_patient(app, "Alpha").await?;
+                create_patient(app, "Beta").await?;
+                create_patient(app, "Gamma").await?;
+
+                let (status, _headers, body) =
+                    app.request(Method::GET, "/fhir/Patient", None).await?;
+                assert_status(status, StatusCode::OK, "search without _count");
+                let bundle: Value = serde_json::from_slice(&body)?;
+                let ids = extract_resource_ids_by_mode(&bundle, "Patient", "match")?;
+                assert_eq!(ids.len(), 2, "page size defaults to default_count");
+
+                let self_url = link_url(&bundle, "self").context("self link")?;
+                assert_eq!(query_param(&self_url, "_count").as_deref(), Some("2"));
+                let next_url = link_url(&bundle, "next").context("next link")?;
+                assert_eq!(query_param(&next_url, "_count").as_deref(), Some("2"));
+
+                let (status, _headers, body) = app
+                    .request(Method::GET, &path_and_query(&next_url)?, None)
+                    .await?;
+                assert_status(status, StatusCode::OK, "next page");
+                let bundle: Value = serde_json::from_slice(&body)?;
+                let next_ids = extract_resource_ids_by_mode(&bundle, "Patient", "match")?;
+                assert_eq!(next_ids.len(), 1);
+                assert!(!ids.contains(&next_ids[0]));
+                assert!(link_url(&bundle, "next").is_none(), "last page has no next");
+
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn default_count_over_max_is_clamped() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |cfg| {
+            cfg.fhir.search.default_count = 50;
+            cfg.fhir.search.max_count = 2;
+        },
+        |app| {
+            Box::pin(async move {
+                create_patient(app, "Alpha").await?;
+                create_patient(app, "Beta").await?;
+                create_patient(app, "Gamma").await?;
+
+                let (status, _headers, body) =
+                    app.request(Method::GET, "/fhir/Patient", None).await?;
+                assert_status(status, StatusCode::OK, "search without _count");
+                let bundle: Value = serde_json::from_slice(&body)?;
+                let ids = extract_resource_ids_by_mode(&bundle, "Patient", "match")?;
+                assert_eq!(ids.len(), 2, "default page size clamped to max_count");
+
+                let self_url = link_url(&bundle, "self").context("self link")?;
+                assert_eq!(query_param(&self_url, "_count").as_deref(), Some("2"));
+                assert!(link_url(&bundle, "next").is_some(), "more results remain");
+
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn invalid_count_is_rejected() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            for count in ["-1", "abc", "99999999999999999999999"] {
+                let (status, _headers, _body) = app
+                    .request(
+                        Method::GET,
+                        &format!("/fhir/Patient?_count={}", count),
+                        None,
+                    )
+                    .await?;
+                assert_status(
+                    status,
+                    StatusCode::BAD_REQUEST,
+                    &format!("_count={}", count),
+                );
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
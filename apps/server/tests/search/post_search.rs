@@ -0,0 +1,123 @@
+//! Search via POST (`_search`)
+//!
+//! `POST [base]/[type]/_search` with an `application/x-www-form-urlencoded` body must behave
+//! exactly like the equivalent GET, with parameters allowed in both the URL and the body.
+
+use crate::support::*;
+use axum::body::Bytes;
+use axum::http::{Method, StatusCode};
+use serde_json::Value;
+
+async fn create_patient(app: &TestApp, family: &str, gender: &str) -> anyhow::Result<String> {
+    let patient = PatientBuilder::new().family(family).gender(gender).build();
+    let (status, _headers, body) = app
+        .request(Method::POST, "/fhir/Patient", Some(to_json_body(&patient)?))
+        .await?;
+    assert_status(status, StatusCode::CREATED, "create patient");
+    let created: Value = serde_json::from_slice(&body)?;
+    Ok(created["id"].as_str().unwrap_or_default().to_string())
+}
+
+async fn post_search(app: &TestApp, path: &str, form: &'static str) -> anyhow::Result<Value> {
+    let (status, _headers, body) = app
+        .request_with_extra_headers(
+            Method::POST,
+            path,
+            Some(Bytes::from_static(form.as_bytes())),
+            &[("content-type", "application/x-www-form-urlencoded")],
+        )
+        .await?;
+    assert_status(status, StatusCode::OK, "POST _search");
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[tokio::test]
+async fn post_search_matches_get_search() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            register_search_parameter(
+                &app.state.db_pool,
+                "family",
+                "Patient",
+                "string",
+                "Patient.name.family",
+                &[],
+            )
+            .await?;
+            register_search_parameter(
+                &app.state.db_pool,
+                "gender",
+                "Patient",
+                "token",
+                "Patient.gender",
+                &[],
+            )
+            .await?;
+
+            create_patient(app, "Smith", "female").await?;
+            create_patient(app, "Smith", "male").await?;
+            create_patient(app, "Jones", "female").await?;
+
+            let (status, _headers, body) = app
+                .request(
+                    Method::GET,
+                    "/fhir/Patient?family=Smith&gender=female",
+                    None,
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "GET search");
+            let get_bundle: Value = serde_json::from_slice(&body)?;
+            let mut expected = extract_resource_ids(&get_bundle, "Patient")?;
+            expected.sort();
+            assert_eq!(expected.len(), 1);
+
+            let post_bundle =
+                post_search(app, "/fhir/Patient/_search", "family=Smith&gender=female").await?;
+            assert_bundle_type(&post_bundle, "searchset")?;
+            let mut ids = extract_resource_ids(&post_bundle, "Patient")?;
+            ids.sort();
+            assert_eq!(ids, expected);
+            assert_eq!(post_bundle["total"], get_bundle["total"]);
+
+            // Parameters in the URL and the body are combined
+            let post_bundle =
+                post_search(app, "/fhir/Patient/_search?family=Smith", "gender=female").await?;
+            let mut ids = extract_resource_ids(&post_bundle, "Patient")?;
+            ids.sort();
+            assert_eq!(ids, expected);
+
+            // Form encoding: '+' is a space and percent-escapes are decoded
+            create_patient(app, "van Dijk", "female").await?;
+            let post_bundle =
+                post_search(app, "/fhir/Patient/_search", "family=van+D%69jk").await?;
+            assert_eq!(extract_resource_ids(&post_bundle, "Patient")?.len(), 1);
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn post_search_rejects_non_form_bodies() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let (status, _headers, _body) = app
+                .request_with_extra_headers(
+                    Method::POST,
+                    "/fhir/Patient/_search",
+                    Some(Bytes::from_static(b"{\"family\": \"Smith\"}")),
+                    &[("content-type", "application/json")],
+                )
+                .await?;
+            assert_status(
+                status,
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "POST _search with JSON body",
+            );
+
+            Ok(())
+        })
+    })
+    .await
+}
@@ -0,0 +1,113 @@
+//! `_sort` by the special `_lastUpdated` and `_id` keys, alone and combined with parameter sorts.
+
+use crate::support::*;
+use axum::http::{Method, StatusCode};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Create a Patient with a client-assigned id, waiting briefly so `lastUpdated` strictly increases.
+async fn put_patient(app: &TestApp, id: &str, family: &str) -> anyhow::Result<()> {
+    let patient = PatientBuilder::new().id(id).family(family).build();
+    let (status, _headers, _body) = app
+        .request(
+            Method::PUT,
+            &format!("/fhir/Patient/{id}"),
+            Some(to_json_body(&patient)?),
+        )
+        .await?;
+    assert_status(status, StatusCode::CREATED, "create patient");
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    Ok(())
+}
+
+async fn sorted_ids(app: &TestApp, query: &str) -> anyhow::Result<Vec<String>> {
+    let (status, _headers, body) = app
+        .request(Method::GET, &format!("/fhir/Patient?{query}"), None)
+        .await?;
+    assert_status(status, StatusCode::OK, query);
+    let bundle: Value = serde_json::from_slice(&body)?;
+    extract_resource_ids(&bundle, "Patient")
+}
+
+#[tokio::test]
+async fn sort_by_last_updated_descending_returns_newest_first() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            put_patient(app, "sort-lu-1", "One").await?;
+            put_patient(app, "sort-lu-2", "Two").await?;
+            put_patient(app, "sort-lu-3", "Three").await?;
+
+            assert_eq!(
+                sorted_ids(app, "_sort=-_lastUpdated").await?,
+                vec!["sort-lu-3", "sort-lu-2", "sort-lu-1"]
+            );
+            assert_eq!(
+                sorted_ids(app, "_sort=_lastUpdated").await?,
+                vec!["sort-lu-1", "sort-lu-2", "sort-lu-3"]
+            );
+
+            // Updating a resource moves it to the front of a newest-first sort
+            put_patient(app, "sort-lu-1", "One").await?;
+            assert_eq!(
+                sorted_ids(app, "_sort=-_lastUpdated").await?,
+                vec!["sort-lu-1", "sort-lu-3", "sort-lu-2"]
+            );
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn sort_by_id_returns_id_order() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            put_patient(app, "sort-id-b", "Bravo").await?;
+            put_patient(app, "sort-id-c", "Charlie").await?;
+            put_patient(app, "sort-id-a", "Alpha").await?;
+
+            assert_eq!(
+                sorted_ids(app, "_sort=_id").await?,
+                vec!["sort-id-a", "sort-id-b", "sort-id-c"]
+            );
+            assert_eq!(
+                sorted_ids(app, "_sort=-_id").await?,
+                vec!["sort-id-c", "sort-id-b", "sort-id-a"]
+            );
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn special_sorts_combine_with_parameter_sorts() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            register_search_parameter(
+                &app.state.db_pool,
+                "family",
+                "Patient",
+                "string",
+                "Patient.name.family",
+                &[],
+            )
+            .await?;
+
+            put_patient(app, "sort-mix-1", "Smith").await?;
+            put_patient(app, "sort-mix-2", "Adams").await?;
+            put_patient(app, "sort-mix-3", "Smith").await?;
+
+            assert_eq!(
+                sorted_ids(app, "_sort=family,-_lastUpdated").await?,
+                vec!["sort-mix-2", "sort-mix-3", "sort-mix-1"]
+            );
+            assert_eq!(
+                sorted_ids(app, "_sort=-family,_id").await?,
+                vec!["sort-mix-1", "sort-mix-3", "sort-mix-2"]
+            );
+            Ok(())
+        })
+    })
+    .await
+}
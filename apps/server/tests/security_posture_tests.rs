@@ -56,3 +56,149 @@ async fn cors_is_not_permissive_by_default() -> anyhow::Result<()> {
     })
     .await
 }
+
+fn configure_cors(config: &mut ferrum::config::Config) {
+    config.server.cors_origins = vec!["https://app.example".to_string()];
+    config.server.cors_methods = vec!["GET".to_string(), "POST".to_string()];
+    config.server.cors_headers = vec!["authorization".to_string(), "content-type".to_string()];
+    config.server.cors_allow_credentials = true;
+    config.server.cors_max_age_seconds = Some(600);
+}
+
+#[tokio::test]
+async fn cors_preflight_carries_configured_headers() -> anyhow::Result<()> {
+    with_test_app_with_config(configure_cors, |app| {
+        Box::pin(async move {
+            let (status, headers, _body) = app
+                .request_with_extra_headers(
+                    Method::OPTIONS,
+                    "/fhir/Patient",
+                    None,
+                    &[
+                        ("origin", "https://app.example"),
+                        ("access-control-request-method", "POST"),
+                        ("access-control-request-headers", "content-type"),
+                    ],
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "preflight");
+
+            let header = |name: &str| {
+                headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("")
+                    .to_string()
+            };
+            assert_eq!(header("access-control-allow-origin"), "https://app.example");
+            assert_eq!(header("access-control-allow-methods"), "GET,POST");
+            assert_eq!(
+                header("access-control-allow-headers"),
+                "authorization,content-type"
+            );
+            assert_eq!(header("access-control-allow-credentials"), "true");
+            assert_eq!(header("access-control-max-age"), "600");
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn cors_rejects_disallowed_origin() -> anyhow::Result<()> {
+    with_test_app_with_config(configure_cors, |app| {
+        Box::pin(async move {
+            let (_status, headers, _body) = app
+                .request_with_extra_headers(
+                    Method::OPTIONS,
+                    "/fhir/Patient",
+                    None,
+                    &[
+                        ("origin", "https://evil.example"),
+                        ("access-control-request-method", "POST"),
+                    ],
+                )
+                .await?;
+            assert!(
+                headers.get("access-control-allow-origin").is_none(),
+                "disallowed origin must not be granted access"
+            );
+            assert!(headers.get("access-control-allow-credentials").is_none());
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn rate_limit_returns_429_with_retry_after() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |cfg| {
+            cfg.server.rate_limit.enabled = true;
+            cfg.server.rate_limit.read.requests_per_minute = 1;
+            cfg.server.rate_limit.read.burst = 2;
+        },
+        |app| {
+            Box::pin(async move {
+                for _ in 0..2 {
+                    let (status, _headers, _body) =
+                        app.request(Method::GET, "/fhir/metadata", None).await?;
+                    assert_status(status, StatusCode::OK, "read under the limit");
+                }
+
+                let (status, headers, body) =
+                    app.request(Method::GET, "/fhir/metadata", None).await?;
+                assert_status(status, StatusCode::TOO_MANY_REQUESTS, "read over the limit");
+                let retry_after: u64 = headers
+                    .get("retry-after")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok())
+                    .expect("Retry-After header");
+                assert!(retry_after >= 1);
+                let outcome: serde_json::Value = serde_json::from_slice(&body)?;
+                assert_eq!(outcome["issue"][0]["code"], "throttled");
+
+                // Health checks are outside the FHIR API and never limited
+                let (status, _headers, _body) = app.request(Method::GET, "/health", None).await?;
+                assert_status(status, StatusCode::OK, "health");
+                Ok(())
+            })
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn oversized_request_body_is_rejected_with_413() -> anyhow::Result<()> {
+    with_test_app_with_config(
+        |cfg| {
+            cfg.server.max_request_body_size = 2048;
+        },
+        |app| {
+            Box::pin(async move {
+                let small = serde_json::json!({ "resourceType": "Patient", "active": true });
+                let (status, _headers, _body) = app
+                    .request(Method::POST, "/fhir/Patient", Some(to_json_body(&small)?))
+                    .await?;
+                assert_status(status, StatusCode::CREATED, "body under the limit");
+
+                let large = serde_json::json!({
+                    "resourceType": "Patient",
+                    "name": [{ "text": "x".repeat(4096) }]
+                });
+                let (status, _headers, body) = app
+                    .request(Method::POST, "/fhir/Patient", Some(to_json_body(&large)?))
+                    .await?;
+                assert_status(status, StatusCode::PAYLOAD_TOO_LARGE, "body over the limit");
+                let outcome: serde_json::Value = serde_json::from_slice(&body)?;
+                assert_eq!(outcome["resourceType"], "OperationOutcome");
+                assert!(outcome["issue"][0]["diagnostics"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .contains("maximum of 2048 bytes"));
+                Ok(())
+            })
+        },
+    )
+    .await
+}
@@ -256,6 +256,89 @@ async fn transaction_failure_records_failed_status() -> anyhow::Result<()> {
     .await
 }
 
+#[tokio::test]
+async fn validate_only_transaction_reports_success_without_persisting() -> anyhow::Result<()> {
+    with_test_app(|app| {
+        Box::pin(async move {
+            let bundle = json!({
+                "resourceType": "Bundle",
+                "type": "transaction",
+                "entry": [
+                    {
+                        "fullUrl": "urn:uuid:aaaa-bbbb",
+                        "request": { "method": "POST", "url": "Patient" },
+                        "resource": minimal_patient()
+                    },
+                    {
+                        "request": { "method": "POST", "url": "Observation" },
+                        "resource": {
+                            "resourceType": "Observation",
+                            "status": "final",
+                            "code": { "text": "test" },
+                            "subject": { "reference": "urn:uuid:aaaa-bbbb" }
+                        }
+                    }
+                ]
+            });
+
+            let (status, _headers, body) = app
+                .request_with_extra_headers(
+                    Method::POST,
+                    "/fhir",
+                    Some(to_json_body(&bundle)?),
+                    &[("x-validate-only", "true")],
+                )
+                .await?;
+            assert_status(status, StatusCode::OK, "validate-only transaction");
+
+            let response: serde_json::Value = serde_json::from_slice(&body)?;
+            assert_eq!(response["type"], "transaction-response");
+            let entries = response["entry"].as_array().unwrap();
+            assert_eq!(entries.len(), 2);
+            for entry in entries {
+                assert_eq!(
+                    status_code_prefix(entry["response"]["status"].as_str().unwrap()),
+                    "201"
+                );
+            }
+            let patient_id = entries[0]["resource"]["id"].as_str().unwrap();
+            assert_eq!(
+                entries[1]["resource"]["subject"]["reference"],
+                format!("Patient/{}", patient_id)
+            );
+
+            let resource_count: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM resources WHERE resource_type IN ('Patient', 'Observation')",
+            )
+            .fetch_one(&app.state.db_pool)
+            .await?;
+            assert_eq!(resource_count, 0, "dry run must not persist resources");
+
+            let tracked: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM fhir_transactions")
+                .fetch_one(&app.state.db_pool)
+                .await?;
+            assert_eq!(tracked, 0, "dry run must not be tracked");
+
+            let (status, _headers, _body) = app
+                .request_with_extra_headers(
+                    Method::POST,
+                    "/fhir",
+                    Some(to_json_body(&json!({
+                        "resourceType": "Bundle",
+                        "type": "batch",
+                        "entry": []
+                    }))?),
+                    &[("x-validate-only", "true")],
+                )
+                .await?;
+            assert_status(status, StatusCode::BAD_REQUEST, "validate-only batch");
+
+            Ok(())
+        })
+    })
+    .await
+}
+
 #[tokio::test]
 async fn empty_batch_records_tracking() -> anyhow::Result<()> {
     with_test_app(|app| {
@@ -25,6 +25,11 @@ pub struct GeneratorConfig {
     pub generate_serde: bool,
     /// Custom module path prefix
     pub module_prefix: Option<String>,
+    /// Whether to generate `_field` companions carrying primitive `id`/`extension`
+    pub primitive_extensions: bool,
+    /// Whether repeating elements are plain `Vec`s (empty when absent) instead of
+    /// `Option<Vec<_>>`. This changes the type of every array field.
+    pub plain_vecs: bool,
 }
 
 impl Default for GeneratorConfig {
@@ -33,6 +38,8 @@ impl Default for GeneratorConfig {
             generate_docs: true,
             generate_serde: true,
             module_prefix: None,
+            primitive_extensions: false,
+            plain_vecs: false,
         }
     }
 }
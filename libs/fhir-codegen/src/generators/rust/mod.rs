@@ -3,7 +3,7 @@
 mod types;
 
 use crate::generators::{Generator, GeneratorConfig};
-use crate::ir::{TypeDefinition, TypeRegistry};
+use crate::ir::{Property, TypeDefinition, TypeRegistry};
 use anyhow::Result;
 use heck::ToSnakeCase;
 use std::collections::HashMap;
@@ -64,6 +64,27 @@ impl Generator for RustGenerator {
     }
 }
 
+/// Whether a type or any of its backbone elements has primitive-typed properties
+fn has_primitive_properties(type_def: &TypeDefinition) -> bool {
+    type_def.properties.iter().any(has_primitive_type)
+        || type_def
+            .backbone_elements
+            .iter()
+            .any(|b| b.properties.iter().any(has_primitive_type))
+}
+
+/// Whether a property, or any alternative of a choice property, is primitive-typed
+fn has_primitive_type(property: &Property) -> bool {
+    if property.is_choice() {
+        property
+            .choice_alternatives()
+            .iter()
+            .any(Property::is_primitive)
+    } else {
+        property.is_primitive()
+    }
+}
+
 impl RustGenerator {
     /// Convert a type name to a module name (snake_case)
     fn get_module_name(&self, type_name: &str) -> String {
@@ -132,6 +153,8 @@ impl RustGenerator {
         // Import primitives if needed
         if needs_primitives {
             code.push_str("use super::primitives::*;\n");
+        } else if self.config.primitive_extensions && has_primitive_properties(type_def) {
+            code.push_str("use super::primitives::PrimitiveElement;\n");
         }
 
         // Import each complex dependency from its own module
@@ -204,8 +227,21 @@ impl RustGenerator {
 
         code.push_str("//! FHIR Primitive Types\n\n");
 
+        let mut imports = String::new();
         if self.config.generate_serde {
-            code.push_str("use serde::{Deserialize, Serialize};\n\n");
+            imports.push_str("use serde::{Deserialize, Serialize};\n");
+        }
+        if self.config.primitive_extensions && registry.get_type_by_name("Extension").is_some() {
+            imports.push_str("use super::extension::Extension;\n");
+        }
+        if !imports.is_empty() {
+            code.push_str(&imports);
+            code.push('\n');
+        }
+
+        if self.config.primitive_extensions {
+            code.push_str(&types::generate_primitive_element(registry, &self.config));
+            code.push_str("\n\n");
         }
 
         for type_def in registry.primitive_types() {
@@ -228,7 +264,7 @@ impl RustGenerator {
         let mut complex_types: Vec<_> = registry.complex_types().collect();
         complex_types.sort_by(|a, b| a.name.cmp(&b.name));
 
-        for type_def in complex_types {
+        for type_def in &complex_types {
             let module_name = type_def.name.to_snake_case();
             code.push_str(&format!("pub mod {};\n", module_name));
         }
@@ -240,7 +276,7 @@ impl RustGenerator {
             .collect();
         resources.sort_by(|a, b| a.name.cmp(&b.name));
 
-        for type_def in resources {
+        for type_def in &resources {
             let module_name = type_def.name.to_snake_case();
             code.push_str(&format!("pub mod {};\n", module_name));
         }
@@ -248,18 +284,163 @@ impl RustGenerator {
         code.push_str("\n// Re-export all types\n");
         code.push_str("pub use primitives::*;\n");
 
-        for type_def in registry.complex_types() {
+        for type_def in complex_types.iter().chain(&resources) {
             let module_name = type_def.name.to_snake_case();
             code.push_str(&format!("pub use {}::*;\n", module_name));
         }
 
-        for type_def in registry.resource_types() {
-            if !type_def.is_abstract {
-                let module_name = type_def.name.to_snake_case();
-                code.push_str(&format!("pub use {}::*;\n", module_name));
-            }
+        code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Cardinality, Property, PropertyType, TypeKind};
+
+    fn property(name: &str, type_code: &str, cardinality: Cardinality) -> Property {
+        Property {
+            name: name.to_string(),
+            path: format!("Patient.{}", name),
+            description: None,
+            types: vec![PropertyType {
+                code: type_code.to_string(),
+                profile: None,
+                target_profiles: Vec::new(),
+            }],
+            cardinality,
+            is_required: false,
+            is_modifier: false,
+            must_support: false,
         }
+    }
 
-        code
+    fn choice_property(name: &str, type_codes: &[&str]) -> Property {
+        let mut choice = property(name, type_codes[0], Cardinality::new(0, Some(1)));
+        choice.types = type_codes
+            .iter()
+            .map(|code| PropertyType {
+                code: code.to_string(),
+                profile: None,
+                target_profiles: Vec::new(),
+            })
+            .collect();
+        choice
+    }
+
+    fn type_definition(name: &str, kind: TypeKind, properties: Vec<Property>) -> TypeDefinition {
+        TypeDefinition {
+            name: name.to_string(),
+            url: None,
+            description: None,
+            kind,
+            base_type: None,
+            properties,
+            is_abstract: false,
+            backbone_elements: Vec::new(),
+            parent_type: None,
+        }
+    }
+
+    fn patient_registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::new();
+        registry.add_type(
+            "Extension".to_string(),
+            type_definition(
+                "Extension",
+                TypeKind::ComplexType,
+                vec![property("url", "uri", Cardinality::new(1, Some(1)))],
+            ),
+        );
+        registry.add_type(
+            "Patient".to_string(),
+            type_definition(
+                "Patient",
+                TypeKind::Resource,
+                vec![
+                    property("birthDate", "date", Cardinality::new(0, Some(1))),
+                    choice_property("deceased[x]", &["boolean", "dateTime"]),
+                    property("extension", "Extension", Cardinality::new(0, None)),
+                ],
+            ),
+        );
+        registry
+    }
+
+    fn generate_with(config: GeneratorConfig) -> HashMap<String, String> {
+        let generator = RustGenerator::new(config);
+        generator.generate(&patient_registry()).unwrap().modules
+    }
+
+    fn generate(primitive_extensions: bool) -> HashMap<String, String> {
+        generate_with(GeneratorConfig {
+            primitive_extensions,
+            ..GeneratorConfig::default()
+        })
+    }
+
+    #[test]
+    fn primitive_companions_are_generated_when_enabled() {
+        let modules = generate(true);
+
+        let patient = &modules["patient.rs"];
+        assert!(patient.contains("use super::primitives::PrimitiveElement;"));
+        assert!(patient.contains("    pub birth_date: Option<String>,\n"));
+        assert!(patient.contains(
+            "    #[serde(rename = \"_birthDate\", skip_serializing_if = \"Option::is_none\")]\n    pub birth_date_ext: Option<PrimitiveElement>,\n"
+        ));
+        // Complex-typed properties have no companion
+        assert!(!patient.contains("extension_ext"));
+
+        // Each alternative of a choice element is its own field with its own companion
+        assert!(!patient.contains("deceased_x"));
+        assert!(patient.contains("    pub deceased_boolean: Option<bool>,\n"));
+        assert!(patient.contains(
+            "    #[serde(rename = \"_deceasedBoolean\", skip_serializing_if = \"Option::is_none\")]\n    pub deceased_boolean_ext: Option<PrimitiveElement>,\n"
+        ));
+        assert!(patient.contains("    pub deceased_date_time: Option<String>,\n"));
+        assert!(patient.contains("    pub deceased_date_time_ext: Option<PrimitiveElement>,\n"));
+
+        // Extension.url is a required primitive; its companion is still optional
+        assert!(modules["extension.rs"].contains("    pub url_ext: Option<PrimitiveElement>,\n"));
+
+        let primitives = &modules["primitives.rs"];
+        assert!(primitives.contains("use super::extension::Extension;"));
+        assert!(primitives.contains("pub struct PrimitiveElement {"));
+        assert!(primitives.contains("    pub extension: Option<Vec<Extension>>,"));
+    }
+
+    #[test]
+    fn primitive_companions_are_omitted_by_default() {
+        let modules = generate(false);
+
+        assert!(!modules["patient.rs"].contains("birth_date_ext"));
+        assert!(!modules["primitives.rs"].contains("PrimitiveElement"));
+    }
+
+    #[test]
+    fn arrays_are_optional_vecs_by_default() {
+        let patient = &generate(true)["patient.rs"];
+        assert!(patient.contains(
+            "    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub extension: Option<Vec<Extension>>,\n"
+        ));
+    }
+
+    #[test]
+    fn plain_vecs_skip_empty_arrays() {
+        let modules = generate_with(GeneratorConfig {
+            primitive_extensions: true,
+            plain_vecs: true,
+            ..GeneratorConfig::default()
+        });
+
+        let patient = &modules["patient.rs"];
+        assert!(patient.contains(
+            "    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub birth_date: Option<String>,\n"
+        ));
+        assert!(patient.contains(
+            "    #[serde(default, skip_serializing_if = \"Vec::is_empty\")]\n    pub extension: Vec<Extension>,\n"
+        ));
+        assert!(modules["primitives.rs"].contains("    pub extension: Vec<Extension>,"));
     }
 }
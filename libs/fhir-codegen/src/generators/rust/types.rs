@@ -5,6 +5,12 @@ use crate::ir::{Property, PropertyType, TypeDefinition, TypeKind, TypeRegistry};
 use heck::ToSnakeCase;
 use ferrum_models::common::structure_definition::StructureDefinitionKind;
 
+/// Serde attribute arguments omitting an absent optional field
+const SKIP_NONE: &str = "skip_serializing_if = \"Option::is_none\"";
+
+/// Serde attribute arguments omitting an empty plain `Vec` field
+const SKIP_EMPTY_VEC: &str = "default, skip_serializing_if = \"Vec::is_empty\"";
+
 /// Generate a Rust struct for a type definition
 pub fn generate_struct(
     type_def: &TypeDefinition,
@@ -77,6 +83,15 @@ fn generate_field(
     registry: &TypeRegistry,
     config: &GeneratorConfig,
 ) -> String {
+    // Choice elements get one optional field per allowed type, named like their JSON keys
+    if property.is_choice() && !property.types.is_empty() {
+        return property
+            .choice_alternatives()
+            .iter()
+            .map(|alternative| generate_field(alternative, registry, config))
+            .collect();
+    }
+
     let mut code = String::new();
 
     // Documentation
@@ -96,9 +111,11 @@ fn generate_field(
 
     // Serde attributes
     if config.generate_serde {
-        // Handle optional fields
-        if property.cardinality.is_optional() {
-            code.push_str("    #[serde(skip_serializing_if = \"Option::is_none\")]\n");
+        // Omit empty arrays and absent values so output is minimal FHIR JSON
+        if config.plain_vecs && property.cardinality.is_array() {
+            code.push_str(&format!("    #[serde({})]\n", SKIP_EMPTY_VEC));
+        } else if property.cardinality.is_optional() {
+            code.push_str(&format!("    #[serde({})]\n", SKIP_NONE));
         }
 
         // Handle special renames (e.g., 'type' is a Rust keyword)
@@ -111,15 +128,106 @@ fn generate_field(
     let field_name = sanitize_field_name(&property.name);
 
     // Field type
-    let field_type = generate_field_type(property, registry);
+    let field_type = generate_field_type(property, registry, config);
 
     code.push_str(&format!("    pub {}: {},\n", field_name, field_type));
 
+    if config.primitive_extensions && property.is_primitive() {
+        code.push_str(&generate_primitive_companion(property, config));
+    }
+
+    code
+}
+
+/// Generate the `_field` companion holding a primitive's `id`/`extension`
+fn generate_primitive_companion(property: &Property, config: &GeneratorConfig) -> String {
+    let mut code = String::new();
+
+    if config.generate_docs {
+        code.push_str(&format!(
+            "    /// Extensions and id for `{}`\n",
+            property.name
+        ));
+    }
+
+    let plain_vec = config.plain_vecs && property.cardinality.is_array();
+
+    if config.generate_serde {
+        let skip = if plain_vec { SKIP_EMPTY_VEC } else { SKIP_NONE };
+        code.push_str(&format!(
+            "    #[serde(rename = \"_{}\", {})]\n",
+            property.name, skip
+        ));
+    }
+
+    // Repeating primitives align their companions by index, with `null` for gaps
+    let companion_type = if plain_vec {
+        "Vec<Option<PrimitiveElement>>"
+    } else if property.cardinality.is_array() {
+        "Option<Vec<Option<PrimitiveElement>>>"
+    } else {
+        "Option<PrimitiveElement>"
+    };
+
+    code.push_str(&format!(
+        "    pub {}_ext: {},\n",
+        property.name.to_snake_case(),
+        companion_type
+    ));
+
+    code
+}
+
+/// Generate the struct used for primitive `_field` companions
+pub fn generate_primitive_element(registry: &TypeRegistry, config: &GeneratorConfig) -> String {
+    let mut code = String::new();
+
+    if config.generate_docs {
+        code.push_str("/// Id and extensions of a primitive value (the JSON `_field` companion)\n");
+    }
+
+    code.push_str("#[derive(Debug, Clone, Default, PartialEq");
+    if config.generate_serde {
+        code.push_str(", Serialize, Deserialize");
+    }
+    code.push_str(")]\n");
+
+    let extension_type = if registry.get_type_by_name("Extension").is_some() {
+        "Extension"
+    } else {
+        "serde_json::Value"
+    };
+
+    code.push_str("pub struct PrimitiveElement {\n");
+    if config.generate_serde {
+        code.push_str("    #[serde(skip_serializing_if = \"Option::is_none\")]\n");
+    }
+    code.push_str("    pub id: Option<String>,\n");
+    if config.generate_serde {
+        let skip = if config.plain_vecs {
+            SKIP_EMPTY_VEC
+        } else {
+            SKIP_NONE
+        };
+        code.push_str(&format!("    #[serde({})]\n", skip));
+    }
+    let extension_field = if config.plain_vecs {
+        format!("Vec<{}>", extension_type)
+    } else {
+        format!("Option<Vec<{}>>", extension_type)
+    };
+    code.push_str(&format!("    pub extension: {},\n", extension_field));
+    code.push('}');
+
     code
 }
 
 /// Generate the Rust type for a property
-fn generate_field_type(property: &Property, registry: &TypeRegistry) -> String {
+fn generate_field_type(
+    property: &Property,
+    registry: &TypeRegistry,
+    config: &GeneratorConfig,
+) -> String {
     // Handle multiple types (use an enum or Box<dyn> in practice, simplified here)
     let base_type = if property.types.is_empty() {
         "serde_json::Value".to_string()
@@ -131,14 +239,15 @@ fn generate_field_type(property: &Property, registry: &TypeRegistry) -> String {
     };
 
     // Wrap in Vec if array
-    let base_type = if property.cardinality.is_array() {
+    let is_array = property.cardinality.is_array();
+    let base_type = if is_array {
         format!("Vec<{}>", base_type)
     } else {
         base_type
     };
 
-    // Wrap in Option if optional
-    if property.cardinality.is_optional() {
+    // Wrap in Option if optional; plain Vecs are empty when absent instead
+    if property.cardinality.is_optional() && !(config.plain_vecs && is_array) {
         format!("Option<{}>", base_type)
     } else {
         base_type
@@ -187,6 +187,48 @@ pub struct Property {
     pub must_support: bool,
 }
 
+impl Property {
+    /// Whether this property has a single FHIR primitive type
+    pub fn is_primitive(&self) -> bool {
+        self.types.len() == 1 && is_primitive_type(&self.types[0].code)
+    }
+
+    /// Whether this property is a choice element (e.g. `value[x]`)
+    pub fn is_choice(&self) -> bool {
+        self.name.ends_with("[x]")
+    }
+
+    /// The concrete properties of a choice element, one per allowed type
+    /// (`value[x]` -> `valueBoolean`, `valueQuantity`, ...)
+    pub fn choice_alternatives(&self) -> Vec<Property> {
+        let name = self.name.trim_end_matches("[x]");
+        let path = self.path.trim_end_matches("[x]");
+        self.types
+            .iter()
+            .map(|property_type| {
+                let suffix = capitalize_first(&property_type.code);
+                Property {
+                    name: format!("{}{}", name, suffix),
+                    path: format!("{}{}", path, suffix),
+                    types: vec![property_type.clone()],
+                    // At most one alternative is present
+                    cardinality: Cardinality::new(0, Some(1)),
+                    is_required: false,
+                    ..self.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+fn capitalize_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+    }
+}
+
 /// Type reference for a property
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PropertyType {
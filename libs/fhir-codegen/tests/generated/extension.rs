@@ -0,0 +1,28 @@
+//! Extension type definition
+//! Canonical URL: http://hl7.org/fhir/StructureDefinition/Extension
+
+use serde::{Deserialize, Serialize};
+use super::primitives::PrimitiveElement;
+
+/// Extension
+///
+/// Canonical URL: http://hl7.org/fhir/StructureDefinition/Extension
+/// Kind: ComplexType
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Extension {
+    pub url: String,
+    /// Extensions and id for `url`
+    #[serde(rename = "_url", skip_serializing_if = "Option::is_none")]
+    pub url_ext: Option<PrimitiveElement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_string: Option<String>,
+    /// Extensions and id for `valueString`
+    #[serde(rename = "_valueString", skip_serializing_if = "Option::is_none")]
+    pub value_string_ext: Option<PrimitiveElement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_boolean: Option<bool>,
+    /// Extensions and id for `valueBoolean`
+    #[serde(rename = "_valueBoolean", skip_serializing_if = "Option::is_none")]
+    pub value_boolean_ext: Option<PrimitiveElement>,
+}
\ No newline at end of file
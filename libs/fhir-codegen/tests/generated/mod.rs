@@ -0,0 +1,10 @@
+//! Generated FHIR data models
+
+pub mod primitives;
+pub mod extension;
+pub mod patient;
+
+// Re-export all types
+pub use primitives::*;
+pub use extension::*;
+pub use patient::*;
@@ -0,0 +1,42 @@
+//! Patient type definition
+//! Canonical URL: http://hl7.org/fhir/StructureDefinition/Patient
+
+use serde::{Deserialize, Serialize};
+use super::primitives::PrimitiveElement;
+use super::extension::Extension;
+
+/// Patient
+///
+/// Canonical URL: http://hl7.org/fhir/StructureDefinition/Patient
+/// Kind: Resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Patient {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Extensions and id for `id`
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id_ext: Option<PrimitiveElement>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extension: Vec<Extension>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    /// Extensions and id for `active`
+    #[serde(rename = "_active", skip_serializing_if = "Option::is_none")]
+    pub active_ext: Option<PrimitiveElement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birth_date: Option<String>,
+    /// Extensions and id for `birthDate`
+    #[serde(rename = "_birthDate", skip_serializing_if = "Option::is_none")]
+    pub birth_date_ext: Option<PrimitiveElement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deceased_boolean: Option<bool>,
+    /// Extensions and id for `deceasedBoolean`
+    #[serde(rename = "_deceasedBoolean", skip_serializing_if = "Option::is_none")]
+    pub deceased_boolean_ext: Option<PrimitiveElement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deceased_date_time: Option<String>,
+    /// Extensions and id for `deceasedDateTime`
+    #[serde(rename = "_deceasedDateTime", skip_serializing_if = "Option::is_none")]
+    pub deceased_date_time_ext: Option<PrimitiveElement>,
+}
\ No newline at end of file
@@ -0,0 +1,14 @@
+//! FHIR Primitive Types
+
+use serde::{Deserialize, Serialize};
+use super::extension::Extension;
+
+/// Id and extensions of a primitive value (the JSON `_field` companion)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrimitiveElement {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extension: Vec<Extension>,
+}
+
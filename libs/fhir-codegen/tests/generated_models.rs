@@ -0,0 +1,195 @@
+//! Serde round trips through checked-in generator output.
+//!
+//! `tests/generated/` holds the Rust generator's output for the small registry below and is
+//! compiled into this test. Regenerate it with `UPDATE_GENERATED_MODELS=1 cargo test`.
+
+#[rustfmt::skip]
+#[allow(dead_code, unused_imports)]
+mod generated;
+
+use ferrum_codegen::generators::rust::RustGenerator;
+use ferrum_codegen::generators::{Generator, GeneratorConfig};
+use ferrum_codegen::ir::{
+    Cardinality, Property, PropertyType, TypeDefinition, TypeKind, TypeRegistry,
+};
+use generated::{Extension, Patient, PrimitiveElement};
+use serde_json::json;
+use std::path::Path;
+
+fn property(parent: &str, name: &str, type_codes: &[&str], cardinality: Cardinality) -> Property {
+    Property {
+        name: name.to_string(),
+        path: format!("{}.{}", parent, name),
+        description: None,
+        types: type_codes
+            .iter()
+            .map(|code| PropertyType {
+                code: code.to_string(),
+                profile: None,
+                target_profiles: Vec::new(),
+            })
+            .collect(),
+        is_required: cardinality.is_required(),
+        cardinality,
+        is_modifier: false,
+        must_support: false,
+    }
+}
+
+fn type_definition(name: &str, kind: TypeKind, properties: Vec<Property>) -> TypeDefinition {
+    TypeDefinition {
+        name: name.to_string(),
+        url: Some(format!("http://hl7.org/fhir/StructureDefinition/{}", name)),
+        description: None,
+        kind,
+        base_type: None,
+        properties,
+        is_abstract: false,
+        backbone_elements: Vec::new(),
+        parent_type: None,
+    }
+}
+
+fn registry() -> TypeRegistry {
+    let optional = || Cardinality::new(0, Some(1));
+    let mut registry = TypeRegistry::new();
+    registry.add_type(
+        "Extension".to_string(),
+        type_definition(
+            "Extension",
+            TypeKind::ComplexType,
+            vec![
+                property("Extension", "url", &["uri"], Cardinality::new(1, Some(1))),
+                property("Extension", "value[x]", &["string", "boolean"], optional()),
+            ],
+        ),
+    );
+    registry.add_type(
+        "Patient".to_string(),
+        type_definition(
+            "Patient",
+            TypeKind::Resource,
+            vec![
+                property("Patient", "id", &["id"], optional()),
+                property(
+                    "Patient",
+                    "extension",
+                    &["Extension"],
+                    Cardinality::new(0, None),
+                ),
+                property("Patient", "active", &["boolean"], optional()),
+                property("Patient", "birthDate", &["date"], optional()),
+                property(
+                    "Patient",
+                    "deceased[x]",
+                    &["boolean", "dateTime"],
+                    optional(),
+                ),
+            ],
+        ),
+    );
+    registry
+}
+
+fn config() -> GeneratorConfig {
+    GeneratorConfig {
+        primitive_extensions: true,
+        plain_vecs: true,
+        ..GeneratorConfig::default()
+    }
+}
+
+#[test]
+fn checked_in_models_match_generator_output() {
+    let modules = RustGenerator::new(config())
+        .generate(&registry())
+        .unwrap()
+        .modules;
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/generated");
+
+    if std::env::var_os("UPDATE_GENERATED_MODELS").is_some() {
+        ferrum_codegen::utils::write_modules(&dir, &modules).unwrap();
+    }
+
+    for (file, code) in &modules {
+        let checked_in = std::fs::read_to_string(dir.join(file)).unwrap_or_default();
+        assert_eq!(
+            &checked_in, code,
+            "tests/generated/{file} is stale; rerun with UPDATE_GENERATED_MODELS=1"
+        );
+    }
+}
+
+#[test]
+fn primitive_extensions_round_trip() {
+    let input = json!({
+        "id": "example",
+        "birthDate": "1970-03-30",
+        "_birthDate": {
+            "id": "bd",
+            "extension": [{
+                "url": "http://hl7.org/fhir/StructureDefinition/patient-birthTime",
+                "valueString": "1970-03-30T14:35:45-05:00"
+            }]
+        },
+        "deceasedBoolean": false,
+        "_deceasedBoolean": {
+            "extension": [{"url": "http://example.org/reason", "valueBoolean": true}]
+        }
+    });
+
+    let patient: Patient = serde_json::from_value(input.clone()).unwrap();
+    let birth_date_ext = patient.birth_date_ext.as_ref().unwrap();
+    assert_eq!(birth_date_ext.id.as_deref(), Some("bd"));
+    assert_eq!(
+        birth_date_ext.extension[0].value_string.as_deref(),
+        Some("1970-03-30T14:35:45-05:00")
+    );
+    assert_eq!(patient.deceased_boolean, Some(false));
+    assert!(patient.deceased_date_time.is_none());
+    assert_eq!(
+        patient.deceased_boolean_ext,
+        Some(PrimitiveElement {
+            id: None,
+            extension: vec![Extension {
+                url: "http://example.org/reason".to_string(),
+                url_ext: None,
+                value_string: None,
+                value_string_ext: None,
+                value_boolean: Some(true),
+                value_boolean_ext: None,
+            }],
+        })
+    );
+
+    assert_eq!(serde_json::to_value(&patient).unwrap(), input);
+}
+
+#[test]
+fn extension_only_primitives_round_trip() {
+    // A primitive may carry extensions without a value
+    let input = json!({
+        "_active": {"extension": [{"url": "http://example.org/unknown", "valueString": "asked"}]}
+    });
+
+    let patient: Patient = serde_json::from_value(input.clone()).unwrap();
+    assert!(patient.active.is_none());
+    assert!(patient.active_ext.is_some());
+    assert_eq!(serde_json::to_value(&patient).unwrap(), input);
+}
+
+#[test]
+fn empty_vecs_and_none_are_not_serialized() {
+    let empty: Patient = serde_json::from_value(json!({})).unwrap();
+    assert!(empty.extension.is_empty());
+    assert_eq!(serde_json::to_string(&empty).unwrap(), "{}");
+
+    let sparse = Patient {
+        birth_date: Some("1970-01-01".to_string()),
+        ..empty
+    };
+    assert_eq!(
+        serde_json::to_string(&sparse).unwrap(),
+        r#"{"birthDate":"1970-01-01"}"#
+    );
+}
@@ -5,60 +5,106 @@
 //! XML → JSON conversion. Metadata is embedded at compile time from
 //! `fhir_type_metadata.json` (generated via `ferrum-cli gen-format-metadata`).
 
-use quick_xml::events::{BytesEnd, BytesStart, Event};
-use quick_xml::Writer;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
 use roxmltree::Document;
+use serde::de::{DeserializeSeed, Deserializer as _, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::io::Cursor;
-use std::sync::LazyLock;
+use std::fmt;
+use std::io::{BufRead, Cursor, Write};
+use std::sync::{Arc, LazyLock};
 use thiserror::Error;
 
-/// Pre-computed FHIR type metadata for determining array cardinality.
-/// Structure: { type_name: { property_name: { "type": String, "multiple": bool } } }
-static FHIR_TYPE_METADATA: LazyLock<HashMap<String, HashMap<String, PropMeta>>> =
-    LazyLock::new(|| {
-        let json = include_str!("fhir_type_metadata.json");
-        let raw: HashMap<String, HashMap<String, Value>> =
-            serde_json::from_str(json).expect("failed to parse embedded fhir_type_metadata.json");
-        raw.into_iter()
-            .map(|(type_name, props)| {
-                let prop_map = props
-                    .into_iter()
-                    .map(|(prop_name, v)| {
-                        let multiple = v
-                            .get("multiple")
-                            .and_then(Value::as_bool)
-                            .unwrap_or(false);
-                        let type_name = v
-                            .get("type")
-                            .and_then(Value::as_str)
-                            .unwrap_or("string")
-                            .to_string();
-                        (prop_name, PropMeta { type_name, multiple })
-                    })
-                    .collect();
-                (type_name, prop_map)
-            })
-            .collect()
-    });
+/// FHIR type metadata for determining array cardinality and primitive types, keyed by type
+/// name and then property name.
+pub type TypeMetadata = HashMap<String, HashMap<String, PropMeta>>;
+
+/// Type metadata for one property: its FHIR type and whether it repeats.
+#[derive(Debug, Clone)]
+pub struct PropMeta {
+    pub type_name: String,
+    pub multiple: bool,
+}
 
-#[derive(Debug)]
-struct PropMeta {
-    type_name: String,
-    multiple: bool,
+/// Pre-computed FHIR R4 type metadata embedded at compile time.
+static FHIR_TYPE_METADATA: LazyLock<Arc<TypeMetadata>> = LazyLock::new(|| {
+    let json = include_str!("fhir_type_metadata.json");
+    Arc::new(parse_type_metadata(json).expect("failed to parse embedded fhir_type_metadata.json"))
+});
+
+/// Converter behind the free conversion functions, sharing the embedded metadata.
+static DEFAULT_CONVERTER: LazyLock<FhirFormatConverter> =
+    LazyLock::new(FhirFormatConverter::default);
+
+/// Parse metadata in the `fhir_type_metadata.json` layout:
+/// `{ type_name: { property_name: { "type": String, "multiple": bool } } }`.
+fn parse_type_metadata(json: &str) -> Result<TypeMetadata, serde_json::Error> {
+    let raw: HashMap<String, HashMap<String, Value>> = serde_json::from_str(json)?;
+    Ok(raw
+        .into_iter()
+        .map(|(type_name, props)| {
+            let prop_map = props
+                .into_iter()
+                .map(|(prop_name, v)| {
+                    let multiple = v
+                        .get("multiple")
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false);
+                    let type_name = v
+                        .get("type")
+                        .and_then(Value::as_str)
+                        .unwrap_or("string")
+                        .to_string();
+                    (prop_name, PropMeta { type_name, multiple })
+                })
+                .collect();
+            (type_name, prop_map)
+        })
+        .collect())
 }
 
 /// Look up property metadata for a given parent type and property name.
-fn lookup_prop_meta<'a>(parent_type: Option<&str>, prop_name: &str) -> Option<&'a PropMeta> {
+fn lookup_prop_meta<'a>(
+    metadata: &'a TypeMetadata,
+    parent_type: Option<&str>,
+    prop_name: &str,
+) -> Option<&'a PropMeta> {
     let parent = parent_type?;
-    FHIR_TYPE_METADATA
-        .get(parent)
-        .and_then(|props| props.get(prop_name))
+    metadata.get(parent).and_then(|props| props.get(prop_name))
+}
+
+/// Resolve a choice-type property (e.g. `valueInteger64` → `value[x]` of type `integer64`).
+///
+/// Returns the concrete FHIR type name and whether the choice element repeats.
+fn lookup_choice_type(
+    metadata: &TypeMetadata,
+    parent_type: Option<&str>,
+    prop_name: &str,
+) -> Option<(String, bool)> {
+    let props = metadata.get(parent_type?)?;
+    props.iter().find_map(|(key, meta)| {
+        let suffix = prop_name.strip_prefix(key.strip_suffix("[x]")?)?;
+        let mut chars = suffix.chars();
+        let first = chars.next().filter(char::is_ascii_uppercase)?;
+        // Complex types keep their capitalized name; primitives start lowercase.
+        let type_name = if metadata.contains_key(suffix) {
+            suffix.to_string()
+        } else {
+            format!("{}{}", first.to_ascii_lowercase(), chars.as_str())
+        };
+        Some((type_name, meta.multiple))
+    })
 }
 
 const FHIR_NS: &str = "http://hl7.org/fhir";
 const XHTML_NS: &str = "http://www.w3.org/1999/xhtml";
+/// JSON property holding the XML comments that precede an element.
+const FHIR_COMMENTS: &str = "fhir_comments";
 
 #[derive(Debug, Error)]
 pub enum FormatError {
@@ -70,248 +116,1203 @@ pub enum FormatError {
     Json(#[from] serde_json::Error),
     #[error("XML parse error: {0}")]
     Xml(#[from] roxmltree::Error),
+    #[error("XML parse error at {line}:{column}: {message}")]
+    XmlAt {
+        line: usize,
+        column: usize,
+        message: String,
+    },
     #[error("UTF-8 error: {0}")]
     Utf8(#[from] std::string::FromUtf8Error),
     #[error("XML write error: {0}")]
     XmlWrite(#[from] quick_xml::Error),
+    #[error("XML read error: {0}")]
+    XmlRead(quick_xml::Error),
+    #[error("unexpected attribute '{attribute}' on element {path}")]
+    UnexpectedAttribute { path: String, attribute: String },
+    #[error("expected a Bundle, found {0}")]
+    NotABundle(String),
+    #[error("duplicate JSON property '{0}'")]
+    DuplicateKey(String),
+    #[error("invalid base64 content in {0}")]
+    InvalidBase64(String),
+    #[error("fhir_comments entry cannot be written as an XML comment (contains '--' or ends with '-'): {0}")]
+    InvalidComment(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("line {line}: {source}")]
+    Record {
+        line: usize,
+        #[source]
+        source: Box<FormatError>,
+    },
 }
 
-/// Convert a FHIR JSON payload into its XML representation.
-pub fn json_to_xml(input: &str) -> Result<String, FormatError> {
-    let value: Value = serde_json::from_str(input)?;
-    let obj = value.as_object().ok_or(FormatError::ExpectedObject)?;
-    let resource_type = obj
-        .get("resourceType")
-        .and_then(Value::as_str)
-        .ok_or(FormatError::MissingResourceType)?;
+/// Options controlling XML → JSON conversion.
+#[derive(Debug, Clone, Default)]
+pub struct XmlToJsonOptions {
+    /// Fail on unrecognized attributes on FHIR elements and on malformed `base64Binary`
+    /// values instead of reporting a warning.
+    pub strict: bool,
+    /// Layout of the JSON output.
+    pub json: JsonOptions,
+}
 
-    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
-    let mut root = BytesStart::new(resource_type);
-    root.push_attribute(("xmlns", FHIR_NS));
-    writer.write_event(Event::Start(root.clone()))?;
+/// Layout of JSON output.
+#[derive(Debug, Clone)]
+pub struct JsonOptions {
+    /// Write one property per line. When unset the output is compact, with no whitespace.
+    pub pretty: bool,
+    /// Spaces per nesting level in pretty output. Defaults to 2.
+    pub indent: usize,
+}
 
-    let mut meta = HashMap::new();
-    for (k, v) in obj {
-        if k.starts_with('_') {
-            meta.insert(k.trim_start_matches('_').to_string(), v.clone());
+impl Default for JsonOptions {
+    fn default() -> Self {
+        Self {
+            pretty: true,
+            indent: 2,
         }
     }
+}
 
-    for (k, v) in obj {
-        if k == "resourceType" || k.starts_with('_') {
-            continue;
+/// A non-fatal problem found while converting, e.g. an attribute that has no JSON mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionWarning {
+    /// Element path in the source document (e.g. `Patient.birthDate`).
+    pub path: String,
+    pub message: String,
+}
+
+/// Converted document together with any warnings raised along the way.
+#[derive(Debug, Clone)]
+pub struct Conversion {
+    pub output: String,
+    pub warnings: Vec<ConversionWarning>,
+}
+
+/// Options controlling JSON → XML conversion.
+#[derive(Debug, Clone, Default)]
+pub struct JsonToXmlOptions {
+    /// Declare `xmlns="http://hl7.org/fhir"` on every nested resource element, not only on
+    /// the root. The declaration is redundant but some consumers require it.
+    pub namespace_nested_resources: bool,
+    /// Start the document with `<?xml version="1.0" encoding="UTF-8"?>`.
+    pub xml_declaration: bool,
+}
+
+/// Options controlling NDJSON → XML stream conversion.
+#[derive(Debug, Clone)]
+pub struct NdjsonToXmlOptions {
+    /// Written after each XML document. Defaults to `\n`.
+    pub delimiter: String,
+    /// Options applied to each document.
+    pub xml: JsonToXmlOptions,
+}
+
+impl Default for NdjsonToXmlOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: "\n".to_string(),
+            xml: JsonToXmlOptions::default(),
         }
-        let meta_entry = meta.get(k);
-        write_json_value(&mut writer, k, v, meta_entry)?;
     }
+}
+
+/// Output and options threaded through the recursive JSON walk.
+struct XmlWriter<'a> {
+    writer: Writer<Cursor<Vec<u8>>>,
+    options: &'a JsonToXmlOptions,
+}
+
+/// State threaded through the recursive XML walk.
+struct XmlReader<'a> {
+    metadata: &'a TypeMetadata,
+    source: &'a str,
+    options: &'a XmlToJsonOptions,
+    warnings: Vec<ConversionWarning>,
+}
+
+/// Convert a FHIR JSON payload into its XML representation.
+pub fn json_to_xml(input: &str) -> Result<String, FormatError> {
+    DEFAULT_CONVERTER.to_xml(input)
+}
 
-    // Handle metadata fields that don't have a corresponding value field
-    // (e.g., _active with extensions but no active field)
-    for (k, v) in &meta {
-        if !obj.contains_key(k) {
-            // This metadata has no corresponding value, write it as a primitive with no value
-            write_json_value(&mut writer, k, &Value::Null, Some(v))?;
+/// Convert a FHIR JSON payload into XML. See [`FhirFormatConverter::to_xml_with_options`].
+pub fn json_to_xml_with_options(
+    input: &str,
+    options: &JsonToXmlOptions,
+) -> Result<String, FormatError> {
+    DEFAULT_CONVERTER.to_xml_with_options(input, options)
+}
+
+/// Convert NDJSON (one FHIR JSON resource per line) into XML documents, one per resource.
+///
+/// Returns the number of resources converted. See [`ndjson_to_xml_stream_with_options`].
+pub fn ndjson_to_xml_stream<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+) -> Result<usize, FormatError> {
+    ndjson_to_xml_stream_with_options(reader, writer, &NdjsonToXmlOptions::default())
+}
+
+/// Convert NDJSON into XML documents, each followed by [`NdjsonToXmlOptions::delimiter`].
+///
+/// Input is read a line at a time, so only the current record is held in memory. Blank lines
+/// are skipped. A record that cannot be read or converted stops the stream with
+/// [`FormatError::Record`] carrying its 1-based line number; the documents before it have
+/// already been written, so the caller can resume after that line or abort.
+pub fn ndjson_to_xml_stream_with_options<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    options: &NdjsonToXmlOptions,
+) -> Result<usize, FormatError> {
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut converted = 0;
+    loop {
+        line.clear();
+        line_number += 1;
+        let record_error = |e: FormatError| FormatError::Record {
+            line: line_number,
+            source: Box::new(e),
+        };
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| record_error(e.into()))?;
+        if read == 0 {
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
         }
-    }
 
-    writer.write_event(Event::End(BytesEnd::new(resource_type)))?;
-    let bytes = writer.into_inner().into_inner();
-    Ok(String::from_utf8(bytes)?)
+        let xml = json_to_xml_with_options(&line, &options.xml).map_err(record_error)?;
+        writer.write_all(xml.as_bytes())?;
+        writer.write_all(options.delimiter.as_bytes())?;
+        converted += 1;
+    }
+    writer.flush()?;
+    Ok(converted)
 }
 
 /// Convert a FHIR XML payload into its JSON representation.
+///
+/// Unrecognized attributes are dropped; use [`xml_to_json_with_options`] to inspect them.
 pub fn xml_to_json(input: &str) -> Result<String, FormatError> {
-    let doc = Document::parse(input)?;
-    let root = doc.root_element();
+    DEFAULT_CONVERTER.to_json(input)
+}
+
+/// Convert a FHIR XML payload into JSON, reporting attributes that have no JSON mapping.
+/// See [`FhirFormatConverter::to_json_with_options`].
+pub fn xml_to_json_with_options(
+    input: &str,
+    options: &XmlToJsonOptions,
+) -> Result<Conversion, FormatError> {
+    DEFAULT_CONVERTER.to_json_with_options(input, options)
+}
+
+/// Converts between FHIR JSON and XML using one set of type metadata.
+///
+/// [`FhirFormatConverter::default`] uses the embedded R4 metadata, shared rather than copied.
+/// [`FhirFormatConverter::with_metadata`] and [`FhirFormatConverter::from_metadata_json`] load
+/// other metadata (e.g. generated for R5 with `ferrum-cli gen-format-metadata`), so converters
+/// for several FHIR versions can live in one process.
+#[derive(Debug, Clone)]
+pub struct FhirFormatConverter {
+    metadata: Arc<TypeMetadata>,
+}
+
+impl Default for FhirFormatConverter {
+    fn default() -> Self {
+        Self {
+            metadata: Arc::clone(&FHIR_TYPE_METADATA),
+        }
+    }
+}
 
-    let resource_type = root.tag_name().name().to_string();
+impl FhirFormatConverter {
+    /// Converter using the given type metadata.
+    pub fn with_metadata(metadata: TypeMetadata) -> Self {
+        Self {
+            metadata: Arc::new(metadata),
+        }
+    }
 
-    let mut map = Map::new();
-    map.insert(
-        "resourceType".to_string(),
-        Value::String(resource_type.clone()),
-    );
+    /// Converter using metadata in the `fhir_type_metadata.json` layout.
+    pub fn from_metadata_json(json: &str) -> Result<Self, FormatError> {
+        Ok(Self::with_metadata(parse_type_metadata(json)?))
+    }
 
-    let mut accumulator = Map::new();
-    for child in root.children().filter(|n| n.is_element()) {
-        process_xml_child(input, &mut accumulator, &child, Some(&resource_type))?;
+    /// Convert a FHIR JSON payload into its XML representation.
+    pub fn to_xml(&self, input: &str) -> Result<String, FormatError> {
+        self.to_xml_with_options(input, &JsonToXmlOptions::default())
     }
 
-    map.extend(accumulator);
-    let json = Value::Object(map);
-    Ok(serde_json::to_string_pretty(&json)?)
+    /// Convert a FHIR JSON payload into XML.
+    ///
+    /// Resources nested in the payload (`contained`, `Bundle.entry.resource`, ...) are written as
+    /// `<resource><Patient>...</Patient></resource>`. The FHIR namespace is declared on the root
+    /// element only and inherited by nested resources unless
+    /// [`JsonToXmlOptions::namespace_nested_resources`] is set.
+    pub fn to_xml_with_options(
+        &self,
+        input: &str,
+        options: &JsonToXmlOptions,
+    ) -> Result<String, FormatError> {
+        let value: Value = serde_json::from_str(&quote_numbers(strip_bom(input)))?;
+        let obj = value.as_object().ok_or(FormatError::ExpectedObject)?;
+
+        let mut writer = XmlWriter {
+            writer: Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2),
+            options,
+        };
+        if options.xml_declaration {
+            writer
+                .writer
+                .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        }
+        writer.write_resource(obj, true)?;
+
+        let bytes = writer.writer.into_inner().into_inner();
+        Ok(String::from_utf8(bytes)?)
+    }
+
+    /// Convert a FHIR XML payload into its JSON representation.
+    ///
+    /// Unrecognized attributes are dropped; use [`Self::to_json_with_options`] to inspect them.
+    pub fn to_json(&self, input: &str) -> Result<String, FormatError> {
+        self.to_json_with_options(input, &XmlToJsonOptions::default())
+            .map(|c| c.output)
+    }
+
+    /// Convert a FHIR XML payload into JSON, reporting attributes that have no JSON mapping.
+    ///
+    /// FHIR elements only carry `id` and `value` attributes (plus `url` on extensions). Any other
+    /// attribute is reported as a [`ConversionWarning`], or rejected with
+    /// [`FormatError::UnexpectedAttribute`] when [`XmlToJsonOptions::strict`] is set. Likewise,
+    /// `base64Binary` values that are not valid base64 warn, or fail with
+    /// [`FormatError::InvalidBase64`] in strict mode.
+    pub fn to_json_with_options(
+        &self,
+        input: &str,
+        options: &XmlToJsonOptions,
+    ) -> Result<Conversion, FormatError> {
+        let input = strip_bom(input);
+        let doc = Document::parse(input).map_err(xml_parse_error)?;
+        let root = doc.root_element();
+
+        let resource_type = root.tag_name().name().to_string();
+
+        let mut map = Map::new();
+        map.insert(
+            "resourceType".to_string(),
+            Value::String(resource_type.clone()),
+        );
+
+        let mut reader = XmlReader {
+            metadata: &self.metadata,
+            source: input,
+            options,
+            warnings: Vec::new(),
+        };
+
+        // Comments around the root element and after its last child belong to the resource
+        let mut comments = preceding_comments(&root);
+        comments.extend(trailing_comments(&root));
+        comments.extend(
+            root.next_siblings()
+                .skip(1)
+                .filter(|n| n.is_comment())
+                .filter_map(|n| n.text())
+                .map(|text| Value::String(text.to_string())),
+        );
+        if !comments.is_empty() {
+            map.insert(FHIR_COMMENTS.to_string(), Value::Array(comments));
+        }
+
+        let mut accumulator = Map::new();
+        for child in root.children().filter(|n| n.is_element()) {
+            reader.process_xml_child(
+                &mut accumulator,
+                &child,
+                Some(&resource_type),
+                &resource_type,
+            )?;
+        }
+
+        map.extend(accumulator);
+        let json = Value::Object(map);
+        Ok(Conversion {
+            output: write_json(&DecimalsAsNumbers(&json), &options.json)?,
+            warnings: reader.warnings,
+        })
+    }
 }
 
-fn write_json_value(
-    writer: &mut Writer<Cursor<Vec<u8>>>,
-    name: &str,
-    value: &Value,
-    meta: Option<&Value>,
-) -> Result<(), FormatError> {
-    match value {
-        Value::Array(items) => {
-            let meta_array = meta.and_then(Value::as_array);
-            for (idx, item) in items.iter().enumerate() {
-                let item_meta = meta_array.and_then(|m| m.get(idx));
-                write_json_value(writer, name, item, item_meta)?;
+/// Read the `resourceType` of a FHIR JSON or XML payload without converting it.
+///
+/// Input starting with `<` is read as XML and yields the root element's name; anything else
+/// is read as JSON. JSON object members are scanned and skipped without building a value, so
+/// `resourceType` may appear anywhere among the top-level keys.
+pub fn sniff_resource_type(input: &str) -> Result<String, FormatError> {
+    let input = strip_bom(input).trim_start();
+    if input.starts_with('<') {
+        sniff_xml_root(input)
+    } else {
+        serde_json::Deserializer::from_str(input)
+            .deserialize_map(ResourceTypeVisitor)?
+            .ok_or(FormatError::MissingResourceType)
+    }
+}
+
+/// Name of the first element, skipping the declaration, comments and processing instructions.
+fn sniff_xml_root(input: &str) -> Result<String, FormatError> {
+    let mut reader = Reader::from_str(input);
+    loop {
+        match reader.read_event().map_err(FormatError::XmlRead)? {
+            Event::Start(e) | Event::Empty(e) => {
+                let name = e.local_name();
+                return Ok(String::from_utf8_lossy(name.as_ref()).into_owned());
             }
+            Event::Eof => return Err(FormatError::MissingResourceType),
+            _ => {}
         }
-        Value::Object(obj) => write_complex(writer, name, obj)?,
-        Value::Null => {}
-        primitive => write_primitive(writer, name, primitive, meta)?,
     }
-    Ok(())
 }
 
-fn write_complex(
-    writer: &mut Writer<Cursor<Vec<u8>>>,
-    name: &str,
-    obj: &Map<String, Value>,
-) -> Result<(), FormatError> {
-    let mut meta = HashMap::new();
-    for (k, v) in obj {
-        if k.starts_with('_') {
-            meta.insert(k.trim_start_matches('_').to_string(), v.clone());
+/// Finds the top-level `resourceType` member, skipping all other members unparsed.
+struct ResourceTypeVisitor;
+
+impl<'de> Visitor<'de> for ResourceTypeVisitor {
+    type Value = Option<String>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON object for the resource")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut resource_type = None;
+        while let Some(key) = map.next_key::<std::borrow::Cow<'de, str>>()? {
+            if key == "resourceType" && resource_type.is_none() {
+                resource_type = Some(map.next_value::<String>()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
         }
+        Ok(resource_type)
+    }
+}
+
+/// Split a FHIR Bundle (JSON or XML) into NDJSON, one `entry.resource` per line.
+///
+/// XML input is converted to JSON first. JSON resources are copied from the input with
+/// insignificant whitespace removed, so decimals keep their digits. Entries without a
+/// resource (e.g. transaction `DELETE` requests) are skipped; each line ends with `\n`.
+pub fn bundle_to_ndjson(input: &str) -> Result<String, FormatError> {
+    let input = strip_bom(input).trim_start();
+    if input.starts_with('<') {
+        bundle_json_to_ndjson(&xml_to_json(input)?)
+    } else {
+        bundle_json_to_ndjson(input)
     }
+}
+
+/// The parts of a Bundle needed to split it, with resources left unparsed.
+#[derive(Deserialize)]
+struct BundleEntries<'a> {
+    #[serde(rename = "resourceType")]
+    resource_type: Option<String>,
+    #[serde(default, borrow)]
+    entry: Vec<BundleEntry<'a>>,
+}
+
+#[derive(Deserialize)]
+struct BundleEntry<'a> {
+    #[serde(default, borrow)]
+    resource: Option<&'a RawValue>,
+}
 
-    let mut start = BytesStart::new(name);
-    if let Some(Value::String(id)) = obj.get("id") {
-        start.push_attribute(("id", id.as_str()));
+fn bundle_json_to_ndjson(input: &str) -> Result<String, FormatError> {
+    let bundle: BundleEntries = serde_json::from_str(input)?;
+    match bundle.resource_type.as_deref() {
+        Some("Bundle") => {}
+        Some(other) => return Err(FormatError::NotABundle(other.to_string())),
+        None => return Err(FormatError::MissingResourceType),
     }
 
-    writer.write_event(Event::Start(start))?;
+    let mut out = String::new();
+    for resource in bundle.entry.iter().filter_map(|e| e.resource) {
+        compact_json_into(resource.get(), &mut out);
+        out.push('\n');
+    }
+    Ok(out)
+}
 
-    for (k, v) in obj {
-        if k.starts_with('_') || k == "id" {
+/// Append `input` to `out` without the whitespace between JSON tokens.
+fn compact_json_into(input: &str, out: &mut String) {
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in input.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c.is_ascii_whitespace() {
             continue;
         }
-        let meta_entry = meta.get(k);
-        write_json_value(writer, k, v, meta_entry)?;
+        out.push(c);
     }
+}
 
-    writer.write_event(Event::End(BytesEnd::new(name)))?;
-    Ok(())
+/// Parse FHIR JSON, rejecting objects that repeat a property name.
+///
+/// `serde_json` keeps the last of several equal keys; FHIR JSON forbids them. The first
+/// duplicate found is reported as [`FormatError::DuplicateKey`] with its path, e.g.
+/// `name[0].family`.
+pub fn parse_strict(input: &str) -> Result<Value, FormatError> {
+    let duplicate = RefCell::new(None);
+    let seed = StrictValue {
+        path: String::new(),
+        duplicate: &duplicate,
+    };
+    let mut deserializer = serde_json::Deserializer::from_str(strip_bom(input));
+    match seed.deserialize(&mut deserializer) {
+        Ok(value) => {
+            deserializer.end()?;
+            Ok(value)
+        }
+        Err(e) => match duplicate.into_inner() {
+            Some(path) => Err(FormatError::DuplicateKey(path)),
+            None => Err(e.into()),
+        },
+    }
 }
 
-fn write_primitive(
-    writer: &mut Writer<Cursor<Vec<u8>>>,
-    name: &str,
-    value: &Value,
-    meta: Option<&Value>,
-) -> Result<(), FormatError> {
-    let mut elem = BytesStart::new(name);
+/// Builds a JSON value, recording the path of the first duplicate object key.
+struct StrictValue<'a> {
+    path: String,
+    duplicate: &'a RefCell<Option<String>>,
+}
+
+impl StrictValue<'_> {
+    fn child(&self, path: String) -> Self {
+        Self {
+            path,
+            duplicate: self.duplicate,
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for StrictValue<'_> {
+    type Value = Value;
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StrictValue<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) =
+            seq.next_element_seed(self.child(format!("{}[{}]", self.path, items.len())))?
+        {
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
 
-    // Only add value attribute if the value is not null
-    let has_value = !matches!(value, Value::Null);
-    if has_value {
-        elem.push_attribute(("value", primitive_to_string(value).as_str()));
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut object = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let path = if self.path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", self.path, key)
+            };
+            if object.contains_key(&key) {
+                let message = format!("duplicate JSON property '{}'", path);
+                *self.duplicate.borrow_mut() = Some(path);
+                return Err(serde::de::Error::custom(message));
+            }
+            let value = map.next_value_seed(self.child(path))?;
+            object.insert(key, value);
+        }
+        Ok(Value::Object(object))
     }
+}
+
+/// Drop a leading UTF-8 byte order mark, which neither parser accepts.
+fn strip_bom(input: &str) -> &str {
+    input.strip_prefix('\u{feff}').unwrap_or(input)
+}
+
+/// Options controlling [`resources_equal_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct CompareOptions {
+    /// Compare numbers by value, so `1`, `1.0` and `1.00` are equal. By default an integer
+    /// literal never equals a decimal one.
+    pub normalize_decimals: bool,
+}
+
+/// Whether two FHIR resources are semantically equal.
+///
+/// Object members may appear in any order; array items must match in order, as FHIR gives
+/// repeating elements an order. See [`resources_equal_with_options`].
+pub fn resources_equal(a: &Value, b: &Value) -> bool {
+    resources_equal_with_options(a, b, &CompareOptions::default())
+}
 
-    let mut has_children = false;
-    if let Some(Value::Object(m)) = meta {
-        if let Some(Value::String(id)) = m.get("id") {
-            elem.push_attribute(("id", id.as_str()));
+/// Whether two FHIR resources are semantically equal, with the given [`CompareOptions`].
+pub fn resources_equal_with_options(a: &Value, b: &Value, options: &CompareOptions) -> bool {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter().all(|(key, value)| {
+                    b.get(key)
+                        .is_some_and(|other| resources_equal_with_options(value, other, options))
+                })
         }
-        if m.get("extension").is_some() {
-            has_children = true;
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|(value, other)| resources_equal_with_options(value, other, options))
         }
+        (Value::Number(a), Value::Number(b)) if options.normalize_decimals => {
+            match (a.as_i64(), b.as_i64()) {
+                (Some(a), Some(b)) => a == b,
+                _ => a.as_f64() == b.as_f64(),
+            }
+        }
+        _ => a == b,
     }
+}
+
+impl XmlWriter<'_> {
+    /// Write a resource element, e.g. `<Patient>...</Patient>`.
+    fn write_resource(
+        &mut self,
+        obj: &Map<String, Value>,
+        declare_namespace: bool,
+    ) -> Result<(), FormatError> {
+        let resource_type = obj
+            .get("resourceType")
+            .and_then(Value::as_str)
+            .ok_or(FormatError::MissingResourceType)?;
+
+        self.write_comments(obj)?;
+        let mut start = BytesStart::new(resource_type);
+        if declare_namespace {
+            start.push_attribute(("xmlns", FHIR_NS));
+        }
+        self.writer.write_event(Event::Start(start))?;
 
-    // If we have neither a value nor children, skip writing this element
-    if !has_value && !has_children {
-        return Ok(());
+        let meta = primitive_metadata(obj);
+
+        for (k, v) in obj {
+            if k == "resourceType" || k == FHIR_COMMENTS || k.starts_with('_') {
+                continue;
+            }
+            let meta_entry = meta.get(k.as_str()).copied();
+            self.write_json_value(k, v, meta_entry)?;
+        }
+
+        // Handle metadata fields that don't have a corresponding value field
+        // (e.g., _active with extensions but no active field)
+        for (&k, &v) in &meta {
+            if !obj.contains_key(k) {
+                // This metadata has no corresponding value, write it as a primitive with no value
+                self.write_json_value(k, &Value::Null, Some(v))?;
+            }
+        }
+
+        self.writer
+            .write_event(Event::End(BytesEnd::new(resource_type)))?;
+        Ok(())
+    }
+
+    fn write_json_value(
+        &mut self,
+        name: &str,
+        value: &Value,
+        meta: Option<&Value>,
+    ) -> Result<(), FormatError> {
+        match value {
+            Value::Array(items) => {
+                let meta_array = meta.and_then(Value::as_array);
+                for (idx, item) in items.iter().enumerate() {
+                    let item_meta = meta_array.and_then(|m| m.get(idx));
+                    self.write_json_value(name, item, item_meta)?;
+                }
+            }
+            Value::Object(obj) if obj.contains_key("resourceType") => {
+                self.writer
+                    .write_event(Event::Start(BytesStart::new(name)))?;
+                self.write_resource(obj, self.options.namespace_nested_resources)?;
+                self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+            }
+            Value::Object(obj) => self.write_complex(name, obj)?,
+            Value::Null => {}
+            primitive => self.write_primitive(name, primitive, meta)?,
+        }
+        Ok(())
+    }
+
+    fn write_complex(&mut self, name: &str, obj: &Map<String, Value>) -> Result<(), FormatError> {
+        self.write_comments(obj)?;
+        let meta = primitive_metadata(obj);
+
+        let mut start = BytesStart::new(name);
+        if let Some(Value::String(id)) = obj.get("id") {
+            start.push_attribute(("id", id.as_str()));
+        }
+        let url_attribute = is_extension_element(name);
+        if url_attribute {
+            if let Some(Value::String(url)) = obj.get("url") {
+                start.push_attribute(("url", url.as_str()));
+            }
+        }
+
+        self.writer.write_event(Event::Start(start))?;
+
+        for (k, v) in obj {
+            if k.starts_with('_')
+                || k == "id"
+                || k == FHIR_COMMENTS
+                || (url_attribute && k == "url")
+            {
+                continue;
+            }
+            let meta_entry = meta.get(k.as_str()).copied();
+            self.write_json_value(k, v, meta_entry)?;
+        }
+
+        self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+        Ok(())
     }
 
-    if has_children {
-        writer.write_event(Event::Start(elem.clone()))?;
+    fn write_primitive(
+        &mut self,
+        name: &str,
+        value: &Value,
+        meta: Option<&Value>,
+    ) -> Result<(), FormatError> {
+        if let Some(Value::Object(m)) = meta {
+            self.write_comments(m)?;
+        }
+        let mut elem = BytesStart::new(name);
+
+        // Only add value attribute if the value is not null
+        let has_value = !matches!(value, Value::Null);
+        if has_value {
+            elem.push_attribute(("value", primitive_to_string(value).as_ref()));
+        }
+
+        let mut has_children = false;
         if let Some(Value::Object(m)) = meta {
-            if let Some(ext) = m.get("extension") {
-                write_json_value(writer, "extension", ext, None)?;
+            if let Some(Value::String(id)) = m.get("id") {
+                elem.push_attribute(("id", id.as_str()));
+            }
+            if m.get("extension").is_some() {
+                has_children = true;
             }
         }
-        writer.write_event(Event::End(BytesEnd::new(name)))?;
-    } else {
-        writer.write_event(Event::Empty(elem))?;
+
+        // If we have neither a value nor children, skip writing this element
+        if !has_value && !has_children {
+            return Ok(());
+        }
+
+        if has_children {
+            self.writer.write_event(Event::Start(elem))?;
+            if let Some(Value::Object(m)) = meta {
+                if let Some(ext) = m.get("extension") {
+                    self.write_json_value("extension", ext, None)?;
+                }
+            }
+            self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+        } else {
+            self.writer.write_event(Event::Empty(elem))?;
+        }
+        Ok(())
+    }
+
+    /// Write the object's `fhir_comments` as XML comments ahead of the element they annotate.
+    ///
+    /// JSON does not record where a comment stood, so comments that followed an element's last
+    /// child in the XML are written before the element. Comments that XML cannot hold (`--`
+    /// inside or a trailing `-`) are rejected rather than letting them end the comment early.
+    fn write_comments(&mut self, obj: &Map<String, Value>) -> Result<(), FormatError> {
+        let Some(Value::Array(comments)) = obj.get(FHIR_COMMENTS) else {
+            return Ok(());
+        };
+        for comment in comments.iter().filter_map(Value::as_str) {
+            if comment.contains("--") || comment.ends_with('-') {
+                return Err(FormatError::InvalidComment(comment.to_string()));
+            }
+            self.writer
+                .write_event(Event::Comment(BytesText::from_escaped(comment)))?;
+        }
+        Ok(())
     }
-    Ok(())
 }
 
-fn primitive_to_string(value: &Value) -> String {
-    match value {
-        Value::String(s) => s.clone(),
-        Value::Number(n) => n.to_string(),
-        Value::Bool(b) => b.to_string(),
-        Value::Null => "".to_string(),
-        other => other.to_string(),
+/// Wrap every JSON number literal in quotes so its lexical form survives parsing.
+///
+/// serde_json reads decimals as `f64`, which turns `2.50` into `2.5`, but FHIR decimals carry
+/// their precision in the digits. XML writes primitives as text, so the quoted literal
+/// produces the same `value` attribute with the input's exact digits. Malformed literals are
+/// left alone for the parser to reject.
+fn quote_numbers(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = String::with_capacity(input.len());
+    let mut copied = 0;
+    let mut in_string = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if in_string => i += 1,
+            b'"' => in_string = !in_string,
+            b'-' | b'0'..=b'9' if !in_string => {
+                let start = i;
+                while i < bytes.len()
+                    && matches!(bytes[i], b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+                {
+                    i += 1;
+                }
+                let literal = &input[start..i];
+                if literal.parse::<serde_json::Number>().is_ok() {
+                    out.push_str(&input[copied..start]);
+                    out.push('"');
+                    out.push_str(literal);
+                    out.push('"');
+                    copied = i;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
     }
+    out.push_str(&input[copied..]);
+    out
 }
 
-fn process_xml_child(
-    source: &str,
-    target: &mut Map<String, Value>,
-    node: &roxmltree::Node,
-    parent_type: Option<&str>,
-) -> Result<(), FormatError> {
-    let name = node.tag_name().name().to_string();
+/// Attach the 1-based line and column of an XML parse failure to the error.
+///
+/// Errors about the document as a whole (no root element, DTDs, size limits) have no
+/// position and keep the plain [`FormatError::Xml`].
+fn xml_parse_error(err: roxmltree::Error) -> FormatError {
+    use roxmltree::Error as E;
+    match err {
+        E::NoRootNode
+        | E::UnclosedRootNode
+        | E::DtdDetected
+        | E::NodesLimitReached
+        | E::AttributesLimitReached
+        | E::NamespacesLimitReached => FormatError::Xml(err),
+        _ => {
+            let pos = err.pos();
+            // roxmltree ends its messages with " at {row}:{col}"
+            let message = err.to_string();
+            let message = message
+                .strip_suffix(&format!(" at {}", pos))
+                .unwrap_or(&message)
+                .to_string();
+            FormatError::XmlAt {
+                line: pos.row as usize,
+                column: pos.col as usize,
+                message,
+            }
+        }
+    }
+}
 
-    // Look up metadata to determine if this property is an array and what its type is.
-    let prop_meta = lookup_prop_meta(parent_type, &name);
-    let force_array = prop_meta.map(|m| m.multiple).unwrap_or(false);
-    let element_type = prop_meta.map(|m| m.type_name.as_str());
+/// Serialize a JSON value with the layout given by `options`.
+fn write_json(value: &impl Serialize, options: &JsonOptions) -> Result<String, FormatError> {
+    if !options.pretty {
+        return Ok(serde_json::to_string(value)?);
+    }
+    let indent = " ".repeat(options.indent);
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut out = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    value.serialize(&mut serializer)?;
+    Ok(String::from_utf8(out)?)
+}
 
-    let (value, meta) = xml_element_to_value(source, node, element_type)?;
+/// Key of the single-entry object that carries a decimal literal through XML to JSON
+/// conversion.
+///
+/// XML element names cannot start with `$`, so no converted element collides with it.
+const DECIMAL_KEY: &str = "$decimal";
 
-    insert_json_property(target, &name, value, meta, force_array);
-    Ok(())
+/// Wrap a decimal literal so it is written as a JSON number with its exact digits.
+///
+/// The inverse of [`quote_numbers`]: parsing into `f64` would turn `1.50` into `1.5`.
+fn decimal_value(literal: &str) -> Value {
+    let mut carrier = Map::new();
+    carrier.insert(DECIMAL_KEY.to_string(), Value::String(literal.to_string()));
+    Value::Object(carrier)
 }
 
-fn xml_element_to_value(
-    source: &str,
-    node: &roxmltree::Node,
-    element_type: Option<&str>,
-) -> Result<(Value, Option<Value>), FormatError> {
-    if node.tag_name().namespace().is_some_and(|ns| ns == XHTML_NS) {
-        let snippet = &source[node.range()];
-        return Ok((Value::String(snippet.to_string()), None));
+/// The literal held by a [`decimal_value`] carrier.
+fn decimal_literal(value: &Value) -> Option<&str> {
+    match value {
+        Value::Object(obj) if obj.len() == 1 => obj.get(DECIMAL_KEY)?.as_str(),
+        _ => None,
     }
+}
 
-    let mut meta_map = Map::new();
-    if let Some(id) = node.attribute("id") {
-        meta_map.insert("id".to_string(), Value::String(id.to_string()));
+/// The object of a complex element, skipping decimal carriers.
+fn complex_object_mut(value: &mut Value) -> Option<&mut Map<String, Value>> {
+    if decimal_literal(value).is_some() {
+        return None;
     }
+    value.as_object_mut()
+}
 
-    if let Some(val) = node.attribute("value") {
-        let mut extensions = Vec::new();
-        for child in node.children().filter(|c| c.is_element()) {
-            if child.tag_name().name() == "extension" {
-                let (ext_val, _ext_meta) =
-                    xml_element_to_value(source, &child, Some("Extension"))?;
-                extensions.push(ext_val);
+/// Serializes a converted document, writing decimal carriers as raw number literals.
+struct DecimalsAsNumbers<'a>(&'a Value);
+
+impl Serialize for DecimalsAsNumbers<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error as _, SerializeMap, SerializeSeq};
+
+        if let Some(literal) = decimal_literal(self.0) {
+            let raw = RawValue::from_string(literal.to_string()).map_err(S::Error::custom)?;
+            return raw.serialize(serializer);
+        }
+        match self.0 {
+            Value::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(&DecimalsAsNumbers(item))?;
+                }
+                seq.end()
             }
+            Value::Object(obj) => {
+                let mut map = serializer.serialize_map(Some(obj.len()))?;
+                for (key, value) in obj {
+                    map.serialize_entry(key, &DecimalsAsNumbers(value))?;
+                }
+                map.end()
+            }
+            value => value.serialize(serializer),
         }
-        if !extensions.is_empty() {
-            meta_map.insert("extension".to_string(), Value::Array(extensions));
+    }
+}
+
+/// Collect the `_name` primitive metadata entries of an object, keyed by the bare property name.
+///
+/// Entries borrow from the source object so large siblings are never copied.
+fn primitive_metadata(obj: &Map<String, Value>) -> HashMap<&str, &Value> {
+    obj.iter()
+        .filter_map(|(k, v)| k.strip_prefix('_').map(|name| (name, v)))
+        .collect()
+}
+
+/// Lexical form of a primitive for the XML `value` attribute.
+///
+/// Strings are borrowed rather than cloned; `base64Binary` payloads such as `Binary.data` or
+/// `Signature.data` can run to many megabytes.
+fn primitive_to_string(value: &Value) -> Cow<'_, str> {
+    match value {
+        Value::String(s) => Cow::Borrowed(s),
+        Value::Null => Cow::Borrowed(""),
+        other => Cow::Owned(other.to_string()),
+    }
+}
+
+/// Check the lexical form of a `base64Binary` value (RFC 4648 alphabet with padding).
+///
+/// Whitespace is ignored, as XML documents commonly wrap long payloads across lines.
+fn is_valid_base64(value: &str) -> bool {
+    let mut len = 0;
+    let mut padding = 0;
+    for b in value.bytes().filter(|b| !b.is_ascii_whitespace()) {
+        match b {
+            b'=' => padding += 1,
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'+' | b'/' if padding == 0 => {}
+            _ => return false,
         }
-        let prim = parse_primitive(val, element_type);
-        let meta = if meta_map.is_empty() {
-            None
-        } else {
-            Some(Value::Object(meta_map))
+        len += 1;
+    }
+    len % 4 == 0 && padding <= 2
+}
+
+fn is_extension_element(name: &str) -> bool {
+    name == "extension" || name == "modifierExtension"
+}
+
+impl XmlReader<'_> {
+    fn process_xml_child(
+        &mut self,
+        target: &mut Map<String, Value>,
+        node: &roxmltree::Node,
+        parent_type: Option<&str>,
+        parent_path: &str,
+    ) -> Result<(), FormatError> {
+        let name = node.tag_name().name().to_string();
+        let path = format!("{}.{}", parent_path, name);
+
+        // Look up metadata to determine if this property is an array and what its type is.
+        let prop_meta = lookup_prop_meta(self.metadata, parent_type, &name);
+        let choice = match prop_meta {
+            Some(_) => None,
+            None => lookup_choice_type(self.metadata, parent_type, &name),
         };
-        return Ok((prim, meta));
+        let force_array = prop_meta
+            .map(|m| m.multiple)
+            .or(choice.as_ref().map(|(_, multiple)| *multiple))
+            .unwrap_or(false);
+        let element_type = prop_meta
+            .map(|m| m.type_name.as_str())
+            .or(choice.as_ref().map(|(type_name, _)| type_name.as_str()));
+
+        let (mut value, mut meta) = self.xml_element_to_value(node, element_type, &path)?;
+        let comments = preceding_comments(node);
+        if !comments.is_empty() {
+            // Complex values carry their comments inline, primitives in `_name`
+            if let Some(obj) = complex_object_mut(&mut value) {
+                prepend_comments(obj, comments);
+            } else {
+                let mut meta_map = match meta.take() {
+                    Some(Value::Object(meta_map)) => meta_map,
+                    _ => Map::new(),
+                };
+                prepend_comments(&mut meta_map, comments);
+                meta = Some(Value::Object(meta_map));
+            }
+        }
+
+        insert_json_property(target, &name, value, meta, force_array);
+        Ok(())
     }
 
-    let mut obj = Map::new();
-    if let Some(id) = node.attribute("id") {
-        obj.insert("id".to_string(), Value::String(id.to_string()));
+    fn xml_element_to_value(
+        &mut self,
+        node: &roxmltree::Node,
+        element_type: Option<&str>,
+        path: &str,
+    ) -> Result<(Value, Option<Value>), FormatError> {
+        if node.tag_name().namespace().is_some_and(|ns| ns == XHTML_NS) {
+            let snippet = &self.source[node.range()];
+            return Ok((Value::String(snippet.to_string()), None));
+        }
+
+        // Resource wrappers (`contained`, `entry.resource`, ...) hold a single resource element;
+        // without type metadata the wrapper is recognised by that shape alone
+        if matches!(element_type, None | Some("Resource")) {
+            if let Some(resource) = self.inline_resource(node)? {
+                return Ok((resource, None));
+            }
+        }
+
+        let is_extension = is_extension_element(node.tag_name().name());
+        self.check_attributes(node, is_extension, path)?;
+
+        let mut meta_map = Map::new();
+        if let Some(id) = node.attribute("id") {
+            meta_map.insert("id".to_string(), Value::String(id.to_string()));
+        }
+
+        if let Some(val) = node.attribute("value") {
+            let mut extensions = Vec::new();
+            for child in node.children().filter(|c| c.is_element()) {
+                if child.tag_name().name() == "extension" {
+                    let ext_path = format!("{}.extension", path);
+                    let (ext_val, _ext_meta) =
+                        self.xml_element_to_value(&child, Some("Extension"), &ext_path)?;
+                    extensions.push(ext_val);
+                }
+            }
+            if !extensions.is_empty() {
+                meta_map.insert("extension".to_string(), Value::Array(extensions));
+            }
+            if element_type == Some("base64Binary") && !is_valid_base64(val) {
+                if self.options.strict {
+                    return Err(FormatError::InvalidBase64(path.to_string()));
+                }
+                self.warnings.push(ConversionWarning {
+                    path: path.to_string(),
+                    message: "value is not valid base64".to_string(),
+                });
+            }
+            let prim = parse_primitive(val, element_type);
+            let meta = if meta_map.is_empty() {
+                None
+            } else {
+                Some(Value::Object(meta_map))
+            };
+            return Ok((prim, meta));
+        }
+
+        let mut obj = Map::new();
+        if let Some(id) = node.attribute("id") {
+            obj.insert("id".to_string(), Value::String(id.to_string()));
+        }
+        if is_extension {
+            if let Some(url) = node.attribute("url") {
+                obj.insert("url".to_string(), Value::String(url.to_string()));
+            }
+        }
+
+        let comments = trailing_comments(node);
+        if !comments.is_empty() {
+            obj.insert(FHIR_COMMENTS.to_string(), Value::Array(comments));
+        }
+
+        for child in node.children().filter(|c| c.is_element()) {
+            self.process_xml_child(&mut obj, &child, element_type, path)?;
+        }
+
+        Ok((Value::Object(obj), None))
+    }
+
+    /// Read a resource wrapped in a `Resource`-typed element (`<contained><Patient>...`) as a
+    /// JSON object with its `resourceType`, or `None` when the element does not hold exactly
+    /// one resource element. Property names are lowerCamelCase, so a lone FHIR element whose
+    /// name starts with an uppercase letter can only be a resource.
+    fn inline_resource(&mut self, node: &roxmltree::Node) -> Result<Option<Value>, FormatError> {
+        if node.has_attribute("value") {
+            return Ok(None);
+        }
+        let mut children = node.children().filter(|c| c.is_element());
+        let (Some(child), None) = (children.next(), children.next()) else {
+            return Ok(None);
+        };
+        let resource_type = child.tag_name().name().to_string();
+        if !resource_type.starts_with(|c: char| c.is_ascii_uppercase())
+            || child.tag_name().namespace() != Some(FHIR_NS)
+        {
+            return Ok(None);
+        }
+
+        let mut obj = Map::new();
+        obj.insert(
+            "resourceType".to_string(),
+            Value::String(resource_type.clone()),
+        );
+        let mut comments = preceding_comments(&child);
+        comments.extend(trailing_comments(&child));
+        if !comments.is_empty() {
+            obj.insert(FHIR_COMMENTS.to_string(), Value::Array(comments));
+        }
+        for grandchild in child.children().filter(|c| c.is_element()) {
+            self.process_xml_child(&mut obj, &grandchild, Some(&resource_type), &resource_type)?;
+        }
+        Ok(Some(Value::Object(obj)))
     }
 
-    for child in node.children().filter(|c| c.is_element()) {
-        process_xml_child(source, &mut obj, &child, element_type)?;
+    /// Report attributes other than `id`/`value` (and `url` on extensions), which have no JSON
+    /// representation and would otherwise be dropped silently.
+    fn check_attributes(
+        &mut self,
+        node: &roxmltree::Node,
+        is_extension: bool,
+        path: &str,
+    ) -> Result<(), FormatError> {
+        for attr in node.attributes() {
+            let known = attr.namespace().is_none()
+                && match attr.name() {
+                    "id" | "value" => true,
+                    "url" => is_extension,
+                    _ => false,
+                };
+            if known {
+                continue;
+            }
+
+            if self.options.strict {
+                return Err(FormatError::UnexpectedAttribute {
+                    path: path.to_string(),
+                    attribute: attr.name().to_string(),
+                });
+            }
+            self.warnings.push(ConversionWarning {
+                path: path.to_string(),
+                message: format!("ignored unexpected attribute '{}'", attr.name()),
+            });
+        }
+        Ok(())
     }
+}
 
-    Ok((Value::Object(obj), None))
+/// Comment nodes between an element and the previous sibling element, in document order.
+fn preceding_comments(node: &roxmltree::Node) -> Vec<Value> {
+    let mut comments: Vec<Value> = std::iter::successors(node.prev_sibling(), |n| n.prev_sibling())
+        .take_while(|n| !n.is_element())
+        .filter(|n| n.is_comment())
+        .filter_map(|n| n.text())
+        .map(|text| Value::String(text.to_string()))
+        .collect();
+    comments.reverse();
+    comments
+}
+
+/// Comment nodes after the last child element, which no following element can carry.
+fn trailing_comments(node: &roxmltree::Node) -> Vec<Value> {
+    let mut comments: Vec<Value> = node
+        .children()
+        .rev()
+        .take_while(|n| !n.is_element())
+        .filter(|n| n.is_comment())
+        .filter_map(|n| n.text())
+        .map(|text| Value::String(text.to_string()))
+        .collect();
+    comments.reverse();
+    comments
+}
+
+/// Put `comments` ahead of any `fhir_comments` already recorded on the object.
+fn prepend_comments(obj: &mut Map<String, Value>, mut comments: Vec<Value>) {
+    if let Some(Value::Array(existing)) = obj.remove(FHIR_COMMENTS) {
+        comments.extend(existing);
+    }
+    obj.insert(FHIR_COMMENTS.to_string(), Value::Array(comments));
 }
 
 fn insert_json_property(
@@ -321,6 +1322,19 @@ fn insert_json_property(
     meta: Option<Value>,
     force_array: bool,
 ) {
+    // Complex elements carry their id and extensions inline; only primitives use `_name`.
+    let (value, meta) = match (value, meta) {
+        (value, meta) if decimal_literal(&value).is_some() => (value, meta),
+        (Value::Object(mut obj), Some(Value::Object(meta))) => {
+            for (key, item) in meta {
+                obj.entry(key).or_insert(item);
+            }
+            (Value::Object(obj), None)
+        }
+        (value @ Value::Object(_), _) => (value, None),
+        other => other,
+    };
+
     let entry = map.entry(name.to_string());
     match entry {
         serde_json::map::Entry::Vacant(v) => {
@@ -399,14 +1413,32 @@ fn insert_json_property(
     }
 }
 
-/// FHIR types that map to JSON numbers.
-const FHIR_NUMBER_TYPES: &[&str] = &[
-    "integer",
-    "positiveInt",
-    "unsignedInt",
-    "integer64",
+/// FHIR primitive types that map to JSON strings, whatever their lexical value looks like
+/// (`data="1234"`, `code="true"`).
+const FHIR_STRING_TYPES: &[&str] = &[
+    "string",
+    "code",
+    "id",
+    "markdown",
+    "uri",
+    "url",
+    "canonical",
+    "oid",
+    "uuid",
+    "base64Binary",
+    "instant",
+    "date",
+    "dateTime",
+    "time",
+    "xhtml",
 ];
 
+/// FHIR types that map to JSON numbers.
+const FHIR_NUMBER_TYPES: &[&str] = &["integer", "positiveInt", "unsignedInt"];
+
+/// FHIR integer types that map to JSON strings to preserve the full 64-bit range.
+const FHIR_STRING_INTEGER_TYPES: &[&str] = &["integer64"];
+
 /// FHIR types that map to JSON booleans.
 const FHIR_BOOLEAN_TYPES: &[&str] = &["boolean"];
 
@@ -415,6 +1447,9 @@ const FHIR_DECIMAL_TYPES: &[&str] = &["decimal"];
 
 fn parse_primitive(input: &str, fhir_type: Option<&str>) -> Value {
     if let Some(ft) = fhir_type {
+        if FHIR_STRING_TYPES.contains(&ft) {
+            return Value::String(input.to_string());
+        }
         if FHIR_BOOLEAN_TYPES.contains(&ft) {
             return match input {
                 "true" => Value::Bool(true),
@@ -428,11 +1463,16 @@ fn parse_primitive(input: &str, fhir_type: Option<&str>) -> Value {
             }
             return Value::String(input.to_string());
         }
+        if FHIR_STRING_INTEGER_TYPES.contains(&ft) {
+            if let Ok(int) = input.parse::<i64>() {
+                return Value::String(int.to_string());
+            }
+            return Value::String(input.to_string());
+        }
         if FHIR_DECIMAL_TYPES.contains(&ft) {
-            // FHIR decimals must preserve precision, so keep as string in JSON
-            // unless it's a simple integer value
-            if let Ok(n) = input.parse::<serde_json::Number>() {
-                return Value::Number(n);
+            // FHIR decimals must preserve precision, so the literal is written back verbatim
+            if input.parse::<serde_json::Number>().is_ok() {
+                return decimal_value(input);
             }
             return Value::String(input.to_string());
         }
@@ -440,23 +1480,252 @@ fn parse_primitive(input: &str, fhir_type: Option<&str>) -> Value {
         return Value::String(input.to_string());
     }
 
-    // No type info available — use heuristic (legacy behavior)
-    match input {
-        "true" => Value::Bool(true),
-        "false" => Value::Bool(false),
-        _ => {
-            if let Ok(int) = input.parse::<i64>() {
-                Value::Number(int.into())
-            } else {
-                Value::String(input.to_string())
+    // No type info available — use heuristic (legacy behavior)
+    match input {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => {
+            if let Ok(int) = input.parse::<i64>() {
+                Value::Number(int.into())
+            } else {
+                Value::String(input.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strict_options() -> XmlToJsonOptions {
+        XmlToJsonOptions {
+            strict: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resources_equal_ignores_member_order() {
+        let a = serde_json::json!({
+            "resourceType": "Observation",
+            "status": "final",
+            "code": { "coding": [{ "system": "http://loinc.org", "code": "8867-4" }] },
+            "valueQuantity": { "value": 72, "unit": "beats/min" }
+        });
+        let b: Value = serde_json::from_str(
+            r#"{
+                "valueQuantity": { "unit": "beats/min", "value": 72 },
+                "code": { "coding": [{ "code": "8867-4", "system": "http://loinc.org" }] },
+                "status": "final",
+                "resourceType": "Observation"
+            }"#,
+        )
+        .unwrap();
+        assert!(resources_equal(&a, &b));
+
+        let mut changed = b.clone();
+        changed["status"] = Value::from("amended");
+        assert!(!resources_equal(&a, &changed));
+
+        let mut missing = b.clone();
+        missing.as_object_mut().unwrap().remove("status");
+        assert!(!resources_equal(&a, &missing));
+        assert!(!resources_equal(&missing, &a));
+    }
+
+    #[test]
+    fn resources_equal_keeps_array_order_significant() {
+        let a = serde_json::json!({ "resourceType": "Patient", "name": [{ "given": ["Ann", "Marie"] }] });
+        let b = serde_json::json!({ "resourceType": "Patient", "name": [{ "given": ["Marie", "Ann"] }] });
+        assert!(!resources_equal(&a, &b));
+    }
+
+    #[test]
+    fn resources_equal_can_normalize_decimals() {
+        let observation = |value: &str| -> Value {
+            let json = format!(r#"{{"resourceType":"Observation","valueDecimal":{value}}}"#);
+            serde_json::from_str(&json).unwrap()
+        };
+        assert!(!resources_equal(&observation("1"), &observation("1.00")));
+
+        let options = CompareOptions {
+            normalize_decimals: true,
+        };
+        assert!(resources_equal_with_options(
+            &observation("1"),
+            &observation("1.00"),
+            &options
+        ));
+        assert!(!resources_equal_with_options(
+            &observation("1"),
+            &observation("1.01"),
+            &options
+        ));
+    }
+
+    #[test]
+    fn sniff_resource_type_reads_json_and_xml() {
+        let json = r#"{
+            "id": "obs-1",
+            "code": { "coding": [{ "code": "8867-4", "display": "{\"resourceType\": \"Patient\"}" }] },
+            "contained": [{ "resourceType": "Patient", "id": "p1" }],
+            "resourceType": "Observation",
+            "status": "final"
+        }"#;
+        assert_eq!(sniff_resource_type(json).unwrap(), "Observation");
+        assert_eq!(
+            sniff_resource_type("\u{feff} {\"resourceType\":\"Patient\"}").unwrap(),
+            "Patient"
+        );
+
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- a comment -->
+<Bundle xmlns="http://hl7.org/fhir"><entry><resource><Patient/></resource></entry></Bundle>"#;
+        assert_eq!(sniff_resource_type(xml).unwrap(), "Bundle");
+        assert_eq!(
+            sniff_resource_type(r#"<Patient xmlns="http://hl7.org/fhir"/>"#).unwrap(),
+            "Patient"
+        );
+
+        assert!(matches!(
+            sniff_resource_type(r#"{"id": "p1"}"#),
+            Err(FormatError::MissingResourceType)
+        ));
+        assert!(matches!(
+            sniff_resource_type("[1, 2]"),
+            Err(FormatError::Json(_))
+        ));
+        assert!(matches!(
+            sniff_resource_type("<!-- only a comment -->"),
+            Err(FormatError::MissingResourceType)
+        ));
+    }
+
+    #[test]
+    fn parse_strict_rejects_duplicate_keys() {
+        assert!(matches!(
+            parse_strict(r#"{"resourceType": "Patient", "id": "a", "id": "b"}"#),
+            Err(FormatError::DuplicateKey(path)) if path == "id"
+        ));
+        assert!(matches!(
+            parse_strict(
+                r#"{"resourceType": "Patient", "name": [{"given": ["A"]}, {"family": "X", "family": "Y"}]}"#
+            ),
+            Err(FormatError::DuplicateKey(path)) if path == "name[1].family"
+        ));
+
+        // serde_json silently keeps the last value
+        let lenient: Value = serde_json::from_str(r#"{"id": "a", "id": "b"}"#).unwrap();
+        assert_eq!(lenient["id"], "b");
+
+        let json = r#"{"resourceType": "Patient", "id": "p1", "active": true, "multipleBirthInteger": 2, "name": [{"family": "Doe"}], "deceasedBoolean": null}"#;
+        assert_eq!(
+            parse_strict(json).unwrap(),
+            serde_json::from_str::<Value>(json).unwrap()
+        );
+        assert!(matches!(
+            parse_strict(r#"{"id": "p1"} trailing"#),
+            Err(FormatError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn bundle_to_ndjson_writes_one_resource_per_line() {
+        let json = r#"{
+            "resourceType": "Bundle",
+            "type": "collection",
+            "entry": [
+                { "fullUrl": "urn:uuid:1", "resource": { "resourceType": "Patient", "id": "p1", "name": [{ "text": "A  B" }] } },
+                { "request": { "method": "DELETE", "url": "Patient/p2" } },
+                { "resource": {
+                    "resourceType": "Observation",
+                    "id": "o1",
+                    "valueQuantity": { "value": 2.50 }
+                } }
+            ]
+        }"#;
+        let ndjson = bundle_to_ndjson(json).unwrap();
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                r#"{"resourceType":"Patient","id":"p1","name":[{"text":"A  B"}]}"#,
+                r#"{"resourceType":"Observation","id":"o1","valueQuantity":{"value":2.50}}"#,
+            ]
+        );
+        assert!(ndjson.ends_with('\n'));
+
+        let xml = r#"<Bundle xmlns="http://hl7.org/fhir">
+  <type value="collection"/>
+  <entry><resource><Patient><id value="p1"/></Patient></resource></entry>
+  <entry><resource><Observation><id value="o1"/><status value="final"/></Observation></resource></entry>
+</Bundle>"#;
+        let resource_types: Vec<String> = bundle_to_ndjson(xml)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let value: Value = serde_json::from_str(line).unwrap();
+                value["resourceType"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(resource_types, vec!["Patient", "Observation"]);
+
+        assert!(matches!(
+            bundle_to_ndjson(r#"{"resourceType": "Patient", "id": "p1"}"#),
+            Err(FormatError::NotABundle(t)) if t == "Patient"
+        ));
+        assert!(matches!(
+            bundle_to_ndjson(r#"<Patient xmlns="http://hl7.org/fhir"/>"#),
+            Err(FormatError::NotABundle(_))
+        ));
+    }
+
+    #[test]
+    fn ndjson_stream_converts_each_line() {
+        let ndjson = concat!(
+            "{\"resourceType\":\"Patient\",\"id\":\"p1\"}\n",
+            "\n",
+            "{\"resourceType\":\"Observation\",\"id\":\"o1\",\"status\":\"final\"}\n",
+        );
+        let mut out = Vec::new();
+        let options = NdjsonToXmlOptions {
+            delimiter: "\n---\n".to_string(),
+            ..Default::default()
+        };
+        let count =
+            ndjson_to_xml_stream_with_options(ndjson.as_bytes(), &mut out, &options).unwrap();
+        assert_eq!(count, 2);
+
+        let out = String::from_utf8(out).unwrap();
+        let docs: Vec<&str> = out.split_terminator("\n---\n").collect();
+        assert_eq!(docs.len(), 2);
+        assert!(docs[0].starts_with("<Patient xmlns=\"http://hl7.org/fhir\">"));
+        assert!(docs[1].contains(r#"<status value="final"/>"#));
+    }
+
+    #[test]
+    fn ndjson_stream_reports_failing_line() {
+        let ndjson = concat!(
+            "{\"resourceType\":\"Patient\",\"id\":\"p1\"}\n",
+            "\n",
+            "{\"id\":\"no-type\"}\n",
+            "{\"resourceType\":\"Patient\",\"id\":\"p2\"}\n",
+        );
+        let mut out = Vec::new();
+        let err = ndjson_to_xml_stream(ndjson.as_bytes(), &mut out).unwrap_err();
+        match err {
+            FormatError::Record { line, source } => {
+                assert_eq!(line, 3);
+                assert!(matches!(*source, FormatError::MissingResourceType));
             }
+            other => panic!("expected a record error, got {other}"),
         }
+        // Records before the failing line have been written
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(r#"<id value="p1"/>"#));
+        assert!(!out.contains("p2"));
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
     fn json_to_xml_basic_patient() {
@@ -478,6 +1747,162 @@ mod tests {
         assert!(xml.contains(r#"<family value="Everyman"/>"#));
     }
 
+    #[test]
+    fn json_to_xml_keeps_decimal_precision() {
+        let json = r#"
+        {
+            "resourceType": "Observation",
+            "valueQuantity": { "value": 2.0, "unit": "mg" },
+            "component": [
+                { "valueDecimal": 2.50 },
+                { "valueInteger": -3 },
+                { "valueDecimal": 1.0e2 },
+                { "valueString": "2.50 \\\" 1.0" }
+            ]
+        }
+        "#;
+
+        let xml = json_to_xml(json).expect("conversion failed");
+        assert!(xml.contains(r#"<value value="2.0"/>"#));
+        assert!(xml.contains(r#"<valueDecimal value="2.50"/>"#));
+        assert!(xml.contains(r#"<valueInteger value="-3"/>"#));
+        assert!(xml.contains(r#"<valueDecimal value="1.0e2"/>"#));
+        assert!(xml.contains(r#"<valueString value="2.50 \&quot; 1.0"/>"#));
+
+        assert!(json_to_xml(r#"{"resourceType": "Basic", "n": 01}"#).is_err());
+    }
+
+    #[test]
+    fn decimal_literals_round_trip_unchanged() {
+        let json = r#"{"resourceType":"Observation","component":[{"valueDecimal":1.50},{"valueDecimal":1.0e2},{"valueDecimal":100}],"valueQuantity":{"value":-0.010}}"#;
+
+        let xml = json_to_xml(json).unwrap();
+        for literal in ["1.50", "1.0e2", "100", "-0.010"] {
+            assert!(xml.contains(&format!(r#"value="{literal}""#)), "{literal}");
+        }
+
+        let back = xml_to_json(&xml).unwrap();
+        for literal in ["1.50", "1.0e2", "100", "-0.010"] {
+            assert!(back.contains(&format!(": {literal}\n")), "{back}");
+        }
+        assert!(!back.contains(DECIMAL_KEY));
+        let val: Value = serde_json::from_str(&back).unwrap();
+        assert!(val["component"][2]["valueDecimal"].is_number());
+    }
+
+    #[test]
+    fn decimals_keep_extensions_and_strings_stay_strings() {
+        let xml = "<Observation xmlns=\"http://hl7.org/fhir\">\
+            <status value=\"\u{E000}1.5\"/>\
+            <valueDecimal id=\"d\" value=\"2.50\">\
+            <extension url=\"http://example.org/x\"><valueString value=\"y\"/></extension>\
+            </valueDecimal></Observation>";
+
+        let back = xml_to_json(xml).unwrap();
+        let val: Value = serde_json::from_str(&back).unwrap();
+        assert_eq!(val["status"], "\u{E000}1.5");
+        assert!(back.contains(": 2.50"), "{back}");
+        assert_eq!(val["_valueDecimal"]["id"], "d");
+        assert_eq!(
+            val["_valueDecimal"]["extension"][0]["url"],
+            "http://example.org/x"
+        );
+    }
+
+    #[test]
+    fn json_layout_follows_options() {
+        let xml = r#"<Observation xmlns="http://hl7.org/fhir">
+            <status value="final"/>
+            <valueQuantity><value value="1.50"/></valueQuantity>
+        </Observation>"#;
+
+        let compact = xml_to_json_with_options(
+            xml,
+            &XmlToJsonOptions {
+                json: JsonOptions {
+                    pretty: false,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .output;
+        assert!(!compact.contains('\n'), "{compact}");
+        assert!(compact.contains(r#""value":1.50"#), "{compact}");
+
+        let indented = xml_to_json_with_options(
+            xml,
+            &XmlToJsonOptions {
+                json: JsonOptions {
+                    pretty: true,
+                    indent: 4,
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .output;
+        assert!(indented.contains("\n    \"status\""), "{indented}");
+        assert!(indented.contains("\n        \"value\": 1.50"), "{indented}");
+
+        // The default layout is unchanged
+        assert!(xml_to_json(xml).unwrap().contains("\n  \"status\""));
+    }
+
+    #[test]
+    fn xml_parse_errors_report_line_and_column() {
+        let xml = "<Patient xmlns=\"http://hl7.org/fhir\">\n  <id value=\"p1\"/>\n  <active value=\"true\">\n</Patient>";
+        let err = xml_to_json(xml).unwrap_err();
+        match &err {
+            FormatError::XmlAt {
+                line,
+                column,
+                message,
+            } => {
+                assert_eq!((*line, *column), (4, 1));
+                assert!(message.contains("active"), "{message}");
+                assert!(!message.contains(" at "), "{message}");
+            }
+            other => panic!("expected XmlAt, got {other:?}"),
+        }
+        assert!(err.to_string().starts_with("XML parse error at 4:1: "));
+
+        // Errors about the document as a whole carry no position
+        assert!(matches!(xml_to_json("   "), Err(FormatError::Xml(_))));
+    }
+
+    #[test]
+    fn bom_and_xml_declaration_are_accepted() {
+        let xml = concat!(
+            "\u{feff}<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            r#"<Patient xmlns="http://hl7.org/fhir"><id value="p1"/></Patient>"#
+        );
+        let value: Value =
+            serde_json::from_str(&xml_to_json(xml).expect("conversion failed")).unwrap();
+        assert_eq!(value["resourceType"], "Patient");
+        assert_eq!(value["id"], "p1");
+
+        let json = concat!("\u{feff}", r#"{"resourceType": "Patient", "id": "p1"}"#);
+        let xml = json_to_xml(json).expect("conversion failed");
+        assert!(xml.starts_with("<Patient"));
+    }
+
+    #[test]
+    fn json_to_xml_optionally_writes_declaration() {
+        let json = r#"{"resourceType": "Patient", "id": "p1"}"#;
+        let options = JsonToXmlOptions {
+            xml_declaration: true,
+            ..Default::default()
+        };
+        let xml = json_to_xml_with_options(json, &options).expect("conversion failed");
+        assert!(
+            xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Patient"),
+            "{xml}"
+        );
+        assert!(!json_to_xml(json).unwrap().starts_with("<?xml"));
+    }
+
     #[test]
     fn xml_to_json_round_trip() {
         let xml = r#"
@@ -500,10 +1925,49 @@ mod tests {
         assert!(value["name"].is_array(), "name should be an array");
         assert_eq!(value["name"][0]["family"], "Everyman");
         // given is also an array field
-        assert!(value["name"][0]["given"].is_array(), "given should be an array");
+        assert!(
+            value["name"][0]["given"].is_array(),
+            "given should be an array"
+        );
         assert_eq!(value["name"][0]["given"][0], "Adam");
     }
 
+    #[test]
+    fn converter_uses_its_own_metadata() {
+        let xml = r#"<Patient xmlns="http://hl7.org/fhir"><name><family value="Chalmers"/></name><multipleBirthInteger value="2"/></Patient>"#;
+
+        let default = FhirFormatConverter::default();
+        let val: Value = serde_json::from_str(&default.to_json(xml).unwrap()).unwrap();
+        assert_eq!(val["name"][0]["family"], "Chalmers");
+        assert_eq!(val["multipleBirthInteger"], 2);
+        assert_eq!(xml_to_json(xml).unwrap(), default.to_json(xml).unwrap());
+
+        // Metadata where Patient.name does not repeat and multipleBirth[x] is unknown
+        let custom = FhirFormatConverter::from_metadata_json(
+            r#"{"Patient": {"name": {"type": "HumanName", "multiple": false}}}"#,
+        )
+        .unwrap();
+        let val: Value = serde_json::from_str(&custom.to_json(xml).unwrap()).unwrap();
+        assert_eq!(val["name"]["family"], "Chalmers");
+        assert_eq!(val["multipleBirthInteger"], 2);
+
+        let mut metadata = TypeMetadata::new();
+        metadata.entry("Patient".to_string()).or_default().insert(
+            "multipleBirth[x]".to_string(),
+            PropMeta {
+                type_name: "string".to_string(),
+                multiple: false,
+            },
+        );
+        let custom = FhirFormatConverter::with_metadata(metadata);
+        let val: Value = serde_json::from_str(&custom.to_json(xml).unwrap()).unwrap();
+        assert_eq!(val["multipleBirthInteger"], 2);
+
+        let json = custom.to_json(xml).unwrap();
+        assert_eq!(custom.to_xml(&json).unwrap(), json_to_xml(&json).unwrap());
+        assert!(FhirFormatConverter::from_metadata_json("[]").is_err());
+    }
+
     #[test]
     fn xml_to_json_single_element_array() {
         // StructureDefinition with a single differential element should produce an array
@@ -553,6 +2017,51 @@ mod tests {
         assert!(!value["id"].is_array(), "id should be scalar");
     }
 
+    #[test]
+    fn meta_round_trips_with_array_cardinality() {
+        let json = r#"
+        {
+            "resourceType": "Observation",
+            "meta": {
+                "versionId": "3",
+                "lastUpdated": "2024-05-01T10:15:30.123Z",
+                "profile": ["http://hl7.org/fhir/StructureDefinition/vitalsigns"],
+                "tag": [
+                    { "system": "http://example.org/tags", "code": "a" },
+                    { "system": "http://example.org/tags", "code": "b" }
+                ],
+                "security": [
+                    { "system": "http://terminology.hl7.org/CodeSystem/v3-Confidentiality", "code": "N" }
+                ]
+            },
+            "status": "final"
+        }
+        "#;
+
+        let xml = json_to_xml(json).unwrap();
+        assert_eq!(xml.matches("<profile ").count(), 1);
+        assert_eq!(xml.matches("<tag>").count(), 2);
+
+        let back = xml_to_json(&xml).unwrap();
+        let val: Value = serde_json::from_str(&back).unwrap();
+        let meta = &val["meta"];
+        // A single profile must still be an array on the way back
+        assert_eq!(
+            meta["profile"],
+            serde_json::json!(["http://hl7.org/fhir/StructureDefinition/vitalsigns"])
+        );
+        assert!(meta["security"].is_array(), "security should be an array");
+        assert_eq!(meta["tag"].as_array().map(Vec::len), Some(2));
+        assert_eq!(meta["tag"][1]["code"], "b");
+        assert_eq!(meta["versionId"], "3");
+        assert_eq!(meta["lastUpdated"], "2024-05-01T10:15:30.123Z");
+        assert_eq!(
+            val,
+            serde_json::from_str::<Value>(json).unwrap(),
+            "meta should round-trip unchanged"
+        );
+    }
+
     #[test]
     fn primitive_metadata_survives_roundtrip() {
         let json = r#"
@@ -572,5 +2081,526 @@ mod tests {
         let val: Value = serde_json::from_str(&back).unwrap();
         assert_eq!(val["birthDate"], "1974-12-25");
         assert_eq!(val["_birthDate"]["id"], "bd1");
+
+        // Comments before the root, between repeated elements and before primitives
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <!-- exported by a legacy system -->
+        <Patient xmlns="http://hl7.org/fhir">
+            <!-- official name -->
+            <name>
+                <family value="Chalmers"/>
+            </name>
+            <!-- nickname -->
+            <name>
+                <given value="Jim"/>
+            </name>
+            <!-- as reported -->
+            <birthDate id="bd1" value="1974-12-25"/>
+        </Patient>
+        "#;
+
+        let val: Value = serde_json::from_str(&xml_to_json(xml).unwrap()).unwrap();
+        assert_eq!(
+            val["fhir_comments"],
+            serde_json::json!([" exported by a legacy system "])
+        );
+        assert_eq!(
+            val["name"][0]["fhir_comments"],
+            serde_json::json!([" official name "])
+        );
+        assert_eq!(
+            val["name"][1]["fhir_comments"],
+            serde_json::json!([" nickname "])
+        );
+        assert_eq!(val["name"][1]["given"], serde_json::json!(["Jim"]));
+        assert_eq!(
+            val["_birthDate"],
+            serde_json::json!({ "id": "bd1", "fhir_comments": [" as reported "] })
+        );
+
+        let xml = json_to_xml(&val.to_string()).unwrap();
+        let root = xml.find("<Patient").unwrap();
+        assert!(xml.find("<!-- exported by a legacy system -->").unwrap() < root);
+        let back: Value = serde_json::from_str(&xml_to_json(&xml).unwrap()).unwrap();
+        assert_eq!(back, val);
+    }
+
+    #[test]
+    fn comments_cannot_close_early() {
+        for comment in [" x --><evil/><!-- ", "a--b", "trailing-"] {
+            let patient = serde_json::json!({
+                "resourceType": "Patient",
+                "name": [{ "fhir_comments": [comment], "family": "Chalmers" }]
+            });
+            assert!(matches!(
+                json_to_xml(&patient.to_string()),
+                Err(FormatError::InvalidComment(c)) if c == comment
+            ));
+        }
+    }
+
+    #[test]
+    fn repeated_complex_elements_keep_ids_inline() {
+        let xml = r#"<Patient xmlns="http://hl7.org/fhir">
+            <name><family value="Smith"/></name>
+            <name id="n2"><family value="Jones"/></name>
+            <name><family value="Brown"/></name>
+        </Patient>"#;
+
+        let val: Value = serde_json::from_str(&xml_to_json(xml).unwrap()).unwrap();
+        assert!(
+            val.get("_name").is_none(),
+            "complex elements have no _name: {val}"
+        );
+        assert_eq!(
+            val["name"],
+            serde_json::json!([
+                { "family": "Smith" },
+                { "id": "n2", "family": "Jones" },
+                { "family": "Brown" }
+            ])
+        );
+    }
+
+    #[test]
+    fn integer64_is_emitted_as_json_string() {
+        assert_eq!(
+            parse_primitive("9007199254740993", Some("integer64")),
+            Value::String("9007199254740993".to_string())
+        );
+        assert_eq!(
+            parse_primitive("+42", Some("integer64")),
+            Value::String("42".to_string())
+        );
+        assert_eq!(
+            parse_primitive("42", Some("integer")),
+            Value::Number(42.into())
+        );
+    }
+
+    #[test]
+    fn integer64_round_trip_beyond_2_pow_53() {
+        // 2^53 + 1 cannot be represented exactly as an IEEE-754 double.
+        let json = r#"
+        {
+            "resourceType": "Patient",
+            "extension": [
+                { "url": "http://example.org/big", "valueInteger64": "9007199254740993" }
+            ]
+        }
+        "#;
+
+        let xml = json_to_xml(json).unwrap();
+        assert!(xml.contains(r#"<valueInteger64 value="9007199254740993"/>"#));
+
+        let back = xml_to_json(&xml).unwrap();
+        let val: Value = serde_json::from_str(&back).unwrap();
+        assert_eq!(
+            val["extension"][0]["valueInteger64"],
+            Value::String("9007199254740993".to_string())
+        );
+
+        let xml_again = json_to_xml(&back).unwrap();
+        assert_eq!(xml, xml_again);
+    }
+
+    #[test]
+    fn choice_type_properties_are_typed() {
+        let xml = r#"
+        <Patient xmlns="http://hl7.org/fhir">
+            <extension url="http://example.org/s">
+                <valueString value="123"/>
+            </extension>
+            <extension url="http://example.org/i">
+                <valueInteger value="123"/>
+            </extension>
+        </Patient>
+        "#;
+
+        let json = xml_to_json(xml).unwrap();
+        let val: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(val["extension"][0]["valueString"], "123");
+        assert_eq!(val["extension"][1]["valueInteger"], 123);
+    }
+
+    #[test]
+    fn extension_url_attribute_round_trips() {
+        let xml = r#"
+        <Patient xmlns="http://hl7.org/fhir">
+            <extension url="http://example.org/s">
+                <valueString value="x"/>
+            </extension>
+        </Patient>
+        "#;
+
+        let conversion = xml_to_json_with_options(xml, &XmlToJsonOptions::default()).unwrap();
+        assert!(conversion.warnings.is_empty());
+        let val: Value = serde_json::from_str(&conversion.output).unwrap();
+        assert_eq!(val["extension"][0]["url"], "http://example.org/s");
+
+        let back = json_to_xml(&conversion.output).unwrap();
+        assert!(back.contains(r#"<extension url="http://example.org/s">"#));
+    }
+
+    #[test]
+    fn unexpected_attribute_warns_or_fails_in_strict_mode() {
+        let xml = r#"
+        <Patient xmlns="http://hl7.org/fhir">
+            <birthDate value="1970-01-01" precision="year"/>
+        </Patient>
+        "#;
+
+        let conversion = xml_to_json_with_options(xml, &XmlToJsonOptions::default()).unwrap();
+        let val: Value = serde_json::from_str(&conversion.output).unwrap();
+        assert_eq!(val["birthDate"], "1970-01-01");
+        assert_eq!(conversion.warnings.len(), 1);
+        assert_eq!(conversion.warnings[0].path, "Patient.birthDate");
+        assert!(conversion.warnings[0].message.contains("precision"));
+
+        let err = xml_to_json_with_options(xml, &strict_options()).unwrap_err();
+        match err {
+            FormatError::UnexpectedAttribute { path, attribute } => {
+                assert_eq!(path, "Patient.birthDate");
+                assert_eq!(attribute, "precision");
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
+    #[test]
+    fn large_signature_data_round_trips() {
+        let data = "QUJD".repeat(500_000);
+        let provenance = serde_json::json!({
+            "resourceType": "Provenance",
+            "recorded": "2024-01-01T00:00:00Z",
+            "signature": [{
+                "type": [{"system": "urn:iso-astm:E1762-95:2013", "code": "1.2.840.10065.1.12.1.1"}],
+                "when": "2024-01-01T00:00:00Z",
+                "who": {"reference": "Practitioner/p1"},
+                "data": data
+            }]
+        });
+
+        let xml = json_to_xml(&provenance.to_string()).unwrap();
+        let conversion = xml_to_json_with_options(&xml, &strict_options()).unwrap();
+        assert!(conversion.warnings.is_empty());
+        let back: Value = serde_json::from_str(&conversion.output).unwrap();
+        assert_eq!(back, provenance);
+    }
+
+    #[test]
+    fn string_mapped_primitives_are_never_coerced() {
+        for fhir_type in FHIR_STRING_TYPES {
+            for input in ["1234", "true", "1.50"] {
+                assert_eq!(
+                    parse_primitive(input, Some(fhir_type)),
+                    Value::String(input.to_string()),
+                    "{fhir_type}"
+                );
+            }
+        }
+
+        let signature = &FHIR_TYPE_METADATA["Signature"];
+        assert_eq!(signature["when"].type_name, "instant");
+        assert_eq!(signature["data"].type_name, "base64Binary");
+        assert_eq!(
+            FHIR_TYPE_METADATA["Bundle"]["timestamp"].type_name,
+            "instant"
+        );
+        assert_eq!(
+            lookup_choice_type(&FHIR_TYPE_METADATA, Some("Extension"), "valueOid"),
+            Some(("oid".to_string(), false))
+        );
+    }
+
+    #[test]
+    fn resource_wrappers_produce_resource_objects() {
+        let xml = r##"
+        <Bundle xmlns="http://hl7.org/fhir">
+            <type value="collection"/>
+            <entry>
+                <fullUrl value="urn:uuid:0c3151bd-1cbf-4d64-b04d-cd9187a4c6e0"/>
+                <resource>
+                    <Observation>
+                        <id value="o1"/>
+                        <contained>
+                            <Patient>
+                                <id value="p1"/>
+                            </Patient>
+                        </contained>
+                        <status value="final"/>
+                        <subject>
+                            <reference value="#p1"/>
+                        </subject>
+                    </Observation>
+                </resource>
+            </entry>
+        </Bundle>
+        "##;
+
+        let val: Value = serde_json::from_str(&xml_to_json(xml).unwrap()).unwrap();
+        let observation = &val["entry"][0]["resource"];
+        assert_eq!(observation["resourceType"], "Observation");
+        assert_eq!(observation["status"], "final");
+        assert!(observation.get("Observation").is_none());
+        assert_eq!(
+            observation["contained"],
+            serde_json::json!([{ "resourceType": "Patient", "id": "p1" }])
+        );
+
+        // Without type metadata for the parent, the wrapper is recognised by its shape
+        let unknown = r#"
+        <CustomResource xmlns="http://hl7.org/fhir">
+            <contained>
+                <Patient>
+                    <id value="p1"/>
+                </Patient>
+            </contained>
+            <detail>
+                <code value="x"/>
+            </detail>
+        </CustomResource>
+        "#;
+        let val: Value = serde_json::from_str(&xml_to_json(unknown).unwrap()).unwrap();
+        assert_eq!(val["contained"]["resourceType"], "Patient");
+        assert_eq!(val["contained"]["id"], "p1");
+        assert_eq!(val["detail"]["code"], "x");
+    }
+
+    #[test]
+    fn bundle_entries_use_their_own_resource_type() {
+        let xml = r#"
+        <Bundle xmlns="http://hl7.org/fhir">
+            <type value="collection"/>
+            <entry>
+                <resource>
+                    <Patient>
+                        <name><family value="Smith"/><given value="Ann"/></name>
+                    </Patient>
+                </resource>
+            </entry>
+            <entry>
+                <resource>
+                    <Observation>
+                        <status value="final"/>
+                        <category><text value="vital-signs"/></category>
+                        <code><text value="Heart rate"/></code>
+                    </Observation>
+                </resource>
+            </entry>
+        </Bundle>
+        "#;
+
+        let val: Value = serde_json::from_str(&xml_to_json(xml).unwrap()).unwrap();
+        let patient = &val["entry"][0]["resource"];
+        assert_eq!(patient["resourceType"], "Patient");
+        assert_eq!(
+            patient["name"],
+            serde_json::json!([{ "family": "Smith", "given": ["Ann"] }])
+        );
+        let observation = &val["entry"][1]["resource"];
+        assert_eq!(observation["resourceType"], "Observation");
+        assert_eq!(
+            observation["category"],
+            serde_json::json!([{ "text": "vital-signs" }])
+        );
+        assert_eq!(observation["code"]["text"], "Heart rate");
+
+        let back = json_to_xml(&val.to_string()).unwrap();
+        assert!(back.contains("<Observation>"), "{back}");
+        let again: Value = serde_json::from_str(&xml_to_json(&back).unwrap()).unwrap();
+        assert_eq!(again, val);
+    }
+
+    #[test]
+    fn outcome_and_parameter_resources_are_inlined() {
+        let xml = r#"
+        <Bundle xmlns="http://hl7.org/fhir">
+            <type value="transaction-response"/>
+            <entry>
+                <response>
+                    <status value="400 Bad Request"/>
+                    <outcome>
+                        <OperationOutcome>
+                            <issue>
+                                <severity value="error"/>
+                                <code value="invalid"/>
+                            </issue>
+                        </OperationOutcome>
+                    </outcome>
+                </response>
+            </entry>
+        </Bundle>
+        "#;
+        let val: Value = serde_json::from_str(&xml_to_json(xml).unwrap()).unwrap();
+        let outcome = &val["entry"][0]["response"]["outcome"];
+        assert_eq!(outcome["resourceType"], "OperationOutcome");
+        assert_eq!(outcome["issue"][0]["code"], "invalid");
+        assert!(outcome.get("OperationOutcome").is_none());
+
+        let xml = r#"
+        <Parameters xmlns="http://hl7.org/fhir">
+            <parameter>
+                <name value="return"/>
+                <resource>
+                    <Patient>
+                        <id value="p1"/>
+                        <active value="true"/>
+                    </Patient>
+                </resource>
+            </parameter>
+        </Parameters>
+        "#;
+        let val: Value = serde_json::from_str(&xml_to_json(xml).unwrap()).unwrap();
+        assert_eq!(
+            val["parameter"][0]["resource"],
+            serde_json::json!({ "resourceType": "Patient", "id": "p1", "active": true })
+        );
+    }
+
+    #[test]
+    fn bundle_signature_round_trips() {
+        // `1234` is valid base64 that would read as a number without type metadata
+        let bundle = serde_json::json!({
+            "resourceType": "Bundle",
+            "type": "document",
+            "timestamp": "2024-01-01T10:00:00.000Z",
+            "signature": {
+                "type": [{"system": "urn:iso-astm:E1762-95:2013", "code": "1.2.840.10065.1.12.1.1"}],
+                "when": "2024-01-01T10:00:00.000+01:00",
+                "who": {"reference": "Practitioner/p1"},
+                "sigFormat": "application/signature+xml",
+                "data": "1234"
+            }
+        });
+
+        let xml = json_to_xml(&bundle.to_string()).unwrap();
+        assert!(xml.contains(r#"<when value="2024-01-01T10:00:00.000+01:00"/>"#));
+        assert!(xml.contains(r#"<data value="1234"/>"#));
+
+        let conversion = xml_to_json_with_options(&xml, &strict_options()).unwrap();
+        assert!(conversion.warnings.is_empty());
+        let back: Value = serde_json::from_str(&conversion.output).unwrap();
+        assert_eq!(back, bundle);
+    }
+
+    #[test]
+    fn invalid_base64_warns_or_fails_in_strict_mode() {
+        let xml = r#"
+        <Binary xmlns="http://hl7.org/fhir">
+            <contentType value="text/plain"/>
+            <data value="not base64!"/>
+        </Binary>
+        "#;
+
+        let conversion = xml_to_json_with_options(xml, &XmlToJsonOptions::default()).unwrap();
+        assert_eq!(conversion.warnings.len(), 1);
+        assert_eq!(conversion.warnings[0].path, "Binary.data");
+
+        let err = xml_to_json_with_options(xml, &strict_options()).unwrap_err();
+        assert!(matches!(err, FormatError::InvalidBase64(path) if path == "Binary.data"));
+
+        let wrapped =
+            "<Binary xmlns=\"http://hl7.org/fhir\"><data value=\"SGVs\n  bG8=\"/></Binary>";
+        assert!(xml_to_json_with_options(wrapped, &strict_options()).is_ok());
+        assert!(!is_valid_base64("SGVsbG8"));
+        assert!(!is_valid_base64("SG=sbG8="));
+        assert!(!is_valid_base64("S==="));
+    }
+
+    #[test]
+    fn nested_resources_inherit_namespace_by_default() {
+        let json = r#"
+        {
+            "resourceType": "Bundle",
+            "type": "collection",
+            "entry": [
+                {
+                    "fullUrl": "urn:uuid:1",
+                    "resource": {
+                        "resourceType": "Patient",
+                        "id": "p1",
+                        "contained": [{ "resourceType": "Organization", "id": "o1" }]
+                    }
+                }
+            ]
+        }
+        "#;
+
+        let xml = json_to_xml(json).unwrap();
+        assert_eq!(xml.matches("xmlns=").count(), 1);
+        assert!(xml.contains(r#"<Bundle xmlns="http://hl7.org/fhir">"#));
+        assert!(xml.contains("<Patient>"));
+        assert!(xml.contains("<Organization>"));
+        assert!(xml.contains(r#"<id value="p1"/>"#));
+
+        let back: Value = serde_json::from_str(&xml_to_json(&xml).unwrap()).unwrap();
+        let patient = &back["entry"][0]["resource"];
+        assert_eq!(patient["resourceType"], "Patient");
+        assert_eq!(patient["id"], "p1");
+        assert_eq!(patient["contained"][0]["resourceType"], "Organization");
+        assert_eq!(patient["contained"][0]["id"], "o1");
+    }
+
+    #[test]
+    fn nested_resources_can_declare_namespace() {
+        let json = r#"
+        {
+            "resourceType": "Patient",
+            "contained": [{ "resourceType": "Organization", "id": "o1" }]
+        }
+        "#;
+
+        let options = JsonToXmlOptions {
+            namespace_nested_resources: true,
+            ..Default::default()
+        };
+        let xml = json_to_xml_with_options(json, &options).unwrap();
+        assert!(xml.contains(r#"<Patient xmlns="http://hl7.org/fhir">"#));
+        assert!(xml.contains(r#"<Organization xmlns="http://hl7.org/fhir">"#));
+
+        let back: Value = serde_json::from_str(&xml_to_json(&xml).unwrap()).unwrap();
+        assert_eq!(back["contained"][0]["resourceType"], "Organization");
+    }
+
+    #[test]
+    fn code_values_are_never_coerced_to_booleans() {
+        assert_eq!(
+            lookup_prop_meta(&FHIR_TYPE_METADATA, Some("Patient"), "gender")
+                .map(|m| m.type_name.as_str()),
+            Some("code")
+        );
+        assert_eq!(
+            lookup_prop_meta(&FHIR_TYPE_METADATA, Some("Patient"), "active")
+                .map(|m| m.type_name.as_str()),
+            Some("boolean")
+        );
+        assert_eq!(
+            parse_primitive("true", Some("code")),
+            Value::String("true".to_string())
+        );
+
+        // Content references (ValueSet.expansion.contains.contains) resolve to the
+        // referenced element, so nested codes keep their type too.
+        let xml = r#"
+        <ValueSet xmlns="http://hl7.org/fhir">
+            <status value="active"/>
+            <expansion>
+                <timestamp value="2024-01-01"/>
+                <contains>
+                    <code value="true"/>
+                    <contains>
+                        <abstract value="false"/>
+                        <code value="true"/>
+                    </contains>
+                </contains>
+            </expansion>
+        </ValueSet>
+        "#;
+        let val: Value = serde_json::from_str(&xml_to_json(xml).unwrap()).unwrap();
+        let contains = &val["expansion"]["contains"][0];
+        assert_eq!(contains["code"], "true");
+        assert_eq!(contains["contains"][0]["code"], "true");
+        assert_eq!(contains["contains"][0]["abstract"], false);
     }
 }
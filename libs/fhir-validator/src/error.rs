@@ -1,10 +1,28 @@
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum ConfigError {
     #[error("terminology validation is required when using ReferenceMode::Full")]
     TerminologyRequiredForFullRef,
 
+    #[error("TerminologyMode::{mode:?} requires a terminology endpoint (terminology.endpoint)")]
+    TerminologyEndpointRequired { mode: crate::TerminologyMode },
+
+    #[error(
+        "TerminologyMode::Hybrid without a terminology endpoint (terminology.endpoint) validates codes locally only"
+    )]
+    HybridTerminologyWithoutEndpoint,
+
+    #[error(
+        "BundleMode::On has no effect when validating against logical model {logical_model}: instances are never Bundles"
+    )]
+    BundleModeWithLogicalModel { logical_model: String },
+
+    #[error(
+        "ProfilesMode::On with an empty explicit_profiles list resolves no profiles; remove the list to use meta.profile"
+    )]
+    NoResolvableProfiles,
+
     #[error("FHIR version mismatch: expected {expected:?}, got {got:?}")]
     FhirVersionMismatch {
         expected: crate::FhirVersion,
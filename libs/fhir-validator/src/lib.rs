@@ -54,7 +54,10 @@ pub use plan::{
     ValidationPlan,
 };
 pub use terminology::{CodeValidationResult, TerminologyProvider};
-pub use validator::{IssueCode, IssueSeverity, ValidationIssue, ValidationOutcome, Validator};
+pub use validator::{
+    fhirpath_to_json_pointer, IssueCode, IssueSeverity, ValidationIssue, ValidationOutcome,
+    Validator,
+};
 
 // ============================================================================
 // Core Config
@@ -185,6 +188,14 @@ pub struct SchemaConfig {
     pub allow_unknown_elements: bool,
     #[serde(default)]
     pub allow_modifier_extensions: bool,
+    /// How to treat extensions whose URL has no Extension StructureDefinition in the context.
+    #[serde(default)]
+    pub extension_policy: ExtensionPolicy,
+    /// Canonical URL of a logical model (StructureDefinition with `kind = logical`).
+    /// If provided, instances are validated against this model instead of the base
+    /// definition named by `resourceType`.
+    #[serde(default)]
+    pub logical_model: Option<String>,
 }
 
 fn default_schema_mode() -> SchemaMode {
@@ -197,12 +208,28 @@ pub enum SchemaMode {
     On,
 }
 
+/// Handling of extensions whose URL does not resolve to an Extension StructureDefinition.
+///
+/// Relative URLs (sub-extensions of complex extensions) are never checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ExtensionPolicy {
+    /// Extensions are open by design; accept any URL
+    #[default]
+    Allow,
+    /// Report unresolvable extension URLs as warnings
+    WarnUnknown,
+    /// Report unresolvable extension URLs as errors
+    RequireDefinition,
+}
+
 impl Default for SchemaConfig {
     fn default() -> Self {
         Self {
             mode: SchemaMode::On,
             allow_unknown_elements: false,
             allow_modifier_extensions: false,
+            extension_policy: ExtensionPolicy::Allow,
+            logical_model: None,
         }
     }
 }
@@ -305,6 +332,11 @@ impl Default for ProfilesConfig {
 pub struct TerminologyConfig {
     #[serde(default)]
     pub mode: TerminologyMode,
+    /// Base URL of the terminology server used by `Remote` and `Hybrid` modes.
+    ///
+    /// Required for `Remote`. `Hybrid` without an endpoint falls back to `Local`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
     #[serde(default)]
     pub extensible_handling: ExtensibleHandling,
     #[serde(with = "duration_millis", default = "default_terminology_timeout")]
@@ -355,6 +387,7 @@ impl Default for TerminologyConfig {
     fn default() -> Self {
         Self {
             mode: TerminologyMode::Off,
+            endpoint: None,
             extensible_handling: ExtensibleHandling::Warn,
             timeout: Duration::from_millis(1500),
             on_timeout: TimeoutPolicy::Warn,
@@ -511,6 +544,40 @@ impl ValidatorConfig {
         {
             return Err(ConfigError::TerminologyRequiredForFullRef);
         }
+        let has_endpoint = self
+            .terminology
+            .endpoint
+            .as_deref()
+            .is_some_and(|e| !e.trim().is_empty());
+        if self.terminology.mode == TerminologyMode::Remote && !has_endpoint {
+            return Err(ConfigError::TerminologyEndpointRequired {
+                mode: self.terminology.mode,
+            });
+        }
+        if self.bundles.mode == BundleMode::On {
+            if let Some(logical_model) = &self.schema.logical_model {
+                return Err(ConfigError::BundleModeWithLogicalModel {
+                    logical_model: logical_model.clone(),
+                });
+            }
+        }
+
+        // Combinations that compile but are probably not what was intended
+        let mut warnings = Vec::new();
+        if self.profiles.mode == ProfilesMode::On
+            && self
+                .profiles
+                .explicit_profiles
+                .as_ref()
+                .is_some_and(|p| p.is_empty())
+        {
+            warnings.push(ConfigError::NoResolvableProfiles);
+        }
+        let mut terminology = TerminologyPlan::from(&self.terminology);
+        if terminology.mode == TerminologyMode::Hybrid && !has_endpoint {
+            warnings.push(ConfigError::HybridTerminologyWithoutEndpoint);
+            terminology.mode = TerminologyMode::Local;
+        }
 
         let mut steps = Vec::new();
 
@@ -524,7 +591,7 @@ impl ValidatorConfig {
             steps.push(Step::Constraints(ConstraintsPlan::from(&self.constraints)));
         }
         if self.terminology.mode != TerminologyMode::Off {
-            steps.push(Step::Terminology(TerminologyPlan::from(&self.terminology)));
+            steps.push(Step::Terminology(terminology));
         }
         if self.references.mode != ReferenceMode::Off {
             steps.push(Step::References(ReferencesPlan::from(&self.references)));
@@ -537,6 +604,7 @@ impl ValidatorConfig {
             steps,
             fail_fast: self.exec.fail_fast,
             max_issues: self.exec.max_issues,
+            warnings,
         })
     }
 
@@ -599,6 +667,11 @@ impl ValidatorConfigBuilder {
         self
     }
 
+    pub fn terminology_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.cfg().terminology.endpoint = Some(endpoint.into());
+        self
+    }
+
     pub fn reference_mode(mut self, mode: ReferenceMode) -> Self {
         self.cfg().references.mode = mode;
         self
@@ -635,6 +708,27 @@ mod tests {
         assert_eq!(cfg.terminology.mode, TerminologyMode::Off);
     }
 
+    #[test]
+    fn test_presets_compile_without_endpoint() {
+        for preset in [Preset::Ingestion, Preset::Authoring, Preset::Server] {
+            let result = ValidatorConfig::preset(preset).compile();
+            assert!(result.is_ok(), "{preset:?}: {result:?}");
+        }
+
+        // Publication validates codes remotely and needs a terminology server
+        assert!(matches!(
+            ValidatorConfig::preset(Preset::Publication).compile(),
+            Err(ConfigError::TerminologyEndpointRequired {
+                mode: TerminologyMode::Remote
+            })
+        ));
+        let cfg = ValidatorConfig::builder()
+            .preset(Preset::Publication)
+            .terminology_endpoint("https://tx.fhir.org/r5")
+            .build();
+        assert!(cfg.compile().is_ok());
+    }
+
     #[test]
     fn test_builder() {
         let cfg = ValidatorConfig::builder()
@@ -670,4 +764,82 @@ mod tests {
             Err(ConfigError::TerminologyRequiredForFullRef)
         ));
     }
+
+    #[test]
+    fn test_remote_terminology_requires_endpoint() {
+        let cfg = ValidatorConfig::builder()
+            .terminology_mode(TerminologyMode::Remote)
+            .build();
+        assert!(matches!(
+            cfg.compile(),
+            Err(ConfigError::TerminologyEndpointRequired {
+                mode: TerminologyMode::Remote
+            })
+        ));
+
+        // Hybrid without an endpoint falls back to local terminology
+        let cfg = ValidatorConfig::builder()
+            .terminology_mode(TerminologyMode::Hybrid)
+            .build();
+        let plan = cfg.compile().unwrap();
+        assert_eq!(
+            plan.warnings,
+            vec![ConfigError::HybridTerminologyWithoutEndpoint]
+        );
+        assert!(plan.steps.iter().any(|s| matches!(
+            s,
+            Step::Terminology(t) if t.mode == TerminologyMode::Local
+        )));
+
+        for mode in [TerminologyMode::Remote, TerminologyMode::Hybrid] {
+            let cfg = ValidatorConfig::builder()
+                .terminology_mode(mode)
+                .terminology_endpoint("https://tx.fhir.org/r5")
+                .build();
+            let plan = cfg.compile().unwrap();
+            assert!(plan.warnings.is_empty());
+            assert!(plan.steps.iter().any(|s| matches!(
+                s,
+                Step::Terminology(t) if t.mode == mode
+            )));
+        }
+
+        // Local terminology needs no endpoint
+        let cfg = ValidatorConfig::builder()
+            .terminology_mode(TerminologyMode::Local)
+            .build();
+        assert!(cfg.compile().is_ok());
+    }
+
+    #[test]
+    fn test_bundle_mode_rejected_for_logical_models() {
+        let mut cfg = ValidatorConfig::defaults();
+        cfg.bundles.mode = BundleMode::On;
+        cfg.schema.logical_model = Some("http://example.org/StructureDefinition/Model".into());
+        assert!(matches!(
+            cfg.compile(),
+            Err(ConfigError::BundleModeWithLogicalModel { logical_model })
+                if logical_model == "http://example.org/StructureDefinition/Model"
+        ));
+
+        cfg.schema.logical_model = None;
+        let plan = cfg.compile().unwrap();
+        assert!(plan.steps.iter().any(|s| matches!(s, Step::Bundles(_))));
+    }
+
+    #[test]
+    fn test_empty_explicit_profiles_warns() {
+        let mut cfg = ValidatorConfig::builder()
+            .profiles_mode(ProfilesMode::On)
+            .build();
+        cfg.profiles.explicit_profiles = Some(vec![]);
+        let plan = cfg.compile().unwrap();
+        assert_eq!(plan.warnings, vec![ConfigError::NoResolvableProfiles]);
+
+        cfg.profiles.explicit_profiles = Some(vec!["http://example.org/Profile".into()]);
+        assert!(cfg.compile().unwrap().warnings.is_empty());
+
+        cfg.profiles.explicit_profiles = None;
+        assert!(cfg.compile().unwrap().warnings.is_empty());
+    }
 }
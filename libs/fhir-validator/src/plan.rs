@@ -1,6 +1,6 @@
 use crate::{
-    BundleConfig, ConstraintsConfig, ProfilesConfig, ReferencesConfig, SchemaConfig,
-    TerminologyConfig,
+    BundleConfig, ConfigError, ConstraintsConfig, ExtensionPolicy, ProfilesConfig,
+    ReferencesConfig, SchemaConfig, TerminologyConfig,
 };
 
 /// Compiled validation plan - list of steps to execute
@@ -9,6 +9,8 @@ pub struct ValidationPlan {
     pub steps: Vec<Step>,
     pub fail_fast: bool,
     pub max_issues: usize,
+    /// Non-fatal configuration problems found by `ValidatorConfig::compile`
+    pub warnings: Vec<ConfigError>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,6 +31,10 @@ pub enum Step {
 pub struct SchemaPlan {
     pub allow_unknown_elements: bool,
     pub allow_modifier_extensions: bool,
+    pub extension_policy: ExtensionPolicy,
+    /// Logical model URL to validate against.
+    /// If Some, instances carry no resourceType and are addressed by the model's root element.
+    pub logical_model: Option<String>,
 }
 
 impl From<&SchemaConfig> for SchemaPlan {
@@ -36,6 +42,8 @@ impl From<&SchemaConfig> for SchemaPlan {
         Self {
             allow_unknown_elements: cfg.allow_unknown_elements,
             allow_modifier_extensions: cfg.allow_modifier_extensions,
+            extension_policy: cfg.extension_policy,
+            logical_model: cfg.logical_model.clone(),
         }
     }
 }
@@ -14,7 +14,7 @@
 //! - Supports severity level overrides
 //! - Handles constraints at all levels of the resource hierarchy
 
-use crate::validator::{IssueCode, IssueSeverity, ValidationIssue};
+use crate::validator::{ChangedPaths, IssueCode, IssueSeverity, ValidationIssue};
 use crate::{BestPracticeMode, ConstraintsPlan, IssueLevel};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
@@ -29,6 +29,7 @@ pub fn validate_constraints<C: FhirContext>(
     plan: &ConstraintsPlan,
     context: &C,
     fhirpath_engine: &Arc<FhirPathEngine>,
+    changed: Option<&ChangedPaths>,
     issues: &mut Vec<ValidationIssue>,
 ) {
     // Extract resourceType
@@ -61,6 +62,7 @@ pub fn validate_constraints<C: FhirContext>(
                 &suppressed_keys,
                 &level_overrides,
                 fhirpath_engine,
+                changed,
                 issues,
             );
         }
@@ -72,6 +74,7 @@ pub fn validate_constraints<C: FhirContext>(
         for profile_url in profile_urls {
             if let Ok(Some(profile_def)) = context.get_structure_definition(&profile_url) {
                 if let Some(snapshot) = profile_def.snapshot.as_ref() {
+                    let mut profile_issues = Vec::new();
                     validate_constraints_from_elements(
                         resource,
                         &resource_type,
@@ -80,7 +83,13 @@ pub fn validate_constraints<C: FhirContext>(
                         &suppressed_keys,
                         &level_overrides,
                         fhirpath_engine,
-                        issues,
+                        changed,
+                        &mut profile_issues,
+                    );
+                    issues.extend(
+                        profile_issues
+                            .into_iter()
+                            .map(|issue| issue.with_profile(profile_url.clone())),
                     );
                 }
             }
@@ -98,6 +107,7 @@ fn validate_constraints_from_elements(
     suppressed_keys: &HashSet<String>,
     level_overrides: &HashMap<String, IssueLevel>,
     fhirpath_engine: &Arc<FhirPathEngine>,
+    changed: Option<&ChangedPaths>,
     issues: &mut Vec<ValidationIssue>,
 ) {
     // Collect all constraints from all elements
@@ -105,12 +115,22 @@ fn validate_constraints_from_elements(
 
     for element in elements {
         if let Some(constraints) = &element.constraint {
+            let untouched = changed.is_some_and(|changed| !changed.touches(&element.path));
             for constraint in constraints {
                 // Check if suppressed
                 if suppressed_keys.contains(&constraint.key) {
                     continue;
                 }
 
+                // Incremental validation: skip constraints the change cannot affect
+                if untouched
+                    && !constraint.expression.as_deref().is_some_and(|expression| {
+                        reaches_outside_element(fhirpath_engine, expression)
+                    })
+                {
+                    continue;
+                }
+
                 // Check if this is a best practice guideline
                 // Best practice guidelines are marked with the elementdefinition-bestpractice extension
                 let is_best_practice =
@@ -259,6 +279,7 @@ fn evaluate_constraint(
             let location = format!("{}[{}]", context_path, i);
             evaluate_constraint_on_node(
                 fhirpath_value,
+                FhirPathValue::from_json_root(root.clone()),
                 expression,
                 constraint,
                 fhirpath_engine,
@@ -268,13 +289,15 @@ fn evaluate_constraint(
         }
     } else {
         // Scalar element — evaluate directly
+        let resource_value = FhirPathValue::from_json_root(root.clone());
         let fhirpath_value = if sub_keys.is_empty() {
-            FhirPathValue::from_json_root(root)
+            resource_value.clone()
         } else {
             FhirPathValue::from_json_at(root, &sub_keys, None)
         };
         evaluate_constraint_on_node(
             fhirpath_value,
+            resource_value,
             expression,
             constraint,
             fhirpath_engine,
@@ -285,15 +308,18 @@ fn evaluate_constraint(
 }
 
 /// Evaluate a FHIRPath constraint expression on a single context node.
+///
+/// The node is the focus and `%context`; `%resource` is the resource it belongs to.
 fn evaluate_constraint_on_node(
     fhirpath_value: FhirPathValue,
+    resource_value: FhirPathValue,
     expression: &str,
     constraint: &ConstraintToEvaluate,
     fhirpath_engine: &Arc<FhirPathEngine>,
     location: &str,
     issues: &mut Vec<ValidationIssue>,
 ) {
-    let ctx = FhirPathContext::new(fhirpath_value);
+    let ctx = FhirPathContext::new_for_element(fhirpath_value, resource_value);
 
     // Evaluate FHIRPath expression
     let result = match fhirpath_engine.evaluate_expr(expression, &ctx, None) {
@@ -344,6 +370,17 @@ fn evaluate_constraint_on_node(
     }
 }
 
+/// Whether a constraint expression can read data outside the element it is defined on.
+///
+/// Any `%` variable or `resolve()` call counts as non-local. Expressions that fail to
+/// compile are treated as non-local too, so the constraint still runs and reports the error.
+fn reaches_outside_element(engine: &FhirPathEngine, expression: &str) -> bool {
+    match engine.analyze(expression, None) {
+        Ok(analysis) => !analysis.variables.is_empty() || analysis.uses_resolve(),
+        Err(_) => true,
+    }
+}
+
 /// Formats a constraint failure message
 fn format_constraint_message(constraint: &ConstraintToEvaluate) -> String {
     let prefix = if constraint.is_best_practice {
@@ -457,6 +494,73 @@ mod tests {
         assert!(context.is_null());
     }
 
+    #[test]
+    fn test_context_is_the_element_and_resource_is_the_root() {
+        let engine = Arc::new(FhirPathEngine::new(
+            Arc::new(ferrum_context::DefaultFhirContext::from_packages(vec![])),
+            None,
+        ));
+        let resource = serde_json::json!({
+            "resourceType": "Patient",
+            "id": "p1",
+            "name": [{ "family": "Smith" }]
+        });
+        let constraint = |key: &str, expression: &str| ConstraintToEvaluate {
+            key: key.to_string(),
+            expression: Some(expression.to_string()),
+            human: key.to_string(),
+            source: None,
+            element_path: "Patient.name".to_string(),
+            severity: IssueSeverity::Error,
+            is_best_practice: false,
+        };
+
+        let mut issues = Vec::new();
+        evaluate_constraint(
+            &resource,
+            "Patient",
+            &constraint(
+                "ctx-1",
+                "%context.family = 'Smith' and %context.id.empty() and \
+                 %resource.id = 'p1' and %resource.family.empty() and family = 'Smith'",
+            ),
+            &engine,
+            &mut issues,
+        );
+        assert!(issues.is_empty(), "unexpected issues: {:?}", issues);
+
+        evaluate_constraint(
+            &resource,
+            "Patient",
+            &constraint("ctx-2", "%context.id = 'p1'"),
+            &engine,
+            &mut issues,
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].location.as_deref(), Some("Patient.name[0]"));
+    }
+
+    #[test]
+    fn test_reaches_outside_element() {
+        let engine = FhirPathEngine::new(
+            Arc::new(ferrum_context::DefaultFhirContext::from_packages(vec![])),
+            None,
+        );
+
+        let outside = |expression| reaches_outside_element(&engine, expression);
+
+        assert!(!outside("family.exists() or given.exists()"));
+        assert!(!outside("where(use = 'official').exists()"));
+        assert!(outside("%resource.active = true"));
+        assert!(outside("%rootResource.id.exists()"));
+        assert!(outside("%context.family.exists()"));
+        // Detected from the compiled expression, so spacing does not hide the call
+        assert!(outside("reference.resolve ( ).exists()"));
+        assert!(outside("reference.resolve().is(Patient)"));
+        // Unparseable expressions still run so the error gets reported
+        assert!(outside("family.exists() and"));
+    }
+
     #[test]
     fn test_format_constraint_message() {
         let constraint = ConstraintToEvaluate {
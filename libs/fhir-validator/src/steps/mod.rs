@@ -1,5 +1,6 @@
 pub mod constraints;
 pub mod profiles;
+pub mod references;
 pub mod schema;
 pub mod slicing;
 pub mod terminology;
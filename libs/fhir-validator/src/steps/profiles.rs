@@ -3,6 +3,7 @@
 //! Validates resources against profiles (StructureDefinitions with derivation=constraint):
 //! - Element cardinality constraints
 //! - Fixed values and patterns
+//! - Value ranges (minValue[x] / maxValue[x])
 //! - Slicing (delegated to slicing module)
 //! - Type restrictions
 //! - Must Support elements
@@ -10,8 +11,9 @@
 use crate::validator::{IssueCode, ValidationIssue};
 use crate::ProfilesPlan;
 use serde_json::Value;
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use ferrum_context::FhirContext;
 use ferrum_models::common::element_definition::{
     DiscriminatorType as ModelDiscType, SlicingRules as ModelSlicingRules,
@@ -22,11 +24,14 @@ use ferrum_fhirpath::Engine as FhirPathEngine;
 use super::slicing::{validate_slicing, SliceDefinition, SlicingRules};
 
 /// Validates a resource against profiles declared in meta.profile or explicit profiles
+///
+/// Every profile is checked independently; issues carry the URL of the profile that raised them.
 pub fn validate_profiles<C: FhirContext>(
     resource: &Value,
     plan: &ProfilesPlan,
     context: &C,
     fhirpath_engine: &Arc<FhirPathEngine>,
+    cache: &ProfileCache,
     issues: &mut Vec<ValidationIssue>,
 ) {
     // Extract resourceType
@@ -46,15 +51,23 @@ pub fn validate_profiles<C: FhirContext>(
         return;
     }
 
-    // Validate against each profile
+    // Validate against each profile; the resource must conform to all of them, and each
+    // issue records the profile it came from
     for profile_url in &profile_urls {
+        let mut profile_issues = Vec::new();
         validate_against_profile(
             resource,
             &resource_type,
             profile_url,
             context,
             fhirpath_engine,
-            issues,
+            cache,
+            &mut profile_issues,
+        );
+        issues.extend(
+            profile_issues
+                .into_iter()
+                .map(|issue| issue.with_profile(profile_url.clone())),
         );
     }
 }
@@ -83,6 +96,32 @@ fn resolve_profile_urls(resource: &Value, plan: &ProfilesPlan) -> Vec<String> {
     profiles
 }
 
+/// Results of checks that depend only on a profile, computed on its first use
+#[derive(Default)]
+pub struct ProfileCache {
+    cardinality_issues: RwLock<HashMap<String, Arc<Vec<ValidationIssue>>>>,
+}
+
+impl ProfileCache {
+    /// Cardinality contradictions of `profile_url`, running `check` only the first time.
+    fn cardinality_issues(
+        &self,
+        profile_url: &str,
+        check: impl FnOnce() -> Vec<ValidationIssue>,
+    ) -> Arc<Vec<ValidationIssue>> {
+        if let Some(cached) = self.cardinality_issues.read().unwrap().get(profile_url) {
+            return cached.clone();
+        }
+
+        let issues = Arc::new(check());
+        self.cardinality_issues
+            .write()
+            .unwrap()
+            .insert(profile_url.to_string(), issues.clone());
+        issues
+    }
+}
+
 /// Validates resource against a single profile StructureDefinition
 fn validate_against_profile<C: FhirContext>(
     resource: &Value,
@@ -90,6 +129,7 @@ fn validate_against_profile<C: FhirContext>(
     profile_url: &str,
     context: &C,
     fhirpath_engine: &Arc<FhirPathEngine>,
+    cache: &ProfileCache,
     issues: &mut Vec<ValidationIssue>,
 ) {
     // Get StructureDefinition for this profile
@@ -197,6 +237,26 @@ fn validate_against_profile<C: FhirContext>(
         return;
     };
 
+    // Flag cardinalities no instance can satisfy before validating against them; these depend
+    // only on the profile, so each profile is checked once
+    let cardinality_issues = cache.cardinality_issues(profile_url, || {
+        let base_snapshot = structure_def
+            .base_definition
+            .as_deref()
+            .and_then(|url| context.get_structure_definition(url).ok().flatten());
+        let mut issues = Vec::new();
+        check_cardinality_consistency(
+            &snapshot.element,
+            base_snapshot
+                .as_ref()
+                .and_then(|sd| sd.snapshot.as_ref())
+                .map(|s| s.element.as_slice()),
+            &mut issues,
+        );
+        issues
+    });
+    issues.extend(cardinality_issues.iter().cloned());
+
     // Build element index (including slices)
     let index = ProfileElementIndex::new(&snapshot.element);
 
@@ -348,8 +408,16 @@ fn validate_profile_object(
             continue;
         };
 
-        // Skip choice base elements ([x])
-        if name.ends_with("[x]") {
+        // Choice base elements ([x]) only get range checks, against each concrete `valueX` key
+        if let Some(base) = name.strip_suffix("[x]") {
+            for (key, v) in obj {
+                let is_choice_of = key
+                    .strip_prefix(base)
+                    .is_some_and(|t| t.starts_with(|c: char| c.is_ascii_uppercase()));
+                if is_choice_of {
+                    validate_value_range(v, child_def, &format!("{}.{}", path, key), issues);
+                }
+            }
             continue;
         }
 
@@ -390,7 +458,7 @@ fn validate_profile_object(
         }
 
         // Validate fixed value
-        if let Some(fixed_value) = &child_def.fixed {
+        if let Some((_, fixed_value)) = choice_property(child_def, "fixed") {
             if let Some(v) = child_value {
                 if !values_match(v, fixed_value) {
                     let child_path_clone = child_path.clone();
@@ -411,7 +479,7 @@ fn validate_profile_object(
         }
 
         // Validate pattern
-        if let Some(pattern_value) = &child_def.pattern {
+        if let Some((_, pattern_value)) = choice_property(child_def, "pattern") {
             if let Some(v) = child_value {
                 if !value_matches_pattern(v, pattern_value) {
                     let child_path_clone = child_path.clone();
@@ -427,6 +495,11 @@ fn validate_profile_object(
             }
         }
 
+        // Validate minValue[x] / maxValue[x]
+        if let Some(v) = child_value {
+            validate_value_range(v, child_def, &child_path, issues);
+        }
+
         // Validate type restrictions (profile may restrict types)
         if let Some(v) = child_value {
             if !v.is_null() {
@@ -640,6 +713,199 @@ fn validate_cardinality(
     }
 }
 
+/// Reports profile elements whose cardinality contradicts itself or the base definition.
+///
+/// A profile may only narrow cardinality: `min` must not exceed `max`, and neither may exceed
+/// the base element's `max` (e.g. `min = 1` on an element the base prohibits with `max = 0`).
+/// Base elements are matched by path, so slices are held to the sliced element's bounds.
+fn check_cardinality_consistency(
+    elements: &[ElementDefinition],
+    base_elements: Option<&[ElementDefinition]>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let base_by_path: HashMap<&str, &ElementDefinition> = base_elements
+        .unwrap_or_default()
+        .iter()
+        .map(|e| (e.path.as_str(), e))
+        .collect();
+    let parse_max = |max: Option<&str>| {
+        max.filter(|m| *m != "*")
+            .and_then(|m| m.parse::<u64>().ok())
+    };
+
+    for element in elements {
+        let id = element.id.as_deref().unwrap_or(&element.path);
+        let min = element.min.unwrap_or(0) as u64;
+        let max = parse_max(element.max.as_deref());
+        let mut report = |message: String| {
+            issues.push(
+                ValidationIssue::error(IssueCode::Invalid, message).with_location(id.to_string()),
+            );
+        };
+
+        if let Some(max) = max.filter(|max| min > *max) {
+            report(format!(
+                "Element '{}' has min {} greater than max {}",
+                id, min, max
+            ));
+        }
+
+        let Some(base_max) = base_by_path
+            .get(element.path.as_str())
+            .and_then(|base| parse_max(base.max.as_deref()))
+        else {
+            continue;
+        };
+        if min > base_max {
+            report(format!(
+                "Element '{}' has min {} but the base definition allows at most {}",
+                id, min, base_max
+            ));
+        } else if element.max.as_deref() == Some("*") || max.is_some_and(|max| max > base_max) {
+            report(format!(
+                "Element '{}' has max {} but the base definition allows at most {}",
+                id,
+                element.max.as_deref().unwrap_or("*"),
+                base_max
+            ));
+        }
+    }
+}
+
+/// Resolve a choice-typed element property such as `fixed[x]` or `minValue[x]`.
+///
+/// Returns the type suffix and value, e.g. `("Integer", 5)` for `minValueInteger: 5`. The
+/// model captures these keys through `#[serde(flatten)]`, so they may sit in `extensions` or
+/// in any of the flattened value fields.
+fn choice_property<'a>(
+    element: &'a ElementDefinition,
+    prefix: &str,
+) -> Option<(&'a str, &'a Value)> {
+    let flattened = [
+        &element.fixed,
+        &element.pattern,
+        &element.min_value,
+        &element.max_value,
+        &element.default_value,
+    ];
+    element
+        .extensions
+        .iter()
+        .chain(
+            flattened
+                .into_iter()
+                .filter_map(|v| v.as_ref().and_then(Value::as_object))
+                .flatten(),
+        )
+        .find_map(|(key, value)| {
+            let type_name = key.strip_prefix(prefix)?;
+            type_name
+                .starts_with(|c: char| c.is_ascii_uppercase())
+                .then_some((type_name, value))
+        })
+}
+
+/// Validates values against the element's minValue[x] / maxValue[x]
+///
+/// Numbers, dates/times and Quantities (with matching units) are compared; values that cannot
+/// be ordered against the bound (e.g. dates of different precision that agree as far as both
+/// go) are not reported.
+fn validate_value_range(
+    value: &Value,
+    element_def: &ElementDefinition,
+    element_path: &str,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let min = choice_property(element_def, "minValue");
+    let max = choice_property(element_def, "maxValue");
+    if min.is_none() && max.is_none() {
+        return;
+    }
+
+    let values: Vec<&Value> = match value {
+        Value::Array(arr) => arr.iter().collect(),
+        _ => vec![value],
+    };
+
+    for val in values {
+        let checks = [
+            (min, Ordering::Less, "below the minimum"),
+            (max, Ordering::Greater, "above the maximum"),
+        ];
+        for (bound, violation, relation) in checks {
+            let Some((type_name, bound)) = bound else {
+                continue;
+            };
+            if compare_to_bound(val, type_name, bound) == Some(violation) {
+                issues.push(
+                    ValidationIssue::error(
+                        IssueCode::Value,
+                        format!(
+                            "Value {} of element '{}' is {} {}",
+                            val, element_path, relation, bound
+                        ),
+                    )
+                    .with_location(element_path.to_string())
+                    .with_expression(vec![element_path.to_string()]),
+                );
+            }
+        }
+    }
+}
+
+/// Order `value` against a minValue[x]/maxValue[x] bound of the given type
+fn compare_to_bound(value: &Value, type_name: &str, bound: &Value) -> Option<Ordering> {
+    match type_name {
+        "Integer" | "Integer64" | "PositiveInt" | "UnsignedInt" | "Decimal" => {
+            as_number(value)?.partial_cmp(&as_number(bound)?)
+        }
+        "Date" | "DateTime" | "Instant" | "Time" => {
+            compare_temporal(value.as_str()?, bound.as_str()?)
+        }
+        "Quantity" => {
+            let (value, bound) = (value.as_object()?, bound.as_object()?);
+            for key in ["system", "code"] {
+                if let (Some(a), Some(b)) = (value.get(key), bound.get(key)) {
+                    if a != b {
+                        return None;
+                    }
+                }
+            }
+            as_number(value.get("value")?)?.partial_cmp(&as_number(bound.get("value")?)?)
+        }
+        _ => None,
+    }
+}
+
+/// Numeric value of a JSON number, or of a string holding one (integer64)
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Order two FHIR date, dateTime, instant or time strings
+///
+/// Full timestamps with offsets are compared as instants. Otherwise the strings are compared
+/// over their common precision; if they agree that far the order is unknown.
+fn compare_temporal(a: &str, b: &str) -> Option<Ordering> {
+    if let (Ok(a), Ok(b)) = (
+        chrono::DateTime::parse_from_rfc3339(a),
+        chrono::DateTime::parse_from_rfc3339(b),
+    ) {
+        return Some(a.cmp(&b));
+    }
+
+    let common = a.len().min(b.len());
+    match a.get(..common)?.cmp(b.get(..common)?) {
+        Ordering::Equal if a.len() == b.len() => Some(Ordering::Equal),
+        Ordering::Equal => None,
+        other => Some(other),
+    }
+}
+
 /// Check if two values match exactly
 fn values_match(a: &Value, b: &Value) -> bool {
     a == b
@@ -753,8 +1019,327 @@ fn get_resource_type(resource: &Value) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use ferrum_context::Result as ContextResult;
+    use serde_json::json;
+
+    const REQUIRES_GENDER: &str = "http://example.org/fhir/StructureDefinition/requires-gender";
+    const REQUIRES_BIRTH_DATE: &str =
+        "http://example.org/fhir/StructureDefinition/requires-birthdate";
+    const RANGED_OBSERVATION: &str =
+        "http://example.org/fhir/StructureDefinition/ranged-observation";
+    const RANGED_CHARGE_ITEM: &str =
+        "http://example.org/fhir/StructureDefinition/ranged-charge-item";
+    const NO_DECEASED: &str = "http://example.org/fhir/StructureDefinition/no-deceased";
+    const REQUIRES_DECEASED: &str = "http://example.org/fhir/StructureDefinition/requires-deceased";
+
+    struct MockContext {
+        profiles: HashMap<String, Arc<Value>>,
+    }
+
+    impl MockContext {
+        fn new() -> Self {
+            let gender_and_birth_date = |required: &str| {
+                [("gender", "code"), ("birthDate", "date")]
+                    .into_iter()
+                    .map(|(name, type_code)| {
+                        json!({
+                            "id": format!("Patient.{}", name),
+                            "path": format!("Patient.{}", name),
+                            "min": if name == required { 1 } else { 0 },
+                            "max": "1",
+                            "type": [{ "code": type_code }]
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let profiles = [
+                (
+                    REQUIRES_GENDER,
+                    profile(REQUIRES_GENDER, "Patient", gender_and_birth_date("gender")),
+                ),
+                (
+                    REQUIRES_BIRTH_DATE,
+                    profile(
+                        REQUIRES_BIRTH_DATE,
+                        "Patient",
+                        gender_and_birth_date("birthDate"),
+                    ),
+                ),
+                (
+                    RANGED_OBSERVATION,
+                    profile(
+                        RANGED_OBSERVATION,
+                        "Observation",
+                        vec![
+                            json!({
+                                "id": "Observation.value[x]",
+                                "path": "Observation.value[x]",
+                                "min": 0,
+                                "max": "1",
+                                "type": [{ "code": "integer" }],
+                                "minValueInteger": 5
+                            }),
+                            json!({
+                                "id": "Observation.effective[x]",
+                                "path": "Observation.effective[x]",
+                                "min": 0,
+                                "max": "1",
+                                "type": [{ "code": "dateTime" }],
+                                "minValueDateTime": "2020-01-01T00:00:00Z",
+                                "maxValueDateTime": "2020-12-31T23:59:59Z"
+                            }),
+                        ],
+                    ),
+                ),
+                (
+                    RANGED_CHARGE_ITEM,
+                    profile(
+                        RANGED_CHARGE_ITEM,
+                        "ChargeItem",
+                        vec![json!({
+                            "id": "ChargeItem.factorOverride",
+                            "path": "ChargeItem.factorOverride",
+                            "min": 0,
+                            "max": "1",
+                            "type": [{ "code": "decimal" }],
+                            "maxValueDecimal": 1.0
+                        })],
+                    ),
+                ),
+                (
+                    NO_DECEASED,
+                    profile(NO_DECEASED, "Patient", vec![deceased(0, "0")]),
+                ),
+                (REQUIRES_DECEASED, {
+                    // Tightens min on an element its base prohibits
+                    let mut sd = profile(REQUIRES_DECEASED, "Patient", vec![deceased(1, "1")]);
+                    sd["baseDefinition"] = json!(NO_DECEASED);
+                    sd
+                }),
+            ];
+
+            Self {
+                profiles: profiles
+                    .into_iter()
+                    .map(|(url, sd)| (url.to_string(), Arc::new(sd)))
+                    .collect(),
+            }
+        }
+    }
+
+    impl FhirContext for MockContext {
+        fn get_resource_by_url(
+            &self,
+            canonical_url: &str,
+            _version: Option<&str>,
+        ) -> ContextResult<Option<Arc<Value>>> {
+            Ok(self.profiles.get(canonical_url).cloned())
+        }
+    }
+
+    /// Constraint profile on `resource_type` with the given snapshot elements below the root
+    fn profile(url: &str, resource_type: &str, elements: Vec<Value>) -> Value {
+        let mut snapshot = vec![json!({
+            "id": resource_type,
+            "path": resource_type,
+            "min": 0,
+            "max": "*"
+        })];
+        snapshot.extend(elements);
+        json!({
+            "resourceType": "StructureDefinition",
+            "url": url,
+            "name": "TestProfile",
+            "status": "active",
+            "kind": "resource",
+            "abstract": false,
+            "type": resource_type,
+            "baseDefinition": format!("http://hl7.org/fhir/StructureDefinition/{}", resource_type),
+            "derivation": "constraint",
+            "snapshot": { "element": snapshot }
+        })
+    }
+
+    fn deceased(min: u32, max: &str) -> Value {
+        json!({
+            "id": "Patient.deceased[x]",
+            "path": "Patient.deceased[x]",
+            "min": min,
+            "max": max,
+            "type": [{ "code": "boolean" }]
+        })
+    }
+
+    fn validate(resource: Value) -> Vec<ValidationIssue> {
+        validate_cached(resource, &ProfileCache::default())
+    }
+
+    fn validate_cached(resource: Value, cache: &ProfileCache) -> Vec<ValidationIssue> {
+        let engine = Arc::new(FhirPathEngine::new(
+            Arc::new(ferrum_context::DefaultFhirContext::from_packages(vec![])),
+            None,
+        ));
+
+        let mut issues = Vec::new();
+        validate_profiles(
+            &resource,
+            &ProfilesPlan {
+                explicit_profiles: None,
+            },
+            &MockContext::new(),
+            &engine,
+            cache,
+            &mut issues,
+        );
+        issues
+    }
+
+    #[test]
+    fn issues_carry_the_profile_they_fail() {
+        let issues = validate(json!({
+            "resourceType": "Patient",
+            "meta": { "profile": [REQUIRES_GENDER, REQUIRES_BIRTH_DATE] }
+        }));
+
+        let mut attributed: Vec<(&str, &str)> = issues
+            .iter()
+            .map(|i| {
+                (
+                    i.location.as_deref().unwrap_or_default(),
+                    i.profile.as_deref().unwrap_or_default(),
+                )
+            })
+            .collect();
+        attributed.sort();
+        assert_eq!(
+            attributed,
+            vec![
+                ("Patient.birthDate", REQUIRES_BIRTH_DATE),
+                ("Patient.gender", REQUIRES_GENDER),
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_below_min_value_is_reported() {
+        let issues = validate(json!({
+            "resourceType": "Observation",
+            "meta": { "profile": [RANGED_OBSERVATION] },
+            "valueInteger": 3
+        }));
+
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].code, IssueCode::Value);
+        assert_eq!(
+            issues[0].location.as_deref(),
+            Some("Observation.valueInteger")
+        );
+        assert!(issues[0].diagnostics.contains("below the minimum 5"));
+
+        let at_bound = validate(json!({
+            "resourceType": "Observation",
+            "meta": { "profile": [RANGED_OBSERVATION] },
+            "valueInteger": 5
+        }));
+        assert!(at_bound.is_empty(), "{:?}", at_bound);
+    }
+
+    #[test]
+    fn decimal_above_max_value_is_reported() {
+        let issues = validate(json!({
+            "resourceType": "ChargeItem",
+            "meta": { "profile": [RANGED_CHARGE_ITEM] },
+            "factorOverride": 1.25
+        }));
+
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(
+            issues[0].location.as_deref(),
+            Some("ChargeItem.factorOverride")
+        );
+        assert!(issues[0].diagnostics.contains("above the maximum 1.0"));
+    }
+
     #[test]
-    fn test_placeholder() {
-        // Profile validation tests will be added as implementation progresses
+    fn date_time_range_respects_offsets_and_precision() {
+        for within in [
+            "2020-06-15T10:00:00+02:00",
+            "2020-12-31T23:30:00-00:00",
+            "2020",
+        ] {
+            let issues = validate(json!({
+                "resourceType": "Observation",
+                "meta": { "profile": [RANGED_OBSERVATION] },
+                "effectiveDateTime": within
+            }));
+            assert!(issues.is_empty(), "{}: {:?}", within, issues);
+        }
+
+        // 2021-01-01T00:30 at +01:00 is still 2020 in UTC
+        let issues = validate(json!({
+            "resourceType": "Observation",
+            "meta": { "profile": [RANGED_OBSERVATION] },
+            "effectiveDateTime": "2021-01-01T00:30:00+01:00"
+        }));
+        assert!(issues.is_empty(), "{:?}", issues);
+
+        let issues = validate(json!({
+            "resourceType": "Observation",
+            "meta": { "profile": [RANGED_OBSERVATION] },
+            "effectiveDateTime": "2021-01-05"
+        }));
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert!(issues[0].diagnostics.contains("above the maximum"));
+    }
+
+    #[test]
+    fn min_above_base_max_is_reported() {
+        let issues = validate(json!({
+            "resourceType": "Patient",
+            "meta": { "profile": [REQUIRES_DECEASED] }
+        }));
+
+        let contradictions: Vec<&ValidationIssue> = issues
+            .iter()
+            .filter(|i| i.code == IssueCode::Invalid)
+            .collect();
+        assert_eq!(contradictions.len(), 1, "{:?}", issues);
+        assert_eq!(
+            contradictions[0].location.as_deref(),
+            Some("Patient.deceased[x]")
+        );
+        assert!(contradictions[0]
+            .diagnostics
+            .contains("base definition allows at most 0"));
+        assert_eq!(
+            contradictions[0].profile.as_deref(),
+            Some(REQUIRES_DECEASED)
+        );
+
+        // A profile that only repeats its base's bounds is consistent
+        let issues = validate(json!({
+            "resourceType": "Patient",
+            "meta": { "profile": [NO_DECEASED] }
+        }));
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn cardinality_contradictions_are_checked_once_per_profile() {
+        let cache = ProfileCache::default();
+        let patient = json!({
+            "resourceType": "Patient",
+            "meta": { "profile": [REQUIRES_DECEASED] }
+        });
+
+        let first = validate_cached(patient.clone(), &cache);
+        assert_eq!(cache.cardinality_issues.read().unwrap().len(), 1);
+
+        // Later instances reuse the cached result and still report it
+        let second = validate_cached(patient, &cache);
+        assert_eq!(first, second);
+        assert!(second.iter().any(|i| i.code == IssueCode::Invalid));
     }
 }
@@ -0,0 +1,446 @@
+//! Reference validation step
+//!
+//! Checks the lexical form of every `Reference.reference` in the resource (any object with a
+//! string `reference` element, including inside contained resources):
+//! - relative references must be `Type/id` (optionally `/_history/vid`) with a known resource
+//!   type and a legal id
+//! - absolute URLs must have a scheme, a host and no whitespace
+//! - `urn:uuid:` and `urn:oid:` must match their FHIR patterns
+//! - local references (`#id`) must name a legal id
+//!
+//! Format checks run in every reference mode; they do not look up the referenced resource.
+//!
+//! A `CapabilityStatement` is also checked for self-consistency: the `definition` canonicals of
+//! its search parameters and operations must resolve to a `SearchParameter` or
+//! `OperationDefinition` in the context.
+
+use ferrum_context::FhirContext;
+use serde_json::Value;
+
+use crate::validator::{IssueCode, ValidationIssue};
+use crate::ReferencesPlan;
+
+/// Run reference validation on a resource.
+pub fn validate_references(
+    resource: &Value,
+    _plan: &ReferencesPlan,
+    context: &dyn FhirContext,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let root = resource
+        .get("resourceType")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Resource");
+    visit(resource, root, context, issues);
+    if root == "CapabilityStatement" {
+        check_capability_definitions(resource, context, issues);
+    }
+}
+
+fn visit(value: &Value, path: &str, context: &dyn FhirContext, issues: &mut Vec<ValidationIssue>) {
+    match value {
+        Value::Object(obj) => {
+            if let Some(reference) = obj.get("reference").and_then(|v| v.as_str()) {
+                if let Err(message) = check_reference_format(reference, context) {
+                    let location = format!("{}.reference", path);
+                    issues.push(
+                        ValidationIssue::error(
+                            IssueCode::Value,
+                            format!("Invalid reference '{}': {}", reference, message),
+                        )
+                        .with_location(location.clone())
+                        .with_expression(vec![location]),
+                    );
+                }
+            }
+            for (key, child) in obj {
+                if key == "reference" {
+                    continue;
+                }
+                visit(child, &format!("{}.{}", path, key), context, issues);
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                visit(item, &format!("{}[{}]", path, i), context, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Check that the search parameter and operation definitions a CapabilityStatement declares
+/// (system-level and per resource) resolve in the context.
+fn check_capability_definitions(
+    resource: &Value,
+    context: &dyn FhirContext,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let entries = |value: &Value, key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    for (i, rest) in entries(resource, "rest").iter().enumerate() {
+        let rest_path = format!("CapabilityStatement.rest[{}]", i);
+        check_definitions(rest, &rest_path, context, issues);
+        for (j, resource) in entries(rest, "resource").iter().enumerate() {
+            let resource_path = format!("{}.resource[{}]", rest_path, j);
+            check_definitions(resource, &resource_path, context, issues);
+        }
+    }
+}
+
+fn check_definitions(
+    value: &Value,
+    path: &str,
+    context: &dyn FhirContext,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    for (key, expected_type) in [
+        ("searchParam", "SearchParameter"),
+        ("operation", "OperationDefinition"),
+    ] {
+        let Some(items) = value.get(key).and_then(Value::as_array) else {
+            continue;
+        };
+        for (i, item) in items.iter().enumerate() {
+            let Some(canonical) = item.get("definition").and_then(Value::as_str) else {
+                continue;
+            };
+            let location = format!("{}.{}[{}].definition", path, key, i);
+            let (url, version) = match canonical.split_once('|') {
+                Some((url, version)) => (url, Some(version)),
+                None => (canonical, None),
+            };
+            let (code, message) = match context.get_resource_by_url(url, version) {
+                Ok(Some(definition)) => {
+                    let actual_type = definition
+                        .get("resourceType")
+                        .and_then(Value::as_str)
+                        .unwrap_or("");
+                    if actual_type == expected_type {
+                        continue;
+                    }
+                    let message = format!(
+                        "Canonical '{}' refers to a {}, expected a {}",
+                        canonical, actual_type, expected_type
+                    );
+                    (IssueCode::Invalid, message)
+                }
+                Ok(None) => (
+                    IssueCode::NotFound,
+                    format!("{} not found: '{}'", expected_type, canonical),
+                ),
+                Err(e) => (
+                    IssueCode::Exception,
+                    format!("Error resolving '{}': {}", canonical, e),
+                ),
+            };
+            issues.push(
+                ValidationIssue::error(code, message)
+                    .with_location(location.clone())
+                    .with_expression(vec![location]),
+            );
+        }
+    }
+}
+
+/// Check the lexical form of a reference string, returning a reason when it is malformed.
+pub(crate) fn check_reference_format(
+    reference: &str,
+    context: &dyn FhirContext,
+) -> Result<(), String> {
+    if reference.is_empty() {
+        return Err("reference is empty".to_string());
+    }
+    if reference.chars().any(char::is_whitespace) {
+        return Err("reference contains whitespace".to_string());
+    }
+
+    if let Some(id) = reference.strip_prefix('#') {
+        // `#` alone refers to the containing resource
+        return if id.is_empty() || is_valid_id(id) {
+            Ok(())
+        } else {
+            Err(format!("'{}' is not a valid contained resource id", id))
+        };
+    }
+
+    if let Some(uuid) = reference.strip_prefix("urn:uuid:") {
+        return if is_valid_uuid(uuid) {
+            Ok(())
+        } else {
+            Err("urn:uuid must be followed by a lowercase UUID".to_string())
+        };
+    }
+
+    if let Some(oid) = reference.strip_prefix("urn:oid:") {
+        return if is_valid_oid(oid) {
+            Ok(())
+        } else {
+            Err("urn:oid must be followed by a dotted numeric OID".to_string())
+        };
+    }
+
+    if let Some((scheme, rest)) = reference.split_once("://") {
+        let valid_scheme = scheme
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if !valid_scheme {
+            return Err(format!("'{}' is not a valid URL scheme", scheme));
+        }
+        let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+        if host.is_empty() {
+            return Err("absolute URL has no host".to_string());
+        }
+        return Ok(());
+    }
+
+    check_relative_reference(reference, context)
+}
+
+/// `Type/id`, `Type/id/_history/vid`, or a conditional `Type?criteria`.
+fn check_relative_reference(reference: &str, context: &dyn FhirContext) -> Result<(), String> {
+    if let Some((resource_type, criteria)) = reference.split_once('?') {
+        check_resource_type(resource_type, context)?;
+        return if criteria.is_empty() {
+            Err("conditional reference has no search criteria".to_string())
+        } else {
+            Ok(())
+        };
+    }
+
+    let parts: Vec<&str> = reference.split('/').collect();
+    match parts.as_slice() {
+        [resource_type, id] | [resource_type, id, "_history", _] => {
+            check_resource_type(resource_type, context)?;
+            if !is_valid_id(id) {
+                return Err(format!("'{}' is not a valid resource id", id));
+            }
+            if let [_, _, _, version] = parts.as_slice() {
+                if !is_valid_id(version) {
+                    return Err(format!("'{}' is not a valid version id", version));
+                }
+            }
+            Ok(())
+        }
+        _ => Err("relative references must have the form ResourceType/id".to_string()),
+    }
+}
+
+fn check_resource_type(resource_type: &str, context: &dyn FhirContext) -> Result<(), String> {
+    let lexically_valid = resource_type
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_uppercase())
+        && resource_type.chars().all(|c| c.is_ascii_alphanumeric());
+    if !lexically_valid {
+        return Err(format!("'{}' is not a valid resource type", resource_type));
+    }
+
+    match context.get_core_structure_definition_by_type(resource_type) {
+        Ok(Some(sd)) if sd.is_resource() => Ok(()),
+        Ok(Some(_)) => Err(format!("'{}' is not a resource type", resource_type)),
+        _ => Err(format!("unknown resource type '{}'", resource_type)),
+    }
+}
+
+/// FHIR id: 1-64 characters from `[A-Za-z0-9\-\.]`
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.')
+}
+
+/// `[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}`
+fn is_valid_uuid(uuid: &str) -> bool {
+    let groups: Vec<&str> = uuid.split('-').collect();
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, len)| {
+            group.len() == len
+                && group
+                    .bytes()
+                    .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        })
+}
+
+/// `[0-2](\.(0|[1-9][0-9]*))+`
+fn is_valid_oid(oid: &str) -> bool {
+    let arcs: Vec<&str> = oid.split('.').collect();
+    arcs.len() >= 2
+        && matches!(arcs[0], "0" | "1" | "2")
+        && arcs[1..].iter().all(|arc| {
+            !arc.is_empty()
+                && arc.bytes().all(|b| b.is_ascii_digit())
+                && (arc.len() == 1 || !arc.starts_with('0'))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReferenceMode;
+    use ferrum_context::Result as ContextResult;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    struct MockContext;
+
+    impl FhirContext for MockContext {
+        fn get_resource_by_url(
+            &self,
+            canonical_url: &str,
+            _version: Option<&str>,
+        ) -> ContextResult<Option<Arc<Value>>> {
+            let (name, kind) = match canonical_url {
+                "http://hl7.org/fhir/StructureDefinition/Patient" => ("Patient", "resource"),
+                "http://hl7.org/fhir/StructureDefinition/HumanName" => {
+                    ("HumanName", "complex-type")
+                }
+                "http://hl7.org/fhir/SearchParameter/Patient-name" => {
+                    return Ok(Some(Arc::new(json!({
+                        "resourceType": "SearchParameter",
+                        "url": canonical_url,
+                        "code": "name"
+                    }))));
+                }
+                _ => return Ok(None),
+            };
+            Ok(Some(Arc::new(json!({
+                "resourceType": "StructureDefinition",
+                "url": canonical_url,
+                "name": name,
+                "status": "active",
+                "kind": kind,
+                "abstract": false,
+                "type": name
+            }))))
+        }
+    }
+
+    fn plan() -> ReferencesPlan {
+        ReferencesPlan {
+            mode: ReferenceMode::TypeOnly,
+            allow_external: true,
+        }
+    }
+
+    fn issues_for(resource: Value) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        validate_references(&resource, &plan(), &MockContext, &mut issues);
+        issues
+    }
+
+    #[test]
+    fn accepts_well_formed_references() {
+        let issues = issues_for(json!({
+            "resourceType": "Observation",
+            "subject": {"reference": "Patient/example"},
+            "performer": [
+                {"reference": "Patient/p-1/_history/2"},
+                {"reference": "urn:uuid:9d3c2b1a-4e5f-4a6b-8c7d-0e1f2a3b4c5d"},
+                {"reference": "urn:oid:1.2.840.113619"},
+                {"reference": "https://example.org/fhir/Patient/1"},
+                {"reference": "#contained-1"},
+                {"display": "no reference"}
+            ]
+        }));
+        assert!(issues.is_empty(), "unexpected issues: {:?}", issues);
+    }
+
+    #[test]
+    fn flags_malformed_references() {
+        let issues = issues_for(json!({
+            "resourceType": "Observation",
+            "subject": {"reference": "Patient//"},
+            "performer": [
+                {"reference": "urn:uuid:not-a-uuid"},
+                {"reference": "urn:oid:1.02"},
+                {"reference": "http:///Patient/1"},
+                {"reference": "HumanName/1"},
+                {"reference": "Unknown/1"},
+                {"reference": "Patient/bad id"}
+            ]
+        }));
+        assert_eq!(issues.len(), 7, "issues: {:?}", issues);
+        assert!(issues.iter().all(|i| i.code == IssueCode::Value));
+        let locations: Vec<_> = issues
+            .iter()
+            .filter_map(|i| i.location.as_deref())
+            .collect();
+        assert!(locations.contains(&"Observation.subject.reference"));
+        assert!(locations.contains(&"Observation.performer[0].reference"));
+    }
+
+    #[test]
+    fn checks_reference_format_directly() {
+        assert!(check_reference_format("Patient/123", &MockContext).is_ok());
+        assert!(check_reference_format("Patient?identifier=a|b", &MockContext).is_ok());
+        assert!(check_reference_format("Patient", &MockContext).is_err());
+        assert!(check_reference_format("patient/123", &MockContext).is_err());
+        assert!(check_reference_format(
+            "urn:uuid:9D3C2B1A-4E5F-4A6B-8C7D-0E1F2A3B4C5D",
+            &MockContext
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn flags_unresolved_capability_statement_definitions() {
+        let issues = issues_for(json!({
+            "resourceType": "CapabilityStatement",
+            "status": "active",
+            "kind": "instance",
+            "rest": [{
+                "mode": "server",
+                "resource": [{
+                    "type": "Patient",
+                    "searchParam": [{
+                        "name": "name",
+                        "type": "string",
+                        "definition": "http://hl7.org/fhir/SearchParameter/Patient-name"
+                    }],
+                    "operation": [{
+                        "name": "everything",
+                        "definition": "http://example.org/OperationDefinition/missing"
+                    }]
+                }],
+                "operation": [{
+                    "name": "export",
+                    "definition": "http://hl7.org/fhir/SearchParameter/Patient-name"
+                }]
+            }]
+        }));
+
+        assert_eq!(issues.len(), 2, "issues: {:?}", issues);
+        let missing = issues
+            .iter()
+            .find(|i| i.code == IssueCode::NotFound)
+            .expect("missing operation definition is reported");
+        assert_eq!(
+            missing.location.as_deref(),
+            Some("CapabilityStatement.rest[0].resource[0].operation[0].definition")
+        );
+        assert!(missing
+            .diagnostics
+            .contains("http://example.org/OperationDefinition/missing"));
+        let wrong_type = issues
+            .iter()
+            .find(|i| i.code == IssueCode::Invalid)
+            .expect("definition of the wrong type is reported");
+        assert_eq!(
+            wrong_type.location.as_deref(),
+            Some("CapabilityStatement.rest[0].operation[0].definition")
+        );
+    }
+}
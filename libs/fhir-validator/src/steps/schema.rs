@@ -7,15 +7,21 @@
 //! - Data type correctness
 //! - Unknown elements (if disallowed)
 //! - Modifier extensions (if disallowed)
+//! - Extension URLs without a definition (per `extension_policy`)
+//!
+//! When a logical model is configured, instances are validated against that model instead
+//! (no `resourceType`; paths are rooted at the model's root element).
 //!
 //! **Note**: This step validates against base StructureDefinitions only, not profiles.
 //! Profile validation (including slicing) is handled by the Profiles step.
 
 use crate::validator::{IssueCode, ValidationIssue};
-use crate::SchemaPlan;
+use crate::{ExtensionPolicy, SchemaPlan};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
 use ferrum_context::FhirContext;
+use ferrum_models::{StructureDefinition, StructureDefinitionKind};
 use ferrum_snapshot::{ElementDefinition, ExpandedFhirContext};
 
 /// Validates a resource against its base StructureDefinition (core FHIR resource type)
@@ -25,6 +31,11 @@ pub fn validate_schema<C: FhirContext>(
     context: &C,
     issues: &mut Vec<ValidationIssue>,
 ) {
+    if let Some(logical_url) = plan.logical_model.as_deref() {
+        validate_logical_model(resource, logical_url, plan, context, issues);
+        return;
+    }
+
     // Extract resourceType
     let resource_type = match get_resource_type(resource) {
         Some(rt) => rt,
@@ -86,47 +97,36 @@ pub fn validate_schema<C: FhirContext>(
         return;
     }
 
-    // Prefer the provided context if it already serves expanded snapshots. If it doesn't (e.g. choice
-    // variants missing), fall back to on-the-fly expansion via ExpandedFhirContext.
-    let structure_def = {
-        let needs_expansion = match structure_def.snapshot.as_ref() {
-            None => true,
-            Some(snapshot) => {
-                let index = ElementIndex::new(&snapshot.element);
-                snapshot_needs_expansion(resource, &resource_type, &index)
-            }
-        };
-
-        if needs_expansion {
-            let expanded_context = ExpandedFhirContext::borrowed(context);
-            match expanded_context.get_structure_definition(&base_profile_url) {
-                Ok(Some(sd)) => sd,
-                Ok(None) => {
-                    issues.push(
-                        ValidationIssue::error(
-                            IssueCode::NotFound,
-                            format!(
-                                "Base StructureDefinition not found for resource type '{}'",
-                                resource_type
-                            ),
-                        )
-                        .with_location(format!("{}.resourceType", resource_type)),
-                    );
-                    return;
-                }
-                Err(e) => {
-                    issues.push(ValidationIssue::error(
-                        IssueCode::Exception,
-                        format!(
-                            "Error expanding base StructureDefinition '{}': {}",
-                            base_profile_url, e
-                        ),
-                    ));
-                    return;
-                }
-            }
-        } else {
-            structure_def
+    let structure_def = match expand_if_needed(
+        resource,
+        &resource_type,
+        &base_profile_url,
+        structure_def,
+        context,
+    ) {
+        Ok(Some(sd)) => sd,
+        Ok(None) => {
+            issues.push(
+                ValidationIssue::error(
+                    IssueCode::NotFound,
+                    format!(
+                        "Base StructureDefinition not found for resource type '{}'",
+                        resource_type
+                    ),
+                )
+                .with_location(format!("{}.resourceType", resource_type)),
+            );
+            return;
+        }
+        Err(e) => {
+            issues.push(ValidationIssue::error(
+                IssueCode::Exception,
+                format!(
+                    "Error expanding base StructureDefinition '{}': {}",
+                    base_profile_url, e
+                ),
+            ));
+            return;
         }
     };
 
@@ -146,6 +146,134 @@ pub fn validate_schema<C: FhirContext>(
 
     let index = ElementIndex::new(&snapshot.element);
     validate_object(resource, &resource_type, &index, plan, issues);
+    check_extension_definitions(resource, &resource_type, plan, context, issues);
+}
+
+/// Validates an instance against a logical model (StructureDefinition with `kind = logical`).
+///
+/// Logical instances have no `resourceType`; elements are addressed by the model's root
+/// element path (e.g., `MyModel.field`), which may differ from the model's `type` URL.
+fn validate_logical_model<C: FhirContext>(
+    resource: &Value,
+    logical_url: &str,
+    plan: &SchemaPlan,
+    context: &C,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let structure_def = match context.get_structure_definition(logical_url) {
+        Ok(Some(sd)) => sd,
+        Ok(None) => {
+            issues.push(ValidationIssue::error(
+                IssueCode::NotFound,
+                format!("Logical model '{}' not found", logical_url),
+            ));
+            return;
+        }
+        Err(e) => {
+            issues.push(ValidationIssue::error(
+                IssueCode::Exception,
+                format!("Error loading logical model '{}': {}", logical_url, e),
+            ));
+            return;
+        }
+    };
+
+    if structure_def.kind != StructureDefinitionKind::Logical {
+        issues.push(ValidationIssue::error(
+            IssueCode::Invalid,
+            format!(
+                "StructureDefinition '{}' is not a logical model (kind is {:?})",
+                logical_url, structure_def.kind
+            ),
+        ));
+        return;
+    }
+
+    if !resource.is_object() {
+        issues.push(
+            ValidationIssue::error(
+                IssueCode::Structure,
+                format!(
+                    "Instance of logical model '{}' must be an object",
+                    logical_url
+                ),
+            )
+            .with_location(structure_def.name.clone()),
+        );
+        return;
+    }
+
+    // Root element path, e.g. "MyModel" - falls back to the differential when no snapshot is present
+    let root_path = structure_def
+        .snapshot
+        .as_ref()
+        .and_then(|s| s.element.first())
+        .or_else(|| {
+            structure_def
+                .differential
+                .as_ref()
+                .and_then(|d| d.element.first())
+        })
+        .map(|e| e.path.split('.').next().unwrap_or(&e.path).to_string())
+        .unwrap_or_else(|| structure_def.name.clone());
+
+    let structure_def =
+        match expand_if_needed(resource, &root_path, logical_url, structure_def, context) {
+            Ok(Some(sd)) => sd,
+            Ok(None) => {
+                issues.push(ValidationIssue::error(
+                    IssueCode::NotFound,
+                    format!("Logical model '{}' not found", logical_url),
+                ));
+                return;
+            }
+            Err(e) => {
+                issues.push(ValidationIssue::error(
+                    IssueCode::Exception,
+                    format!("Error expanding logical model '{}': {}", logical_url, e),
+                ));
+                return;
+            }
+        };
+
+    let Some(snapshot) = structure_def.snapshot.as_ref() else {
+        issues.push(
+            ValidationIssue::error(
+                IssueCode::Exception,
+                format!("Logical model '{}' has no snapshot", logical_url),
+            )
+            .with_location(root_path),
+        );
+        return;
+    };
+
+    let index = ElementIndex::new(&snapshot.element);
+    validate_object(resource, index.root_path(), &index, plan, issues);
+    check_extension_definitions(resource, index.root_path(), plan, context, issues);
+}
+
+/// Prefer the provided context if it already serves expanded snapshots. If it doesn't (e.g. choice
+/// variants missing), fall back to on-the-fly expansion via ExpandedFhirContext.
+fn expand_if_needed<C: FhirContext>(
+    resource: &Value,
+    root_path: &str,
+    url: &str,
+    structure_def: Arc<StructureDefinition>,
+    context: &C,
+) -> ferrum_context::Result<Option<Arc<StructureDefinition>>> {
+    let needs_expansion = match structure_def.snapshot.as_ref() {
+        None => true,
+        Some(snapshot) => {
+            let index = ElementIndex::new(&snapshot.element);
+            snapshot_needs_expansion(resource, root_path, &index)
+        }
+    };
+
+    if needs_expansion {
+        ExpandedFhirContext::borrowed(context).get_structure_definition(url)
+    } else {
+        Ok(Some(structure_def))
+    }
 }
 
 struct ChoiceBase<'a> {
@@ -639,6 +767,89 @@ fn check_modifier_extensions(resource: &Value, path: &str, issues: &mut Vec<Vali
     }
 }
 
+/// Checks that extension URLs resolve to Extension StructureDefinitions, per `extension_policy`
+fn check_extension_definitions<C: FhirContext>(
+    resource: &Value,
+    path: &str,
+    plan: &SchemaPlan,
+    context: &C,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    if plan.extension_policy == ExtensionPolicy::Allow {
+        return;
+    }
+    let mut resolved = HashMap::new();
+    visit_extensions(
+        resource,
+        path,
+        plan.extension_policy,
+        context,
+        &mut resolved,
+        issues,
+    );
+}
+
+fn visit_extensions<C: FhirContext>(
+    value: &Value,
+    path: &str,
+    policy: ExtensionPolicy,
+    context: &C,
+    resolved: &mut HashMap<String, bool>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let Some(obj) = value.as_object() else {
+        return;
+    };
+
+    for (key, child) in obj {
+        let items: Vec<&Value> = match child {
+            Value::Array(arr) => arr.iter().collect(),
+            Value::Object(_) => vec![child],
+            _ => continue,
+        };
+        let is_extension = key == "extension" || key == "modifierExtension";
+
+        for (idx, item) in items.into_iter().enumerate() {
+            let item_path = if child.is_array() {
+                format!("{}.{}[{}]", path, key, idx)
+            } else {
+                format!("{}.{}", path, key)
+            };
+
+            // Sub-extensions of complex extensions use relative URLs and are defined by
+            // their parent's definition.
+            let url = item.get("url").and_then(Value::as_str);
+            if let Some(url) = url.filter(|url| is_extension && url.contains(':')) {
+                let known = *resolved.entry(url.to_string()).or_insert_with(|| {
+                    matches!(
+                        context.get_structure_definition(url),
+                        Ok(Some(sd)) if sd.type_ == "Extension"
+                    )
+                });
+                if !known {
+                    let message = format!(
+                        "Extension '{}' does not resolve to a known extension definition",
+                        url
+                    );
+                    let issue = match policy {
+                        ExtensionPolicy::RequireDefinition => {
+                            ValidationIssue::error(IssueCode::Extension, message)
+                        }
+                        _ => ValidationIssue::warning(IssueCode::Extension, message),
+                    };
+                    issues.push(
+                        issue
+                            .with_location(item_path.clone())
+                            .with_expression(vec![item_path.clone()]),
+                    );
+                }
+            }
+
+            visit_extensions(item, &item_path, policy, context, resolved, issues);
+        }
+    }
+}
+
 /// Helper to extract resourceType from resource
 fn get_resource_type(resource: &Value) -> Option<String> {
     resource
@@ -650,6 +861,7 @@ fn get_resource_type(resource: &Value) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::validator::IssueSeverity;
     use serde_json::json;
     use std::collections::HashMap;
     use std::sync::Arc;
@@ -790,6 +1002,8 @@ mod tests {
         let plan = SchemaPlan {
             allow_unknown_elements: false,
             allow_modifier_extensions: true,
+            extension_policy: ExtensionPolicy::Allow,
+            logical_model: None,
         };
 
         // Deep snapshot expansion should allow validating nested fields under Patient.name.*
@@ -807,4 +1021,150 @@ mod tests {
             .any(|i| i.location.as_deref() == Some("Patient.name.unknown")
                 && i.code == IssueCode::Structure));
     }
+
+    #[test]
+    fn schema_validates_logical_model_instances() {
+        let mut by_url = HashMap::new();
+        by_url.insert(
+            "http://example.org/fhir/StructureDefinition/Sample".to_string(),
+            Arc::new(json!({
+                "resourceType": "StructureDefinition",
+                "url": "http://example.org/fhir/StructureDefinition/Sample",
+                "name": "Sample",
+                "status": "active",
+                "kind": "logical",
+                "abstract": false,
+                "type": "http://example.org/fhir/StructureDefinition/Sample",
+                "snapshot": { "element": [
+                    { "id": "Sample", "path": "Sample", "min": 0, "max": "*" },
+                    { "id": "Sample.label", "path": "Sample.label", "min": 1, "max": "1", "type": [{ "code": "string" }] },
+                    { "id": "Sample.tag", "path": "Sample.tag", "min": 0, "max": "2", "type": [{ "code": "code" }] }
+                ]}
+            })),
+        );
+
+        let ctx = MockContext { by_url };
+        let plan = SchemaPlan {
+            allow_unknown_elements: false,
+            allow_modifier_extensions: true,
+            extension_policy: ExtensionPolicy::Allow,
+            logical_model: Some("http://example.org/fhir/StructureDefinition/Sample".to_string()),
+        };
+
+        let mut issues = Vec::new();
+        validate_schema(
+            &json!({ "label": "ok", "tag": ["a"] }),
+            &plan,
+            &ctx,
+            &mut issues,
+        );
+        assert!(issues.is_empty(), "unexpected issues: {:?}", issues);
+
+        // Missing required label and too many tags; no resourceType is required
+        let mut issues = Vec::new();
+        validate_schema(&json!({ "tag": ["a", "b", "c"] }), &plan, &ctx, &mut issues);
+        assert_eq!(issues.len(), 2, "issues: {:?}", issues);
+        assert!(issues.iter().any(
+            |i| i.location.as_deref() == Some("Sample.label") && i.code == IssueCode::Required
+        ));
+        assert!(
+            issues
+                .iter()
+                .any(|i| i.location.as_deref() == Some("Sample.tag")
+                    && i.code == IssueCode::Structure)
+        );
+    }
+
+    #[test]
+    fn schema_extension_policy_checks_extension_definitions() {
+        let mut by_url = HashMap::new();
+        by_url.insert(
+            "http://hl7.org/fhir/StructureDefinition/Patient".to_string(),
+            Arc::new(json!({
+                "resourceType": "StructureDefinition",
+                "url": "http://hl7.org/fhir/StructureDefinition/Patient",
+                "name": "Patient",
+                "status": "active",
+                "kind": "resource",
+                "abstract": false,
+                "type": "Patient",
+                "snapshot": { "element": [
+                    { "id": "Patient", "path": "Patient" },
+                    { "id": "Patient.active", "path": "Patient.active", "min": 0, "max": "1", "type": [{ "code": "boolean" }] }
+                ]}
+            })),
+        );
+        by_url.insert(
+            "http://example.org/fhir/StructureDefinition/known".to_string(),
+            Arc::new(json!({
+                "resourceType": "StructureDefinition",
+                "url": "http://example.org/fhir/StructureDefinition/known",
+                "name": "Known",
+                "status": "active",
+                "kind": "complex-type",
+                "abstract": false,
+                "type": "Extension",
+                "derivation": "constraint",
+                "snapshot": { "element": [{ "id": "Extension", "path": "Extension" }] }
+            })),
+        );
+        let ctx = MockContext { by_url };
+
+        let validate = |policy: ExtensionPolicy, resource: &Value| {
+            let plan = SchemaPlan {
+                allow_unknown_elements: false,
+                allow_modifier_extensions: true,
+                extension_policy: policy,
+                logical_model: None,
+            };
+            let mut issues = Vec::new();
+            validate_schema(resource, &plan, &ctx, &mut issues);
+            issues
+        };
+
+        // A defined extension (with a relative sub-extension URL) passes every policy
+        let known = json!({
+            "resourceType": "Patient",
+            "extension": [{
+                "url": "http://example.org/fhir/StructureDefinition/known",
+                "extension": [{ "url": "part", "valueString": "x" }]
+            }]
+        });
+        for policy in [
+            ExtensionPolicy::Allow,
+            ExtensionPolicy::WarnUnknown,
+            ExtensionPolicy::RequireDefinition,
+        ] {
+            let issues = validate(policy, &known);
+            assert!(issues.is_empty(), "{:?}: {:?}", policy, issues);
+        }
+
+        // Undefined URLs, and URLs of definitions that are not extensions
+        let unknown = json!({
+            "resourceType": "Patient",
+            "_active": {
+                "extension": [{ "url": "http://example.org/fhir/StructureDefinition/unknown" }]
+            },
+            "modifierExtension": [{ "url": "http://hl7.org/fhir/StructureDefinition/Patient" }]
+        });
+        assert!(validate(ExtensionPolicy::Allow, &unknown).is_empty());
+
+        let issues = validate(ExtensionPolicy::WarnUnknown, &unknown);
+        assert_eq!(issues.len(), 2, "issues: {:?}", issues);
+        assert!(issues
+            .iter()
+            .all(|i| i.severity == IssueSeverity::Warning && i.code == IssueCode::Extension));
+        assert!(issues
+            .iter()
+            .any(|i| i.location.as_deref() == Some("Patient._active.extension[0]")));
+        assert!(issues
+            .iter()
+            .any(|i| i.location.as_deref() == Some("Patient.modifierExtension[0]")));
+
+        let issues = validate(ExtensionPolicy::RequireDefinition, &unknown);
+        assert_eq!(issues.len(), 2, "issues: {:?}", issues);
+        assert!(issues
+            .iter()
+            .all(|i| i.severity == IssueSeverity::Error && i.code == IssueCode::Extension));
+    }
 }
@@ -8,7 +8,7 @@ use ferrum_models::BindingStrength;
 use serde_json::Value;
 
 use crate::terminology::TerminologyProvider;
-use crate::validator::{IssueCode, IssueSeverity, ValidationIssue};
+use crate::validator::{ChangedPaths, IssueCode, IssueSeverity, ValidationIssue};
 use crate::{ExtensibleHandling, TerminologyPlan};
 
 /// Run terminology validation on a resource.
@@ -17,6 +17,7 @@ pub fn validate_terminology(
     plan: &TerminologyPlan,
     context: &dyn FhirContext,
     terminology: &dyn TerminologyProvider,
+    changed: Option<&ChangedPaths>,
     issues: &mut Vec<ValidationIssue>,
 ) {
     let resource_type = match resource.get("resourceType").and_then(|v| v.as_str()) {
@@ -42,6 +43,11 @@ pub fn validate_terminology(
             None => continue,
         };
 
+        // Incremental validation: only re-check bindings on changed elements
+        if changed.is_some_and(|changed| !changed.touches(&element.path)) {
+            continue;
+        }
+
         // Skip Example bindings — never validated
         if binding.strength == BindingStrength::Example {
             continue;
@@ -413,6 +419,7 @@ fn validate_single_code(
                     diagnostics: msg.clone(),
                     location: Some(location.to_string()),
                     expression: None,
+                    profile: None,
                 },
             );
         }
@@ -444,6 +451,7 @@ fn validate_single_code(
             diagnostics: msg,
             location: Some(location.to_string()),
             expression: None,
+            profile: None,
         },
     );
 }
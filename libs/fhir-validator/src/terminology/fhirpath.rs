@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use ferrum_fhirpath::Error as FhirPathError;
+
+use super::provider::TerminologyProvider;
+
+/// Exposes a [`TerminologyProvider`] to the FHIRPath engine so invariants can call `memberOf()`.
+pub struct FhirPathTerminology {
+    provider: Arc<dyn TerminologyProvider>,
+}
+
+impl FhirPathTerminology {
+    pub fn new(provider: Arc<dyn TerminologyProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+impl ferrum_fhirpath::TerminologyProvider for FhirPathTerminology {
+    fn member_of(
+        &self,
+        system: Option<&str>,
+        code: &str,
+        value_set_url: &str,
+    ) -> ferrum_fhirpath::Result<Option<bool>> {
+        // The validator's providers treat an empty system as a bare code
+        self.provider
+            .validate_code(system.unwrap_or(""), code, None, value_set_url)
+            .map(|result| result.map(|result| result.valid))
+            .map_err(|e| {
+                FhirPathError::EvaluationError(format!(
+                    "memberOf('{}') failed: {}",
+                    value_set_url, e
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminology::CodeValidationResult;
+    use ferrum_context::DefaultFhirContext;
+    use ferrum_fhirpath::{Context, Engine, Value};
+    use serde_json::json;
+
+    /// Knows a ValueSet with the LOINC code 8480-6
+    struct BloodPressure;
+
+    impl TerminologyProvider for BloodPressure {
+        fn validate_code(
+            &self,
+            system: &str,
+            code: &str,
+            _display: Option<&str>,
+            value_set_url: &str,
+        ) -> Result<Option<CodeValidationResult>, Box<dyn std::error::Error>> {
+            if value_set_url != "http://example.org/ValueSet/bp" {
+                return Ok(None);
+            }
+            Ok(Some(CodeValidationResult {
+                valid: system == "http://loinc.org" && code == "8480-6",
+                display: None,
+                message: None,
+                severity_override: None,
+            }))
+        }
+
+        fn validate_code_in_system(
+            &self,
+            _system: &str,
+            _code: &str,
+        ) -> Result<Option<CodeValidationResult>, Box<dyn std::error::Error>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn member_of_uses_the_terminology_provider() {
+        let engine = Engine::new(Arc::new(DefaultFhirContext::from_packages(vec![])), None)
+            .with_terminology_provider(Arc::new(FhirPathTerminology::new(Arc::new(BloodPressure))));
+        let ctx = Context::new(Value::from_json(json!({
+            "resourceType": "Observation",
+            "code": {"coding": [{"system": "http://loinc.org", "code": "8480-6"}]}
+        })));
+
+        let member = engine
+            .evaluate_expr(
+                "code.memberOf('http://example.org/ValueSet/bp')",
+                &ctx,
+                None,
+            )
+            .unwrap();
+        assert!(member.as_boolean().unwrap());
+
+        let unknown = engine
+            .evaluate_expr(
+                "code.memberOf('http://example.org/ValueSet/other')",
+                &ctx,
+                None,
+            )
+            .unwrap();
+        assert!(unknown.is_empty());
+    }
+}
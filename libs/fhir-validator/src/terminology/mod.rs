@@ -1,5 +1,7 @@
 mod provider;
 mod in_memory;
+mod fhirpath;
 
 pub use provider::{CodeValidationResult, TerminologyProvider};
 pub use in_memory::InMemoryTerminologyProvider;
+pub use fhirpath::FhirPathTerminology;
@@ -1,9 +1,11 @@
-use crate::terminology::{InMemoryTerminologyProvider, TerminologyProvider};
+use crate::steps::profiles::ProfileCache;
+use crate::terminology::{FhirPathTerminology, InMemoryTerminologyProvider, TerminologyProvider};
 use crate::{ConfigError, TerminologyMode, ValidationPlan};
 use ferrum_context::FhirContext;
 use ferrum_fhirpath::Engine as FhirPathEngine;
 use ferrum_snapshot::ExpandedFhirContext;
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 /// Reusable validator - owns plan, context, FHIRPath engine, and optional terminology provider
@@ -12,26 +14,25 @@ pub struct Validator<C: FhirContext> {
     context: Arc<C>,
     fhirpath_engine: Arc<FhirPathEngine>,
     terminology: Option<Arc<dyn TerminologyProvider>>,
+    profile_cache: ProfileCache,
 }
 
 impl<C: FhirContext + 'static> Validator<C> {
     pub fn new(plan: ValidationPlan, context: C) -> Self {
         let context = Arc::new(context);
 
-        // Create FHIRPath engine sharing the same context for discriminator evaluation
-        let fhirpath_engine = Arc::new(FhirPathEngine::new(
-            context.clone() as Arc<dyn FhirContext>,
-            None,
-        ));
-
         // Create terminology provider based on plan
         let terminology = Self::create_terminology_provider(&plan, &context);
 
+        // Create FHIRPath engine sharing the same context for discriminator evaluation
+        let fhirpath_engine = Self::create_fhirpath_engine(&context, terminology.as_ref());
+
         Self {
             plan,
             context,
             fhirpath_engine,
             terminology,
+            profile_cache: ProfileCache::default(),
         }
     }
 
@@ -53,23 +54,24 @@ impl<C: FhirContext + 'static> Validator<C> {
         let expanded_context = ExpandedFhirContext::new(inner_context);
         let expanded_arc = Arc::new(expanded_context);
 
-        // Create new engine for the expanded context
-        let fhirpath_engine = Arc::new(FhirPathEngine::new(
-            expanded_arc.clone() as Arc<dyn FhirContext>,
-            None,
-        ));
-
         // Create terminology provider for expanded context
         let terminology = Validator::<ExpandedFhirContext<C>>::create_terminology_provider_from_plan_and_context(
             &self.plan,
             &expanded_arc,
         );
 
+        // Create new engine for the expanded context
+        let fhirpath_engine = Validator::<ExpandedFhirContext<C>>::create_fhirpath_engine(
+            &expanded_arc,
+            terminology.as_ref(),
+        );
+
         Validator {
             plan: self.plan,
             context: expanded_arc,
             fhirpath_engine,
             terminology,
+            profile_cache: ProfileCache::default(),
         }
     }
 
@@ -79,15 +81,66 @@ impl<C: FhirContext + 'static> Validator<C> {
             &self.context,
             &self.fhirpath_engine,
             self.terminology.as_deref(),
+            &self.profile_cache,
             resource,
         )
         .execute()
     }
 
+    /// Validate an updated resource, re-checking only what the update can have affected.
+    ///
+    /// `changed_paths` are element paths (`Patient.name[0].family` or `name.family`) or JSON
+    /// Pointers (`/name/0/family`), e.g. from a JSON Patch. Schema, profile and reference checks
+    /// run in full. Constraints and bindings run only for elements that contain a change or lie
+    /// inside a changed element, plus constraints that look beyond their own element
+    /// (`%resource`, `%rootResource`, `resolve()`). Root-level invariants contain every change,
+    /// so they always run.
+    pub fn validate_changed(
+        &self,
+        resource: &Value,
+        changed_paths: &[String],
+    ) -> ValidationOutcome {
+        let resource_type = resource
+            .get("resourceType")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let changed = ChangedPaths::new(resource_type, changed_paths);
+
+        let mut run = ValidationRun::new(
+            &self.plan,
+            &self.context,
+            &self.fhirpath_engine,
+            self.terminology.as_deref(),
+            &self.profile_cache,
+            resource,
+        );
+        run.changed = Some(&changed);
+        run.execute()
+    }
+
     pub fn validate_batch(&self, resources: &[Value]) -> Vec<ValidationOutcome> {
         resources.iter().map(|r| self.validate(r)).collect()
     }
 
+    /// Validate each `entry[].resource` of a Bundle, keyed by entry index.
+    ///
+    /// Entries without a resource (e.g. transaction DELETEs) are skipped, so indices can have
+    /// gaps. Anything other than a Bundle yields no outcomes.
+    pub fn validate_bundle(&self, bundle: &Value) -> Vec<(usize, ValidationOutcome)> {
+        if bundle.get("resourceType").and_then(Value::as_str) != Some("Bundle") {
+            return Vec::new();
+        }
+
+        bundle
+            .get("entry")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(index, entry)| Some((index, self.validate(entry.get("resource")?))))
+            .collect()
+    }
+
     pub fn plan(&self) -> &ValidationPlan {
         &self.plan
     }
@@ -103,6 +156,20 @@ impl<C: FhirContext + 'static> Validator<C> {
         Self::create_terminology_provider_from_plan_and_context(plan, context)
     }
 
+    /// FHIRPath engine for constraints and discriminators; `memberOf()` uses the terminology
+    /// provider when there is one.
+    fn create_fhirpath_engine(
+        context: &Arc<C>,
+        terminology: Option<&Arc<dyn TerminologyProvider>>,
+    ) -> Arc<FhirPathEngine> {
+        let engine = FhirPathEngine::new(context.clone() as Arc<dyn FhirContext>, None);
+        Arc::new(match terminology {
+            Some(terminology) => engine
+                .with_terminology_provider(Arc::new(FhirPathTerminology::new(terminology.clone()))),
+            None => engine,
+        })
+    }
+
     fn create_terminology_provider_from_plan_and_context(
         plan: &ValidationPlan,
         context: &Arc<C>,
@@ -127,7 +194,10 @@ struct ValidationRun<'a, C: FhirContext> {
     context: &'a Arc<C>,
     fhirpath_engine: &'a Arc<FhirPathEngine>,
     terminology: Option<&'a dyn TerminologyProvider>,
+    profile_cache: &'a ProfileCache,
     resource: &'a Value,
+    /// Restricts constraint and terminology checks for incremental validation
+    changed: Option<&'a ChangedPaths>,
     issues: Vec<ValidationIssue>,
 }
 
@@ -137,6 +207,7 @@ impl<'a, C: FhirContext> ValidationRun<'a, C> {
         context: &'a Arc<C>,
         fhirpath_engine: &'a Arc<FhirPathEngine>,
         terminology: Option<&'a dyn TerminologyProvider>,
+        profile_cache: &'a ProfileCache,
         resource: &'a Value,
     ) -> Self {
         Self {
@@ -144,7 +215,9 @@ impl<'a, C: FhirContext> ValidationRun<'a, C> {
             context,
             fhirpath_engine,
             terminology,
+            profile_cache,
             resource,
+            changed: None,
             issues: Vec::new(),
         }
     }
@@ -162,11 +235,13 @@ impl<'a, C: FhirContext> ValidationRun<'a, C> {
             self.execute_step(step);
         }
 
-        ValidationOutcome {
+        let mut outcome = ValidationOutcome {
             resource_type: self.get_resource_type(),
             valid: !self.has_errors(),
             issues: self.issues,
-        }
+        };
+        outcome.normalize();
+        outcome
     }
 
     fn execute_step(&mut self, step: &crate::Step) {
@@ -197,6 +272,7 @@ impl<'a, C: FhirContext> ValidationRun<'a, C> {
             plan,
             self.context.as_ref(),
             self.fhirpath_engine,
+            self.profile_cache,
             &mut self.issues,
         );
     }
@@ -207,6 +283,7 @@ impl<'a, C: FhirContext> ValidationRun<'a, C> {
             plan,
             self.context.as_ref(),
             self.fhirpath_engine,
+            self.changed,
             &mut self.issues,
         );
     }
@@ -218,13 +295,19 @@ impl<'a, C: FhirContext> ValidationRun<'a, C> {
                 plan,
                 self.context.as_ref(),
                 terminology,
+                self.changed,
                 &mut self.issues,
             );
         }
     }
 
-    fn validate_references(&mut self, _plan: &crate::ReferencesPlan) {
-        // TODO: Implement reference validation
+    fn validate_references(&mut self, plan: &crate::ReferencesPlan) {
+        crate::steps::references::validate_references(
+            self.resource,
+            plan,
+            self.context.as_ref(),
+            &mut self.issues,
+        );
     }
 
     fn validate_bundles(&mut self, _plan: &crate::BundlePlan) {
@@ -280,6 +363,44 @@ impl ValidationOutcome {
             .count()
     }
 
+    /// Sort issues by location, severity, code, message and profile, and drop exact duplicates.
+    ///
+    /// Issues without a location sort first. Duplicates keep the first occurrence's expression.
+    pub fn normalize(&mut self) {
+        self.issues.sort_by(|a, b| {
+            a.location
+                .cmp(&b.location)
+                .then(a.severity.cmp(&b.severity))
+                .then(a.code.cmp(&b.code))
+                .then_with(|| a.diagnostics.cmp(&b.diagnostics))
+                .then_with(|| a.profile.cmp(&b.profile))
+        });
+        self.issues.dedup_by(|a, b| {
+            a.location == b.location
+                && a.severity == b.severity
+                && a.code == b.code
+                && a.diagnostics == b.diagnostics
+                && a.profile == b.profile
+        });
+    }
+
+    /// Group issues by their normalized element path, sorted by path.
+    ///
+    /// With `collapse_indices`, `Patient.name[0].family` and `Patient.name[1].family` share the
+    /// key `Patient.name.family`. Issues without a location are grouped under `""`. Issues keep
+    /// their order within a group.
+    pub fn group_by_path(&self, collapse_indices: bool) -> BTreeMap<String, Vec<ValidationIssue>> {
+        let mut groups: BTreeMap<String, Vec<ValidationIssue>> = BTreeMap::new();
+        for issue in &self.issues {
+            let path = issue
+                .fhirpath()
+                .map(|path| normalize_issue_path(path, collapse_indices))
+                .unwrap_or_default();
+            groups.entry(path).or_default().push(issue.clone());
+        }
+        groups
+    }
+
     pub fn to_operation_outcome(&self) -> Value {
         serde_json::json!({
             "resourceType": "OperationOutcome",
@@ -296,6 +417,8 @@ pub struct ValidationIssue {
     pub diagnostics: String,
     pub location: Option<String>,
     pub expression: Option<Vec<String>>,
+    /// Canonical URL of the profile whose constraint produced the issue, if any.
+    pub profile: Option<String>,
 }
 
 impl ValidationIssue {
@@ -306,6 +429,7 @@ impl ValidationIssue {
             diagnostics,
             location: None,
             expression: None,
+            profile: None,
         }
     }
 
@@ -316,6 +440,7 @@ impl ValidationIssue {
             diagnostics,
             location: None,
             expression: None,
+            profile: None,
         }
     }
 
@@ -326,6 +451,7 @@ impl ValidationIssue {
             diagnostics,
             location: None,
             expression: None,
+            profile: None,
         }
     }
 
@@ -339,6 +465,23 @@ impl ValidationIssue {
         self
     }
 
+    pub fn with_profile(mut self, profile: String) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Element path of the issue as a FHIRPath expression (e.g. `Patient.name[0].family`).
+    pub fn fhirpath(&self) -> Option<&str> {
+        self.location
+            .as_deref()
+            .or_else(|| self.expression.as_ref()?.first().map(String::as_str))
+    }
+
+    /// Element path of the issue as a JSON Pointer into the resource (e.g. `/name/0/family`).
+    pub fn json_pointer(&self) -> Option<String> {
+        self.fhirpath().map(fhirpath_to_json_pointer)
+    }
+
     fn to_json(&self) -> Value {
         let mut issue = serde_json::json!({
             "severity": self.severity.to_string().to_lowercase(),
@@ -358,7 +501,157 @@ impl ValidationIssue {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Convert an element path such as `Patient.name[0].family` to a JSON Pointer (RFC 6901)
+/// relative to the resource, e.g. `/name/0/family`.
+///
+/// The leading resource type is dropped, indices become their own tokens and `~` and `/` are
+/// escaped. Slice names (`name:official`) and choice markers (`value[x]`) are ignored, and the
+/// pointer stops at the first function call (`where(...)`), pointing at the nearest element
+/// that can be addressed.
+pub fn fhirpath_to_json_pointer(path: &str) -> String {
+    let mut pointer = String::new();
+    let mut push_token = |token: &str| {
+        pointer.push('/');
+        pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+    };
+
+    for (i, segment) in split_path_segments(path).into_iter().enumerate() {
+        if i == 0 && segment.starts_with(|c: char| c.is_ascii_uppercase()) {
+            continue;
+        }
+        if segment.contains('(') {
+            break;
+        }
+
+        let (name, mut rest) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        let name = name.split(':').next().unwrap_or(name);
+        push_token(name);
+
+        while let Some(open) = rest.find('[') {
+            let Some(close) = rest[open..].find(']') else {
+                break;
+            };
+            let index = &rest[open + 1..open + close];
+            if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) {
+                push_token(index);
+            }
+            rest = &rest[open + close + 1..];
+        }
+    }
+
+    pointer
+}
+
+/// Normalize an element path for grouping: trim whitespace, drop empty segments and, with
+/// `collapse_indices`, remove numeric indices (`name[0]` becomes `name`). Function calls and
+/// choice markers such as `value[x]` are kept as written.
+fn normalize_issue_path(path: &str, collapse_indices: bool) -> String {
+    split_path_segments(path.trim())
+        .into_iter()
+        .map(|segment| {
+            if !collapse_indices || segment.contains('(') {
+                return segment.to_string();
+            }
+            let mut out = String::with_capacity(segment.len());
+            let mut rest = segment;
+            while let Some(open) = rest.find('[') {
+                let Some(close) = rest[open..].find(']') else {
+                    break;
+                };
+                let index = &rest[open + 1..open + close];
+                out.push_str(&rest[..open]);
+                if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+                    out.push_str(&rest[open..=open + close]);
+                }
+                rest = &rest[open + close + 1..];
+            }
+            out.push_str(rest);
+            out
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Element paths changed by an update, see [`Validator::validate_changed`].
+///
+/// Paths are kept relative to the resource, without indices (`name[0].family` becomes
+/// `["name", "family"]`); an empty path stands for the whole resource.
+pub(crate) struct ChangedPaths {
+    paths: Vec<Vec<String>>,
+}
+
+impl ChangedPaths {
+    pub(crate) fn new(resource_type: &str, changed_paths: &[String]) -> Self {
+        let paths = changed_paths
+            .iter()
+            .map(|path| {
+                let path = path.trim();
+                if let Some(pointer) = path.strip_prefix('/') {
+                    return pointer
+                        .split('/')
+                        .filter(|token| !token.bytes().all(|b| b.is_ascii_digit()))
+                        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+                        .collect();
+                }
+
+                let mut segments = split_path_segments(path);
+                if segments.first() == Some(&resource_type) {
+                    segments.remove(0);
+                }
+                segments
+                    .into_iter()
+                    // A function call selects within the element before it
+                    .take_while(|segment| !segment.contains('('))
+                    .map(|segment| {
+                        let name = &segment[..segment.find('[').unwrap_or(segment.len())];
+                        name.trim_start_matches('_').to_string()
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { paths }
+    }
+
+    /// Whether the element at `element_path` (e.g. `Patient.name.family`) contains a change or
+    /// lies inside a changed element. Choice elements (`value[x]`) match any of their types.
+    pub(crate) fn touches(&self, element_path: &str) -> bool {
+        let element: Vec<&str> = element_path.split('.').skip(1).collect();
+        self.paths.iter().any(|changed| {
+            element
+                .iter()
+                .zip(changed)
+                .all(|(defined, actual)| match defined.strip_suffix("[x]") {
+                    Some(stem) => actual.starts_with(stem),
+                    None => defined == actual,
+                })
+        })
+    }
+}
+
+/// Split a path on `.` outside of brackets, parentheses and quotes.
+fn split_path_segments(path: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut in_quote = false;
+    let mut start = 0;
+    for (i, c) in path.char_indices() {
+        match c {
+            '\'' => in_quote = !in_quote,
+            '[' | '(' if !in_quote => depth += 1,
+            ']' | ')' if !in_quote => depth = depth.saturating_sub(1),
+            '.' if !in_quote && depth == 0 => {
+                segments.push(&path[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&path[start..]);
+    segments.retain(|segment| !segment.is_empty());
+    segments
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum IssueSeverity {
     Fatal,
     Error,
@@ -377,7 +670,7 @@ impl std::fmt::Display for IssueSeverity {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum IssueCode {
     Invalid,
     Structure,
@@ -454,6 +747,9 @@ impl std::fmt::Display for IssueCode {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ferrum_context::Result as ContextResult;
+    use serde_json::json;
+    use std::collections::HashMap;
 
     #[test]
     fn test_validation_outcome_operations() {
@@ -472,6 +768,60 @@ mod tests {
         assert_eq!(outcome.warning_count(), 1);
     }
 
+    #[test]
+    fn test_normalize_sorts_and_deduplicates_issues() {
+        let at = |issue: ValidationIssue, location: &str| issue.with_location(location.to_string());
+        let mut outcome = ValidationOutcome {
+            resource_type: Some("Patient".to_string()),
+            valid: false,
+            issues: vec![
+                at(
+                    ValidationIssue::warning(IssueCode::Value, "b".to_string()),
+                    "Patient.name",
+                ),
+                at(
+                    ValidationIssue::error(IssueCode::Value, "a".to_string()),
+                    "Patient.name",
+                ),
+                ValidationIssue::error(IssueCode::Structure, "no location".to_string()),
+                at(
+                    ValidationIssue::error(IssueCode::Required, "a".to_string()),
+                    "Patient.gender",
+                ),
+                at(
+                    ValidationIssue::warning(IssueCode::Value, "b".to_string()),
+                    "Patient.name",
+                ),
+                at(
+                    ValidationIssue::error(IssueCode::Value, "a".to_string()),
+                    "Patient.name",
+                )
+                .with_expression(vec!["Patient.name".to_string()]),
+            ],
+        };
+
+        outcome.normalize();
+
+        let order: Vec<_> = outcome
+            .issues
+            .iter()
+            .map(|i| (i.location.as_deref(), i.diagnostics.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (None, "no location"),
+                (Some("Patient.gender"), "a"),
+                (Some("Patient.name"), "a"),
+                (Some("Patient.name"), "b"),
+            ]
+        );
+        assert_eq!(outcome.issues[2].severity, IssueSeverity::Error);
+        assert_eq!(outcome.issues[3].severity, IssueSeverity::Warning);
+        // The first of the duplicates is kept
+        assert!(outcome.issues[2].expression.is_none());
+    }
+
     #[test]
     fn test_operation_outcome_conversion() {
         let outcome = ValidationOutcome {
@@ -490,4 +840,243 @@ mod tests {
         assert_eq!(op_outcome["issue"][0]["severity"], "error");
         assert_eq!(op_outcome["issue"][0]["code"], "required");
     }
+
+    #[test]
+    fn test_issue_location_as_fhirpath_and_json_pointer() {
+        let issue = ValidationIssue::error(IssueCode::Value, "bad family".to_string())
+            .with_location("Patient.name[0].family".to_string());
+        assert_eq!(issue.fhirpath(), Some("Patient.name[0].family"));
+        assert_eq!(issue.json_pointer().as_deref(), Some("/name/0/family"));
+
+        let issue = ValidationIssue::error(IssueCode::Value, "bad value".to_string())
+            .with_expression(vec!["Bundle.entry[2].resource.telecom[1].value".to_string()]);
+        assert_eq!(
+            issue.json_pointer().as_deref(),
+            Some("/entry/2/resource/telecom/1/value")
+        );
+
+        let issue = ValidationIssue::error(IssueCode::Value, "no location".to_string());
+        assert!(issue.fhirpath().is_none());
+        assert!(issue.json_pointer().is_none());
+    }
+
+    #[test]
+    fn test_group_by_path() {
+        let at = |issue: ValidationIssue, location: &str| issue.with_location(location.to_string());
+        let outcome = ValidationOutcome {
+            resource_type: Some("Patient".to_string()),
+            valid: false,
+            issues: vec![
+                at(
+                    ValidationIssue::error(IssueCode::Value, "bad given".to_string()),
+                    "Patient.name[1].given",
+                ),
+                at(
+                    ValidationIssue::error(IssueCode::Value, "bad family".to_string()),
+                    "Patient.name[0].family",
+                ),
+                ValidationIssue::warning(IssueCode::Processing, "no location".to_string()),
+                at(
+                    ValidationIssue::warning(IssueCode::Value, "odd family".to_string()),
+                    " Patient.name[0].family ",
+                ),
+            ],
+        };
+
+        let groups = outcome.group_by_path(false);
+        assert_eq!(
+            groups.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["", "Patient.name[0].family", "Patient.name[1].given"]
+        );
+        let diagnostics = |path: &str| {
+            groups[path]
+                .iter()
+                .map(|i| i.diagnostics.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            diagnostics("Patient.name[0].family"),
+            vec!["bad family", "odd family"]
+        );
+        assert_eq!(diagnostics("Patient.name[1].given"), vec!["bad given"]);
+        assert_eq!(diagnostics(""), vec!["no location"]);
+
+        let collapsed = outcome.group_by_path(true);
+        assert_eq!(
+            collapsed.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["", "Patient.name.family", "Patient.name.given"]
+        );
+        assert_eq!(collapsed["Patient.name.family"].len(), 2);
+        assert_eq!(
+            normalize_issue_path("Observation.value[x]", true),
+            "Observation.value[x]"
+        );
+    }
+
+    #[test]
+    fn test_json_pointer_escaping_and_unmappable_segments() {
+        assert_eq!(fhirpath_to_json_pointer("Basic.a/b.c~d"), "/a~1b/c~0d");
+        assert_eq!(fhirpath_to_json_pointer("Patient"), "");
+        assert_eq!(
+            fhirpath_to_json_pointer("Patient.identifier:mrn[0].system"),
+            "/identifier/0/system"
+        );
+        assert_eq!(fhirpath_to_json_pointer("Observation.value[x]"), "/value");
+        assert_eq!(
+            fhirpath_to_json_pointer("Patient.name.where(use = 'official').family"),
+            "/name"
+        );
+    }
+
+    struct MockContext {
+        by_url: HashMap<String, Arc<Value>>,
+    }
+
+    impl FhirContext for MockContext {
+        fn get_resource_by_url(
+            &self,
+            canonical_url: &str,
+            _version: Option<&str>,
+        ) -> ContextResult<Option<Arc<Value>>> {
+            Ok(self.by_url.get(canonical_url).cloned())
+        }
+    }
+
+    #[test]
+    fn test_validate_changed_reruns_only_affected_constraints() {
+        fn constraint(key: &str, expr: &str) -> Value {
+            json!({ "key": key, "severity": "error", "human": key, "expression": expr })
+        }
+        let mut by_url = HashMap::new();
+        by_url.insert(
+            "http://hl7.org/fhir/StructureDefinition/Patient".to_string(),
+            Arc::new(json!({
+                "resourceType": "StructureDefinition",
+                "url": "http://hl7.org/fhir/StructureDefinition/Patient",
+                "name": "Patient",
+                "status": "active",
+                "kind": "resource",
+                "abstract": false,
+                "type": "Patient",
+                "snapshot": { "element": [
+                    { "id": "Patient", "path": "Patient" },
+                    { "id": "Patient.active", "path": "Patient.active", "min": 0, "max": "1", "type": [{ "code": "boolean" }] },
+                    { "id": "Patient.name", "path": "Patient.name", "min": 0, "max": "*", "type": [{ "code": "HumanName" }],
+                      "constraint": [constraint("nam-1", "family.exists()")] },
+                    { "id": "Patient.telecom", "path": "Patient.telecom", "min": 0, "max": "*", "type": [{ "code": "ContactPoint" }],
+                      "constraint": [
+                        constraint("tel-1", "value.exists()"),
+                        constraint("tel-2", "%resource.active.exists()")
+                      ] }
+                ]}
+            })),
+        );
+        let mut config = crate::ValidatorConfig::preset(crate::Preset::Ingestion);
+        config.constraints.mode = crate::ConstraintsMode::Full;
+        let validator = Validator::from_config(&config, MockContext { by_url }).unwrap();
+
+        let patient = json!({
+            "resourceType": "Patient",
+            "name": [{ "given": ["Ann"] }],
+            "telecom": [{ "system": "phone" }]
+        });
+        let failed = |outcome: ValidationOutcome| {
+            let mut keys: Vec<String> = outcome
+                .issues
+                .iter()
+                .filter(|i| i.code == IssueCode::Invariant)
+                .map(|i| i.diagnostics.split('\'').nth(1).unwrap().to_string())
+                .collect();
+            keys.sort();
+            keys
+        };
+
+        assert_eq!(
+            failed(validator.validate(&patient)),
+            ["nam-1", "tel-1", "tel-2"]
+        );
+
+        // tel-2 reads %resource, so it runs whatever changed
+        let changed = ["Patient.name[0].given".to_string()];
+        assert_eq!(
+            failed(validator.validate_changed(&patient, &changed)),
+            ["nam-1", "tel-2"]
+        );
+        let changed = ["/telecom/0/system".to_string()];
+        assert_eq!(
+            failed(validator.validate_changed(&patient, &changed)),
+            ["tel-1", "tel-2"]
+        );
+        // Replacing the whole element list re-runs the constraints on its items
+        let changed = ["name".to_string()];
+        assert_eq!(
+            failed(validator.validate_changed(&patient, &changed)),
+            ["nam-1", "tel-2"]
+        );
+    }
+
+    #[test]
+    fn test_changed_paths_touch_ancestors_descendants_and_choices() {
+        let changed = ChangedPaths::new(
+            "Observation",
+            &[
+                "Observation.valueQuantity.value".to_string(),
+                "/component/1/code".to_string(),
+            ],
+        );
+        assert!(changed.touches("Observation"));
+        assert!(changed.touches("Observation.value[x]"));
+        assert!(changed.touches("Observation.value[x].value"));
+        assert!(!changed.touches("Observation.value[x].unit"));
+        assert!(changed.touches("Observation.component.code.coding"));
+        assert!(!changed.touches("Observation.component.value[x]"));
+        assert!(!changed.touches("Observation.status"));
+
+        let primitive = ChangedPaths::new("Patient", &["Patient._birthDate.extension".to_string()]);
+        assert!(primitive.touches("Patient.birthDate"));
+    }
+
+    #[test]
+    fn test_validate_bundle_reports_outcomes_per_entry() {
+        let mut by_url = HashMap::new();
+        by_url.insert(
+            "http://hl7.org/fhir/StructureDefinition/Patient".to_string(),
+            Arc::new(json!({
+                "resourceType": "StructureDefinition",
+                "url": "http://hl7.org/fhir/StructureDefinition/Patient",
+                "name": "Patient",
+                "status": "active",
+                "kind": "resource",
+                "abstract": false,
+                "type": "Patient",
+                "snapshot": { "element": [
+                    { "id": "Patient", "path": "Patient" },
+                    { "id": "Patient.active", "path": "Patient.active", "min": 0, "max": "1", "type": [{ "code": "boolean" }] }
+                ]}
+            })),
+        );
+        let config = crate::ValidatorConfig::preset(crate::Preset::Ingestion);
+        let validator = Validator::from_config(&config, MockContext { by_url }).unwrap();
+
+        let bundle = json!({
+            "resourceType": "Bundle",
+            "type": "transaction",
+            "entry": [
+                { "resource": { "resourceType": "Patient", "active": true } },
+                { "request": { "method": "DELETE", "url": "Patient/gone" } },
+                { "resource": { "resourceType": "Patient", "active": "yes" } }
+            ]
+        });
+
+        let outcomes = validator.validate_bundle(&bundle);
+        let indices: Vec<usize> = outcomes.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, vec![0, 2]);
+        assert!(outcomes[0].1.valid, "{:?}", outcomes[0].1.issues);
+        assert!(outcomes[1].1.has_errors());
+        assert_eq!(outcomes[1].1.resource_type.as_deref(), Some("Patient"));
+
+        assert!(validator
+            .validate_bundle(&json!({ "resourceType": "Patient" }))
+            .is_empty());
+    }
 }
@@ -3,7 +3,7 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use serde_json::json;
 use std::time::Duration;
-use ferrum_fhirpath::{Context, Engine, Value};
+use ferrum_fhirpath::{CompileOptions, Context, Engine, Value};
 
 fn create_test_engine() -> Engine {
     tokio::runtime::Runtime::new()
@@ -1103,6 +1103,33 @@ fn bench_check_digit_validation(c: &mut Criterion) {
     });
 }
 
+fn bench_constant_folding(c: &mut Criterion) {
+    let engine = create_test_engine();
+    let patient = json!({
+        "resourceType": "Patient",
+        "name": [{"given": ["John", "Jacob"]}, {"given": ["Jingleheimer"]}]
+    });
+    let ctx = Context::new(Value::from_json(patient));
+    let expr = "name.given.where(1 + 1 = 2).count() + (10 * 6 - 2) / 2";
+
+    for optimize in [false, true] {
+        let plan = engine
+            .compile_with_options(
+                expr,
+                CompileOptions {
+                    optimize,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let label = if optimize { "folded" } else { "unfolded" };
+
+        c.bench_function(&format!("constant_folding_{}", label), |b| {
+            b.iter(|| engine.evaluate(black_box(&plan), &ctx).unwrap())
+        });
+    }
+}
+
 criterion_group! {
     name = benches;
     config = custom_criterion();
@@ -1128,6 +1155,7 @@ criterion_group! {
         bench_conversion_operations,
         bench_complex_fhir_resource_expressions,
         bench_equivalence_operations,
-        bench_check_digit_validation
+        bench_check_digit_validation,
+        bench_constant_folding
 }
 criterion_main!(benches);
@@ -0,0 +1,256 @@
+//! Static analysis of compiled expressions
+//!
+//! Reports what an expression references (functions, operators, environment variables)
+//! and its inferred result type without evaluating it. See [`Engine::analyze`].
+//!
+//! [`Engine::analyze`]: crate::Engine::analyze
+
+use std::collections::BTreeSet;
+
+use crate::functions::FunctionRegistry;
+use crate::hir::{FunctionId, HirBinaryOperator, HirNode, HirTypeOperator, HirUnaryOperator};
+use crate::types::ExprType;
+
+/// Summary of what a FHIRPath expression references.
+#[derive(Debug, Clone)]
+pub struct PlanAnalysis {
+    /// Names of the functions called, e.g. `where`, `resolve`, `ofType`.
+    pub functions: BTreeSet<String>,
+    /// Operators used, by their FHIRPath spelling, e.g. `=`, `and`, `|`, `is`.
+    pub operators: BTreeSet<String>,
+    /// Environment variables referenced, without the leading `%` (e.g. `resource`).
+    pub variables: BTreeSet<String>,
+    /// Inferred type and cardinality of the result.
+    pub return_type: ExprType,
+}
+
+impl PlanAnalysis {
+    pub(crate) fn from_hir(hir: &HirNode, functions: &FunctionRegistry) -> Self {
+        let mut analysis = Self {
+            functions: BTreeSet::new(),
+            operators: BTreeSet::new(),
+            variables: BTreeSet::new(),
+            return_type: hir.result_type().unwrap_or_else(ExprType::unknown),
+        };
+        analysis.visit(hir, functions);
+        analysis
+    }
+
+    /// Whether the expression calls `resolve()`.
+    pub fn uses_resolve(&self) -> bool {
+        self.uses_function("resolve")
+    }
+
+    /// Whether the expression calls the named function.
+    pub fn uses_function(&self, name: &str) -> bool {
+        self.functions.contains(name)
+    }
+
+    fn visit(&mut self, node: &HirNode, functions: &FunctionRegistry) {
+        match node {
+            HirNode::Literal { .. } => {}
+            HirNode::Path { base, .. } => self.visit(base, functions),
+            HirNode::FunctionCall { func_id, args, .. } => {
+                self.add_function(*func_id, functions);
+                for arg in args {
+                    self.visit(arg, functions);
+                }
+            }
+            HirNode::MethodCall {
+                base,
+                func_id,
+                args,
+                ..
+            } => {
+                self.visit(base, functions);
+                self.add_function(*func_id, functions);
+                for arg in args {
+                    self.visit(arg, functions);
+                }
+            }
+            HirNode::BinaryOp {
+                op, left, right, ..
+            } => {
+                self.operators
+                    .insert(binary_operator_symbol(*op).to_string());
+                self.visit(left, functions);
+                self.visit(right, functions);
+            }
+            HirNode::UnaryOp { op, expr, .. } => {
+                let symbol = match op {
+                    HirUnaryOperator::Plus => "+",
+                    HirUnaryOperator::Minus => "-",
+                };
+                self.operators.insert(symbol.to_string());
+                self.visit(expr, functions);
+            }
+            HirNode::TypeOp { op, expr, .. } => {
+                let symbol = match op {
+                    HirTypeOperator::Is => "is",
+                    HirTypeOperator::As => "as",
+                };
+                self.operators.insert(symbol.to_string());
+                self.visit(expr, functions);
+            }
+            HirNode::Variable { name, .. } => {
+                // $this, $index and $total carry no name
+                if let Some(name) = name {
+                    self.variables.insert(name.to_string());
+                }
+            }
+            HirNode::Where {
+                collection,
+                predicate_hir,
+                ..
+            } => {
+                self.functions.insert("where".to_string());
+                self.visit(collection, functions);
+                self.visit(predicate_hir, functions);
+            }
+            HirNode::Select {
+                collection,
+                projection_hir,
+                ..
+            } => {
+                self.functions.insert("select".to_string());
+                self.visit(collection, functions);
+                self.visit(projection_hir, functions);
+            }
+            HirNode::Repeat {
+                collection,
+                projection_hir,
+                ..
+            } => {
+                self.functions.insert("repeat".to_string());
+                self.visit(collection, functions);
+                self.visit(projection_hir, functions);
+            }
+            HirNode::Aggregate {
+                collection,
+                aggregator_hir,
+                init_value_hir,
+                ..
+            } => {
+                self.functions.insert("aggregate".to_string());
+                self.visit(collection, functions);
+                self.visit(aggregator_hir, functions);
+                if let Some(init) = init_value_hir {
+                    self.visit(init, functions);
+                }
+            }
+            HirNode::Exists {
+                collection,
+                predicate_hir,
+                ..
+            } => {
+                self.functions.insert("exists".to_string());
+                self.visit(collection, functions);
+                if let Some(predicate) = predicate_hir {
+                    self.visit(predicate, functions);
+                }
+            }
+            HirNode::All {
+                collection,
+                predicate_hir,
+                ..
+            } => {
+                self.functions.insert("all".to_string());
+                self.visit(collection, functions);
+                self.visit(predicate_hir, functions);
+            }
+        }
+    }
+
+    fn add_function(&mut self, func_id: FunctionId, functions: &FunctionRegistry) {
+        if let Some(metadata) = functions.get_metadata(func_id) {
+            self.functions.insert(metadata.name.to_string());
+        }
+    }
+}
+
+fn binary_operator_symbol(op: HirBinaryOperator) -> &'static str {
+    match op {
+        HirBinaryOperator::Add => "+",
+        HirBinaryOperator::Sub => "-",
+        HirBinaryOperator::Mul => "*",
+        HirBinaryOperator::Div => "/",
+        HirBinaryOperator::DivInt => "div",
+        HirBinaryOperator::Mod => "mod",
+        HirBinaryOperator::Eq => "=",
+        HirBinaryOperator::Ne => "!=",
+        HirBinaryOperator::Equivalent => "~",
+        HirBinaryOperator::NotEquivalent => "!~",
+        HirBinaryOperator::Lt => "<",
+        HirBinaryOperator::Le => "<=",
+        HirBinaryOperator::Gt => ">",
+        HirBinaryOperator::Ge => ">=",
+        HirBinaryOperator::And => "and",
+        HirBinaryOperator::Or => "or",
+        HirBinaryOperator::Xor => "xor",
+        HirBinaryOperator::Implies => "implies",
+        HirBinaryOperator::Union => "|",
+        HirBinaryOperator::In => "in",
+        HirBinaryOperator::Contains => "contains",
+        HirBinaryOperator::Concat => "&",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::engine;
+    use crate::types::TypeNamespace;
+
+    #[test]
+    fn flags_resolve_and_lists_functions() {
+        let analysis = engine()
+            .analyze(
+                "subject.where(resolve() is Patient).reference.startsWith('Patient/')",
+                None,
+            )
+            .unwrap();
+        assert!(analysis.uses_resolve());
+        assert_eq!(
+            analysis
+                .functions
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            vec!["resolve", "startsWith", "where"]
+        );
+        assert!(analysis.operators.contains("is"));
+
+        let analysis = engine()
+            .analyze("Observation.value.ofType(Quantity).exists()", None)
+            .unwrap();
+        assert!(!analysis.uses_resolve());
+        assert_eq!(
+            analysis
+                .functions
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            vec!["exists", "ofType"]
+        );
+    }
+
+    #[test]
+    fn lists_variables_and_return_type() {
+        let analysis = engine()
+            .analyze("(%resource.id | %patientId).count() > 1", None)
+            .unwrap();
+        assert_eq!(
+            analysis
+                .variables
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            vec!["patientId", "resource"]
+        );
+        assert!(analysis.operators.contains("|"));
+        assert!(analysis.operators.contains(">"));
+        let types: Vec<_> = analysis.return_type.types.iter().collect();
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].namespace, TypeNamespace::System);
+        assert_eq!(types[0].name.as_ref(), "Boolean");
+    }
+}
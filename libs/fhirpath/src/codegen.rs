@@ -140,6 +140,12 @@ impl CodeGenerator {
                     return Ok(());
                 }
 
+                // Special handling for trace(): projection is evaluated per item as a subplan
+                if func_id == 500 {
+                    self.opcodes.push(Opcode::LoadThis);
+                    return self.generate_trace(args);
+                }
+
                 let arg_count = args.len();
 
                 // Standalone function call - use current focus ($this) as implicit input collection,
@@ -215,6 +221,12 @@ impl CodeGenerator {
                     return Ok(());
                 }
 
+                // Special handling for trace(): projection is evaluated per item as a subplan
+                if func_id == 500 {
+                    self.generate_node(*base)?;
+                    return self.generate_trace(args);
+                }
+
                 let arg_count = args.len();
 
                 // Generate base first (will be on bottom of stack)
@@ -473,6 +485,37 @@ impl CodeGenerator {
         Ok(())
     }
 
+    /// Generate trace(name, projection?) with the input collection already on the stack.
+    /// The name is evaluated eagerly; the projection is compiled as a per-item subplan.
+    fn generate_trace(&mut self, mut args: Vec<HirNode>) -> Result<()> {
+        if args.is_empty() || args.len() > 2 {
+            return Err(Error::InvalidOperation(
+                "trace() requires 1 or 2 arguments".into(),
+            ));
+        }
+
+        let projection = if args.len() == 2 { args.pop() } else { None };
+        let name = args.pop().expect("trace() name argument");
+
+        self.generate_node(name)?;
+
+        let projection_idx = match projection {
+            Some(projection) => {
+                let mut cg = CodeGenerator::new();
+                cg.generate_node(projection)?;
+                cg.opcodes.push(Opcode::Return);
+                let plan = cg.build();
+                let idx = self.subplans.len();
+                self.subplans.push(plan);
+                Some(idx)
+            }
+            None => None,
+        };
+
+        self.opcodes.push(Opcode::Trace(projection_idx));
+        Ok(())
+    }
+
     fn add_constant(&mut self, value: Value) -> u16 {
         // Check if constant already exists by comparing with existing ones
         for (i, existing) in self.constants.iter().enumerate() {
@@ -552,7 +595,7 @@ fn compute_max_stack_depth(opcodes: &[Opcode]) -> u16 {
             Opcode::Dup => {
                 depth += 1;
             }
-            Opcode::CallBinary(_) => {
+            Opcode::CallBinary(_) | Opcode::Trace(_) => {
                 depth = depth.saturating_sub(1);
             }
             Opcode::CallFunction(_, argc) => {
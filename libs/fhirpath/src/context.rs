@@ -6,6 +6,31 @@ use crate::value::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Bounds on the work a single evaluation may do.
+///
+/// Expressions come from search parameters and package invariants, and data can be deeply
+/// nested, so `repeat()`, `descendants()` and large unions are capped. Exceeding a limit fails
+/// the evaluation with [`Error::InvalidOperation`](crate::Error::InvalidOperation).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EvalLimits {
+    /// Items `repeat()` may process before giving up.
+    pub max_repeat_iterations: usize,
+    /// Items any intermediate collection may hold.
+    pub max_collection_size: usize,
+    /// Instructions executed across the expression and all of its sub-expressions.
+    pub max_steps: usize,
+}
+
+impl Default for EvalLimits {
+    fn default() -> Self {
+        Self {
+            max_repeat_iterations: 10_000,
+            max_collection_size: 1_000_000,
+            max_steps: 10_000_000,
+        }
+    }
+}
+
 /// Evaluation context containing variables and iteration state
 #[derive(Clone)]
 pub struct Context {
@@ -18,10 +43,12 @@ pub struct Context {
     /// Environment variables (%resource, %context, etc.). Note: the lexer drops the leading `%`
     /// when parsing external constants, so runtime lookups typically use the un-prefixed names.
     pub variables: Arc<HashMap<Arc<str>, Value>>,
-    /// The root resource being evaluated
+    /// The node being evaluated: the resource, or the element an invariant is attached to
     pub resource: Value,
     /// Root container resource (usually same as `resource`)
     pub root: Value,
+    /// Work limits enforced while evaluating
+    pub limits: EvalLimits,
 }
 
 impl Context {
@@ -85,9 +112,21 @@ impl Context {
             variables: Arc::new(variables),
             resource,
             root: root_resource,
+            limits: EvalLimits::default(),
         }
     }
 
+    /// Create a context focused on `node`, an element inside `resource`.
+    ///
+    /// Used for element-level invariants: the focus and `%context` are the element the
+    /// constraint is attached to, while `%resource` and `%rootResource` remain the whole resource.
+    pub fn new_for_element(node: Value, resource: Value) -> Self {
+        let mut ctx = Self::new_with_root_resource(resource.clone(), resource);
+        ctx.set_variable("context", node.clone());
+        ctx.resource = node;
+        ctx
+    }
+
     fn insert_variable_pair(variables: &mut HashMap<Arc<str>, Value>, name: &str, value: Value) {
         variables.insert(Arc::from(name), value.clone());
         variables.insert(Arc::from(format!("%{}", name)), value);
@@ -99,6 +138,25 @@ impl Context {
         self
     }
 
+    /// Override the default evaluation limits
+    pub fn with_limits(mut self, limits: EvalLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Context for evaluating a sub-expression with the given `$this` and `$index`.
+    pub(crate) fn for_item(&self, this: Option<Value>, index: Option<usize>) -> Self {
+        Self {
+            this,
+            index,
+            strict: self.strict,
+            variables: self.variables.clone(),
+            resource: self.resource.clone(),
+            root: self.root.clone(),
+            limits: self.limits,
+        }
+    }
+
     /// Push a new iteration context with $this and $index
     pub fn push_this(mut self, this: Value) -> Self {
         self.this = Some(this.clone());
@@ -142,6 +200,12 @@ impl Context {
         }
     }
 
+    /// Bind an external constant, e.g. `with_variable("x", value)` for `%x`.
+    pub fn with_variable(mut self, name: impl Into<Arc<str>>, value: Value) -> Self {
+        self.set_variable(name, value);
+        self
+    }
+
     /// Convenience setter for the FHIRPath `%profile` variable (used in profile invariants).
     pub fn with_profile(mut self, canonical_url: impl Into<Arc<str>>) -> Self {
         self.set_variable("profile", Value::string(canonical_url));
@@ -23,6 +23,7 @@
 use crate::value::{DatePrecision, DateTimePrecision, TimePrecision, Value, ValueData};
 use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, Utc};
 use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 use serde_json::Value as JsonValue;
 
 /// Trait for converting FHIRPath values to JSON
@@ -100,6 +101,52 @@ pub fn ferrum_fhirpath_value_to_json(value: &Value) -> Option<JsonValue> {
     }
 }
 
+/// Convert a FHIRPath value to its string form, as `toString()` does
+///
+/// Primitives use their canonical FHIRPath representation: temporal values keep their
+/// precision and timezone, quantities are written as `value 'unit'` (calendar units
+/// unquoted).
+///
+/// # Returns
+///
+/// - `Some(String)` for primitive values
+/// - `None` for complex values (objects) and `ValueData::Empty`, which have no string form
+pub fn ferrum_fhirpath_value_to_string(value: &Value) -> Option<String> {
+    match value.data() {
+        ValueData::String(s) => Some(s.to_string()),
+        ValueData::Integer(i) => Some(i.to_string()),
+        ValueData::Decimal(d) => Some(d.to_string()),
+        ValueData::Boolean(b) => Some(b.to_string()),
+        ValueData::Date { value, precision } => Some(format_date_value(*value, *precision)),
+        ValueData::DateTime {
+            value,
+            precision,
+            timezone_offset,
+        } => Some(format_datetime_value(value, *precision, *timezone_offset)),
+        ValueData::Time { value, precision } => Some(format_time_value(*value, *precision)),
+        ValueData::Quantity { value, unit } => Some(format_quantity_value(value, unit)),
+        ValueData::Object(_) | ValueData::LazyJson { .. } | ValueData::Empty => None,
+    }
+}
+
+/// Format a quantity as a FHIRPath quantity literal
+///
+/// UCUM units are quoted (`5 'mg'`), calendar duration units are not (`3 days`), and a
+/// missing unit is written as `'1'`.
+pub fn format_quantity_value(value: &Decimal, unit: &str) -> String {
+    if unit.is_empty() || unit == "1" {
+        format!("{} '1'", value)
+    } else if matches!(
+        unit,
+        "day" | "days" | "week" | "weeks" | "month" | "months" | "year" | "years"
+    ) || (unit.chars().all(|c| c.is_alphanumeric()) && unit.len() > 2)
+    {
+        format!("{} {}", value, unit)
+    } else {
+        format!("{} '{}'", value, unit)
+    }
+}
+
 /// Format a date value with appropriate precision
 ///
 /// # Precision Formatting
@@ -2,12 +2,15 @@
 //!
 //! Orchestrates the compilation pipeline: Parse → AST → HIR → VM Plan → Execution
 
+use crate::analysis::PlanAnalysis;
 use crate::analyzer::{self, Analyzer};
 use crate::codegen::CodeGenerator;
 use crate::context::Context;
 use crate::error::{Error, Result};
 use crate::functions::FunctionRegistry;
 use crate::resolver::ResourceResolver;
+use crate::terminology::TerminologyProvider;
+use crate::trace::TraceSink;
 use crate::types::TypeRegistry;
 use crate::value::{Collection, Value};
 use crate::variables::VariableRegistry;
@@ -23,6 +26,9 @@ pub struct CompileOptions {
     pub base_type: Option<String>,
     /// If `true`, invalid path navigation on resolvable FHIR types errors at compile time.
     pub strict: bool,
+    /// If `true`, run the HIR optimization pass (constant folding, trivial `where()` filters)
+    /// before code generation. See [`crate::optimize`].
+    pub optimize: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -60,6 +66,8 @@ pub struct Engine {
     variable_registry: Arc<Mutex<VariableRegistry>>,
     fhir_context: Arc<dyn FhirContext>,
     resource_resolver: Option<Arc<dyn ResourceResolver>>,
+    trace_sink: Option<Arc<dyn TraceSink>>,
+    terminology_provider: Option<Arc<dyn TerminologyProvider>>,
 }
 
 impl Engine {
@@ -95,6 +103,8 @@ impl Engine {
             variable_registry: Arc::new(Mutex::new(VariableRegistry::new())),
             fhir_context: context,
             resource_resolver: resolver,
+            trace_sink: None,
+            terminology_provider: None,
         }
     }
 
@@ -136,11 +146,37 @@ impl Engine {
         &self.fhir_context
     }
 
+    pub(crate) fn function_registry(&self) -> &FunctionRegistry {
+        &self.function_registry
+    }
+
     /// Get the custom resource resolver (if any)
     pub fn resource_resolver(&self) -> Option<&Arc<dyn ResourceResolver>> {
         self.resource_resolver.as_ref()
     }
 
+    /// Install a sink that receives `trace()` output instead of stderr
+    pub fn with_trace_sink(mut self, sink: Arc<dyn TraceSink>) -> Self {
+        self.trace_sink = Some(sink);
+        self
+    }
+
+    /// Get the custom trace sink (if any)
+    pub fn trace_sink(&self) -> Option<&Arc<dyn TraceSink>> {
+        self.trace_sink.as_ref()
+    }
+
+    /// Install a terminology provider used by `memberOf()`
+    pub fn with_terminology_provider(mut self, provider: Arc<dyn TerminologyProvider>) -> Self {
+        self.terminology_provider = Some(provider);
+        self
+    }
+
+    /// Get the terminology provider (if any)
+    pub fn terminology_provider(&self) -> Option<&Arc<dyn TerminologyProvider>> {
+        self.terminology_provider.as_ref()
+    }
+
     // ============================================================================
     // Compilation
     // ============================================================================
@@ -182,6 +218,7 @@ impl Engine {
             CompileOptions {
                 base_type: base_type.map(|s| s.to_string()),
                 strict: base_type.is_some(),
+                optimize: false,
             },
         )
     }
@@ -191,6 +228,30 @@ impl Engine {
         self.compile_internal(expr, &options)
     }
 
+    /// Statically analyze a FHIRPath expression without evaluating it.
+    ///
+    /// Reports the functions, operators and environment variables the expression
+    /// references, and its inferred result type. `base_type` is used for typing only;
+    /// unknown fields are not rejected.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let analysis = engine.analyze("subject.resolve().ofType(Patient)", Some("Observation"))?;
+    /// assert!(analysis.uses_resolve());
+    /// ```
+    pub fn analyze(&self, expr: &str, base_type: Option<&str>) -> Result<PlanAnalysis> {
+        let hir = self.lower(
+            expr,
+            &CompileOptions {
+                base_type: base_type.map(|s| s.to_string()),
+                strict: false,
+                optimize: false,
+            },
+        )?;
+        Ok(PlanAnalysis::from_hir(&hir, &self.function_registry))
+    }
+
     fn leading_identifier(expr: &str) -> Option<&str> {
         let s = expr.trim_start();
         let mut chars = s.char_indices();
@@ -273,6 +334,11 @@ impl Engine {
         } else {
             format!("lenient::{}", expr)
         };
+        let cache_key = if options.optimize {
+            format!("opt:{}", cache_key)
+        } else {
+            cache_key
+        };
 
         // Check cache first
         {
@@ -282,6 +348,31 @@ impl Engine {
             }
         }
 
+        let hir = self.lower(expr, options)?;
+
+        // 4. HIR optimizations (existence short-circuits always, folding on request)
+        let hir = crate::optimize::short_circuit_existence(hir);
+        let hir = if options.optimize {
+            crate::optimize::fold_constants(hir)
+        } else {
+            hir
+        };
+
+        // 5. Generate VM plan
+        let plan = self.codegen(hir)?;
+        let plan = Arc::new(plan);
+
+        // Cache the plan
+        {
+            let mut cache = self.cache.lock().unwrap();
+            cache.put(cache_key, plan.clone());
+        }
+
+        Ok(plan)
+    }
+
+    /// Parse, analyze and type-check an expression into typed HIR.
+    fn lower(&self, expr: &str, options: &CompileOptions) -> Result<crate::hir::HirNode> {
         // 1. Parse → AST
         let mut parser = crate::parser::Parser::new(expr.to_string());
         let ast = parser.parse()?;
@@ -316,19 +407,7 @@ impl Engine {
             Arc::clone(&self.function_registry),
             Arc::clone(&self.fhir_context),
         );
-        let hir = type_pass.resolve(hir, typing_base_type, options.strict)?;
-
-        // 4. Generate VM plan
-        let plan = self.codegen(hir)?;
-        let plan = Arc::new(plan);
-
-        // Cache the plan
-        {
-            let mut cache = self.cache.lock().unwrap();
-            cache.put(cache_key, plan.clone());
-        }
-
-        Ok(plan)
+        type_pass.resolve(hir, typing_base_type, options.strict)
     }
 
     // ============================================================================
@@ -438,6 +517,7 @@ impl Engine {
             CompileOptions {
                 base_type: inferred_base.or(options.base_type),
                 strict: options.strict,
+                optimize: false,
             },
         )?;
         self.evaluate(&plan, ctx)
@@ -16,128 +16,134 @@ pub struct FunctionMetadata {
     pub min_args: usize,
     pub max_args: Option<usize>, // None = unbounded
     pub return_type: TypeId,     // Return type (Unknown if polymorphic/context-dependent)
+    /// Computes new System values instead of returning (part of) its input, so the input's
+    /// element path no longer describes the result: `Patient.active.not()` is a
+    /// `System.Boolean`, not a `FHIR.boolean`.
+    pub returns_system_value: bool,
 }
 
 /// Static compile-time function registry using perfect hash map
 /// This provides O(1) lookups with zero runtime allocation
 static FUNCTIONS_BY_NAME: phf::Map<&'static str, FunctionMetadata> = phf_map! {
     // Boolean logic functions
-    "not" => FunctionMetadata { id: 0, name: "not", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "as" => FunctionMetadata { id: 1, name: "as", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
+    "not" => FunctionMetadata { id: 0, name: "not", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "as" => FunctionMetadata { id: 1, name: "as", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
 
     // Existence functions
-    "empty" => FunctionMetadata { id: 10, name: "empty", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "exists" => FunctionMetadata { id: 11, name: "exists", min_args: 0, max_args: Some(1), return_type: TypeId::Boolean },
-    "all" => FunctionMetadata { id: 12, name: "all", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
-    "allTrue" => FunctionMetadata { id: 13, name: "allTrue", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "anyTrue" => FunctionMetadata { id: 14, name: "anyTrue", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "allFalse" => FunctionMetadata { id: 15, name: "allFalse", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "anyFalse" => FunctionMetadata { id: 16, name: "anyFalse", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "subsetOf" => FunctionMetadata { id: 17, name: "subsetOf", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
-    "supersetOf" => FunctionMetadata { id: 18, name: "supersetOf", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
-    "count" => FunctionMetadata { id: 19, name: "count", min_args: 0, max_args: Some(0), return_type: TypeId::Integer },
-    "distinct" => FunctionMetadata { id: 20, name: "distinct", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown },
-    "isDistinct" => FunctionMetadata { id: 21, name: "isDistinct", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
+    "empty" => FunctionMetadata { id: 10, name: "empty", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "exists" => FunctionMetadata { id: 11, name: "exists", min_args: 0, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "all" => FunctionMetadata { id: 12, name: "all", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "allTrue" => FunctionMetadata { id: 13, name: "allTrue", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "anyTrue" => FunctionMetadata { id: 14, name: "anyTrue", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "allFalse" => FunctionMetadata { id: 15, name: "allFalse", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "anyFalse" => FunctionMetadata { id: 16, name: "anyFalse", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "subsetOf" => FunctionMetadata { id: 17, name: "subsetOf", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "supersetOf" => FunctionMetadata { id: 18, name: "supersetOf", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "count" => FunctionMetadata { id: 19, name: "count", min_args: 0, max_args: Some(0), return_type: TypeId::Integer, returns_system_value: true },
+    "distinct" => FunctionMetadata { id: 20, name: "distinct", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown, returns_system_value: false },
+    "isDistinct" => FunctionMetadata { id: 21, name: "isDistinct", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
 
     // Filtering functions
-    "where" => FunctionMetadata { id: 30, name: "where", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
-    "select" => FunctionMetadata { id: 31, name: "select", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
-    "repeat" => FunctionMetadata { id: 32, name: "repeat", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
-    "ofType" => FunctionMetadata { id: 33, name: "ofType", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
-    "extension" => FunctionMetadata { id: 34, name: "extension", min_args: 1, max_args: Some(2), return_type: TypeId::Unknown },
+    "where" => FunctionMetadata { id: 30, name: "where", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
+    "select" => FunctionMetadata { id: 31, name: "select", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
+    "repeat" => FunctionMetadata { id: 32, name: "repeat", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
+    "ofType" => FunctionMetadata { id: 33, name: "ofType", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
+    "extension" => FunctionMetadata { id: 34, name: "extension", min_args: 1, max_args: Some(2), return_type: TypeId::Unknown, returns_system_value: false },
 
     // Subsetting functions
-    "single" => FunctionMetadata { id: 40, name: "single", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown },
-    "first" => FunctionMetadata { id: 41, name: "first", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown },
-    "last" => FunctionMetadata { id: 42, name: "last", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown },
-    "tail" => FunctionMetadata { id: 43, name: "tail", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown },
-    "skip" => FunctionMetadata { id: 44, name: "skip", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
-    "take" => FunctionMetadata { id: 45, name: "take", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
-    "intersect" => FunctionMetadata { id: 46, name: "intersect", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
-    "exclude" => FunctionMetadata { id: 47, name: "exclude", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
+    "single" => FunctionMetadata { id: 40, name: "single", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown, returns_system_value: false },
+    "first" => FunctionMetadata { id: 41, name: "first", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown, returns_system_value: false },
+    "last" => FunctionMetadata { id: 42, name: "last", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown, returns_system_value: false },
+    "tail" => FunctionMetadata { id: 43, name: "tail", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown, returns_system_value: false },
+    "skip" => FunctionMetadata { id: 44, name: "skip", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
+    "take" => FunctionMetadata { id: 45, name: "take", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
+    "intersect" => FunctionMetadata { id: 46, name: "intersect", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
+    "exclude" => FunctionMetadata { id: 47, name: "exclude", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
 
     // Combining functions
-    "union" => FunctionMetadata { id: 50, name: "union", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
-    "combine" => FunctionMetadata { id: 51, name: "combine", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
+    "union" => FunctionMetadata { id: 50, name: "union", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
+    "combine" => FunctionMetadata { id: 51, name: "combine", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
 
     // String functions
-    "toString" => FunctionMetadata { id: 100, name: "toString", min_args: 0, max_args: Some(0), return_type: TypeId::String },
-    "indexOf" => FunctionMetadata { id: 101, name: "indexOf", min_args: 1, max_args: Some(1), return_type: TypeId::Integer },
-    "lastIndexOf" => FunctionMetadata { id: 102, name: "lastIndexOf", min_args: 1, max_args: Some(1), return_type: TypeId::Integer },
-    "substring" => FunctionMetadata { id: 103, name: "substring", min_args: 1, max_args: Some(2), return_type: TypeId::String },
-    "startsWith" => FunctionMetadata { id: 104, name: "startsWith", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
-    "endsWith" => FunctionMetadata { id: 105, name: "endsWith", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
-    "contains" => FunctionMetadata { id: 106, name: "contains", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
-    "upper" => FunctionMetadata { id: 107, name: "upper", min_args: 0, max_args: Some(0), return_type: TypeId::String },
-    "lower" => FunctionMetadata { id: 108, name: "lower", min_args: 0, max_args: Some(0), return_type: TypeId::String },
-    "replace" => FunctionMetadata { id: 109, name: "replace", min_args: 2, max_args: Some(2), return_type: TypeId::String },
-    "matches" => FunctionMetadata { id: 110, name: "matches", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
-    "matchesFull" => FunctionMetadata { id: 111, name: "matchesFull", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
-    "replaceMatches" => FunctionMetadata { id: 112, name: "replaceMatches", min_args: 2, max_args: Some(2), return_type: TypeId::String },
-    "length" => FunctionMetadata { id: 113, name: "length", min_args: 0, max_args: Some(0), return_type: TypeId::Integer },
-    "toChars" => FunctionMetadata { id: 114, name: "toChars", min_args: 0, max_args: Some(0), return_type: TypeId::String },
-    "trim" => FunctionMetadata { id: 115, name: "trim", min_args: 0, max_args: Some(0), return_type: TypeId::String },
-    "encode" => FunctionMetadata { id: 116, name: "encode", min_args: 1, max_args: Some(1), return_type: TypeId::String },
-    "decode" => FunctionMetadata { id: 117, name: "decode", min_args: 1, max_args: Some(1), return_type: TypeId::String },
-    "escape" => FunctionMetadata { id: 118, name: "escape", min_args: 1, max_args: Some(1), return_type: TypeId::String },
-    "unescape" => FunctionMetadata { id: 119, name: "unescape", min_args: 1, max_args: Some(1), return_type: TypeId::String },
-    "split" => FunctionMetadata { id: 120, name: "split", min_args: 1, max_args: Some(1), return_type: TypeId::String },
-    "join" => FunctionMetadata { id: 121, name: "join", min_args: 1, max_args: Some(1), return_type: TypeId::String },
+    "toString" => FunctionMetadata { id: 100, name: "toString", min_args: 0, max_args: Some(0), return_type: TypeId::String, returns_system_value: true },
+    "indexOf" => FunctionMetadata { id: 101, name: "indexOf", min_args: 1, max_args: Some(1), return_type: TypeId::Integer, returns_system_value: true },
+    "lastIndexOf" => FunctionMetadata { id: 102, name: "lastIndexOf", min_args: 1, max_args: Some(1), return_type: TypeId::Integer, returns_system_value: true },
+    "substring" => FunctionMetadata { id: 103, name: "substring", min_args: 1, max_args: Some(2), return_type: TypeId::String, returns_system_value: true },
+    "startsWith" => FunctionMetadata { id: 104, name: "startsWith", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "endsWith" => FunctionMetadata { id: 105, name: "endsWith", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "contains" => FunctionMetadata { id: 106, name: "contains", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "upper" => FunctionMetadata { id: 107, name: "upper", min_args: 0, max_args: Some(0), return_type: TypeId::String, returns_system_value: true },
+    "lower" => FunctionMetadata { id: 108, name: "lower", min_args: 0, max_args: Some(0), return_type: TypeId::String, returns_system_value: true },
+    "replace" => FunctionMetadata { id: 109, name: "replace", min_args: 2, max_args: Some(2), return_type: TypeId::String, returns_system_value: true },
+    "matches" => FunctionMetadata { id: 110, name: "matches", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "matchesFull" => FunctionMetadata { id: 111, name: "matchesFull", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "replaceMatches" => FunctionMetadata { id: 112, name: "replaceMatches", min_args: 2, max_args: Some(2), return_type: TypeId::String, returns_system_value: true },
+    "length" => FunctionMetadata { id: 113, name: "length", min_args: 0, max_args: Some(0), return_type: TypeId::Integer, returns_system_value: true },
+    "toChars" => FunctionMetadata { id: 114, name: "toChars", min_args: 0, max_args: Some(0), return_type: TypeId::String, returns_system_value: true },
+    "trim" => FunctionMetadata { id: 115, name: "trim", min_args: 0, max_args: Some(0), return_type: TypeId::String, returns_system_value: true },
+    "encode" => FunctionMetadata { id: 116, name: "encode", min_args: 1, max_args: Some(1), return_type: TypeId::String, returns_system_value: true },
+    "decode" => FunctionMetadata { id: 117, name: "decode", min_args: 1, max_args: Some(1), return_type: TypeId::String, returns_system_value: true },
+    "escape" => FunctionMetadata { id: 118, name: "escape", min_args: 1, max_args: Some(1), return_type: TypeId::String, returns_system_value: true },
+    "unescape" => FunctionMetadata { id: 119, name: "unescape", min_args: 1, max_args: Some(1), return_type: TypeId::String, returns_system_value: true },
+    "split" => FunctionMetadata { id: 120, name: "split", min_args: 1, max_args: Some(1), return_type: TypeId::String, returns_system_value: true },
+    "join" => FunctionMetadata { id: 121, name: "join", min_args: 1, max_args: Some(1), return_type: TypeId::String, returns_system_value: true },
 
     // Math functions
-    "abs" => FunctionMetadata { id: 200, name: "abs", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown },
-    "ceiling" => FunctionMetadata { id: 201, name: "ceiling", min_args: 0, max_args: Some(0), return_type: TypeId::Integer },
-    "exp" => FunctionMetadata { id: 202, name: "exp", min_args: 0, max_args: Some(0), return_type: TypeId::Decimal },
-    "floor" => FunctionMetadata { id: 203, name: "floor", min_args: 0, max_args: Some(0), return_type: TypeId::Integer },
-    "ln" => FunctionMetadata { id: 204, name: "ln", min_args: 0, max_args: Some(0), return_type: TypeId::Decimal },
-    "log" => FunctionMetadata { id: 205, name: "log", min_args: 1, max_args: Some(1), return_type: TypeId::Decimal },
-    "power" => FunctionMetadata { id: 206, name: "power", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown },
-    "round" => FunctionMetadata { id: 207, name: "round", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown },
-    "sqrt" => FunctionMetadata { id: 208, name: "sqrt", min_args: 0, max_args: Some(0), return_type: TypeId::Decimal },
-    "truncate" => FunctionMetadata { id: 209, name: "truncate", min_args: 0, max_args: Some(0), return_type: TypeId::Integer },
+    "abs" => FunctionMetadata { id: 200, name: "abs", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown, returns_system_value: true },
+    "ceiling" => FunctionMetadata { id: 201, name: "ceiling", min_args: 0, max_args: Some(0), return_type: TypeId::Integer, returns_system_value: true },
+    "exp" => FunctionMetadata { id: 202, name: "exp", min_args: 0, max_args: Some(0), return_type: TypeId::Decimal, returns_system_value: true },
+    "floor" => FunctionMetadata { id: 203, name: "floor", min_args: 0, max_args: Some(0), return_type: TypeId::Integer, returns_system_value: true },
+    "ln" => FunctionMetadata { id: 204, name: "ln", min_args: 0, max_args: Some(0), return_type: TypeId::Decimal, returns_system_value: true },
+    "log" => FunctionMetadata { id: 205, name: "log", min_args: 1, max_args: Some(1), return_type: TypeId::Decimal, returns_system_value: true },
+    "power" => FunctionMetadata { id: 206, name: "power", min_args: 1, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: true },
+    "round" => FunctionMetadata { id: 207, name: "round", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: true },
+    "sqrt" => FunctionMetadata { id: 208, name: "sqrt", min_args: 0, max_args: Some(0), return_type: TypeId::Decimal, returns_system_value: true },
+    "truncate" => FunctionMetadata { id: 209, name: "truncate", min_args: 0, max_args: Some(0), return_type: TypeId::Integer, returns_system_value: true },
 
     // Conversion functions
-    "iif" => FunctionMetadata { id: 300, name: "iif", min_args: 2, max_args: Some(3), return_type: TypeId::Unknown },
-    "toBoolean" => FunctionMetadata { id: 301, name: "toBoolean", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "convertsToBoolean" => FunctionMetadata { id: 302, name: "convertsToBoolean", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "toInteger" => FunctionMetadata { id: 303, name: "toInteger", min_args: 0, max_args: Some(0), return_type: TypeId::Integer },
-    "convertsToInteger" => FunctionMetadata { id: 304, name: "convertsToInteger", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "toDecimal" => FunctionMetadata { id: 305, name: "toDecimal", min_args: 0, max_args: Some(0), return_type: TypeId::Decimal },
-    "convertsToDecimal" => FunctionMetadata { id: 306, name: "convertsToDecimal", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "convertsToString" => FunctionMetadata { id: 307, name: "convertsToString", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "toDate" => FunctionMetadata { id: 308, name: "toDate", min_args: 0, max_args: Some(0), return_type: TypeId::Date },
-    "convertsToDate" => FunctionMetadata { id: 309, name: "convertsToDate", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "toDateTime" => FunctionMetadata { id: 310, name: "toDateTime", min_args: 0, max_args: Some(0), return_type: TypeId::DateTime },
-    "convertsToDateTime" => FunctionMetadata { id: 311, name: "convertsToDateTime", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "toTime" => FunctionMetadata { id: 312, name: "toTime", min_args: 0, max_args: Some(0), return_type: TypeId::Time },
-    "convertsToTime" => FunctionMetadata { id: 313, name: "convertsToTime", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "toQuantity" => FunctionMetadata { id: 314, name: "toQuantity", min_args: 0, max_args: Some(0), return_type: TypeId::Quantity },
-    "convertsToQuantity" => FunctionMetadata { id: 315, name: "convertsToQuantity", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
+    "iif" => FunctionMetadata { id: 300, name: "iif", min_args: 2, max_args: Some(3), return_type: TypeId::Unknown, returns_system_value: false },
+    "toBoolean" => FunctionMetadata { id: 301, name: "toBoolean", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "convertsToBoolean" => FunctionMetadata { id: 302, name: "convertsToBoolean", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "toInteger" => FunctionMetadata { id: 303, name: "toInteger", min_args: 0, max_args: Some(0), return_type: TypeId::Integer, returns_system_value: true },
+    "convertsToInteger" => FunctionMetadata { id: 304, name: "convertsToInteger", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "toDecimal" => FunctionMetadata { id: 305, name: "toDecimal", min_args: 0, max_args: Some(0), return_type: TypeId::Decimal, returns_system_value: true },
+    "convertsToDecimal" => FunctionMetadata { id: 306, name: "convertsToDecimal", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "convertsToString" => FunctionMetadata { id: 307, name: "convertsToString", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "toDate" => FunctionMetadata { id: 308, name: "toDate", min_args: 0, max_args: Some(0), return_type: TypeId::Date, returns_system_value: true },
+    "convertsToDate" => FunctionMetadata { id: 309, name: "convertsToDate", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "toDateTime" => FunctionMetadata { id: 310, name: "toDateTime", min_args: 0, max_args: Some(0), return_type: TypeId::DateTime, returns_system_value: true },
+    "convertsToDateTime" => FunctionMetadata { id: 311, name: "convertsToDateTime", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "toTime" => FunctionMetadata { id: 312, name: "toTime", min_args: 0, max_args: Some(0), return_type: TypeId::Time, returns_system_value: true },
+    "convertsToTime" => FunctionMetadata { id: 313, name: "convertsToTime", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "toQuantity" => FunctionMetadata { id: 314, name: "toQuantity", min_args: 0, max_args: Some(0), return_type: TypeId::Quantity, returns_system_value: true },
+    "convertsToQuantity" => FunctionMetadata { id: 315, name: "convertsToQuantity", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
 
     // Navigation functions
-    "children" => FunctionMetadata { id: 400, name: "children", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown },
-    "descendants" => FunctionMetadata { id: 401, name: "descendants", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown },
+    "children" => FunctionMetadata { id: 400, name: "children", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
+    "descendants" => FunctionMetadata { id: 401, name: "descendants", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
 
     // Type functions
-    "is" => FunctionMetadata { id: 410, name: "is", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
+    "is" => FunctionMetadata { id: 410, name: "is", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
 
     // Utility functions
-    "trace" => FunctionMetadata { id: 500, name: "trace", min_args: 1, max_args: Some(2), return_type: TypeId::Unknown },
-    "now" => FunctionMetadata { id: 501, name: "now", min_args: 0, max_args: Some(0), return_type: TypeId::DateTime },
-    "today" => FunctionMetadata { id: 502, name: "today", min_args: 0, max_args: Some(0), return_type: TypeId::Date },
-    "timeOfDay" => FunctionMetadata { id: 503, name: "timeOfDay", min_args: 0, max_args: Some(0), return_type: TypeId::Time },
-    "sort" => FunctionMetadata { id: 504, name: "sort", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown },
-    "lowBoundary" => FunctionMetadata { id: 505, name: "lowBoundary", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown },
-    "highBoundary" => FunctionMetadata { id: 506, name: "highBoundary", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown },
-    "comparable" => FunctionMetadata { id: 507, name: "comparable", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
-    "precision" => FunctionMetadata { id: 508, name: "precision", min_args: 0, max_args: Some(0), return_type: TypeId::Integer },
-    "type" => FunctionMetadata { id: 509, name: "type", min_args: 0, max_args: Some(0), return_type: TypeId::String },
-    "conformsTo" => FunctionMetadata { id: 510, name: "conformsTo", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean },
-    "hasValue" => FunctionMetadata { id: 511, name: "hasValue", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean },
-    "resolve" => FunctionMetadata { id: 512, name: "resolve", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown },
+    "trace" => FunctionMetadata { id: 500, name: "trace", min_args: 1, max_args: Some(2), return_type: TypeId::Unknown, returns_system_value: false },
+    "now" => FunctionMetadata { id: 501, name: "now", min_args: 0, max_args: Some(0), return_type: TypeId::DateTime, returns_system_value: true },
+    "today" => FunctionMetadata { id: 502, name: "today", min_args: 0, max_args: Some(0), return_type: TypeId::Date, returns_system_value: true },
+    "timeOfDay" => FunctionMetadata { id: 503, name: "timeOfDay", min_args: 0, max_args: Some(0), return_type: TypeId::Time, returns_system_value: true },
+    "sort" => FunctionMetadata { id: 504, name: "sort", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: false },
+    "lowBoundary" => FunctionMetadata { id: 505, name: "lowBoundary", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: true },
+    "highBoundary" => FunctionMetadata { id: 506, name: "highBoundary", min_args: 0, max_args: Some(1), return_type: TypeId::Unknown, returns_system_value: true },
+    "comparable" => FunctionMetadata { id: 507, name: "comparable", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "precision" => FunctionMetadata { id: 508, name: "precision", min_args: 0, max_args: Some(0), return_type: TypeId::Integer, returns_system_value: true },
+    "type" => FunctionMetadata { id: 509, name: "type", min_args: 0, max_args: Some(0), return_type: TypeId::String, returns_system_value: true },
+    "conformsTo" => FunctionMetadata { id: 510, name: "conformsTo", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
+    "hasValue" => FunctionMetadata { id: 511, name: "hasValue", min_args: 0, max_args: Some(0), return_type: TypeId::Boolean, returns_system_value: true },
+    "resolve" => FunctionMetadata { id: 512, name: "resolve", min_args: 0, max_args: Some(0), return_type: TypeId::Unknown, returns_system_value: false },
+    "until" => FunctionMetadata { id: 513, name: "until", min_args: 2, max_args: Some(2), return_type: TypeId::Quantity, returns_system_value: false },
+    "memberOf" => FunctionMetadata { id: 514, name: "memberOf", min_args: 1, max_args: Some(1), return_type: TypeId::Boolean, returns_system_value: true },
 
     // Aggregate functions
-    "aggregate" => FunctionMetadata { id: 600, name: "aggregate", min_args: 2, max_args: Some(2), return_type: TypeId::Unknown },
+    "aggregate" => FunctionMetadata { id: 600, name: "aggregate", min_args: 2, max_args: Some(2), return_type: TypeId::Unknown, returns_system_value: false },
 };
 
 /// Function registry
@@ -340,6 +346,8 @@ mod tests {
             "conformsTo",
             "hasValue",
             "resolve",
+            "until",
+            "memberOf",
             // Aggregate
             "aggregate",
         ];
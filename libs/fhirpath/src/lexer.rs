@@ -583,6 +583,11 @@ impl Lexer {
             .unwrap_or(false)
     }
 
+    /// Up to `len` characters of the input starting at character offset `position`
+    pub fn snippet(&self, position: usize, len: usize) -> String {
+        self.chars.iter().skip(position).take(len).collect()
+    }
+
     /// Get the next token from the input
     pub fn next_token(&mut self) -> Token {
         // Skip whitespace and comments
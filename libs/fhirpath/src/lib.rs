@@ -19,6 +19,7 @@
 //! VM Execution -> Result Collection
 //! ```
 
+pub mod analysis;
 pub mod analyzer;
 pub mod ast;
 pub mod codegen;
@@ -29,10 +30,15 @@ pub mod error;
 pub mod functions;
 pub mod hir;
 pub mod lexer;
+pub mod optimize;
 pub mod parser;
 pub mod resolver;
 mod temporal_parse;
+pub mod terminology;
+#[cfg(test)]
+mod test_support;
 pub mod token;
+pub mod trace;
 pub mod typecheck;
 pub mod types;
 pub mod value;
@@ -41,10 +47,13 @@ pub mod visualize;
 pub mod vm;
 
 // Re-export main types
-pub use context::Context;
-pub use conversion::{ferrum_fhirpath_value_to_json, ToJson};
+pub use analysis::PlanAnalysis;
+pub use context::{Context, EvalLimits};
+pub use conversion::{ferrum_fhirpath_value_to_json, ferrum_fhirpath_value_to_string, ToJson};
 pub use engine::{CompileOptions, Engine, EvalOptions, PipelineVisualization};
 pub use error::{Error, Result};
 pub use resolver::ResourceResolver;
+pub use terminology::TerminologyProvider;
+pub use trace::TraceSink;
 pub use value::{Collection, Value};
 pub use visualize::{VisualizationFormat, Visualize};
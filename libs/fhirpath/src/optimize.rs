@@ -0,0 +1,713 @@
+//! HIR optimization passes
+//!
+//! Optional rewrites applied between type checking and code generation
+//! (enabled with [`CompileOptions::optimize`](crate::CompileOptions::optimize)):
+//! - Constant folding: operators whose operands are all literals are evaluated once at
+//!   compile time (`1 + 2` → `3`, `'a' + 'b'` → `'ab'`).
+//! - Trivial filters: `where(true)` becomes the input collection and `where(false)`
+//!   becomes the empty collection.
+//!
+//! Existence checks over `where()` are always rewritten ([`short_circuit_existence`]):
+//! `where(criteria).exists()`, `.empty()` and `.count() > 0` become `exists(criteria)`, which
+//! stops evaluating the criteria at the first match instead of building the filtered collection.
+//!
+//! Folding never changes results: an operator is only folded when evaluating it succeeds
+//! and yields at most one value, so runtime errors are still raised at runtime. `trace()`
+//! calls are left untouched, and `where(false)` is only dropped when its input has no
+//! function calls that could have side effects or fail.
+
+use crate::hir::{HirBinaryOperator, HirNode, HirUnaryOperator};
+use crate::types::ExprType;
+use crate::value::{Collection, ValueData};
+
+/// Function ids of `not()`, `empty()`, `count()` and `trace()` (see `functions.rs`)
+const NOT_FUNCTION_ID: u16 = 0;
+const EMPTY_FUNCTION_ID: u16 = 10;
+const COUNT_FUNCTION_ID: u16 = 19;
+const TRACE_FUNCTION_ID: u16 = 500;
+
+/// Fold constant subexpressions and trivial filters in a HIR tree.
+pub fn fold_constants(node: HirNode) -> HirNode {
+    match node {
+        HirNode::BinaryOp {
+            op,
+            left,
+            right,
+            impl_id,
+            result_ty,
+        } => {
+            let left = fold_constants(*left);
+            let right = fold_constants(*right);
+            if let (Some(l), Some(r)) = (literal_collection(&left), literal_collection(&right)) {
+                if let Some(folded) = fold_binary(op, l, r, &result_ty) {
+                    return folded;
+                }
+            }
+            HirNode::BinaryOp {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+                impl_id,
+                result_ty,
+            }
+        }
+
+        HirNode::UnaryOp {
+            op,
+            expr,
+            result_ty,
+        } => {
+            let expr = fold_constants(*expr);
+            if let Some(operand) = literal_collection(&expr) {
+                let result = match op {
+                    HirUnaryOperator::Plus => crate::vm::unary_plus(operand),
+                    HirUnaryOperator::Minus => crate::vm::unary_minus(operand),
+                };
+                if let Some(folded) = result.ok().and_then(|c| to_literal(c, &result_ty)) {
+                    return folded;
+                }
+            }
+            HirNode::UnaryOp {
+                op,
+                expr: Box::new(expr),
+                result_ty,
+            }
+        }
+
+        HirNode::Where {
+            collection,
+            predicate_hir,
+            predicate_plan_id,
+            result_ty,
+        } => {
+            let collection = fold_constants(*collection);
+            let predicate_hir = fold_constants(*predicate_hir);
+            if let HirNode::Literal { value, .. } = &predicate_hir {
+                match value.data() {
+                    ValueData::Boolean(true) => return collection,
+                    ValueData::Boolean(false) | ValueData::Empty if is_pure(&collection) => {
+                        return empty_literal()
+                    }
+                    _ => {}
+                }
+            }
+            HirNode::Where {
+                collection: Box::new(collection),
+                predicate_hir: Box::new(predicate_hir),
+                predicate_plan_id,
+                result_ty,
+            }
+        }
+
+        // trace() is observable: leave the call and its arguments exactly as written
+        node @ (HirNode::FunctionCall {
+            func_id: TRACE_FUNCTION_ID,
+            ..
+        }
+        | HirNode::MethodCall {
+            func_id: TRACE_FUNCTION_ID,
+            ..
+        }) => node,
+
+        HirNode::FunctionCall {
+            func_id,
+            args,
+            result_ty,
+        } => HirNode::FunctionCall {
+            func_id,
+            args: args.into_iter().map(fold_constants).collect(),
+            result_ty,
+        },
+
+        HirNode::MethodCall {
+            base,
+            func_id,
+            args,
+            result_ty,
+        } => HirNode::MethodCall {
+            base: Box::new(fold_constants(*base)),
+            func_id,
+            args: args.into_iter().map(fold_constants).collect(),
+            result_ty,
+        },
+
+        HirNode::Path {
+            base,
+            segments,
+            result_ty,
+        } => HirNode::Path {
+            base: Box::new(fold_constants(*base)),
+            segments,
+            result_ty,
+        },
+
+        HirNode::TypeOp {
+            op,
+            expr,
+            type_specifier,
+            result_ty,
+        } => HirNode::TypeOp {
+            op,
+            expr: Box::new(fold_constants(*expr)),
+            type_specifier,
+            result_ty,
+        },
+
+        HirNode::Select {
+            collection,
+            projection_hir,
+            projection_plan_id,
+            result_ty,
+        } => HirNode::Select {
+            collection: Box::new(fold_constants(*collection)),
+            projection_hir: Box::new(fold_constants(*projection_hir)),
+            projection_plan_id,
+            result_ty,
+        },
+
+        HirNode::Repeat {
+            collection,
+            projection_hir,
+            projection_plan_id,
+            result_ty,
+        } => HirNode::Repeat {
+            collection: Box::new(fold_constants(*collection)),
+            projection_hir: Box::new(fold_constants(*projection_hir)),
+            projection_plan_id,
+            result_ty,
+        },
+
+        HirNode::Aggregate {
+            collection,
+            aggregator_hir,
+            init_value_hir,
+            aggregator_plan_id,
+            result_ty,
+        } => HirNode::Aggregate {
+            collection: Box::new(fold_constants(*collection)),
+            aggregator_hir: Box::new(fold_constants(*aggregator_hir)),
+            init_value_hir: init_value_hir.map(|init| Box::new(fold_constants(*init))),
+            aggregator_plan_id,
+            result_ty,
+        },
+
+        HirNode::Exists {
+            collection,
+            predicate_hir,
+            predicate_plan_id,
+            result_ty,
+        } => HirNode::Exists {
+            collection: Box::new(fold_constants(*collection)),
+            predicate_hir: predicate_hir.map(|p| Box::new(fold_constants(*p))),
+            predicate_plan_id,
+            result_ty,
+        },
+
+        HirNode::All {
+            collection,
+            predicate_hir,
+            predicate_plan_id,
+            result_ty,
+        } => HirNode::All {
+            collection: Box::new(fold_constants(*collection)),
+            predicate_hir: Box::new(fold_constants(*predicate_hir)),
+            predicate_plan_id,
+            result_ty,
+        },
+
+        node @ (HirNode::Literal { .. } | HirNode::Variable { .. }) => node,
+    }
+}
+
+/// Rewrite existence checks over `where()` so that the criteria stop being evaluated at the
+/// first matching item:
+/// - `c.where(criteria).exists()` → `c.exists(criteria)`
+/// - `c.where(criteria).empty()` → `c.exists(criteria).not()`
+/// - `c.where(criteria).count() > 0` (or `>= 1`) → `c.exists(criteria)`
+///
+/// The result is unchanged because the criteria are side-effect free; the only observable
+/// difference is that items after the first match are never visited, so `trace()` calls in
+/// the criteria record fewer items and errors the criteria would raise on those items are not
+/// raised.
+pub fn short_circuit_existence(node: HirNode) -> HirNode {
+    match map_children(node, short_circuit_existence) {
+        HirNode::Exists {
+            collection,
+            predicate_hir: None,
+            predicate_plan_id,
+            result_ty,
+        } => match *collection {
+            HirNode::Where {
+                collection,
+                predicate_hir,
+                ..
+            } => HirNode::Exists {
+                collection,
+                predicate_hir: Some(predicate_hir),
+                predicate_plan_id,
+                result_ty,
+            },
+            collection => HirNode::Exists {
+                collection: Box::new(collection),
+                predicate_hir: None,
+                predicate_plan_id,
+                result_ty,
+            },
+        },
+
+        HirNode::MethodCall {
+            base,
+            func_id: EMPTY_FUNCTION_ID,
+            args,
+            result_ty,
+        } if args.is_empty() && matches!(*base, HirNode::Where { .. }) => HirNode::MethodCall {
+            base: Box::new(where_to_exists(*base, result_ty.clone())),
+            func_id: NOT_FUNCTION_ID,
+            args,
+            result_ty,
+        },
+
+        HirNode::BinaryOp {
+            op,
+            left,
+            right,
+            impl_id,
+            result_ty,
+        } if is_positive_count_test(op, &right) => match *left {
+            HirNode::MethodCall {
+                base,
+                func_id: COUNT_FUNCTION_ID,
+                args,
+                ..
+            } if args.is_empty() && matches!(*base, HirNode::Where { .. }) => {
+                where_to_exists(*base, result_ty)
+            }
+            left => HirNode::BinaryOp {
+                op,
+                left: Box::new(left),
+                right,
+                impl_id,
+                result_ty,
+            },
+        },
+
+        node => node,
+    }
+}
+
+/// `c.where(criteria)` → `c.exists(criteria)`; other nodes are returned unchanged.
+fn where_to_exists(node: HirNode, result_ty: ExprType) -> HirNode {
+    match node {
+        HirNode::Where {
+            collection,
+            predicate_hir,
+            ..
+        } => HirNode::Exists {
+            collection,
+            predicate_hir: Some(predicate_hir),
+            predicate_plan_id: 0,
+            result_ty,
+        },
+        node => node,
+    }
+}
+
+/// Whether `count <op> right` holds exactly when the count is non-zero (`> 0` or `>= 1`).
+fn is_positive_count_test(op: HirBinaryOperator, right: &HirNode) -> bool {
+    let HirNode::Literal { value, .. } = right else {
+        return false;
+    };
+    matches!(
+        (op, value.data()),
+        (HirBinaryOperator::Gt, ValueData::Integer(0))
+            | (HirBinaryOperator::Ge, ValueData::Integer(1))
+    )
+}
+
+/// Apply `f` to each direct child of a node.
+fn map_children(node: HirNode, f: fn(HirNode) -> HirNode) -> HirNode {
+    let boxed = |node: Box<HirNode>| Box::new(f(*node));
+    match node {
+        HirNode::Path {
+            base,
+            segments,
+            result_ty,
+        } => HirNode::Path {
+            base: boxed(base),
+            segments,
+            result_ty,
+        },
+        HirNode::FunctionCall {
+            func_id,
+            args,
+            result_ty,
+        } => HirNode::FunctionCall {
+            func_id,
+            args: args.into_iter().map(f).collect(),
+            result_ty,
+        },
+        HirNode::MethodCall {
+            base,
+            func_id,
+            args,
+            result_ty,
+        } => HirNode::MethodCall {
+            base: boxed(base),
+            func_id,
+            args: args.into_iter().map(f).collect(),
+            result_ty,
+        },
+        HirNode::BinaryOp {
+            op,
+            left,
+            right,
+            impl_id,
+            result_ty,
+        } => HirNode::BinaryOp {
+            op,
+            left: boxed(left),
+            right: boxed(right),
+            impl_id,
+            result_ty,
+        },
+        HirNode::UnaryOp {
+            op,
+            expr,
+            result_ty,
+        } => HirNode::UnaryOp {
+            op,
+            expr: boxed(expr),
+            result_ty,
+        },
+        HirNode::TypeOp {
+            op,
+            expr,
+            type_specifier,
+            result_ty,
+        } => HirNode::TypeOp {
+            op,
+            expr: boxed(expr),
+            type_specifier,
+            result_ty,
+        },
+        HirNode::Where {
+            collection,
+            predicate_hir,
+            predicate_plan_id,
+            result_ty,
+        } => HirNode::Where {
+            collection: boxed(collection),
+            predicate_hir: boxed(predicate_hir),
+            predicate_plan_id,
+            result_ty,
+        },
+        HirNode::Select {
+            collection,
+            projection_hir,
+            projection_plan_id,
+            result_ty,
+        } => HirNode::Select {
+            collection: boxed(collection),
+            projection_hir: boxed(projection_hir),
+            projection_plan_id,
+            result_ty,
+        },
+        HirNode::Repeat {
+            collection,
+            projection_hir,
+            projection_plan_id,
+            result_ty,
+        } => HirNode::Repeat {
+            collection: boxed(collection),
+            projection_hir: boxed(projection_hir),
+            projection_plan_id,
+            result_ty,
+        },
+        HirNode::Aggregate {
+            collection,
+            aggregator_hir,
+            init_value_hir,
+            aggregator_plan_id,
+            result_ty,
+        } => HirNode::Aggregate {
+            collection: boxed(collection),
+            aggregator_hir: boxed(aggregator_hir),
+            init_value_hir: init_value_hir.map(boxed),
+            aggregator_plan_id,
+            result_ty,
+        },
+        HirNode::Exists {
+            collection,
+            predicate_hir,
+            predicate_plan_id,
+            result_ty,
+        } => HirNode::Exists {
+            collection: boxed(collection),
+            predicate_hir: predicate_hir.map(boxed),
+            predicate_plan_id,
+            result_ty,
+        },
+        HirNode::All {
+            collection,
+            predicate_hir,
+            predicate_plan_id,
+            result_ty,
+        } => HirNode::All {
+            collection: boxed(collection),
+            predicate_hir: boxed(predicate_hir),
+            predicate_plan_id,
+            result_ty,
+        },
+        node @ (HirNode::Literal { .. } | HirNode::Variable { .. }) => node,
+    }
+}
+
+/// Evaluate a binary operator over literal operands.
+fn fold_binary(
+    op: HirBinaryOperator,
+    left: Collection,
+    right: Collection,
+    result_ty: &ExprType,
+) -> Option<HirNode> {
+    let result = crate::vm::execute_binary_op(op, left, right).ok()?;
+    to_literal(result, result_ty)
+}
+
+/// The collection a literal node pushes at runtime (`{}` for the empty literal).
+fn literal_collection(node: &HirNode) -> Option<Collection> {
+    match node {
+        HirNode::Literal { value, .. } => Some(match value.data() {
+            ValueData::Empty => Collection::empty(),
+            _ => Collection::singleton(value.clone()),
+        }),
+        _ => None,
+    }
+}
+
+/// Turn an evaluated collection back into a literal node, if it has at most one value.
+fn to_literal(result: Collection, result_ty: &ExprType) -> Option<HirNode> {
+    match result.len() {
+        0 => Some(empty_literal()),
+        1 => Some(HirNode::Literal {
+            value: result.iter().next()?.clone(),
+            ty: result_ty.clone(),
+        }),
+        _ => None,
+    }
+}
+
+fn empty_literal() -> HirNode {
+    HirNode::Literal {
+        value: crate::value::Value::empty(),
+        ty: ExprType::empty(),
+    }
+}
+
+/// Whether evaluating a node can neither fail nor have side effects (paths, variables and
+/// literals only), so that it may be dropped without changing behavior.
+fn is_pure(node: &HirNode) -> bool {
+    match node {
+        HirNode::Literal { .. } | HirNode::Variable { .. } => true,
+        HirNode::Path { base, .. } => is_pure(base),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Context;
+    use crate::engine::CompileOptions;
+    use crate::test_support::engine;
+    use crate::trace::TraceSink;
+    use crate::value::{Collection, Value, ValueData};
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct RecordingSink {
+        values: Mutex<Vec<String>>,
+    }
+
+    impl RecordingSink {
+        fn take(&self) -> Vec<String> {
+            std::mem::take(&mut *self.values.lock().unwrap())
+        }
+    }
+
+    impl TraceSink for RecordingSink {
+        fn trace(&self, _name: &str, values: &Collection) {
+            let mut recorded = self.values.lock().unwrap();
+            for value in values.iter() {
+                if let ValueData::String(s) = value.data() {
+                    recorded.push(s.to_string());
+                }
+            }
+        }
+    }
+
+    fn options(optimize: bool) -> CompileOptions {
+        CompileOptions {
+            optimize,
+            ..Default::default()
+        }
+    }
+
+    fn patient_ctx() -> Context {
+        Context::new(Value::from_json(json!({
+            "resourceType": "Patient",
+            "name": [{"given": ["Ann", "Bo"]}, {"given": ["Cy"]}]
+        })))
+    }
+
+    #[test]
+    fn folded_and_unfolded_plans_agree() {
+        let engine = engine();
+        let ctx = patient_ctx();
+        let cases = [
+            "1 + 2",
+            "1 + 2 * 3 - 4",
+            "'a' + 'b'",
+            "'a' & {}",
+            "-(3 - 5)",
+            "1 / 0",
+            "2 > 1 and true",
+            "{} = 1",
+            "1 | 2",
+            "@2020-01-01 + 1 day",
+            "name.given.where(true)",
+            "name.given.where(false)",
+            "name.given.where(1 < 2).count()",
+            "name.given.where({})",
+            "name.select(given.where(1 = 1))",
+            "name.given.where(1 + 1 = 2) | 'x' + 'y'",
+            "name.given.trace('t').where(false)",
+            "name.given.trace('t', 1 + 2)",
+        ];
+
+        for expr in cases {
+            let unfolded = engine.compile_with_options(expr, options(false)).unwrap();
+            let folded = engine.compile_with_options(expr, options(true)).unwrap();
+            let expected = engine.evaluate(&unfolded, &ctx).unwrap();
+            let actual = engine.evaluate(&folded, &ctx).unwrap();
+            assert_eq!(
+                format!("{:?}", actual),
+                format!("{:?}", expected),
+                "results differ for `{}`",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn folding_reduces_instruction_count() {
+        let engine = engine();
+        let opcodes = |expr: &str, optimize: bool| {
+            engine
+                .compile_with_options(expr, options(optimize))
+                .unwrap()
+                .opcodes
+                .len()
+        };
+
+        // PushConst + Return
+        assert_eq!(opcodes("1 + 2 * 3", true), 2);
+        assert!(opcodes("1 + 2 * 3", false) > 2);
+        assert_eq!(opcodes("'a' + 'b'", true), 2);
+        assert!(opcodes("name.where(true)", true) < opcodes("name.where(true)", false));
+        assert!(opcodes("name.where(false)", true) < opcodes("name.where(false)", false));
+    }
+
+    #[test]
+    fn existence_checks_over_where_short_circuit() {
+        let engine = engine();
+        let ctx = patient_ctx();
+        let cases = [
+            ("name.where(given = 'Cy').exists()", Some(true)),
+            ("name.where(given = 'Zed').exists()", Some(false)),
+            ("name.where(given = 'Cy').empty()", Some(false)),
+            ("name.where(given = 'Zed').empty()", Some(true)),
+            ("name.where(given.exists()).count() > 0", Some(true)),
+            ("name.where(family.exists()).count() >= 1", Some(false)),
+            ("name.where(given.count() > 1).count() > 1", Some(false)),
+            ("telecom.where(system = 'phone').exists()", Some(false)),
+            ("telecom.where(system = 'phone').empty()", Some(true)),
+            (
+                "name.where(given.where($this = 'Bo').exists()).exists()",
+                Some(true),
+            ),
+            ("name.select(given.where($this = 'Cy').empty())", None),
+        ];
+
+        for (expr, expected) in cases {
+            let plan = engine.compile_with_options(expr, options(false)).unwrap();
+            let result = engine.evaluate(&plan, &ctx).unwrap();
+            match expected {
+                Some(expected) => assert_eq!(
+                    result.as_boolean().unwrap(),
+                    expected,
+                    "wrong result for `{}`",
+                    expr
+                ),
+                None => {
+                    let values: Vec<bool> = result
+                        .iter()
+                        .map(|v| matches!(v.data(), ValueData::Boolean(true)))
+                        .collect();
+                    assert_eq!(values, vec![true, false], "wrong result for `{}`", expr);
+                }
+            }
+        }
+
+        let plan = engine
+            .compile_with_options("name.where(given = 'Cy').empty()", options(false))
+            .unwrap();
+        assert!(!plan
+            .opcodes
+            .iter()
+            .any(|op| matches!(op, crate::vm::Opcode::Where(_))));
+    }
+
+    #[test]
+    fn existence_checks_stop_at_first_match() {
+        let sink = Arc::new(RecordingSink::default());
+        let engine = engine().with_trace_sink(sink.clone());
+        let ctx = Context::new(Value::from_json(json!({
+            "resourceType": "Patient",
+            "name": [{"given": ["Ann"]}, {"given": ["Bo"]}, {"given": ["Cy"]}]
+        })));
+
+        let result = engine
+            .evaluate_expr(
+                "name.where(given.trace('seen').exists()).exists()",
+                &ctx,
+                None,
+            )
+            .unwrap();
+        assert!(result.as_boolean().unwrap());
+        assert_eq!(sink.take(), vec!["Ann"]);
+
+        // count() needs every match, so every item is visited
+        let result = engine
+            .evaluate_expr(
+                "name.where(given.trace('seen').exists()).count()",
+                &ctx,
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.as_integer().unwrap(), 3);
+        assert_eq!(sink.take(), vec!["Ann", "Bo", "Cy"]);
+    }
+
+    #[test]
+    fn trace_is_not_folded_away() {
+        let engine = engine();
+        let expr = "name.trace('t').where(false)";
+        let unfolded = engine.compile_with_options(expr, options(false)).unwrap();
+        let folded = engine.compile_with_options(expr, options(true)).unwrap();
+        assert_eq!(folded.opcodes.len(), unfolded.opcodes.len());
+        assert!(folded
+            .opcodes
+            .iter()
+            .any(|op| matches!(op, crate::vm::Opcode::Trace(_))));
+    }
+}
@@ -32,7 +32,14 @@ pub struct Parser {
     recursion_depth: usize,
 }
 
-const MAX_RECURSION_DEPTH: usize = 200;
+/// Nesting limit for parentheses, function arguments, indexers and unary operators.
+///
+/// Each level costs a dozen stack frames, so this is kept well below what would overflow a
+/// default thread stack on adversarial input such as thousands of nested parentheses.
+const MAX_RECURSION_DEPTH: usize = 100;
+
+/// Number of characters of input quoted in parse errors
+const SNIPPET_LENGTH: usize = 20;
 
 impl Parser {
     /// Create a new parser for the given input string
@@ -84,10 +91,15 @@ impl Parser {
                 self.advance();
                 Ok(token)
             }
-            Some(token) => Err(Error::ParseError(format!(
-                "Expected {:?}, got {:?} at line {}, column {}",
-                token_type, token.token_type, token.line, token.column
-            ))),
+            Some(token) => {
+                let err = Error::ParseError(format!(
+                    "Expected {:?}, got {:?} at line {}, column {}",
+                    token_type, token.token_type, token.line, token.column
+                ));
+                // Keep the offending token so the error can be located
+                self.current_token = Some(token);
+                Err(err)
+            }
             None => Err(Error::ParseError(format!(
                 "Expected {:?}, but reached end of input",
                 token_type
@@ -96,7 +108,13 @@ impl Parser {
     }
 
     /// Parse the entire expression (top-level entry point)
+    ///
+    /// Parse errors report the offset of the offending token and a snippet of the input there.
     pub fn parse(&mut self) -> Result<AstNode> {
+        self.parse_root().map_err(|err| self.locate(err))
+    }
+
+    fn parse_root(&mut self) -> Result<AstNode> {
         let expr = self.parse_expression()?;
 
         // Ensure we've consumed all input
@@ -111,6 +129,32 @@ impl Parser {
         Ok(expr)
     }
 
+    /// Add the offset and a snippet of the input at the current token to a parse error
+    fn locate(&self, err: Error) -> Error {
+        let Error::ParseError(message) = err else {
+            return err;
+        };
+        let Some(token) = self.current_token() else {
+            return Error::ParseError(message);
+        };
+        // The lexer reports its own errors through the token, which is the more useful message
+        let mut message = if token.token_type == TokenType::Error {
+            token.value.clone()
+        } else {
+            message
+        };
+        if !message.contains(" at line ") {
+            message.push_str(&format!(" at line {}, column {}", token.line, token.column));
+        }
+        let snippet = self.lexer.snippet(token.position, SNIPPET_LENGTH);
+        if snippet.is_empty() {
+            message.push_str(&format!(" (offset {}, at end of input)", token.position));
+        } else {
+            message.push_str(&format!(" (offset {}, near '{}')", token.position, snippet));
+        }
+        Error::ParseError(message)
+    }
+
     /// Check recursion depth and increment
     fn check_recursion_depth(&mut self) -> Result<()> {
         self.recursion_depth += 1;
@@ -497,7 +541,9 @@ impl Parser {
                 } else {
                     PolarityOperator::Plus
                 };
+                self.check_recursion_depth()?;
                 let expr = self.parse_polarity_expression()?; // Recursive for multiple unary ops
+                self.decrement_recursion_depth();
                 Ok(AstNode::PolarityExpression {
                     operator: op,
                     expression: Box::new(expr),
@@ -1301,4 +1347,41 @@ mod tests {
             _ => panic!("Expected TermExpression, got {:?}", ast),
         }
     }
+
+    #[test]
+    fn test_errors_report_position_and_snippet() {
+        let err = parse("Patient.name.where(given = 'x'")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("line 1, column 31"), "{err}");
+        assert!(err.contains("offset 30, at end of input"), "{err}");
+
+        let err = parse("name.given * * 2").unwrap_err().to_string();
+        assert!(err.contains("Expected invocation"), "{err}");
+        assert!(err.contains("line 1, column 14"), "{err}");
+        assert!(err.contains("offset 13, near '* 2'"), "{err}");
+
+        let err = parse("name.given = 'unterminated").unwrap_err().to_string();
+        assert!(err.contains("Unterminated string literal"), "{err}");
+    }
+
+    #[test]
+    fn test_pathological_input_is_rejected() {
+        let parens = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        let err = parse(&parens).unwrap_err().to_string();
+        assert!(err.contains("too deeply nested"), "{err}");
+
+        let err = parse(&format!("{}1", "-".repeat(100_000)))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("too deeply nested"), "{err}");
+
+        let err = parse(&format!("{}1{}", "f(".repeat(100_000), ")".repeat(100_000)))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("too deeply nested"), "{err}");
+
+        assert!(parse(&"9".repeat(10_000)).is_err());
+        assert!(parse(&format!("{}1", "(".repeat(50))).is_err());
+    }
 }
@@ -0,0 +1,30 @@
+//! Terminology provider trait for the `memberOf()` function
+//!
+//! The engine has no terminology knowledge of its own. Consumers that want to evaluate
+//! `memberOf()` (e.g. in invariants) install a [`TerminologyProvider`] on the
+//! [`Engine`](crate::Engine), typically backed by package ValueSets or a terminology server.
+
+use crate::error::Result;
+
+/// Answers ValueSet membership questions for the FHIRPath `memberOf()` function
+pub trait TerminologyProvider: Send + Sync {
+    /// Check whether a code is a member of a ValueSet
+    ///
+    /// # Arguments
+    ///
+    /// * `system` - Code system of the code, `None` when `memberOf()` is called on a plain string
+    /// * `code` - The code to check
+    /// * `value_set_url` - Canonical URL of the ValueSet
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(member))` - The ValueSet is known and `member` tells whether it contains the code
+    /// * `Ok(None)` - The ValueSet cannot be resolved; `memberOf()` returns empty
+    /// * `Err(_)` - The provider failed (e.g. the terminology server is unreachable)
+    fn member_of(
+        &self,
+        system: Option<&str>,
+        code: &str,
+        value_set_url: &str,
+    ) -> Result<Option<bool>>;
+}
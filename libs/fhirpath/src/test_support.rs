@@ -0,0 +1,23 @@
+//! Fixtures shared by the crate's unit tests.
+
+use crate::context::Context;
+use crate::error::Result;
+use crate::value::{Collection, Value};
+use crate::Engine;
+use ferrum_context::DefaultFhirContext;
+use std::sync::Arc;
+
+/// An engine without any loaded packages
+pub(crate) fn engine() -> Engine {
+    Engine::new(Arc::new(DefaultFhirContext::from_packages(vec![])), None)
+}
+
+/// Evaluate `expr` against an empty input
+pub(crate) fn try_eval(expr: &str) -> Result<Collection> {
+    engine().evaluate_expr(expr, &Context::new(Value::empty()), None)
+}
+
+/// Evaluate `expr` against an empty input, panicking on errors
+pub(crate) fn eval(expr: &str) -> Collection {
+    try_eval(expr).unwrap()
+}
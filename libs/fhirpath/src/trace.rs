@@ -0,0 +1,17 @@
+//! Trace sink trait for capturing `trace()` output
+//!
+//! By default the engine writes `trace()` output to stderr. Consumers that want to
+//! inspect traced values (debuggers, test harnesses, request logging) can install a
+//! custom [`TraceSink`] on the [`Engine`](crate::Engine).
+
+use crate::value::Collection;
+
+/// Receives the values recorded by the FHIRPath `trace()` function
+///
+/// `values` is the input collection for `trace(name)`, or the result of applying the
+/// projection to each input item for `trace(name, projection)`. The collection flowing
+/// through the expression is never affected by the sink.
+pub trait TraceSink: Send + Sync {
+    /// Record a traced collection under the given label
+    fn trace(&self, name: &str, values: &Collection);
+}
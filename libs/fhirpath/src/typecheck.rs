@@ -726,9 +726,10 @@ impl TypePass {
                 cardinality: Cardinality::ZERO_TO_ONE,
             },
 
+            // Empty operands count as '', so `&` always yields exactly one string
             Concat => self
                 .type_registry
-                .expr_from_system_type(TypeId::String, Cardinality::ZERO_TO_ONE),
+                .expr_from_system_type(TypeId::String, Cardinality::ONE_TO_ONE),
 
             Union => ExprType {
                 types: left.types.union(&right.types),
@@ -6,10 +6,26 @@ use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use rust_decimal::Decimal;
 use serde_json::Value as JsonValue;
 use smallvec::SmallVec;
+#[cfg(test)]
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
+#[cfg(test)]
+thread_local! {
+    static MATERIALIZATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Number of lazy JSON values materialized on the current thread.
+///
+/// Test-only counter used to verify that hot paths (e.g. `count()`, `exists()`)
+/// stay lazy and never force full conversion of large JSON trees.
+#[cfg(test)]
+pub(crate) fn materialization_count() -> usize {
+    MATERIALIZATIONS.with(Cell::get)
+}
+
 /// JSON navigation token for lazy JSON-backed values.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum JsonPathToken {
@@ -477,10 +493,13 @@ impl ValueData {
     /// that iterate all keys), but most path navigation avoids this.
     pub(crate) fn materialize(&self) -> ValueData {
         match self {
-            ValueData::LazyJson { .. } => self
-                .resolved_json()
-                .map(Self::from_json_eager)
-                .unwrap_or(ValueData::Empty),
+            ValueData::LazyJson { .. } => {
+                #[cfg(test)]
+                MATERIALIZATIONS.with(|count| count.set(count.get() + 1));
+                self.resolved_json()
+                    .map(Self::from_json_eager)
+                    .unwrap_or(ValueData::Empty)
+            }
             other => other.clone(),
         }
     }
@@ -576,34 +595,29 @@ impl Collection {
     }
 
     /// Get a mutable reference to the underlying SmallVec.
-    /// If the collection is Arc-wrapped, this will clone it to make it mutable.
+    /// If the collection is Arc-wrapped and shared, this clones it (copy-on-write).
     fn get_mut(&mut self) -> &mut SmallVec<[Value; 4]> {
-        // If we have an Arc-wrapped collection, convert it to SmallVec first
-        if let CollectionInner::Large(arc) = &self.inner {
-            let vec = (**arc).clone();
-            self.inner = CollectionInner::Small(vec);
-        }
-
-        // Now we can safely get a mutable reference
         match &mut self.inner {
             CollectionInner::Small(vec) => vec,
-            CollectionInner::Large(_) => unreachable!(),
+            CollectionInner::Large(arc) => Arc::make_mut(arc),
         }
     }
 
     /// Ensure the collection is in the appropriate representation based on its size.
     /// If it's large and currently Small, convert to Arc. If it's small and currently Arc, convert back.
+    ///
+    /// Items are moved rather than cloned, so repeated `push` stays amortized O(1).
     fn ensure_representation(&mut self) {
         let len = self.len();
-        match &self.inner {
+        match &mut self.inner {
             CollectionInner::Small(vec) if len > COLLECTION_ARC_THRESHOLD => {
                 // Convert to Arc-wrapped
-                let vec = vec.clone();
+                let vec = std::mem::take(vec);
                 self.inner = CollectionInner::Large(Arc::new(vec));
             }
             CollectionInner::Large(arc) if len <= COLLECTION_ARC_THRESHOLD => {
                 // Convert back to SmallVec (unlikely but possible if items are removed)
-                let vec = (**arc).clone();
+                let vec = std::mem::take(Arc::make_mut(arc));
                 self.inner = CollectionInner::Small(vec);
             }
             _ => {
@@ -660,6 +674,22 @@ impl Collection {
         }
     }
 
+    /// Singleton evaluation of a collection where a Boolean is expected: empty is `None`, a
+    /// single Boolean is its value, any other single item is `true`, and more than one item
+    /// is an error.
+    pub fn singleton_boolean(&self) -> Result<Option<bool>> {
+        match self.len() {
+            0 => Ok(None),
+            1 => match self.get(0).unwrap().data() {
+                ValueData::Boolean(b) => Ok(Some(*b)),
+                _ => Ok(Some(true)),
+            },
+            _ => Err(Error::TypeError(
+                "Expected a singleton where a Boolean is required".into(),
+            )),
+        }
+    }
+
     pub fn as_string(&self) -> Result<Arc<str>> {
         if self.is_empty() {
             return Err(Error::TypeError("Empty collection".into()));
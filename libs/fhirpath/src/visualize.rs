@@ -1185,6 +1185,13 @@ fn format_opcode(opcode: &Opcode, plan: &Plan) -> String {
             }
         }
         Opcode::All(plan_id) => format!("ALL subplan[{}]", plan_id),
+        Opcode::Trace(projection_id) => {
+            if let Some(projection) = projection_id {
+                format!("TRACE subplan[{}]", projection)
+            } else {
+                "TRACE".to_string()
+            }
+        }
         Opcode::Jump(target) => format!("JUMP {}", target),
         Opcode::JumpIfEmpty(target) => format!("JUMP_IF_EMPTY {}", target),
         Opcode::JumpIfNotEmpty(target) => format!("JUMP_IF_NOT_EMPTY {}", target),
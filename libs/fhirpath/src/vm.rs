@@ -9,13 +9,15 @@ use crate::context::Context;
 use crate::error::{Error, Result};
 use crate::hir::HirBinaryOperator;
 use crate::value::{Collection, Value, ValueData};
-use functions::{aggregate_with_subplans, execute_function};
-use operations::execute_binary_op;
+use functions::{aggregate_with_subplans, execute_function, EvalServices};
+pub(crate) use operations::execute_binary_op;
 use serde_json::Value as JsonValue;
+use std::cell::Cell;
+use std::rc::Rc;
 use std::sync::Arc;
 
 /// Unary plus operation
-fn unary_plus(collection: Collection) -> Result<Collection> {
+pub(crate) fn unary_plus(collection: Collection) -> Result<Collection> {
     if collection.is_empty() {
         return Ok(Collection::empty());
     }
@@ -37,7 +39,7 @@ fn unary_plus(collection: Collection) -> Result<Collection> {
 }
 
 /// Unary minus operation
-fn unary_minus(collection: Collection) -> Result<Collection> {
+pub(crate) fn unary_minus(collection: Collection) -> Result<Collection> {
     if collection.is_empty() {
         return Ok(Collection::empty());
     }
@@ -122,6 +124,7 @@ pub enum Opcode {
     Aggregate(usize, Option<usize>), // Aggregate with aggregator subplan index and optional init value subplan index
     Exists(Option<usize>),           // exists() with optional predicate subplan
     All(usize),                      // all(predicate) with predicate subplan
+    Trace(Option<usize>),            // trace(name, projection?) with optional projection subplan
 
     // Control flow
     Jump(usize),                      // Unconditional jump
@@ -144,6 +147,7 @@ pub struct Vm<'a> {
     total: Option<Collection>,           // $total for aggregate() function
     current_path: Option<Vec<Arc<str>>>, // Current navigation path segments for type inference
     resource_type_name: Option<String>,  // Cached root resource/complex type name
+    steps: Rc<Cell<usize>>,              // Instructions executed, shared with sub-expression VMs
 }
 
 impl<'a> Vm<'a> {
@@ -155,6 +159,7 @@ impl<'a> Vm<'a> {
             total: None,
             current_path: None,
             resource_type_name: Self::infer_resource_type_name(&ctx.resource),
+            steps: Rc::new(Cell::new(0)),
         }
     }
 
@@ -167,6 +172,7 @@ impl<'a> Vm<'a> {
             total: None,
             current_path: Some(Vec::new()), // Empty path segments, not root
             resource_type_name: Self::infer_resource_type_name(&ctx.resource),
+            steps: Rc::new(Cell::new(0)),
         }
     }
 
@@ -259,6 +265,7 @@ impl<'a> Vm<'a> {
                     max_instructions
                 )));
             }
+            self.check_limits()?;
             match plan.opcodes[ip] {
                 // Stack operations
                 Opcode::PushConst(idx) => {
@@ -568,6 +575,7 @@ impl<'a> Vm<'a> {
                     let type_spec = &plan.type_specifiers[type_idx as usize];
                     let result = self.type_is(collection, type_spec)?;
                     self.stack.push(result);
+                    self.current_path = None;
                     ip += 1;
                 }
                 Opcode::TypeAs(type_idx) => {
@@ -605,10 +613,18 @@ impl<'a> Vm<'a> {
                         args,
                         self.ctx,
                         path_str.as_deref(),
-                        Some(self.engine.fhir_context().as_ref()),
-                        self.engine.resource_resolver(),
+                        EvalServices {
+                            fhir_context: Some(self.engine.fhir_context().as_ref()),
+                            resource_resolver: self.engine.resource_resolver(),
+                            terminology_provider: self.engine.terminology_provider(),
+                        },
                     )?;
                     self.stack.push(result);
+                    // Computed results are System values, not the FHIR element the path names
+                    let metadata = self.engine.function_registry().get_metadata(func_id);
+                    if metadata.is_some_and(|metadata| metadata.returns_system_value) {
+                        self.current_path = None;
+                    }
                     ip += 1;
                 }
 
@@ -664,30 +680,11 @@ impl<'a> Vm<'a> {
                     let mut any = false;
 
                     if let Some(pred_idx) = subplan_idx {
+                        // Same criteria semantics as where(), stopping at the first match
                         let predicate_plan = &plan.subplans[pred_idx];
 
                         for (index, item) in collection.iter().enumerate() {
-                            let item_context = Context {
-                                this: Some(item.clone()),
-                                index: Some(index),
-                                strict: self.ctx.strict,
-                                variables: self.ctx.variables.clone(),
-                                resource: self.ctx.resource.clone(),
-                                root: self.ctx.root.clone(),
-                            };
-
-                            let mut item_vm = Vm::new_for_predicate(&item_context, self.engine);
-                            let predicate_result = item_vm.execute(predicate_plan)?;
-
-                            let matches = if predicate_result.is_empty() {
-                                false
-                            } else {
-                                predicate_result
-                                    .as_boolean()
-                                    .unwrap_or_else(|_| !predicate_result.is_empty())
-                            };
-
-                            if matches {
+                            if self.predicate_matches(item, index, predicate_plan)? {
                                 any = true;
                                 break;
                             }
@@ -697,6 +694,7 @@ impl<'a> Vm<'a> {
                     }
 
                     self.stack.push(Collection::singleton(Value::boolean(any)));
+                    self.current_path = None;
                     ip += 1;
                 }
 
@@ -705,6 +703,8 @@ impl<'a> Vm<'a> {
                         .stack
                         .pop()
                         .ok_or_else(|| Error::EvaluationError("Stack underflow on All".into()))?;
+                    // The result is a System Boolean, not the input element
+                    self.current_path = None;
 
                     // Per FHIRPath: all() over empty collection is true.
                     if collection.is_empty() {
@@ -717,16 +717,10 @@ impl<'a> Vm<'a> {
                     let mut all_true = true;
 
                     for (index, item) in collection.iter().enumerate() {
-                        let item_context = Context {
-                            this: Some(item.clone()),
-                            index: Some(index),
-                            strict: self.ctx.strict,
-                            variables: self.ctx.variables.clone(),
-                            resource: self.ctx.resource.clone(),
-                            root: self.ctx.root.clone(),
-                        };
+                        let item_context = self.ctx.for_item(Some(item.clone()), Some(index));
 
                         let mut item_vm = Vm::new_for_predicate(&item_context, self.engine);
+                        item_vm.steps = self.steps.clone();
                         item_vm.total = self.total.clone();
                         let predicate_result = item_vm.execute(predicate_plan)?;
 
@@ -779,6 +773,32 @@ impl<'a> Vm<'a> {
                         ip += 1;
                     }
                 }
+                Opcode::Trace(projection_idx) => {
+                    let name = self
+                        .stack
+                        .pop()
+                        .ok_or_else(|| Error::EvaluationError("Stack underflow on Trace".into()))?;
+                    let collection = self
+                        .stack
+                        .pop()
+                        .ok_or_else(|| Error::EvaluationError("Stack underflow on Trace".into()))?;
+
+                    let projected = match projection_idx {
+                        Some(idx) => {
+                            Some(self.execute_select(collection.clone(), &plan.subplans[idx])?)
+                        }
+                        None => None,
+                    };
+
+                    let result = functions::trace(
+                        collection,
+                        Some(&name),
+                        projected.as_ref(),
+                        self.engine.trace_sink(),
+                    )?;
+                    self.stack.push(result);
+                    ip += 1;
+                }
                 Opcode::Iif(predicate_idx, true_idx, false_idx) => {
                     // Input collection for iif (method-call form)
                     let input_collection = self.stack.pop().unwrap_or_else(Collection::empty);
@@ -789,35 +809,20 @@ impl<'a> Vm<'a> {
                     }
 
                     let this_value = input_collection.iter().next().cloned();
-                    let local_ctx = Context {
-                        this: this_value,
-                        index: self.ctx.index,
-                        strict: self.ctx.strict,
-                        variables: self.ctx.variables.clone(),
-                        resource: self.ctx.resource.clone(),
-                        root: self.ctx.root.clone(),
-                    };
+                    let local_ctx = self.ctx.for_item(this_value, self.ctx.index);
 
                     // Evaluate predicate
                     let predicate_plan = &plan.subplans[predicate_idx];
                     let mut pred_vm = Vm::new(&local_ctx, self.engine);
+                    pred_vm.steps = self.steps.clone();
                     pred_vm.total = self.total.clone();
                     pred_vm.current_path = self.current_path.clone();
                     let pred_result = pred_vm.execute(predicate_plan)?;
 
-                    if !pred_result.is_empty() && pred_result.len() > 1 {
-                        return Err(Error::TypeError(
-                            "iif() criterion must be empty or singleton".into(),
-                        ));
-                    }
-
-                    let predicate_bool = if pred_result.is_empty() {
-                        None
-                    } else {
-                        Some(pred_result.as_boolean().map_err(|_| {
-                            Error::TypeError("iif() criterion must be a boolean".into())
-                        })?)
-                    };
+                    // Singleton evaluation, as in where(): a non-Boolean singleton is true
+                    let predicate_bool = pred_result.singleton_boolean().map_err(|_| {
+                        Error::TypeError("iif() criterion must be empty or singleton".into())
+                    })?;
 
                     let branch_plan = if predicate_bool == Some(true) {
                         Some(&plan.subplans[true_idx])
@@ -827,6 +832,7 @@ impl<'a> Vm<'a> {
 
                     if let Some(chosen) = branch_plan {
                         let mut branch_vm = Vm::new(&local_ctx, self.engine);
+                        branch_vm.steps = self.steps.clone();
                         branch_vm.total = self.total.clone();
                         branch_vm.current_path = self.current_path.clone();
                         let branch_result = branch_vm.execute(chosen)?;
@@ -851,6 +857,30 @@ impl<'a> Vm<'a> {
         Err(Error::EvaluationError("Plan did not return".into()))
     }
 
+    /// Count one instruction against the shared step budget and check the size of the
+    /// collection produced by the previous one.
+    fn check_limits(&self) -> Result<()> {
+        let limits = &self.ctx.limits;
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        if steps > limits.max_steps {
+            return Err(Error::InvalidOperation(format!(
+                "evaluation exceeded the maximum of {} steps",
+                limits.max_steps
+            )));
+        }
+        if let Some(top) = self.stack.last() {
+            if top.len() > limits.max_collection_size {
+                return Err(Error::InvalidOperation(format!(
+                    "collection of {} items exceeds the maximum size of {}",
+                    top.len(),
+                    limits.max_collection_size
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Navigate to a field in a collection
     ///
     /// `path` is the current navigation path segments (e.g., ["Patient","name"]) for strict errors.
@@ -878,16 +908,6 @@ impl<'a> Vm<'a> {
             }
         }
 
-        fn is_choice_variant_key(key: &str, base: &str) -> bool {
-            let kb = key.as_bytes();
-            let bb = base.as_bytes();
-            if kb.len() <= bb.len() || !key.starts_with(base) {
-                return false;
-            }
-            let next = kb[bb.len()];
-            next.is_ascii_uppercase()
-        }
-
         let mut result = Collection::empty();
         let mut resolved_segment: Option<Arc<str>> = None;
         let mut found = false;
@@ -944,9 +964,10 @@ impl<'a> Vm<'a> {
 
                     // Check for choice types by scanning keys
                     let base = field_name.as_ref();
+                    let choice = self.choice_element(item, base);
                     for (key, field_value) in obj.iter() {
                         let key_str = key.as_str();
-                        if !is_choice_variant_key(key_str, base) {
+                        if !choice.accepts(key_str, base) {
                             continue;
                         }
 
@@ -991,8 +1012,9 @@ impl<'a> Vm<'a> {
                         // We dynamically check all fields that start with the base field name
                         // This is a runtime check based on actual data structure
                         let base = field_name.as_ref();
+                        let choice = self.choice_element(item, base);
                         for key in obj.keys() {
-                            if is_choice_variant_key(key.as_ref(), base) {
+                            if choice.accepts(key.as_ref(), base) {
                                 // Check if this is a valid choice variant
                                 // e.g., "valueQuantity" starts with "value" and has more characters
                                 // The next character should be uppercase (camelCase)
@@ -1030,6 +1052,40 @@ impl<'a> Vm<'a> {
         Ok((result, resolved_segment))
     }
 
+    /// Look up the choice element `field[x]` on the type that owns `item`.
+    ///
+    /// Only consulted when `field` has no direct match, so the StructureDefinition lookups stay
+    /// off the common navigation path.
+    fn choice_element(&self, item: &Value, field: &str) -> ChoiceElement {
+        let Some((resource_type, element_path)) = owner_element_path(item) else {
+            return ChoiceElement::Unknown;
+        };
+        let fhir_context = self.engine.fhir_context();
+        let lookup =
+            |type_name: &str, path: &str| match fhir_context.get_element_type(type_name, path) {
+                Ok(Some(info)) if info.is_choice => ChoiceElement::Types(info.type_codes),
+                Ok(_) => ChoiceElement::NotChoice,
+                Err(_) => ChoiceElement::Unknown,
+            };
+
+        if element_path.is_empty() {
+            return lookup(&resource_type, field);
+        }
+
+        // Backbone elements are declared inline in the resource's StructureDefinition,
+        // datatypes (e.g. Extension) in their own
+        match fhir_context.get_element_type(&resource_type, &element_path) {
+            Ok(Some(info)) => match info.type_codes.as_slice() {
+                [code] if code == "BackboneElement" || code == "Element" => {
+                    lookup(&resource_type, &format!("{}.{}", element_path, field))
+                }
+                [code] => lookup(code.rsplit('/').next().unwrap_or(code), field),
+                _ => ChoiceElement::Unknown,
+            },
+            _ => ChoiceElement::Unknown,
+        }
+    }
+
     /// Index into a collection
     fn index_collection(&self, collection: Collection, index: usize) -> Result<Collection> {
         if collection.is_empty() {
@@ -1085,46 +1141,7 @@ impl<'a> Vm<'a> {
         let mut result = Collection::empty();
 
         for (index, item) in collection.iter().enumerate() {
-            // Create new context with $this and $index
-            let item_context = Context {
-                this: Some(item.clone()),
-                index: Some(index),
-                strict: self.ctx.strict,
-                variables: self.ctx.variables.clone(),
-                resource: self.ctx.resource.clone(),
-                root: self.ctx.root.clone(),
-            };
-
-            // Execute predicate subplan
-            let mut item_vm = Vm::new_for_predicate(&item_context, self.engine);
-            let predicate_result = match item_vm.execute(predicate_plan) {
-                Ok(res) => res,
-                Err(Error::TypeError(msg)) if msg.contains("Empty collection") => {
-                    // Treat empty/missing predicate result as false per FHIRPath truthiness
-                    Collection::empty()
-                }
-                Err(e) => return Err(e),
-            };
-
-            // Check if predicate evaluates to true
-            // Per FHIRPath spec for where():
-            // - Empty collection = false (exclude item)
-            // - Boolean true = true (include item)
-            // - Boolean false = false (exclude item)
-            // - Non-empty, non-boolean collection = error (but we treat as truthy for now)
-            let should_include = if predicate_result.is_empty() {
-                false
-            } else {
-                // Try to get boolean value - this is what where() expects
-                // Per FHIRPath spec, where() requires predicate to evaluate to boolean
-                predicate_result.as_boolean().unwrap_or_else(|_| {
-                    // If not a boolean, per spec this should error, but for compatibility
-                    // treat non-empty collection as truthy
-                    !predicate_result.is_empty()
-                })
-            };
-
-            if should_include {
+            if self.predicate_matches(item, index, predicate_plan)? {
                 result.push(item.clone());
             }
         }
@@ -1132,6 +1149,38 @@ impl<'a> Vm<'a> {
         Ok(result)
     }
 
+    /// Evaluate where()/exists() criteria for one item of the input collection.
+    fn predicate_matches(
+        &mut self,
+        item: &Value,
+        index: usize,
+        predicate_plan: &Plan,
+    ) -> Result<bool> {
+        // Create new context with $this and $index
+        let item_context = self.ctx.for_item(Some(item.clone()), Some(index));
+
+        // Execute predicate subplan
+        let mut item_vm = Vm::new_for_predicate(&item_context, self.engine);
+        item_vm.steps = self.steps.clone();
+        let predicate_result = match item_vm.execute(predicate_plan) {
+            Ok(res) => res,
+            Err(Error::TypeError(msg)) if msg.contains("Empty collection") => {
+                // Treat empty/missing predicate result as false per FHIRPath truthiness
+                Collection::empty()
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Singleton evaluation: empty excludes the item, a non-Boolean singleton includes
+        // it, more than one item is an error
+        let matches = predicate_result
+            .singleton_boolean()
+            .map_err(|_| Error::TypeError("where() criteria must be a singleton".into()))?
+            .unwrap_or(false);
+
+        Ok(matches)
+    }
+
     /// Execute select clause with projection subplan
     fn execute_select(
         &mut self,
@@ -1142,17 +1191,11 @@ impl<'a> Vm<'a> {
 
         for (index, item) in collection.iter().enumerate() {
             // Create new context with $this and $index
-            let item_context = Context {
-                this: Some(item.clone()),
-                index: Some(index),
-                strict: self.ctx.strict,
-                variables: self.ctx.variables.clone(),
-                resource: self.ctx.resource.clone(),
-                root: self.ctx.root.clone(),
-            };
+            let item_context = self.ctx.for_item(Some(item.clone()), Some(index));
 
             // Execute projection subplan
             let mut item_vm = Vm::new_for_predicate(&item_context, self.engine);
+            item_vm.steps = self.steps.clone();
             let projection_result = item_vm.execute(projection_plan)?;
 
             // Add all items from projection result
@@ -1181,7 +1224,7 @@ impl<'a> Vm<'a> {
         // Seen items: track items already in output (cycle detection)
         let mut seen_items = Vec::new();
         // Safety limit to prevent infinite loops
-        const MAX_ITERATIONS: usize = 10000;
+        let max_iterations = self.ctx.limits.max_repeat_iterations;
         let mut iterations = 0;
 
         // Helper to check if item is already seen using FHIRPath equality semantics.
@@ -1199,25 +1242,19 @@ impl<'a> Vm<'a> {
         // Process queue until empty (with safety limit)
         while let Some(current_item) = input_queue.pop() {
             iterations += 1;
-            if iterations > MAX_ITERATIONS {
-                return Err(Error::EvaluationError(format!(
+            if iterations > max_iterations {
+                return Err(Error::InvalidOperation(format!(
                     "repeat() exceeded maximum iterations ({}) - possible infinite loop",
-                    MAX_ITERATIONS
+                    max_iterations
                 )));
             }
 
             // Create context with $this = current_item
-            let item_context = Context {
-                this: Some(current_item.clone()),
-                index: None,
-                strict: self.ctx.strict,
-                variables: self.ctx.variables.clone(),
-                resource: self.ctx.resource.clone(),
-                root: self.ctx.root.clone(),
-            };
+            let item_context = self.ctx.for_item(Some(current_item.clone()), None);
 
             // Execute projection subplan
             let mut item_vm = Vm::new_for_predicate(&item_context, self.engine);
+            item_vm.steps = self.steps.clone();
             let projection_result = item_vm.execute(projection_plan)?;
 
             // Process all items from projection result
@@ -1353,10 +1390,77 @@ impl<'a> Vm<'a> {
             init_value_plan,
             self.ctx,
             self.engine,
+            &self.steps,
         )
     }
 }
 
+/// What the FHIR context knows about a choice element `field[x]`
+enum ChoiceElement {
+    /// `field[x]` is declared with these type codes (e.g. `Quantity`, `string`)
+    Types(Vec<String>),
+    /// The owning type is known and `field` is not a choice element
+    NotChoice,
+    /// The owning type could not be resolved; any `field` + uppercase suffix is accepted
+    Unknown,
+}
+
+impl ChoiceElement {
+    /// Whether `key` is a variant of the choice element `base` (e.g. `valueQuantity` for `value`)
+    fn accepts(&self, key: &str, base: &str) -> bool {
+        let Some(suffix) = key.strip_prefix(base) else {
+            return false;
+        };
+        if !suffix.as_bytes().first().is_some_and(u8::is_ascii_uppercase) {
+            return false;
+        }
+        match self {
+            ChoiceElement::Types(type_codes) => type_codes.iter().any(|code| {
+                code.rsplit(['/', '.'])
+                    .next()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(suffix))
+            }),
+            ChoiceElement::NotChoice => false,
+            ChoiceElement::Unknown => true,
+        }
+    }
+}
+
+/// Nearest enclosing resource type of a value and the element path from that resource,
+/// e.g. `("Observation", "component")` for an item of `Observation.component`.
+fn owner_element_path(item: &Value) -> Option<(String, String)> {
+    match item.data() {
+        ValueData::LazyJson { root, path } => {
+            let mut current = root.as_ref();
+            let mut resource_type = current.get("resourceType").and_then(|v| v.as_str());
+            let mut segments: Vec<&str> = Vec::new();
+            for token in path.iter() {
+                match token {
+                    crate::value::JsonPathToken::Key(key) => {
+                        current = current.get(key.as_ref())?;
+                        segments.push(key.as_ref());
+                    }
+                    crate::value::JsonPathToken::Index(idx) => {
+                        current = current.get(*idx)?;
+                    }
+                }
+                // Contained and bundled resources start a new element path
+                if let Some(rt) = current.get("resourceType").and_then(|v| v.as_str()) {
+                    resource_type = Some(rt);
+                    segments.clear();
+                }
+            }
+            Some((resource_type?.to_string(), segments.join(".")))
+        }
+        ValueData::Object(obj) => obj
+            .get("resourceType")
+            .and_then(|c| c.iter().next())
+            .and_then(|v| v.data().as_string())
+            .map(|rt| (rt.to_string(), String::new())),
+        _ => None,
+    }
+}
+
 /// Extract a declared type hint from the current navigation path (e.g., valueQuantity → Quantity, birthDate → Date)
 fn declared_type_from_path(path_hint: Option<&str>) -> Option<String> {
     let path = path_hint?;
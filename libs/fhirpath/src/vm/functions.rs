@@ -43,10 +43,11 @@ pub use string::{
 pub use subsetting::{exclude, first, intersect, last, single, skip, tail, take};
 pub(crate) use type_helpers::validate_type_specifier;
 pub use type_helpers::{matches_type_specifier, matches_type_specifier_exact};
+pub use temporal::until;
 pub use type_op::is_type;
 pub use utility::{
-    comparable, conforms_to, has_value, high_boundary, low_boundary, now, precision, resolve, sort,
-    time_of_day, today, trace, type_function,
+    comparable, conforms_to, has_value, high_boundary, low_boundary, member_of, now, precision,
+    resolve, sort, time_of_day, today, trace, type_function,
 };
 
 // Main dispatcher
@@ -54,10 +55,19 @@ use crate::context::Context;
 use crate::error::{Error, Result};
 use crate::hir::FunctionId;
 use crate::resolver::ResourceResolver;
+use crate::terminology::TerminologyProvider;
 use crate::value::Collection;
 use std::sync::Arc;
 use ferrum_context::FhirContext;
 
+/// Engine-provided services that some functions need during evaluation.
+#[derive(Clone, Copy)]
+pub struct EvalServices<'a> {
+    pub fhir_context: Option<&'a dyn FhirContext>,
+    pub resource_resolver: Option<&'a Arc<dyn ResourceResolver>>,
+    pub terminology_provider: Option<&'a Arc<dyn TerminologyProvider>>,
+}
+
 /// Execute a function call by dispatching to the appropriate implementation.
 ///
 /// This is the main entry point for all FHIRPath function execution. Functions are
@@ -68,9 +78,9 @@ pub fn execute_function(
     args: Vec<Collection>,
     ctx: &Context,
     path_hint: Option<&str>,
-    fhir_context: Option<&dyn FhirContext>,
-    resource_resolver: Option<&Arc<dyn ResourceResolver>>,
+    services: EvalServices<'_>,
 ) -> Result<Collection> {
+    let fhir_context = services.fhir_context;
     match func_id {
         // Boolean logic functions
         0 => not(collection),
@@ -93,7 +103,7 @@ pub fn execute_function(
         // Filtering functions
         30 => where_func(collection, args.first()),
         31 => select_func(collection, args.first()),
-        32 => repeat(collection, args.first()),
+        32 => repeat(collection, args.first(), &ctx.limits),
         33 => of_type(collection, args.first(), path_hint, fhir_context, ctx),
         34 => extension(collection, args.first(), path_hint, ctx),
 
@@ -174,13 +184,14 @@ pub fn execute_function(
             }
             children(collection, args.first())
         }
-        401 => descendants(collection, args.first()),
+        401 => descendants(collection, args.first(), &ctx.limits),
 
         // Type functions
         410 => is_type(collection, args.first(), path_hint, fhir_context, ctx),
 
         // Utility functions
-        500 => trace(collection, args.first(), args.get(1)),
+        // trace() is normally compiled to Opcode::Trace so the projection can be lazy
+        500 => trace(collection, args.first(), None, None),
         501 => now(),
         502 => today(),
         503 => time_of_day(),
@@ -192,7 +203,9 @@ pub fn execute_function(
         509 => type_function(collection, path_hint, fhir_context, ctx),
         510 => conforms_to(collection, args.first(), ctx),
         511 => has_value(collection),
-        512 => resolve(collection, ctx, resource_resolver),
+        512 => resolve(collection, ctx, services.resource_resolver),
+        513 => until(collection, args.first(), args.get(1)),
+        514 => member_of(collection, args.first(), services.terminology_provider),
 
         // Aggregate functions
         600 => aggregate(collection, args.first(), args.get(1)),
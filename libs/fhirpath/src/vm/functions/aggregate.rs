@@ -8,6 +8,8 @@ use crate::engine::Engine;
 use crate::error::{Error, Result};
 use crate::value::Collection;
 use crate::vm::Plan;
+use std::cell::Cell;
+use std::rc::Rc;
 
 /// Aggregate function implementation.
 ///
@@ -39,11 +41,13 @@ pub fn aggregate_with_subplans(
     init_value_plan: Option<&Plan>,
     ctx: &Context,
     engine: &Engine,
+    steps: &Rc<Cell<usize>>,
 ) -> Result<Collection> {
     // When the input is empty, return the init expression (or empty) per spec.
     if collection.is_empty() {
         if let Some(init_plan) = init_value_plan {
             let mut init_vm = crate::vm::Vm::new(ctx, engine);
+            init_vm.steps = steps.clone();
             return init_vm.execute(init_plan);
         }
         return Ok(Collection::empty());
@@ -52,6 +56,7 @@ pub fn aggregate_with_subplans(
     // Evaluate the init expression once to seed $total (or start with empty).
     let mut total = if let Some(init_plan) = init_value_plan {
         let mut init_vm = crate::vm::Vm::new(ctx, engine);
+        init_vm.steps = steps.clone();
         init_vm.execute(init_plan)?
     } else {
         Collection::empty()
@@ -59,16 +64,10 @@ pub fn aggregate_with_subplans(
 
     // Iterate through each element, evaluating the aggregator with $this/$index/$total.
     for (index, item) in collection.iter().enumerate() {
-        let item_context = Context {
-            this: Some(item.clone()),
-            index: Some(index),
-            strict: ctx.strict,
-            variables: ctx.variables.clone(),
-            resource: ctx.resource.clone(),
-            root: ctx.root.clone(),
-        };
+        let item_context = ctx.for_item(Some(item.clone()), Some(index));
 
         let mut item_vm = crate::vm::Vm::new_for_predicate(&item_context, engine);
+        item_vm.steps = steps.clone();
         item_vm.set_total(total.clone());
 
         let aggregator_result = item_vm.execute(aggregator_plan)?;
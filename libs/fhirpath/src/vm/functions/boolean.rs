@@ -9,21 +9,16 @@ use ferrum_context::FhirContext;
 
 use super::type_helpers::{matches_type_specifier_exact, validate_type_specifier};
 
+/// `not()`: the negation of the input's singleton evaluation. A single non-Boolean item
+/// counts as `true` (so `1.not()` is `false`); more than one item is an error.
 pub fn not(collection: Collection) -> Result<Collection> {
-    if collection.is_empty() {
-        return Ok(Collection::empty());
-    }
-
-    if collection.len() > 1 {
-        return Err(Error::TypeError(
+    match collection.singleton_boolean() {
+        Ok(Some(b)) => Ok(Collection::singleton(Value::boolean(!b))),
+        Ok(None) => Ok(Collection::empty()),
+        Err(_) => Err(Error::TypeError(
             "not() requires singleton or empty collection".into(),
-        ));
+        )),
     }
-
-    // Per FHIRPath spec: singleton collections evaluate to true when boolean is expected
-    // Try to get as boolean, or treat singleton as true
-    let bool_val = collection.as_boolean().unwrap_or(true);
-    Ok(Collection::singleton(Value::boolean(!bool_val)))
 }
 
 pub fn as_type(
@@ -74,3 +69,29 @@ pub fn as_type(
         Ok(Collection::empty())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::try_eval as eval;
+
+    #[test]
+    fn not_follows_singleton_evaluation() {
+        assert!(!eval("true.not()").unwrap().as_boolean().unwrap());
+        assert!(eval("false.not()").unwrap().as_boolean().unwrap());
+        assert!(eval("{}.not()").unwrap().is_empty());
+        // A single non-Boolean item evaluates to true
+        assert!(!eval("1.not()").unwrap().as_boolean().unwrap());
+        assert!(eval("(1 | 2).not()").is_err());
+    }
+
+    #[test]
+    fn where_uses_the_same_boolean_coercion() {
+        assert_eq!(eval("(1 | 2 | 3).where($this > 1)").unwrap().len(), 2);
+        assert_eq!(eval("(1 | 2 | 3).where('x')").unwrap().len(), 3);
+        assert!(eval("(1 | 2 | 3).where({})").unwrap().is_empty());
+        assert!(eval("(1 | 2 | 3).where(1 | 2)").is_err());
+        assert_eq!(eval("iif('x', 1, 2)").unwrap().as_integer().unwrap(), 1);
+        assert_eq!(eval("iif({}, 1, 2)").unwrap().as_integer().unwrap(), 2);
+        assert!(eval("iif(1 | 2, 1, 2)").is_err());
+    }
+}
@@ -5,6 +5,7 @@
 use crate::error::{Error, Result};
 use crate::value::Collection;
 
+/// Merge two collections without duplicates, keeping first occurrences in input-then-other order.
 pub fn union_func(collection: Collection, other: Option<&Collection>) -> Result<Collection> {
     use std::collections::HashSet;
 
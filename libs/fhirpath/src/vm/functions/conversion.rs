@@ -63,25 +63,10 @@ pub fn iif(
         arg2.ok_or_else(|| Error::InvalidOperation("iif() requires true-result argument".into()))?;
     let otherwise_result = arg3;
 
-    // Criterion must be empty or singleton
-    if !criterion.is_empty() && criterion.len() > 1 {
-        return Err(Error::TypeError(
-            "iif() criterion must be empty or singleton".into(),
-        ));
-    }
-
-    // Evaluate criterion as boolean
-    let criterion_bool = if criterion.is_empty() {
-        None
-    } else {
-        // Must be a boolean value
-        match criterion.as_boolean() {
-            Ok(b) => Some(b),
-            Err(_) => {
-                return Err(Error::TypeError("iif() criterion must be a boolean".into()));
-            }
-        }
-    };
+    // Singleton evaluation of the criterion: a non-Boolean singleton counts as true
+    let criterion_bool = criterion
+        .singleton_boolean()
+        .map_err(|_| Error::TypeError("iif() criterion must be empty or singleton".into()))?;
 
     // Apply conditional logic
     if criterion_bool == Some(true) {
@@ -92,6 +77,37 @@ pub fn iif(
     }
 }
 
+/// Boolean value of a singleton per the FHIRPath conversion table, or `None` when it has none.
+///
+/// Integers `1`/`0`, decimals `1.0`/`0.0`, and the strings `'true'`, `'t'`, `'yes'`, `'y'`,
+/// `'1'`, `'1.0'` (and their false counterparts) convert, strings case-insensitively.
+fn boolean_value(item: &Value) -> Option<bool> {
+    match item.data() {
+        ValueData::Boolean(b) => Some(*b),
+        ValueData::Integer(i) => match *i {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        },
+        ValueData::Decimal(d) => {
+            use rust_decimal::Decimal;
+            if *d == Decimal::ZERO {
+                Some(false)
+            } else if *d == Decimal::ONE {
+                Some(true)
+            } else {
+                None
+            }
+        }
+        ValueData::String(s) => match s.trim().to_lowercase().as_str() {
+            "true" | "t" | "yes" | "y" | "1" | "1.0" => Some(true),
+            "false" | "f" | "no" | "n" | "0" | "0.0" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 pub fn to_boolean(collection: Collection) -> Result<Collection> {
     // Empty collection returns empty
     if collection.is_empty() {
@@ -108,39 +124,10 @@ pub fn to_boolean(collection: Collection) -> Result<Collection> {
 
     let item = collection.iter().next().unwrap();
 
-    match item.data() {
-        ValueData::Boolean(b) => {
-            // Boolean: identity operation
-            Ok(Collection::singleton(Value::boolean(*b)))
-        }
-        ValueData::Integer(i) => match *i {
-            0 => Ok(Collection::singleton(Value::boolean(false))),
-            1 => Ok(Collection::singleton(Value::boolean(true))),
-            _ => Ok(Collection::empty()),
-        },
-        ValueData::Decimal(d) => {
-            use rust_decimal::Decimal;
-            if *d == Decimal::ZERO {
-                Ok(Collection::singleton(Value::boolean(false)))
-            } else if *d == Decimal::ONE {
-                Ok(Collection::singleton(Value::boolean(true)))
-            } else {
-                Ok(Collection::empty())
-            }
-        }
-        ValueData::String(s) => {
-            let s_lower = s.to_lowercase().trim().to_string();
-            match s_lower.as_str() {
-                "true" | "yes" => Ok(Collection::singleton(Value::boolean(true))),
-                "false" | "no" => Ok(Collection::singleton(Value::boolean(false))),
-                _ => Ok(Collection::empty()),
-            }
-        }
-        _ => {
-            // Other types cannot be converted to boolean
-            Ok(Collection::empty())
-        }
-    }
+    Ok(match boolean_value(item) {
+        Some(b) => Collection::singleton(Value::boolean(b)),
+        None => Collection::empty(),
+    })
 }
 
 pub fn converts_to_boolean(collection: Collection) -> Result<Collection> {
@@ -159,21 +146,9 @@ pub fn converts_to_boolean(collection: Collection) -> Result<Collection> {
 
     let item = collection.iter().next().unwrap();
 
-    let can_convert = match item.data() {
-        ValueData::Boolean(_) => true,               // Boolean always converts
-        ValueData::Integer(i) => *i == 0 || *i == 1, // Only 0/1 are convertible
-        ValueData::Decimal(d) => {
-            use rust_decimal::Decimal;
-            *d == Decimal::ZERO || *d == Decimal::ONE
-        }
-        ValueData::String(s) => {
-            let s_lower = s.to_lowercase().trim().to_string();
-            matches!(s_lower.as_str(), "true" | "false")
-        }
-        _ => false,
-    };
-
-    Ok(Collection::singleton(Value::boolean(can_convert)))
+    Ok(Collection::singleton(Value::boolean(
+        boolean_value(item).is_some(),
+    )))
 }
 
 pub fn to_integer(collection: Collection) -> Result<Collection> {
@@ -772,3 +747,48 @@ pub fn converts_to_quantity(collection: Collection) -> Result<Collection> {
 
     Ok(Collection::singleton(Value::boolean(ok)))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::eval;
+
+    #[test]
+    fn boolean_conversion_follows_the_spec_table() {
+        let forms = [
+            ("'true'", true),
+            ("'T'", true),
+            ("'Yes'", true),
+            ("'y'", true),
+            ("'1'", true),
+            ("'1.0'", true),
+            ("1", true),
+            ("1.0", true),
+            ("'FALSE'", false),
+            ("'f'", false),
+            ("'no'", false),
+            ("'N'", false),
+            ("'0'", false),
+            ("'0.0'", false),
+            ("0", false),
+            ("0.0", false),
+        ];
+        for (input, expected) in forms {
+            let converted = eval(&format!("{}.toBoolean()", input));
+            assert_eq!(
+                converted.as_boolean().ok(),
+                Some(expected),
+                "{}.toBoolean()",
+                input
+            );
+            let converts = eval(&format!("{}.convertsToBoolean()", input));
+            assert_eq!(converts.as_boolean().ok(), Some(true), "{}", input);
+        }
+
+        for input in ["'maybe'", "'2'", "2", "0.5"] {
+            let converted = eval(&format!("{}.toBoolean()", input));
+            assert!(converted.is_empty(), "{}", input);
+            let converts = eval(&format!("{}.convertsToBoolean()", input));
+            assert_eq!(converts.as_boolean().ok(), Some(false), "{}", input);
+        }
+    }
+}
@@ -6,6 +6,9 @@
 use crate::error::{Error, Result};
 use crate::value::{Collection, Value, ValueData};
 
+// `empty()`, `exists()` and `count()` only inspect the collection length. Items produced
+// by navigation stay `LazyJson`, so none of them are materialized here.
+
 pub fn empty(collection: Collection) -> Result<Collection> {
     Ok(Collection::singleton(Value::boolean(collection.is_empty())))
 }
@@ -164,6 +167,7 @@ pub fn count(collection: Collection) -> Result<Collection> {
     )))
 }
 
+/// Remove duplicates, keeping the first occurrence of each item in input order.
 pub fn distinct(collection: Collection) -> Result<Collection> {
     use std::collections::HashSet;
 
@@ -191,3 +195,96 @@ pub fn is_distinct(collection: Collection) -> Result<Collection> {
         original_len == distinct_len,
     )))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Context;
+    use crate::test_support::engine;
+    use crate::value::{materialization_count, Value, ValueData};
+    use serde_json::json;
+
+    #[test]
+    fn count_empty_and_exists_do_not_materialize_lazy_items() {
+        let entries: Vec<_> = (0..5_000)
+            .map(|i| json!({"resource": {"resourceType": "Patient", "id": format!("p{i}")}}))
+            .collect();
+        let bundle = json!({"resourceType": "Bundle", "type": "collection", "entry": entries});
+        let ctx = Context::new(Value::from_json(bundle));
+        let engine = engine();
+
+        let before = materialization_count();
+
+        let count = engine.evaluate_expr("entry.count()", &ctx, None).unwrap();
+        assert_eq!(count.as_integer().unwrap(), 5_000);
+
+        let exists = engine.evaluate_expr("entry.exists()", &ctx, None).unwrap();
+        assert!(exists.as_boolean().unwrap());
+
+        let empty = engine.evaluate_expr("entry.empty()", &ctx, None).unwrap();
+        assert!(!empty.as_boolean().unwrap());
+
+        let nested = engine
+            .evaluate_expr("entry.resource.count()", &ctx, None)
+            .unwrap();
+        assert_eq!(nested.as_integer().unwrap(), 5_000);
+
+        assert_eq!(materialization_count(), before);
+    }
+
+    #[test]
+    fn exists_and_all_evaluate_criteria_per_item() {
+        let patient = json!({
+            "resourceType": "Patient",
+            "name": [
+                {"use": "usual", "given": ["Jim"]},
+                {"use": "official", "given": ["James"]}
+            ],
+            "telecom": [
+                {"system": "phone", "value": "555-0100"},
+                {"value": "555-0101"}
+            ]
+        });
+        let ctx = Context::new(Value::from_json(patient));
+        let engine = engine();
+        let eval = |expr: &str| {
+            engine
+                .evaluate_expr(expr, &ctx, None)
+                .unwrap()
+                .as_boolean()
+                .unwrap()
+        };
+
+        assert!(eval("name.exists(use = 'official')"));
+        assert!(!eval("name.exists(use = 'nickname')"));
+        assert!(eval("name.exists($this.given = 'Jim')"));
+
+        assert!(!eval("telecom.all(system.exists())"));
+        assert!(eval("telecom.all(value.exists())"));
+        assert!(eval("telecom.all($this.value.startsWith('555'))"));
+        // Vacuously true on an empty collection
+        assert!(eval("address.all(city.exists())"));
+    }
+
+    #[test]
+    fn distinct_and_union_keep_first_occurrence_order() {
+        let ctx = Context::new(Value::from_json(json!({"resourceType": "Patient"})));
+        let engine = engine();
+        let eval = |expr: &str| -> Vec<i64> {
+            engine
+                .evaluate_expr(expr, &ctx, None)
+                .unwrap()
+                .iter()
+                .map(|v| match v.data() {
+                    ValueData::Integer(i) => *i,
+                    other => panic!("expected integer, got {other:?}"),
+                })
+                .collect()
+        };
+
+        for _ in 0..50 {
+            assert_eq!(eval("(3 | 1 | 3 | 2).distinct()"), vec![3, 1, 2]);
+            assert_eq!(eval("(3 | 1).union(2 | 3 | 4)"), vec![3, 1, 2, 4]);
+            assert_eq!(eval("(5 | 4) | (4 | 6 | 5)"), vec![5, 4, 6]);
+        }
+    }
+}
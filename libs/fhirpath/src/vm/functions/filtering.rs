@@ -6,7 +6,7 @@
 use std::collections::HashSet;
 use std::sync::Arc;
 
-use crate::context::Context;
+use crate::context::{Context, EvalLimits};
 use crate::error::{Error, Result};
 use crate::value::{Collection, Value, ValueData};
 use serde_json::Value as JsonValue;
@@ -32,7 +32,11 @@ pub fn select_func(
     ))
 }
 
-pub fn repeat(collection: Collection, projection_arg: Option<&Collection>) -> Result<Collection> {
+pub fn repeat(
+    collection: Collection,
+    projection_arg: Option<&Collection>,
+    limits: &EvalLimits,
+) -> Result<Collection> {
     // Implements FHIRPath repeat() by repeatedly applying the projection and
     // accumulating newly discovered items until no new items are found. Equality
     // for cycle detection follows the = (equals) semantics used elsewhere.
@@ -80,15 +84,14 @@ pub fn repeat(collection: Collection, projection_arg: Option<&Collection>) -> Re
     }
 
     // Repeat breadth-first until no new items are produced (or safety limit hit)
-    const MAX_ITERATIONS: usize = 10_000;
     let mut iterations = 0;
 
     while let Some(current) = queue.pop() {
         iterations += 1;
-        if iterations > MAX_ITERATIONS {
-            return Err(Error::EvaluationError(format!(
+        if iterations > limits.max_repeat_iterations {
+            return Err(Error::InvalidOperation(format!(
                 "repeat() exceeded maximum iterations ({}) - possible infinite loop",
-                MAX_ITERATIONS
+                limits.max_repeat_iterations
             )));
         }
 
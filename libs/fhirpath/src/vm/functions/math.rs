@@ -5,7 +5,8 @@
 
 use std::str::FromStr;
 
-use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, RoundingStrategy};
 
 use crate::error::{Error, Result};
 use crate::value::{Collection, Value, ValueData};
@@ -34,18 +35,8 @@ pub fn ceiling(collection: Collection) -> Result<Collection> {
 
     let item = collection.iter().next().unwrap();
     match item.data() {
-        ValueData::Decimal(d) => {
-            // Use proper ceiling: round up to nearest integer
-            // For positive: if fractional part exists, add 1 to rounded value
-            // For negative: just round (ceiling of -3.7 is -3, not -4)
-            let rounded = d.round_dp(0);
-            let result = if *d > rounded {
-                rounded + Decimal::ONE
-            } else {
-                rounded
-            };
-            Ok(Collection::singleton(Value::decimal(result)))
-        }
+        // Smallest integer not less than the input: ceiling of -2.5 is -2
+        ValueData::Decimal(d) => Ok(Collection::singleton(Value::decimal(d.ceil()))),
         ValueData::Integer(i) => Ok(Collection::singleton(Value::integer(*i))),
         _ => Err(Error::TypeError("ceiling() requires numeric type".into())),
     }
@@ -65,36 +56,8 @@ pub fn exp(collection: Collection) -> Result<Collection> {
         ));
     }
 
-    let item = collection.iter().next().unwrap();
-
-    // Convert to decimal for calculation
-    let num = match item.data() {
-        ValueData::Integer(i) => Decimal::from(*i),
-        ValueData::Decimal(d) => *d,
-        _ => return Err(Error::TypeError("exp() requires numeric value".into())),
-    };
-
-    // Convert to f64 for calculation
-    let num_f64: f64 = num
-        .to_string()
-        .parse::<f64>()
-        .map_err(|_| Error::InvalidOperation("exp() input value too large".into()))?;
-
-    // Calculate exp
-    let result_f64 = num_f64.exp();
-
-    // Check for overflow
-    if result_f64.is_infinite() || result_f64.is_nan() {
-        return Err(Error::InvalidOperation(
-            "exp() result too large to represent".into(),
-        ));
-    }
-
-    // Convert back to Decimal
-    let result = Decimal::from_str(&result_f64.to_string())
-        .map_err(|_| Error::InvalidOperation("exp() result cannot be represented".into()))?;
-
-    Ok(Collection::singleton(Value::decimal(result)))
+    let num = numeric_operand(collection.iter().next().unwrap(), "exp()")?;
+    Ok(decimal_result(num.exp()))
 }
 
 pub fn floor(collection: Collection) -> Result<Collection> {
@@ -104,18 +67,8 @@ pub fn floor(collection: Collection) -> Result<Collection> {
 
     let item = collection.iter().next().unwrap();
     match item.data() {
-        ValueData::Decimal(d) => {
-            // Floor: round down to nearest integer
-            // For positive: just round (floor of 3.7 is 3)
-            // For negative: if fractional part exists, subtract 1 from rounded value
-            let rounded = d.round_dp(0);
-            let result = if *d < rounded {
-                rounded - Decimal::ONE
-            } else {
-                rounded
-            };
-            Ok(Collection::singleton(Value::decimal(result)))
-        }
+        // Largest integer not greater than the input: floor of -2.5 is -3
+        ValueData::Decimal(d) => Ok(Collection::singleton(Value::decimal(d.floor()))),
         ValueData::Integer(i) => Ok(Collection::singleton(Value::integer(*i))),
         _ => Err(Error::TypeError("floor() requires numeric type".into())),
     }
@@ -124,6 +77,7 @@ pub fn floor(collection: Collection) -> Result<Collection> {
 pub fn ln(collection: Collection) -> Result<Collection> {
     // ln() returns the natural logarithm of the input (i.e. the logarithm base e)
     // When used with an Integer, it will be implicitly converted to a Decimal
+    // If the result cannot be represented (ln of a non-positive number), the result is empty
 
     if collection.is_empty() {
         return Ok(Collection::empty());
@@ -135,48 +89,18 @@ pub fn ln(collection: Collection) -> Result<Collection> {
         ));
     }
 
-    let item = collection.iter().next().unwrap();
-
-    // Convert to decimal for calculation
-    let num = match item.data() {
-        ValueData::Integer(i) => Decimal::from(*i),
-        ValueData::Decimal(d) => *d,
-        _ => return Err(Error::TypeError("ln() requires numeric value".into())),
-    };
-
-    // Check for non-positive numbers
-    if num <= Decimal::ZERO {
-        return Err(Error::InvalidOperation(
-            "ln() called with non-positive number".into(),
-        ));
-    }
-
-    // Convert to f64 for calculation
-    let num_f64: f64 = num
-        .to_string()
-        .parse()
-        .map_err(|_| Error::InvalidOperation("ln() input value too large".into()))?;
-
-    // Calculate natural logarithm
-    let result_f64 = num_f64.ln();
-
-    // Check for invalid result
-    if result_f64.is_infinite() || result_f64.is_nan() {
-        return Err(Error::InvalidOperation(
-            "ln() called with invalid input".into(),
-        ));
+    let num = numeric_operand(collection.iter().next().unwrap(), "ln()")?;
+    if num <= 0.0 {
+        return Ok(Collection::empty());
     }
 
-    // Convert back to Decimal
-    let result = Decimal::from_str(&result_f64.to_string())
-        .map_err(|_| Error::InvalidOperation("ln() result cannot be represented".into()))?;
-
-    Ok(Collection::singleton(Value::decimal(result)))
+    Ok(decimal_result(num.ln()))
 }
 
 pub fn log(collection: Collection, base_arg: Option<&Collection>) -> Result<Collection> {
     // log() returns the logarithm base base of the input number
     // When used with Integers, the arguments will be implicitly converted to Decimal
+    // If the result cannot be represented (non-positive number or base, base 1), the result is empty
 
     if collection.is_empty() {
         return Ok(Collection::empty());
@@ -195,65 +119,22 @@ pub fn log(collection: Collection, base_arg: Option<&Collection>) -> Result<Coll
         ));
     }
 
-    let number_item = collection.iter().next().unwrap();
-    let base_item = base.iter().next().unwrap();
-
-    // Convert to decimals for calculation
-    let num = match number_item.data() {
-        ValueData::Integer(i) => Decimal::from(*i),
-        ValueData::Decimal(d) => *d,
-        _ => return Err(Error::TypeError("log() requires numeric value".into())),
-    };
-
-    let base_num = match base_item.data() {
-        ValueData::Integer(i) => Decimal::from(*i),
-        ValueData::Decimal(d) => *d,
-        _ => return Err(Error::TypeError("log() base must be numeric".into())),
-    };
-
-    // Check for non-positive numbers
-    if num <= Decimal::ZERO || base_num <= Decimal::ZERO {
-        return Err(Error::InvalidOperation(
-            "log() called with non-positive number".into(),
-        ));
-    }
-
-    // Check for base 1
-    if base_num == Decimal::ONE {
-        return Err(Error::InvalidOperation("log() called with base 1".into()));
-    }
-
-    // Convert to f64 for calculation
-    let num_f64: f64 = num
-        .to_string()
-        .parse()
-        .map_err(|_| Error::InvalidOperation("log() input value too large".into()))?;
-    let base_f64: f64 = base_num
-        .to_string()
-        .parse()
-        .map_err(|_| Error::InvalidOperation("log() base value too large".into()))?;
-
-    // Calculate logarithm: log_base(num) = ln(num) / ln(base)
-    let result_f64 = num_f64.ln() / base_f64.ln();
+    let num = numeric_operand(collection.iter().next().unwrap(), "log()")?;
+    let base_num = numeric_operand(base.iter().next().unwrap(), "log() base")?;
 
-    // Check for invalid result
-    if result_f64.is_infinite() || result_f64.is_nan() {
-        return Err(Error::InvalidOperation(
-            "log() called with invalid input".into(),
-        ));
+    if num <= 0.0 || base_num <= 0.0 || base_num == 1.0 {
+        return Ok(Collection::empty());
     }
 
-    // Convert back to Decimal
-    let result = Decimal::from_str(&result_f64.to_string())
-        .map_err(|_| Error::InvalidOperation("log() result cannot be represented".into()))?;
-
-    Ok(Collection::singleton(Value::decimal(result)))
+    // log_base(num) = ln(num) / ln(base)
+    Ok(decimal_result(num.ln() / base_num.ln()))
 }
 
 pub fn power(collection: Collection, exponent_arg: Option<&Collection>) -> Result<Collection> {
     // power() raises a number to the exponent power
     // If used with Integers, result is Integer. If used with Decimals, result is Decimal.
     // If mixed types, Integer is converted to Decimal and result is Decimal.
+    // If the power cannot be represented (such as -1 raised to 0.5), the result is empty
 
     if collection.is_empty() {
         return Ok(Collection::empty());
@@ -275,70 +156,41 @@ pub fn power(collection: Collection, exponent_arg: Option<&Collection>) -> Resul
     let base_item = collection.iter().next().unwrap();
     let exp_item = exponent.iter().next().unwrap();
 
-    // Extract numbers
-    let (base_num, base_is_int) = match base_item.data() {
-        ValueData::Integer(i) => (Decimal::from(*i), true),
-        ValueData::Decimal(d) => (*d, false),
-        _ => return Err(Error::TypeError("power() requires numeric base".into())),
-    };
-
-    let (exp_num, exp_is_int) = match exp_item.data() {
-        ValueData::Integer(i) => (Decimal::from(*i), true),
-        ValueData::Decimal(d) => (*d, false),
-        _ => return Err(Error::TypeError("power() requires numeric exponent".into())),
-    };
-
-    // Check for cases that cannot be represented
-    // Negative base with non-integer exponent
-    if base_num < Decimal::ZERO {
-        let exp_f64: f64 = exp_num
-            .to_string()
-            .parse()
-            .map_err(|_| Error::InvalidOperation("power() exponent value too large".into()))?;
-        if exp_f64.fract() != 0.0 {
-            // Non-integer exponent with negative base - return empty
-            return Ok(Collection::empty());
+    // Integer base and non-negative Integer exponent: exact Integer result
+    if let (ValueData::Integer(base), ValueData::Integer(exp)) = (base_item.data(), exp_item.data())
+    {
+        if *exp >= 0 {
+            return Ok(u32::try_from(*exp)
+                .ok()
+                .and_then(|exp| base.checked_pow(exp))
+                .map(|result| Collection::singleton(Value::integer(result)))
+                .unwrap_or_else(Collection::empty));
         }
     }
 
-    // Convert to f64 for calculation
-    let base_f64: f64 = base_num
-        .to_string()
-        .parse()
-        .map_err(|_| Error::InvalidOperation("power() base value too large".into()))?;
-    let exp_f64: f64 = exp_num
-        .to_string()
-        .parse()
-        .map_err(|_| Error::InvalidOperation("power() exponent value too large".into()))?;
-
-    // Calculate power
-    let result_f64 = base_f64.powf(exp_f64);
-
-    // Check for overflow or invalid result
-    if result_f64.is_infinite() || result_f64.is_nan() {
-        return Ok(Collection::empty());
-    }
-
-    // Determine result type based on input types
-    if base_is_int && exp_is_int && exp_num >= Decimal::ZERO {
-        // Both integers and non-negative exponent - try integer result
-        // Check if result is an exact integer
-        if result_f64.fract() == 0.0 {
-            let result_int = result_f64 as i64;
-            // Check if conversion back to f64 is exact (and within i64 range)
-            if (result_int as f64) == result_f64 {
-                return Ok(Collection::singleton(Value::integer(result_int)));
+    // Decimal base and non-negative Integer exponent: computed in Decimal arithmetic
+    if let (ValueData::Decimal(base), ValueData::Integer(exp)) = (base_item.data(), exp_item.data())
+    {
+        if *exp >= 0 {
+            if let Some(result) = checked_powi(*base, *exp) {
+                return Ok(Collection::singleton(Value::decimal(result)));
             }
         }
     }
 
-    // At least one decimal or negative exponent - result is decimal
-    let result = Decimal::from_str(&result_f64.to_string())
-        .map_err(|_| Error::InvalidOperation("power() result cannot be represented".into()))?;
+    let base_num = numeric_operand(base_item, "power() base")?;
+    let exp_num = numeric_operand(exp_item, "power() exponent")?;
 
-    Ok(Collection::singleton(Value::decimal(result)))
+    // Negative base with non-integer exponent has no real result
+    if base_num < 0.0 && exp_num.fract() != 0.0 {
+        return Ok(Collection::empty());
+    }
+
+    Ok(decimal_result(base_num.powf(exp_num)))
 }
 
+/// `round([precision])` uses the spec's "traditional" rounding, where a half rounds away from
+/// zero (`2.5` -> `3`, `-2.5` -> `-3`), rather than `Decimal::round_dp`'s round-half-to-even.
 pub fn round(collection: Collection, precision_arg: Option<&Collection>) -> Result<Collection> {
     if collection.is_empty() {
         return Ok(Collection::empty());
@@ -352,7 +204,9 @@ pub fn round(collection: Collection, precision_arg: Option<&Collection>) -> Resu
             } else {
                 0
             };
-            Ok(Collection::singleton(Value::decimal(d.round_dp(precision))))
+            Ok(Collection::singleton(Value::decimal(
+                d.round_dp_with_strategy(precision, RoundingStrategy::MidpointAwayFromZero),
+            )))
         }
         ValueData::Integer(i) => Ok(Collection::singleton(Value::integer(*i))),
         _ => Err(Error::TypeError("round() requires numeric type".into())),
@@ -415,29 +269,147 @@ pub fn truncate(collection: Collection) -> Result<Collection> {
 
     let item = collection.iter().next().unwrap();
     match item.data() {
-        ValueData::Decimal(d) => {
-            // Truncate: round towards zero (remove fractional part)
-            // For positive: floor (3.7 -> 3)
-            // For negative: ceiling (-3.7 -> -3)
-            let rounded = d.round_dp(0);
-            let result = if *d >= Decimal::ZERO {
-                // Positive: use floor behavior
-                if *d < rounded {
-                    rounded - Decimal::ONE
-                } else {
-                    rounded
-                }
-            } else {
-                // Negative: use ceiling behavior
-                if *d > rounded {
-                    rounded + Decimal::ONE
-                } else {
-                    rounded
-                }
-            };
-            Ok(Collection::singleton(Value::decimal(result)))
-        }
+        // Drops the fractional part, rounding towards zero: truncate of -2.5 is -2
+        ValueData::Decimal(d) => Ok(Collection::singleton(Value::decimal(d.trunc()))),
         ValueData::Integer(i) => Ok(Collection::singleton(Value::integer(*i))),
         _ => Err(Error::TypeError("truncate() requires numeric type".into())),
     }
 }
+
+/// Significant digits kept when a math function result computed in `f64` is converted back to
+/// a Decimal. `f64` carries 15-17 significant decimal digits; keeping 15 drops the binary
+/// rounding noise (so `ln(1)` is `0` and `log(100, 10)` is `2`).
+const F64_SIGNIFICANT_DIGITS: u32 = 15;
+
+/// Integer or Decimal operand of a math function, as `f64`
+fn numeric_operand(item: &Value, function: &str) -> Result<f64> {
+    let value = match item.data() {
+        ValueData::Integer(i) => Some(*i as f64),
+        ValueData::Decimal(d) => d.to_f64(),
+        _ => {
+            return Err(Error::TypeError(format!(
+                "{} requires numeric value",
+                function
+            )))
+        }
+    };
+    value.ok_or_else(|| Error::InvalidOperation(format!("{} value out of range", function)))
+}
+
+/// Convert an `f64` result to a Decimal with [`F64_SIGNIFICANT_DIGITS`] precision.
+///
+/// Returns empty when the result is not finite or does not fit in a Decimal.
+fn decimal_result(value: f64) -> Collection {
+    if !value.is_finite() {
+        return Collection::empty();
+    }
+    Decimal::from_f64(value)
+        .and_then(|d| {
+            d.round_sf_with_strategy(
+                F64_SIGNIFICANT_DIGITS,
+                RoundingStrategy::MidpointAwayFromZero,
+            )
+        })
+        .map(|d| Collection::singleton(Value::decimal(d.normalize())))
+        .unwrap_or_else(Collection::empty)
+}
+
+/// Exact `base^exp` by repeated squaring, `None` on Decimal overflow.
+fn checked_powi(base: Decimal, mut exp: i64) -> Option<Decimal> {
+    let mut result = Decimal::ONE;
+    let mut factor = base;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.checked_mul(factor)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            factor = factor.checked_mul(factor)?;
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::eval;
+    use crate::value::ValueData;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn decimal(expr: &str) -> Decimal {
+        match eval(expr).iter().next().map(|v| v.data().clone()) {
+            Some(ValueData::Decimal(d)) => d,
+            other => panic!("`{}` should be a Decimal, got {:?}", expr, other),
+        }
+    }
+
+    #[test]
+    fn rounding_of_halves_and_negatives() {
+        // (input, round, floor, ceiling, truncate)
+        let table = [
+            ("2.5", "3", "2", "3", "2"),
+            ("(-2.5)", "-3", "-3", "-2", "-2"),
+            ("3.5", "4", "3", "4", "3"),
+            ("(-3.5)", "-4", "-4", "-3", "-3"),
+        ];
+        for (input, round, floor, ceiling, truncate) in table {
+            for (function, expected) in [
+                ("round", round),
+                ("floor", floor),
+                ("ceiling", ceiling),
+                ("truncate", truncate),
+            ] {
+                let expr = format!("{}.{}()", input, function);
+                assert_eq!(
+                    decimal(&expr),
+                    Decimal::from_str(expected).unwrap(),
+                    "{}",
+                    expr
+                );
+            }
+        }
+        assert_eq!(
+            decimal("(-1.25).round(1)"),
+            Decimal::from_str("-1.3").unwrap()
+        );
+    }
+
+    #[test]
+    fn integer_power_stays_exact() {
+        assert_eq!(eval("2.power(10)").as_integer().unwrap(), 1024);
+        assert_eq!(
+            eval("3.power(39)").as_integer().unwrap(),
+            4_052_555_153_018_976_267
+        );
+        assert!(eval("2.power(64)").is_empty(), "Integer overflow is empty");
+        assert_eq!(decimal("2.power(-1)"), Decimal::from_str("0.5").unwrap());
+        assert_eq!(decimal("2.5.power(2)"), Decimal::from_str("6.25").unwrap());
+        assert!(eval("(-1).power(0.5)").is_empty());
+    }
+
+    #[test]
+    fn logarithms_and_exp() {
+        assert_eq!(decimal("1.ln()"), Decimal::ZERO);
+        assert_eq!(decimal("100.log(10)"), Decimal::from(2));
+        assert_eq!(decimal("16.log(2)"), Decimal::from(4));
+        assert_eq!(decimal("0.exp()"), Decimal::ONE);
+        assert_eq!(
+            decimal("1.exp()"),
+            Decimal::from_str("2.71828182845905").unwrap()
+        );
+        assert_eq!(
+            decimal("2.ln()"),
+            Decimal::from_str("0.693147180559945").unwrap()
+        );
+    }
+
+    #[test]
+    fn domain_errors_are_empty() {
+        assert!(eval("0.ln()").is_empty());
+        assert!(eval("(-1.5).ln()").is_empty());
+        assert!(eval("0.log(10)").is_empty());
+        assert!(eval("10.log(1)").is_empty());
+        assert!(eval("1000.exp()").is_empty());
+    }
+}
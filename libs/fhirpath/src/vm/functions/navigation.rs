@@ -2,6 +2,7 @@
 //!
 //! This module implements tree navigation functions like `children()` and `descendants()`.
 
+use crate::context::EvalLimits;
 use crate::error::{Error, Result};
 use crate::value::{Collection, Value, ValueData};
 use serde_json::Value as JsonValue;
@@ -114,7 +115,11 @@ pub fn children(collection: Collection, name_arg: Option<&Collection>) -> Result
     Ok(result)
 }
 
-pub fn descendants(collection: Collection, name_arg: Option<&Collection>) -> Result<Collection> {
+pub fn descendants(
+    collection: Collection,
+    name_arg: Option<&Collection>,
+    limits: &EvalLimits,
+) -> Result<Collection> {
     // descendants() returns all descendant nodes (recursive children)
     // This is equivalent to repeat(children())
     // Uses cycle detection to prevent infinite loops
@@ -252,6 +257,12 @@ pub fn descendants(collection: Collection, name_arg: Option<&Collection>) -> Res
                 input_queue.push(child.clone());
             }
         }
+        if result.len() > limits.max_collection_size {
+            return Err(Error::InvalidOperation(format!(
+                "descendants() exceeded the maximum collection size ({})",
+                limits.max_collection_size
+            )));
+        }
     }
 
     Ok(result)
@@ -3,8 +3,6 @@
 //! This module implements all string-related functions like `toString()`, `indexOf()`,
 //! `substring()`, `upper()`, `lower()`, `matches()`, `replace()`, etc.
 
-use std::sync::Arc;
-
 #[cfg(feature = "regex")]
 use regex::Regex;
 
@@ -14,22 +12,13 @@ use base64::{engine::general_purpose, Engine};
 #[cfg(feature = "html-escape")]
 use html_escape;
 
+use crate::conversion::ferrum_fhirpath_value_to_string;
 use crate::error::{Error, Result};
-use crate::value::{Collection, Value, ValueData};
-use chrono::Timelike;
-
-/// Format a timezone suffix for a fixed offset (seconds east of UTC).
-fn format_timezone_suffix(offset_secs: i32) -> String {
-    if offset_secs == 0 {
-        return "Z".to_string();
-    }
-    let sign = if offset_secs < 0 { '-' } else { '+' };
-    let abs = offset_secs.abs();
-    let hours = abs / 3600;
-    let minutes = (abs % 3600) / 60;
-    format!("{}{:02}:{:02}", sign, hours, minutes)
-}
+use crate::value::{Collection, Value};
 
+/// `toString()`: the canonical string form of a single primitive.
+///
+/// Complex values (objects) have no string representation and yield empty.
 pub fn to_string(collection: Collection) -> Result<Collection> {
     if collection.is_empty() {
         return Ok(Collection::empty());
@@ -37,110 +26,10 @@ pub fn to_string(collection: Collection) -> Result<Collection> {
 
     if collection.len() == 1 {
         let item = collection.iter().next().unwrap();
-        let str_val: Arc<str> = match item.data() {
-            ValueData::String(s) => s.clone(),
-            ValueData::Integer(i) => format!("{}", i).into(),
-            ValueData::Decimal(d) => format!("{}", d).into(),
-            ValueData::Boolean(b) => format!("{}", b).into(),
-            ValueData::Date {
-                value: d,
-                precision,
-            } => match *precision {
-                crate::value::DatePrecision::Year => d.format("%Y").to_string().into(),
-                crate::value::DatePrecision::Month => d.format("%Y-%m").to_string().into(),
-                crate::value::DatePrecision::Day => d.format("%Y-%m-%d").to_string().into(),
-            },
-            ValueData::DateTime {
-                value: dt,
-                precision,
-                timezone_offset,
-            } => {
-                let (dt_str, include_tz) = match *precision {
-                    crate::value::DateTimePrecision::Year => (dt.format("%Y").to_string(), false),
-                    crate::value::DateTimePrecision::Month => {
-                        (dt.format("%Y-%m").to_string(), false)
-                    }
-                    crate::value::DateTimePrecision::Day => {
-                        (dt.format("%Y-%m-%d").to_string(), false)
-                    }
-                    crate::value::DateTimePrecision::Hour => {
-                        (dt.format("%Y-%m-%dT%H").to_string(), true)
-                    }
-                    crate::value::DateTimePrecision::Minute => {
-                        (dt.format("%Y-%m-%dT%H:%M").to_string(), true)
-                    }
-                    crate::value::DateTimePrecision::Second => {
-                        (dt.format("%Y-%m-%dT%H:%M:%S").to_string(), true)
-                    }
-                    crate::value::DateTimePrecision::Millisecond => {
-                        let ms = dt.timestamp_subsec_millis();
-                        (
-                            format!("{}.{:03}", dt.format("%Y-%m-%dT%H:%M:%S"), ms),
-                            true,
-                        )
-                    }
-                };
-
-                if include_tz {
-                    if let Some(offset_secs) = timezone_offset {
-                        // Convert the instant to the original offset for display.
-                        let offset = chrono::FixedOffset::east_opt(*offset_secs)
-                            .unwrap_or_else(|| chrono::FixedOffset::east_opt(0).unwrap());
-                        let dt_local = dt.with_timezone(&offset);
-                        let dt_local_str = match *precision {
-                            crate::value::DateTimePrecision::Hour => {
-                                dt_local.format("%Y-%m-%dT%H").to_string()
-                            }
-                            crate::value::DateTimePrecision::Minute => {
-                                dt_local.format("%Y-%m-%dT%H:%M").to_string()
-                            }
-                            crate::value::DateTimePrecision::Second => {
-                                dt_local.format("%Y-%m-%dT%H:%M:%S").to_string()
-                            }
-                            crate::value::DateTimePrecision::Millisecond => {
-                                let ms = dt_local.timestamp_subsec_millis();
-                                format!("{}.{:03}", dt_local.format("%Y-%m-%dT%H:%M:%S"), ms)
-                            }
-                            _ => dt_str,
-                        };
-                        format!("{}{}", dt_local_str, format_timezone_suffix(*offset_secs)).into()
-                    } else {
-                        // No timezone specified in the value.
-                        dt_str.into()
-                    }
-                } else {
-                    dt_str.into()
-                }
-            }
-            ValueData::Time {
-                value: t,
-                precision,
-            } => match *precision {
-                crate::value::TimePrecision::Hour => t.format("%H").to_string().into(),
-                crate::value::TimePrecision::Minute => t.format("%H:%M").to_string().into(),
-                crate::value::TimePrecision::Second => t.format("%H:%M:%S").to_string().into(),
-                crate::value::TimePrecision::Millisecond => {
-                    let ms = t.nanosecond() / 1_000_000;
-                    format!("{}.{:03}", t.format("%H:%M:%S"), ms).into()
-                }
-            },
-            ValueData::Quantity { value, unit } => {
-                let unit_str = unit.as_ref();
-                if unit_str.is_empty() || unit_str == "1" {
-                    format!("{} '1'", value).into()
-                } else if matches!(
-                    unit_str,
-                    "day" | "days" | "week" | "weeks" | "month" | "months" | "year" | "years"
-                ) || (unit_str.chars().all(|c| c.is_alphanumeric()) && unit_str.len() > 2)
-                {
-                    format!("{} {}", value, unit_str).into()
-                } else {
-                    format!("{} '{}'", value, unit_str).into()
-                }
-            }
-            _ => return Err(Error::TypeError("Cannot convert to string".into())),
-        };
-        Ok(Collection::singleton(Value::string(str_val)))
+        Ok(match ferrum_fhirpath_value_to_string(item) {
+            Some(s) => Collection::singleton(Value::string(s)),
+            None => Collection::empty(),
+        })
     } else {
         Err(Error::TypeError(
             "toString() requires singleton collection".into(),
@@ -238,75 +127,48 @@ pub fn substring(
     Ok(Collection::singleton(Value::string(result)))
 }
 
-pub fn starts_with(collection: Collection, prefix_arg: Option<&Collection>) -> Result<Collection> {
-    // If collection is empty, return empty (per FHIRPath spec)
-    if collection.is_empty() {
+/// Shared implementation of `startsWith()`, `endsWith()` and `contains()`.
+///
+/// An empty input or an empty argument collection yields empty; an empty string argument
+/// matches every string (`str` predicates already behave that way).
+fn string_predicate(
+    name: &str,
+    collection: Collection,
+    arg: Option<&Collection>,
+    predicate: fn(&str, &str) -> bool,
+) -> Result<Collection> {
+    let arg =
+        arg.ok_or_else(|| Error::InvalidOperation(format!("{}() requires 1 argument", name)))?;
+
+    if collection.is_empty() || arg.is_empty() {
         return Ok(Collection::empty());
     }
 
-    // Extract prefix argument - must be present
-    let prefix_collection = match prefix_arg {
-        Some(c) => {
-            if c.is_empty() {
-                // Empty prefix collection means empty argument, return empty result
-                return Ok(Collection::empty());
-            }
-            c
-        }
-        None => {
-            return Err(Error::InvalidOperation(
-                "startsWith() requires 1 argument".into(),
-            ));
-        }
-    };
-
-    // Extract strings from collections
     let str_val = collection
         .as_string()
-        .map_err(|e| Error::TypeError(format!("startsWith() requires string collection: {}", e)))?;
-    let prefix_str = prefix_collection
+        .map_err(|e| Error::TypeError(format!("{}() requires string input: {}", name, e)))?;
+    let arg_str = arg
         .as_string()
-        .map_err(|e| Error::TypeError(format!("startsWith() requires string prefix: {}", e)))?;
+        .map_err(|e| Error::TypeError(format!("{}() requires string argument: {}", name, e)))?;
 
-    // Empty string prefix always matches (per FHIRPath spec)
-    if prefix_str.is_empty() {
-        return Ok(Collection::singleton(Value::boolean(true)));
-    }
+    Ok(Collection::singleton(Value::boolean(predicate(
+        str_val.as_ref(),
+        arg_str.as_ref(),
+    ))))
+}
 
-    // Perform the starts_with check
-    // Note: Both str_val and prefix_str are Arc<str>, so we use as_ref() to get &str
-    let result = str_val.starts_with(prefix_str.as_ref());
-    Ok(Collection::singleton(Value::boolean(result)))
+pub fn starts_with(collection: Collection, prefix_arg: Option<&Collection>) -> Result<Collection> {
+    string_predicate("startsWith", collection, prefix_arg, |s, p| {
+        s.starts_with(p)
+    })
 }
 
 pub fn ends_with(collection: Collection, suffix_arg: Option<&Collection>) -> Result<Collection> {
-    let suffix = suffix_arg
-        .ok_or_else(|| Error::InvalidOperation("endsWith() requires 1 argument".into()))?
-        .as_string()?;
-
-    if collection.is_empty() {
-        return Ok(Collection::empty());
-    }
-
-    let str_val = collection.as_string()?;
-    Ok(Collection::singleton(Value::boolean(
-        str_val.ends_with(suffix.as_ref()),
-    )))
+    string_predicate("endsWith", collection, suffix_arg, |s, p| s.ends_with(p))
 }
 
 pub fn contains_str(collection: Collection, substr_arg: Option<&Collection>) -> Result<Collection> {
-    let substr = substr_arg
-        .ok_or_else(|| Error::InvalidOperation("contains() requires 1 argument".into()))?
-        .as_string()?;
-
-    if collection.is_empty() {
-        return Ok(Collection::empty());
-    }
-
-    let str_val = collection.as_string()?;
-    Ok(Collection::singleton(Value::boolean(
-        str_val.contains(substr.as_ref()),
-    )))
+    string_predicate("contains", collection, substr_arg, |s, p| s.contains(p))
 }
 
 pub fn upper(collection: Collection) -> Result<Collection> {
@@ -908,6 +770,40 @@ mod tests {
         Context::new(Value::empty())
     }
 
+    #[test]
+    fn test_to_string_primitives_and_objects() {
+        let as_str = |value: Value| {
+            to_string(Collection::singleton(value))
+                .unwrap()
+                .as_string()
+                .unwrap()
+                .to_string()
+        };
+
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 3, 7).unwrap();
+        assert_eq!(as_str(Value::date(date)), "2024-03-07");
+        assert_eq!(
+            as_str(Value::date_with_precision(
+                date,
+                crate::value::DatePrecision::Month
+            )),
+            "2024-03"
+        );
+        assert_eq!(
+            as_str(Value::quantity(Decimal::new(5, 0), "mg".into())),
+            "5 'mg'"
+        );
+
+        // Complex values have no string form
+        let object = Value::from_json(serde_json::json!({"family": "Doe"}));
+        assert!(to_string(Collection::singleton(object.clone()))
+            .unwrap()
+            .is_empty());
+        assert!(to_string(Collection::singleton(object.materialize()))
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_starts_with_direct() {
         let hello_col = Collection::singleton(Value::string("hello"));
@@ -921,6 +817,51 @@ mod tests {
         assert!(!result.as_boolean().unwrap());
     }
 
+    #[test]
+    fn test_string_predicates_empty_semantics() {
+        let s = |v: &str| Collection::singleton(Value::string(v));
+        let empty = Collection::empty();
+        type Predicate = fn(Collection, Option<&Collection>) -> Result<Collection>;
+
+        // (function, input, argument, expected result; None = empty collection)
+        let cases: Vec<(&str, Predicate, Collection, Collection, Option<bool>)> = vec![
+            ("startsWith", starts_with, s("hello"), s("he"), Some(true)),
+            ("startsWith", starts_with, s("hello"), s("lo"), Some(false)),
+            ("startsWith", starts_with, s("hello"), s(""), Some(true)),
+            ("startsWith", starts_with, s(""), s(""), Some(true)),
+            ("startsWith", starts_with, s(""), s("a"), Some(false)),
+            ("startsWith", starts_with, empty.clone(), s("a"), None),
+            ("startsWith", starts_with, s("hello"), empty.clone(), None),
+            ("endsWith", ends_with, s("hello"), s("lo"), Some(true)),
+            ("endsWith", ends_with, s("hello"), s("he"), Some(false)),
+            ("endsWith", ends_with, s("hello"), s(""), Some(true)),
+            ("endsWith", ends_with, s(""), s(""), Some(true)),
+            ("endsWith", ends_with, s(""), s("a"), Some(false)),
+            ("endsWith", ends_with, empty.clone(), s("a"), None),
+            ("endsWith", ends_with, s("hello"), empty.clone(), None),
+            ("contains", contains_str, s("hello"), s("ell"), Some(true)),
+            ("contains", contains_str, s("hello"), s("xyz"), Some(false)),
+            ("contains", contains_str, s("hello"), s(""), Some(true)),
+            ("contains", contains_str, s(""), s(""), Some(true)),
+            ("contains", contains_str, s(""), s("a"), Some(false)),
+            ("contains", contains_str, empty.clone(), s("a"), None),
+            ("contains", contains_str, s("hello"), empty.clone(), None),
+        ];
+
+        for (name, f, input, arg, expected) in cases {
+            let result = f(input.clone(), Some(&arg)).unwrap();
+            let actual = if result.is_empty() {
+                None
+            } else {
+                Some(result.as_boolean().unwrap())
+            };
+            assert_eq!(actual, expected, "{}({:?}) on {:?}", name, arg, input);
+        }
+
+        assert!(starts_with(s("hello"), None).is_err());
+        assert!(ends_with(s("hello"), Some(&Collection::singleton(Value::integer(1)))).is_err());
+    }
+
     #[test]
     pub fn matches_fhir_integer_with_path_hint() {
         let val = Value::integer(1);
@@ -3,6 +3,7 @@
 //! This module implements functions that extract subsets from collections like
 //! `single()`, `first()`, `last()`, `tail()`, `skip()`, `take()`, `intersect()`, `exclude()`.
 
+use super::helpers::items_equal;
 use crate::error::{Error, Result};
 use crate::value::{Collection, Value};
 
@@ -29,90 +30,67 @@ pub fn first(collection: Collection) -> Result<Collection> {
 }
 
 pub fn last(collection: Collection) -> Result<Collection> {
-    if collection.is_empty() {
-        return Ok(Collection::empty());
+    match collection.iter().last() {
+        Some(item) => Ok(Collection::singleton(item.clone())),
+        None => Ok(Collection::empty()),
     }
-
-    // Collect into Vec to get owned values
-    let items: Vec<Value> = collection.iter().cloned().collect();
-    let last_item = items.last().unwrap().clone();
-    Ok(Collection::singleton(last_item))
 }
 
+/// All but the first item; empty for empty or singleton input.
 pub fn tail(collection: Collection) -> Result<Collection> {
-    if collection.is_empty() {
-        return Ok(Collection::empty());
-    }
-
     let mut result = Collection::empty();
-    let mut iter = collection.iter();
-    iter.next(); // Skip first
-
-    for item in iter {
+    for item in collection.iter().skip(1) {
         result.push(item.clone());
     }
 
     Ok(result)
 }
 
+/// All but the first `num` items. A `num` of zero or less returns the input unchanged; an
+/// empty `num` yields empty.
 pub fn skip(collection: Collection, count_arg: Option<&Collection>) -> Result<Collection> {
-    let count = count_arg
-        .ok_or_else(|| Error::InvalidOperation("skip() requires 1 argument".into()))?
-        .as_integer()?;
-
-    if count < 0 {
-        return Err(Error::InvalidOperation(
-            "skip() count must be non-negative".into(),
-        ));
+    let count_arg =
+        count_arg.ok_or_else(|| Error::InvalidOperation("skip() requires 1 argument".into()))?;
+    if count_arg.is_empty() {
+        return Ok(Collection::empty());
     }
 
-    let mut result = Collection::empty();
-    let mut iter = collection.iter();
-
-    // Skip first count items
-    for _ in 0..count {
-        if iter.next().is_none() {
-            break;
-        }
+    let count = count_arg.as_integer()?;
+    if count <= 0 {
+        return Ok(collection);
     }
 
-    // Add remaining items
-    for item in iter {
+    let mut result = Collection::empty();
+    for item in collection.iter().skip(count as usize) {
         result.push(item.clone());
     }
 
     Ok(result)
 }
 
+/// The first `num` items. A `num` of zero or less, or an empty `num`, yields empty.
 pub fn take(collection: Collection, count_arg: Option<&Collection>) -> Result<Collection> {
-    let count = count_arg
-        .ok_or_else(|| Error::InvalidOperation("take() requires 1 argument".into()))?
-        .as_integer()?;
+    let count_arg =
+        count_arg.ok_or_else(|| Error::InvalidOperation("take() requires 1 argument".into()))?;
+    if count_arg.is_empty() {
+        return Ok(Collection::empty());
+    }
 
-    if count < 0 {
-        return Err(Error::InvalidOperation(
-            "take() count must be non-negative".into(),
-        ));
+    let count = count_arg.as_integer()?;
+    if count <= 0 {
+        return Ok(Collection::empty());
     }
 
     let mut result = Collection::empty();
-    let mut iter = collection.iter();
-
-    // Take first count items
-    for _ in 0..count {
-        if let Some(item) = iter.next() {
-            result.push(item.clone());
-        } else {
-            break;
-        }
+    for item in collection.iter().take(count as usize) {
+        result.push(item.clone());
     }
 
     Ok(result)
 }
 
+/// Items present in both collections, matched with FHIRPath `=` and without duplicates.
 pub fn intersect(collection: Collection, other: Option<&Collection>) -> Result<Collection> {
-    use std::collections::HashSet;
-
     let other =
         other.ok_or_else(|| Error::InvalidOperation("intersect() requires 1 argument".into()))?;
 
@@ -121,14 +99,14 @@ pub fn intersect(collection: Collection, other: Option<&Collection>) -> Result<C
         return Ok(Collection::empty());
     }
 
-    // Build HashSet from other collection for O(1) lookups
-    let other_set: HashSet<&Value> = other.iter().collect();
-
-    let mut seen = HashSet::with_capacity(collection.len().min(other.len()));
+    // Equality is FHIRPath `=` (1 = 1.0, objects by content), which has no hash, so match
+    // pairwise
     let mut result = Collection::empty();
 
     for item in collection.iter() {
-        if other_set.contains(item) && seen.insert(item) {
+        if other.iter().any(|o| items_equal(item, o))
+            && !result.iter().any(|r| items_equal(item, r))
+        {
             result.push(item.clone());
         }
     }
@@ -166,3 +144,94 @@ pub fn exclude(collection: Collection, other: Option<&Collection>) -> Result<Col
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Context;
+    use crate::test_support::engine;
+    use crate::value::Value;
+    use serde_json::json;
+
+    fn eval_bool(ctx: &Context, expr: &str) -> bool {
+        engine()
+            .evaluate_expr(expr, ctx, None)
+            .unwrap()
+            .as_boolean()
+            .unwrap()
+    }
+
+    #[test]
+    fn tail_and_last() {
+        let ctx = Context::new(Value::empty());
+
+        assert!(eval_bool(&ctx, "(1|2|3|4).tail() = (2|3|4)"));
+        assert!(eval_bool(&ctx, "(1).tail().empty()"));
+        assert!(eval_bool(&ctx, "{}.tail().empty()"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).last() = 4"));
+        assert!(eval_bool(&ctx, "(1).last() = 1"));
+        assert!(eval_bool(&ctx, "{}.last().empty()"));
+    }
+
+    #[test]
+    fn skip_handles_boundaries_and_negative_counts() {
+        let ctx = Context::new(Value::empty());
+
+        assert!(eval_bool(&ctx, "(1|2|3|4).skip(1) = (2|3|4)"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).skip(3) = 4"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).skip(4).empty()"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).skip(10).empty()"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).skip(0) = (1|2|3|4)"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).skip(-1) = (1|2|3|4)"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).skip({}).empty()"));
+        assert!(eval_bool(&ctx, "{}.skip(1).empty()"));
+    }
+
+    #[test]
+    fn take_handles_boundaries_and_negative_counts() {
+        let ctx = Context::new(Value::empty());
+
+        assert!(eval_bool(&ctx, "(1|2|3|4).take(1) = 1"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).take(3) = (1|2|3)"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).take(4) = (1|2|3|4)"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).take(10) = (1|2|3|4)"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).take(0).empty()"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).take(-1).empty()"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).take({}).empty()"));
+        assert!(eval_bool(&ctx, "(1|2|3|4).skip(1).take(2) = (2|3)"));
+    }
+
+    #[test]
+    fn intersect_returns_distinct_common_items() {
+        let ctx = Context::new(Value::empty());
+
+        assert!(eval_bool(&ctx, "(1|2|3).intersect(2|3|4) = (2|3)"));
+        assert!(eval_bool(&ctx, "(1|2|3).intersect({}).empty()"));
+        assert!(eval_bool(&ctx, "{}.intersect(1|2).empty()"));
+        assert!(eval_bool(&ctx, "(1|2|3).intersect(5|6).empty()"));
+        // Duplicates collapse and matching uses `=`, so 2 meets 2.0
+        assert!(eval_bool(
+            &ctx,
+            "(1|2|3).combine(2).intersect(2.0|3) = (2|3)"
+        ));
+    }
+
+    #[test]
+    fn intersect_compares_objects_by_content() {
+        let observation = json!({
+            "resourceType": "Observation",
+            "code": {"coding": [
+                {"system": "http://loinc.org", "code": "8480-6"},
+                {"system": "http://snomed.info/sct", "code": "271649006"}
+            ]},
+            "category": [{"coding": [
+                {"system": "http://loinc.org", "code": "8480-6"}
+            ]}]
+        });
+        let ctx = Context::new(Value::from_json(observation));
+
+        assert!(eval_bool(
+            &ctx,
+            "code.coding.intersect(category.coding).code = '8480-6'"
+        ));
+    }
+}
@@ -3,6 +3,179 @@
 //! This module implements temporal-related functions. Note that `now()`, `today()`, and
 //! `timeOfDay()` are implemented in the utility module.
 
+use chrono::{Months, NaiveDate, NaiveDateTime, NaiveTime};
+use rust_decimal::Decimal;
+
+use crate::error::{Error, Result};
+use crate::value::{Collection, DatePrecision, DateTimePrecision, TimePrecision, Value, ValueData};
+use crate::vm::operations::{normalize_unit, UnitKind};
+
+/// until(other, unit) : Quantity
+///
+/// Returns the number of whole `unit`s elapsed from the input to `other` as a calendar
+/// duration quantity, e.g. `@2020-01-01.until(@2021-01-01, 'days')` is `366 days`.
+/// Partial units are truncated toward zero, so the result is negative when `other` is
+/// earlier than the input. Months and years are counted on the calendar (a month has
+/// elapsed once the same day-of-month is reached, clamped to the end of shorter months).
+///
+/// The result is empty when either operand is empty, when the operands are not comparable
+/// (a Time against a Date or DateTime), or when either operand is less precise than `unit`.
+pub fn until(
+    collection: Collection,
+    other_arg: Option<&Collection>,
+    unit_arg: Option<&Collection>,
+) -> Result<Collection> {
+    let (Some(other_arg), Some(unit_arg)) = (other_arg, unit_arg) else {
+        return Err(Error::InvalidOperation(
+            "until() requires a value and a unit".into(),
+        ));
+    };
+    if collection.len() > 1 || other_arg.len() > 1 {
+        return Err(Error::TypeError(
+            "until() requires singleton collections".into(),
+        ));
+    }
+    let (Some(from), Some(to), Some(unit)) = (
+        collection.iter().next(),
+        other_arg.iter().next(),
+        unit_arg.iter().next(),
+    ) else {
+        return Ok(Collection::empty());
+    };
+
+    let ValueData::String(unit) = unit.data() else {
+        return Err(Error::TypeError("until() unit must be a String".into()));
+    };
+    let (unit_name, required_precision) = match normalize_unit(unit) {
+        UnitKind::Years => ("years", 0),
+        UnitKind::Months => ("months", 1),
+        UnitKind::Weeks => ("weeks", 2),
+        UnitKind::Days => ("days", 2),
+        UnitKind::Hours => ("hours", 3),
+        UnitKind::Minutes => ("minutes", 4),
+        UnitKind::Seconds => ("seconds", 5),
+        UnitKind::Milliseconds => ("milliseconds", 6),
+        UnitKind::Dimensionless | UnitKind::Unknown => {
+            return Err(Error::InvalidOperation(format!(
+                "until() does not support unit '{}'",
+                unit
+            )));
+        }
+    };
+
+    let (Some(from), Some(to)) = (
+        TemporalPoint::from_value(from),
+        TemporalPoint::from_value(to),
+    ) else {
+        return Ok(Collection::empty());
+    };
+    if from.precision.min(to.precision) < required_precision {
+        return Ok(Collection::empty());
+    }
+    if from.is_time != to.is_time || (from.is_time && required_precision < 3) {
+        return Ok(Collection::empty());
+    }
+
+    let elapsed = to.value.signed_duration_since(from.value);
+    let amount = match unit_name {
+        "years" => whole_months(from.value, to.value).map(|months| months / 12),
+        "months" => whole_months(from.value, to.value),
+        "weeks" => Some(elapsed.num_weeks()),
+        "days" => Some(elapsed.num_days()),
+        "hours" => Some(elapsed.num_hours()),
+        "minutes" => Some(elapsed.num_minutes()),
+        "seconds" => Some(elapsed.num_seconds()),
+        _ => Some(elapsed.num_milliseconds()),
+    };
+
+    Ok(match amount {
+        Some(amount) => {
+            Collection::singleton(Value::quantity(Decimal::from(amount), unit_name.into()))
+        }
+        None => Collection::empty(),
+    })
+}
+
+/// A Date, DateTime or Time reduced to a comparable timestamp.
+///
+/// `precision` ranks the least significant component present: 0 = year, 1 = month,
+/// 2 = day, 3 = hour, 4 = minute, 5 = second, 6 = millisecond. Times are anchored on
+/// 1970-01-01 and only compared with other times.
+struct TemporalPoint {
+    value: NaiveDateTime,
+    precision: u8,
+    is_time: bool,
+}
+
+impl TemporalPoint {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value.data() {
+            ValueData::Date { value, precision } => Some(Self {
+                value: value.and_time(NaiveTime::MIN),
+                precision: match precision {
+                    DatePrecision::Year => 0,
+                    DatePrecision::Month => 1,
+                    DatePrecision::Day => 2,
+                },
+                is_time: false,
+            }),
+            ValueData::DateTime {
+                value, precision, ..
+            } => Some(Self {
+                value: value.naive_utc(),
+                precision: match precision {
+                    DateTimePrecision::Year => 0,
+                    DateTimePrecision::Month => 1,
+                    DateTimePrecision::Day => 2,
+                    DateTimePrecision::Hour => 3,
+                    DateTimePrecision::Minute => 4,
+                    DateTimePrecision::Second => 5,
+                    DateTimePrecision::Millisecond => 6,
+                },
+                is_time: false,
+            }),
+            ValueData::Time { value, precision } => Some(Self {
+                value: NaiveDate::from_ymd_opt(1970, 1, 1)?.and_time(*value),
+                precision: match precision {
+                    TimePrecision::Hour => 3,
+                    TimePrecision::Minute => 4,
+                    TimePrecision::Second => 5,
+                    TimePrecision::Millisecond => 6,
+                },
+                is_time: true,
+            }),
+            ValueData::String(s) => {
+                let parsed = crate::temporal_parse::parse_datetime_value_lenient(s.as_ref())
+                    .or_else(|| crate::temporal_parse::parse_time_value(s.as_ref()))?;
+                Self::from_value(&parsed)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Count the calendar months from `from` to `to`, truncated toward zero.
+fn whole_months(from: NaiveDateTime, to: NaiveDateTime) -> Option<i64> {
+    use chrono::Datelike;
+
+    let mut months =
+        (to.year() as i64 - from.year() as i64) * 12 + to.month() as i64 - from.month() as i64;
+    let shift = |months: i64| {
+        let count = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+        if months >= 0 {
+            from.checked_add_months(count)
+        } else {
+            from.checked_sub_months(count)
+        }
+    };
+    if months > 0 && shift(months)? > to {
+        months -= 1;
+    } else if months < 0 && shift(months)? < to {
+        months += 1;
+    }
+    Some(months)
+}
+
 // Parse partial dateTime strings allowed by FHIRPath (e.g., YYYY, YYYY-MM, YYYY-MM-DDThh, YYYY-MM-DDThh:mm, YYYY-MM-DDThh:mm:ss(.fff)(zzz))
 pub(super) fn parse_partial_datetime(input: &str) -> Option<chrono::DateTime<chrono::Utc>> {
     // Try RFC3339 first (already handled elsewhere)
@@ -172,3 +345,69 @@ pub(super) fn is_valid_time_string(s: &str) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::eval;
+    use crate::value::ValueData;
+
+    fn quantity(expr: &str) -> (String, String) {
+        let result = eval(expr);
+        let value = result.iter().next().map(|v| v.data());
+        match value {
+            Some(ValueData::Quantity { value, unit }) => (value.to_string(), unit.to_string()),
+            other => panic!("{} returned {:?}", expr, other),
+        }
+    }
+
+    #[test]
+    fn until_counts_days_between_dates() {
+        assert_eq!(
+            quantity("@2020-01-01.until(@2021-01-01, 'days')"),
+            ("366".into(), "days".into())
+        );
+        assert_eq!(
+            quantity("@2021-01-01.until(@2020-01-01, 'day')"),
+            ("-366".into(), "days".into())
+        );
+        assert_eq!(
+            quantity("@2020-01-01T00:00:00.until(@2020-01-02T23:59:59, 'days')"),
+            ("1".into(), "days".into())
+        );
+        assert_eq!(
+            quantity("@2020-01-01.until(@2020-01-15, 'weeks')"),
+            ("2".into(), "weeks".into())
+        );
+    }
+
+    #[test]
+    fn until_counts_whole_calendar_months() {
+        assert_eq!(
+            quantity("@2020-01-31.until(@2020-03-30, 'months')"),
+            ("1".into(), "months".into())
+        );
+        assert_eq!(
+            quantity("@2020-01-31.until(@2020-03-31, 'months')"),
+            ("2".into(), "months".into())
+        );
+        assert_eq!(
+            quantity("@2020-03-15.until(@2020-01-16, 'months')"),
+            ("-1".into(), "months".into())
+        );
+        assert_eq!(
+            quantity("@1990-06-15.until(@2020-06-14, 'years')"),
+            ("29".into(), "years".into())
+        );
+    }
+
+    #[test]
+    fn until_is_empty_for_imprecise_or_incompatible_operands() {
+        assert!(eval("@2020-01.until(@2020-03-01, 'days')").is_empty());
+        assert!(eval("@T10:00.until(@2020-03-01, 'hours')").is_empty());
+        assert!(eval("{}.until(@2020-03-01, 'days')").is_empty());
+        assert_eq!(
+            quantity("@T10:00.until(@T12:30, 'minutes')"),
+            ("150".into(), "minutes".into())
+        );
+    }
+}
@@ -50,8 +50,15 @@ fn infer_structural_fhir_type_from_object(
         return Some("Narrative");
     }
 
-    // Extension
-    if obj.contains_key("url") && (obj.contains_key("extension") || has_choice_value_key(obj)) {
+    // Extension (other elements such as Attachment or RelatedArtifact also carry `url` and
+    // `extension`, so every key must belong to Extension)
+    if obj.contains_key("url")
+        && (obj.contains_key("extension") || has_choice_value_key(obj))
+        && obj.keys().all(|k| {
+            matches!(k.as_ref(), "id" | "url" | "extension")
+                || k.strip_prefix('_').unwrap_or(k).starts_with("value")
+        })
+    {
         return Some("Extension");
     }
 
@@ -363,7 +370,36 @@ fn normalize_type_specifier(spec: &str) -> (Option<String>, String) {
     }
 }
 
+/// FHIR primitive type names, known without consulting loaded packages.
+const FHIR_PRIMITIVE_TYPES: &[&str] = &[
+    "base64Binary",
+    "boolean",
+    "canonical",
+    "code",
+    "date",
+    "dateTime",
+    "decimal",
+    "id",
+    "instant",
+    "integer",
+    "integer64",
+    "markdown",
+    "oid",
+    "positiveInt",
+    "string",
+    "time",
+    "unsignedInt",
+    "uri",
+    "url",
+    "uuid",
+    "xhtml",
+];
+
 fn fhir_type_exists(fc: &dyn FhirContext, type_name: &str) -> bool {
+    if FHIR_PRIMITIVE_TYPES.contains(&type_name) {
+        return true;
+    }
+
     if fc
         .get_core_structure_definition_by_type(type_name)
         .ok()
@@ -55,3 +55,91 @@ pub fn is_type(
     let matches = matches_type_specifier(item, type_spec.as_ref(), path_hint, fhir_context, ctx);
     Ok(Collection::singleton(Value::boolean(matches)))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Context;
+    use crate::test_support::engine;
+    use crate::value::{Value, ValueData};
+    use serde_json::json;
+
+    #[test]
+    fn fhir_primitives_match_fhir_types_not_system_types() {
+        let ctx = Context::new(Value::from_json(json!({
+            "resourceType": "Patient",
+            "active": true,
+            "gender": "male",
+            "birthDate": "1970-01-01"
+        })));
+        let engine = engine();
+        let eval = |expr: &str| {
+            engine
+                .evaluate_expr(expr, &ctx, None)
+                .unwrap()
+                .as_boolean()
+                .unwrap()
+        };
+
+        // Patient.active is a FHIR.boolean, not a System.Boolean
+        assert!(!eval("Patient.active is Boolean"));
+        assert!(!eval("Patient.active is System.Boolean"));
+        assert!(eval("Patient.active is boolean"));
+        assert!(eval("Patient.active is FHIR.boolean"));
+        assert!(eval("Patient.active.is(boolean)"));
+        assert!(!eval("Patient.active.is(Boolean)"));
+        assert!(eval("(Patient.active as boolean).exists()"));
+        assert!(eval("(Patient.active as Boolean).empty()"));
+
+        // FHIR primitive specializations
+        assert!(eval("Patient.gender is code"));
+        assert!(eval("Patient.gender is string"));
+        assert!(!eval("Patient.gender is String"));
+        assert!(!eval("Patient.birthDate is Date"));
+
+        // Values computed from FHIR primitives are System values
+        assert!(eval("Patient.active.not() is Boolean"));
+        assert!(eval("Patient.active.exists() is Boolean"));
+        assert!(eval("Patient.birthDate.toString() is String"));
+        assert!(eval("Patient.gender.length() is Integer"));
+        assert!(eval("Patient.active.first() is boolean"));
+        assert!(!eval("Patient.active.first() is Boolean"));
+
+        // System literals
+        assert!(eval("true is Boolean"));
+        assert!(eval("true is System.Boolean"));
+    }
+
+    #[test]
+    fn as_narrows_choice_elements() {
+        let engine = engine();
+        let quantity = Context::new(Value::from_json(json!({
+            "resourceType": "Observation",
+            "status": "final",
+            "valueQuantity": {"value": 5, "unit": "mg"}
+        })));
+        let string = Context::new(Value::from_json(json!({
+            "resourceType": "Observation",
+            "status": "final",
+            "valueString": "high"
+        })));
+        let eval = |expr: &str, ctx: &Context| -> Vec<String> {
+            engine
+                .evaluate_expr(expr, ctx, None)
+                .unwrap()
+                .iter()
+                .map(|v| match v.data() {
+                    ValueData::String(s) => s.to_string(),
+                    other => panic!("expected a string, got {other:?}"),
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            eval("(Observation.value as Quantity).unit", &quantity),
+            ["mg"]
+        );
+        assert!(eval("Observation.value as string", &quantity).is_empty());
+        assert!(eval("(Observation.value as Quantity).unit", &string).is_empty());
+        assert_eq!(eval("Observation.value as string", &string), ["high"]);
+    }
+}
@@ -1,7 +1,7 @@
 //! Utility functions for FHIRPath.
 //!
 //! This module implements various utility functions like `trace()`, `now()`, `today()`,
-//! `sort()`, `type()`, `conformsTo()`, etc.
+//! `sort()`, `type()`, `conformsTo()`, `memberOf()`, etc.
 
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -14,6 +14,8 @@ use crate::context::Context;
 use crate::error::{Error, Result};
 use crate::hir::HirBinaryOperator;
 use crate::resolver::ResourceResolver;
+use crate::terminology::TerminologyProvider;
+use crate::trace::TraceSink;
 use crate::value::{Collection, Value, ValueData};
 use crate::vm::operations::execute_binary_op;
 use ferrum_context::FhirContext;
@@ -26,11 +28,13 @@ use super::type_helpers::{
 pub fn trace(
     collection: Collection,
     name_arg: Option<&Collection>,
-    projection_arg: Option<&Collection>,
+    projected: Option<&Collection>,
+    sink: Option<&Arc<dyn TraceSink>>,
 ) -> Result<Collection> {
     // trace() is a debugging function that logs the collection and returns it unchanged
     // The name argument is used as a label for the trace
-    // The projection argument (if provided) is evaluated and traced instead of the collection
+    // If a projection was provided, the VM has already applied it to each item and
+    // passes the projected values here; they are traced instead of the collection
 
     // Extract trace name
     let name = if let Some(name_arg) = name_arg {
@@ -46,22 +50,16 @@ pub fn trace(
         "trace".to_string()
     };
 
-    // If projection provided, evaluate it (but we can't evaluate expressions here, so just use collection)
-    // In a full implementation, this would evaluate the projection expression
-    let value_to_trace = if projection_arg.is_some() {
-        // Projection would be evaluated in the evaluator/engine, not here
-        // For now, just trace the original collection
-        &collection
-    } else {
-        &collection
-    };
+    let value_to_trace = projected.unwrap_or(&collection);
 
-    // Log the trace (using eprintln for now - could be configurable)
-    eprintln!(
-        "[FHIRPath trace: {}] Collection with {} items",
-        name,
-        value_to_trace.len()
-    );
+    match sink {
+        Some(sink) => sink.trace(&name, value_to_trace),
+        None => eprintln!(
+            "[FHIRPath trace: {}] Collection with {} items",
+            name,
+            value_to_trace.len()
+        ),
+    }
 
     // Always return the original collection unchanged
     Ok(collection)
@@ -1286,3 +1284,202 @@ pub fn resolve(
 
     Ok(resolved)
 }
+
+/// `memberOf(valueset)`: whether a code, Coding or CodeableConcept is in a ValueSet
+///
+/// A CodeableConcept is a member when any of its codings is. The result is empty when the
+/// input is not a single item, or when the ValueSet cannot be resolved, which includes engines
+/// without a terminology provider.
+pub fn member_of(
+    collection: Collection,
+    value_set_arg: Option<&Collection>,
+    terminology_provider: Option<&Arc<dyn TerminologyProvider>>,
+) -> Result<Collection> {
+    let value_set = value_set_arg
+        .ok_or_else(|| Error::InvalidOperation("memberOf() requires a ValueSet url".into()))?;
+    if collection.len() != 1 || value_set.is_empty() {
+        return Ok(Collection::empty());
+    }
+    let value_set_url = value_set
+        .as_string()
+        .map_err(|_| Error::TypeError("memberOf() expects a ValueSet url string".into()))?;
+    let Some(provider) = terminology_provider else {
+        return Ok(Collection::empty());
+    };
+
+    let item = collection.iter().next().unwrap().materialize();
+    let codings: Vec<(Option<Arc<str>>, Arc<str>)> = match item.data() {
+        ValueData::String(code) => vec![(None, code.clone())],
+        ValueData::Object(obj) if obj.contains_key("coding") => obj
+            .get("coding")
+            .into_iter()
+            .flat_map(|codings| codings.iter())
+            .filter_map(|coding| match coding.materialize().data() {
+                ValueData::Object(coding) => coding_parts(coding),
+                _ => None,
+            })
+            .collect(),
+        ValueData::Object(obj) => coding_parts(obj).into_iter().collect(),
+        _ => {
+            return Err(Error::TypeError(
+                "memberOf() expects a code, Coding or CodeableConcept".into(),
+            ))
+        }
+    };
+
+    let mut known = false;
+    for (system, code) in &codings {
+        match provider.member_of(system.as_deref(), code, value_set_url.as_ref())? {
+            Some(true) => return Ok(Collection::singleton(Value::boolean(true))),
+            Some(false) => known = true,
+            None => {}
+        }
+    }
+
+    if known {
+        Ok(Collection::singleton(Value::boolean(false)))
+    } else {
+        Ok(Collection::empty())
+    }
+}
+
+/// The `system` and `code` of a Coding; `None` without a code
+fn coding_parts(coding: &HashMap<Arc<str>, Collection>) -> Option<(Option<Arc<str>>, Arc<str>)> {
+    let string_field = |name: &str| {
+        coding
+            .get(name)
+            .and_then(|col| col.iter().next())
+            .and_then(|v| v.data().as_string())
+    };
+    Some((string_field("system"), string_field("code")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Context;
+    use crate::error::Result;
+    use crate::terminology::TerminologyProvider;
+    use crate::test_support::engine;
+    use crate::trace::TraceSink;
+    use crate::value::{Collection, Value, ValueData};
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct RecordingSink {
+        entries: Mutex<Vec<(String, Vec<String>)>>,
+    }
+
+    impl TraceSink for RecordingSink {
+        fn trace(&self, name: &str, values: &Collection) {
+            let values = values
+                .iter()
+                .map(|v| match v.data() {
+                    ValueData::String(s) => s.to_string(),
+                    _ => String::new(),
+                })
+                .collect();
+            self.entries
+                .lock()
+                .unwrap()
+                .push((name.to_string(), values));
+        }
+    }
+
+    #[test]
+    fn trace_with_projection_records_projected_values() {
+        let sink = Arc::new(RecordingSink::default());
+        let engine = engine().with_trace_sink(sink.clone());
+
+        let bundle = json!({
+            "resourceType": "Bundle",
+            "type": "collection",
+            "entry": [
+                {"resource": {"resourceType": "Patient", "id": "a"}},
+                {"resource": {"resourceType": "Patient", "id": "b"}},
+                {"resource": {"resourceType": "Observation", "id": "c"}}
+            ]
+        });
+        let ctx = Context::new(Value::from_json(bundle));
+
+        // The projection is only traced; the full resources continue down the pipeline
+        let result = engine
+            .evaluate_expr(
+                "entry.resource.trace('ids', id).where(resourceType = 'Patient').count()",
+                &ctx,
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.as_integer().unwrap(), 2);
+
+        let result = engine
+            .evaluate_expr("entry.resource.trace('all').count()", &ctx, None)
+            .unwrap();
+        assert_eq!(result.as_integer().unwrap(), 3);
+
+        let entries = sink.entries.lock().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, "ids");
+        assert_eq!(entries[0].1, vec!["a", "b", "c"]);
+        assert_eq!(entries[1].0, "all");
+        assert_eq!(entries[1].1.len(), 3);
+    }
+
+    /// Knows one ValueSet containing LOINC 8480-6
+    struct VitalsValueSet;
+
+    impl TerminologyProvider for VitalsValueSet {
+        fn member_of(
+            &self,
+            system: Option<&str>,
+            code: &str,
+            value_set_url: &str,
+        ) -> Result<Option<bool>> {
+            if value_set_url != "http://example.org/ValueSet/vitals" {
+                return Ok(None);
+            }
+            Ok(Some(
+                system.unwrap_or("http://loinc.org") == "http://loinc.org" && code == "8480-6",
+            ))
+        }
+    }
+
+    #[test]
+    fn member_of_checks_codes_against_the_terminology_provider() {
+        let with_provider = engine().with_terminology_provider(Arc::new(VitalsValueSet));
+        let observation = json!({
+            "resourceType": "Observation",
+            "code": {
+                "coding": [
+                    {"system": "http://snomed.info/sct", "code": "271649006"},
+                    {"system": "http://loinc.org", "code": "8480-6"}
+                ]
+            }
+        });
+        let ctx = Context::new(Value::from_json(observation));
+        let eval = |expr: &str| with_provider.evaluate_expr(expr, &ctx, None).unwrap();
+        let vitals = "'http://example.org/ValueSet/vitals'";
+
+        // CodeableConcept: any coding may match
+        assert!(eval(&format!("code.memberOf({vitals})"))
+            .as_boolean()
+            .unwrap());
+        // Coding
+        assert!(!eval(&format!("code.coding.first().memberOf({vitals})"))
+            .as_boolean()
+            .unwrap());
+        // Plain code
+        assert!(eval(&format!("code.coding.last().code.memberOf({vitals})"))
+            .as_boolean()
+            .unwrap());
+        // Unknown ValueSet and multiple inputs are empty
+        assert!(eval("code.memberOf('http://example.org/ValueSet/unknown')").is_empty());
+        assert!(eval(&format!("code.coding.memberOf({vitals})")).is_empty());
+
+        // Without a provider the ValueSet cannot be resolved
+        let result = engine()
+            .evaluate_expr(&format!("code.memberOf({vitals})"), &ctx, None)
+            .unwrap();
+        assert!(result.is_empty());
+    }
+}
@@ -8,11 +8,12 @@ use crate::value::{Collection, Value, ValueData};
 use chrono::{Duration, Months};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum UnitKind {
+pub(crate) enum UnitKind {
     Days,
     Weeks,
     Months,
@@ -178,7 +179,7 @@ fn units_equivalent(unit1: &str, unit2: &str) -> bool {
     false
 }
 
-fn normalize_unit(unit: &str) -> UnitKind {
+pub(crate) fn normalize_unit(unit: &str) -> UnitKind {
     let u = unit.trim().to_ascii_lowercase();
     match u.as_str() {
         "" | "1" => UnitKind::Dimensionless,
@@ -912,15 +913,22 @@ fn multiply(left: Collection, right: Collection) -> Result<Collection> {
                 return Ok(Collection::empty());
             }
 
+            // A dimensionless side scales the other by its UCUM factor (50 '%' * 2 'mg' = 1 'mg')
             if l_unit.dimensions == ferrum_ucum::DimensionVector::ZERO {
+                let Ok(lv) = ferrum_ucum::convert_decimal(*lv, lu_eff, "1") else {
+                    return Ok(Collection::empty());
+                };
                 return Ok(Collection::singleton(Value::quantity(
-                    *lv * *rv,
+                    lv * *rv,
                     Arc::from(ru_eff),
                 )));
             }
             if r_unit.dimensions == ferrum_ucum::DimensionVector::ZERO {
+                let Ok(rv) = ferrum_ucum::convert_decimal(*rv, ru_eff, "1") else {
+                    return Ok(Collection::empty());
+                };
                 return Ok(Collection::singleton(Value::quantity(
-                    *lv * *rv,
+                    *lv * rv,
                     Arc::from(lu_eff),
                 )));
             }
@@ -972,6 +980,15 @@ fn multiply(left: Collection, right: Collection) -> Result<Collection> {
                 Arc::from(result_unit.as_str()),
             )))
         }
+        // A number scales the quantity and keeps its unit
+        (ValueData::Quantity { value, unit }, ValueData::Integer(n))
+        | (ValueData::Integer(n), ValueData::Quantity { value, unit }) => Ok(
+            Collection::singleton(Value::quantity(*value * Decimal::from(*n), unit.clone())),
+        ),
+        (ValueData::Quantity { value, unit }, ValueData::Decimal(d))
+        | (ValueData::Decimal(d), ValueData::Quantity { value, unit }) => Ok(
+            Collection::singleton(Value::quantity(*value * *d, unit.clone())),
+        ),
         _ => Err(Error::TypeError(
             "Multiplication requires numeric types".into(),
         )),
@@ -1788,12 +1805,62 @@ fn items_equal(left: &Value, right: &Value) -> Option<bool> {
                 Some(false)
             }
         }
-        // Complex types (objects) - recursive equivalence mapped to boolean
-        (ValueData::Object(_), ValueData::Object(_)) => Some(items_equivalent(left, right)),
+        // Complex types (objects) - property-wise equality
+        (ValueData::Object(l_obj), ValueData::Object(r_obj)) => objects_equal(l_obj, r_obj),
         _ => Some(false),
     }
 }
 
+/// Equality of complex values: same properties, each equal in order.
+fn objects_equal(
+    left: &HashMap<Arc<str>, Collection>,
+    right: &HashMap<Arc<str>, Collection>,
+) -> Option<bool> {
+    if left.len() != right.len() {
+        return Some(false);
+    }
+    let mut incomparable = false;
+    for (key, l_val) in left.iter() {
+        let Some(r_val) = right.get(key) else {
+            return Some(false);
+        };
+        if l_val.len() != r_val.len() {
+            return Some(false);
+        }
+        for (l_item, r_item) in l_val.iter().zip(r_val.iter()) {
+            match items_equal(l_item, r_item) {
+                Some(true) => {}
+                Some(false) => return Some(false),
+                None => incomparable = true,
+            }
+        }
+    }
+    if incomparable {
+        None
+    } else {
+        Some(true)
+    }
+}
+
+/// Properties of the FHIR `Coding` datatype.
+const CODING_PROPERTIES: &[&str] = &[
+    "id",
+    "extension",
+    "system",
+    "version",
+    "code",
+    "display",
+    "userSelected",
+];
+
+/// `system` and `code` of an object shaped like a `Coding`, if it is one.
+fn coding_identity(obj: &HashMap<Arc<str>, Collection>) -> Option<(&Collection, &Collection)> {
+    if !obj.keys().all(|k| CODING_PROPERTIES.contains(&k.as_ref())) {
+        return None;
+    }
+    Some((obj.get("system")?, obj.get("code")?))
+}
+
 /// Normalize whitespace in a string (collapse multiple spaces to single space, trim)
 fn normalize_whitespace(s: &str) -> String {
     s.split_whitespace().collect::<Vec<_>>().join(" ")
@@ -2321,6 +2388,19 @@ fn items_equivalent(left: &Value, right: &Value) -> bool {
         }
         // Complex types (objects) - recursive equivalence
         (ValueData::Object(l_obj), ValueData::Object(r_obj)) => {
+            // Codings are equivalent when they denote the same concept, regardless of
+            // display, version or userSelected.
+            if let (Some((l_system, l_code)), Some((r_system, r_code))) =
+                (coding_identity(l_obj), coding_identity(r_obj))
+            {
+                let same = |l: &Collection, r: &Collection| {
+                    l.len() == r.len()
+                        && l.iter()
+                            .zip(r.iter())
+                            .all(|(l, r)| items_equal(l, r) == Some(true))
+                };
+                return same(l_system, r_system) && same(l_code, r_code);
+            }
             if l_obj.len() != r_obj.len() {
                 return false;
             }
@@ -3056,8 +3136,6 @@ fn union(left: Collection, right: Collection) -> Result<Collection> {
 }
 
 fn membership_in(left: Collection, right: Collection) -> Result<Collection> {
-    use std::collections::HashSet;
-
     // If left is empty, return empty (empty in collection is empty)
     if left.is_empty() {
         return Ok(Collection::empty());
@@ -3076,11 +3154,11 @@ fn membership_in(left: Collection, right: Collection) -> Result<Collection> {
 
     let left_val = left.iter().next().unwrap();
 
-    // Build HashSet from right for O(1) lookup
-    let right_set: HashSet<&Value> = right.iter().collect();
-
-    // Check if left_val is in right collection
-    let found = right_set.contains(left_val);
+    // Membership uses `=` semantics (1 in (1.0 | 2), 1 'g' in (1000 'mg')); incomparable
+    // items simply don't match
+    let found = right
+        .iter()
+        .any(|item| items_equal(left_val, item) == Some(true));
 
     Ok(Collection::singleton(Value::boolean(found)))
 }
@@ -3094,33 +3172,25 @@ fn membership_contains(left: Collection, right: Collection) -> Result<Collection
 // String Operations
 // ============================================
 
+/// `&`: string concatenation where an empty operand counts as `''`, so unlike `+` the result
+/// is always a single string (`'a' & {}` is `'a'`, `{} & {}` is `''`).
 fn concatenate(left: Collection, right: Collection) -> Result<Collection> {
-    // Per FHIRPath spec: if either operand is empty, return empty
-    // But testConcatenate2 expects: '1' & {} = '1'
-    // This suggests empty should be treated as empty string for concatenation
-    // Actually, let's check the spec behavior: empty collection concatenation returns empty
-    // But the test expects '1' & {} = '1', which suggests {} is treated as empty string
-
-    // Handle empty collections as empty strings for concatenation
-    let left_str = if left.is_empty() {
-        Arc::from("")
-    } else {
-        left.as_string()?
-    };
-
-    let right_str = if right.is_empty() {
-        Arc::from("")
-    } else {
-        right.as_string()?
+    let operand = |collection: &Collection| -> Result<Arc<str>> {
+        if collection.is_empty() {
+            Ok(Arc::from(""))
+        } else {
+            collection.as_string()
+        }
     };
 
-    let result = format!("{}{}", left_str, right_str);
+    let result = format!("{}{}", operand(&left)?, operand(&right)?);
     Ok(Collection::singleton(Value::string(result)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::eval;
     use chrono::{NaiveDate, TimeZone, Utc};
 
     #[test]
@@ -3144,4 +3214,220 @@ mod tests {
             "date vs datetime with time precision should be incomparable"
         );
     }
+
+    #[test]
+    fn implies_follows_three_valued_truth_table() {
+        let operand = |value: Option<bool>| match value {
+            Some(b) => Collection::singleton(Value::boolean(b)),
+            None => Collection::empty(),
+        };
+        let cases = [
+            (Some(true), Some(true), Some(true)),
+            (Some(true), Some(false), Some(false)),
+            (Some(true), None, None),
+            (Some(false), Some(true), Some(true)),
+            (Some(false), Some(false), Some(true)),
+            (Some(false), None, Some(true)),
+            (None, Some(true), Some(true)),
+            (None, Some(false), None),
+            (None, None, None),
+        ];
+        for (left, right, expected) in cases {
+            let result =
+                execute_binary_op(HirBinaryOperator::Implies, operand(left), operand(right))
+                    .unwrap();
+            let actual = (!result.is_empty()).then(|| result.as_boolean().unwrap());
+            assert_eq!(actual, expected, "{left:?} implies {right:?}");
+        }
+
+        // A non-boolean singleton operand evaluates to true
+        let result = execute_binary_op(
+            HirBinaryOperator::Implies,
+            Collection::singleton(Value::string("male")),
+            operand(Some(false)),
+        )
+        .unwrap();
+        assert!(!result.as_boolean().unwrap());
+    }
+
+    fn coding(display: &str) -> Collection {
+        Collection::singleton(Value::from_json(serde_json::json!({
+            "system": "http://loinc.org",
+            "code": "8867-4",
+            "display": display
+        })))
+    }
+
+    #[test]
+    fn codings_differing_in_display_are_equivalent_but_not_equal() {
+        let equal = execute_binary_op(HirBinaryOperator::Eq, coding("Heart rate"), coding("Pulse"))
+            .unwrap();
+        assert!(!equal.is_empty());
+        assert!(!equal.as_boolean().unwrap());
+
+        let equivalent = execute_binary_op(
+            HirBinaryOperator::Equivalent,
+            coding("Heart rate"),
+            coding("Pulse"),
+        )
+        .unwrap();
+        assert!(equivalent.as_boolean().unwrap());
+
+        let other_code = Collection::singleton(Value::from_json(serde_json::json!({
+            "system": "http://loinc.org",
+            "code": "8310-5",
+            "display": "Heart rate"
+        })));
+        let equivalent = execute_binary_op(
+            HirBinaryOperator::Equivalent,
+            coding("Heart rate"),
+            other_code,
+        )
+        .unwrap();
+        assert!(!equivalent.as_boolean().unwrap());
+    }
+
+    fn quantities(items: &[(i64, &str)]) -> Collection {
+        let mut collection = Collection::empty();
+        for (value, unit) in items {
+            collection.push(Value::quantity(Decimal::from(*value), Arc::from(*unit)));
+        }
+        collection
+    }
+
+    fn is_equivalent(left: Collection, right: Collection) -> bool {
+        execute_binary_op(HirBinaryOperator::Equivalent, left, right)
+            .unwrap()
+            .as_boolean()
+            .unwrap()
+    }
+
+    #[test]
+    fn single_quantities_with_equivalent_units_are_equivalent() {
+        assert!(is_equivalent(
+            quantities(&[(1, "g")]),
+            quantities(&[(1000, "mg")])
+        ));
+        assert!(!is_equivalent(
+            quantities(&[(1, "g")]),
+            quantities(&[(100, "mg")])
+        ));
+        assert!(!is_equivalent(
+            quantities(&[(1, "g")]),
+            quantities(&[(1, "m")])
+        ));
+    }
+
+    fn membership(op: HirBinaryOperator, left: Collection, right: Collection) -> Collection {
+        execute_binary_op(op, left, right).unwrap()
+    }
+
+    #[test]
+    fn concatenation_treats_empty_as_empty_string() {
+        let concat = |left: Collection, right: Collection| {
+            execute_binary_op(HirBinaryOperator::Concat, left, right)
+                .unwrap()
+                .as_string()
+                .unwrap()
+        };
+        let string = |s: &str| Collection::singleton(Value::string(s));
+
+        assert_eq!(&*concat(string("a"), Collection::empty()), "a");
+        assert_eq!(&*concat(Collection::empty(), string("b")), "b");
+        assert_eq!(&*concat(Collection::empty(), Collection::empty()), "");
+        assert_eq!(&*concat(string("a"), string("b")), "ab");
+
+        // `+` propagates empty instead
+        let sum = execute_binary_op(HirBinaryOperator::Add, string("a"), Collection::empty());
+        assert!(sum.unwrap().is_empty());
+    }
+
+    #[test]
+    fn membership_handles_empty_operands() {
+        let numbers = || {
+            let mut collection = Collection::empty();
+            collection.push(Value::integer(1));
+            collection.push(Value::integer(2));
+            collection
+        };
+
+        // {} in (1 | 2) and (1 | 2) contains {} are empty
+        assert!(membership(HirBinaryOperator::In, Collection::empty(), numbers()).is_empty());
+        assert!(membership(HirBinaryOperator::Contains, numbers(), Collection::empty()).is_empty());
+
+        // 1 in {} and {} contains 1 are false
+        let one = || Collection::singleton(Value::integer(1));
+        let result = membership(HirBinaryOperator::In, one(), Collection::empty());
+        assert!(!result.as_boolean().unwrap());
+        let result = membership(HirBinaryOperator::Contains, Collection::empty(), one());
+        assert!(!result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn membership_uses_equality_semantics() {
+        let mut numbers = Collection::empty();
+        numbers.push(Value::integer(1));
+        numbers.push(Value::decimal(Decimal::new(25, 1)));
+
+        let result = membership(
+            HirBinaryOperator::In,
+            Collection::singleton(Value::decimal(Decimal::new(10, 1))),
+            numbers.clone(),
+        );
+        assert!(result.as_boolean().unwrap());
+        let result = membership(
+            HirBinaryOperator::Contains,
+            numbers,
+            Collection::singleton(Value::integer(3)),
+        );
+        assert!(!result.as_boolean().unwrap());
+
+        let result = membership(
+            HirBinaryOperator::In,
+            quantities(&[(1, "g")]),
+            quantities(&[(5, "m"), (1000, "mg")]),
+        );
+        assert!(result.as_boolean().unwrap());
+        let result = membership(
+            HirBinaryOperator::Contains,
+            quantities(&[(5, "m"), (1000, "mg")]),
+            quantities(&[(2, "g")]),
+        );
+        assert!(!result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn numbers_scale_quantities_and_keep_the_unit() {
+        for expr in [
+            "5 'mg' * 2 = 10 'mg'",
+            "2 * 5 'mg' = 10 'mg'",
+            "5 'mg' * 0.5 = 2.5 'mg'",
+            r"(100 '%' * 0.5).toString() = '50.0 \'%\''",
+        ] {
+            assert!(eval(expr).as_boolean().unwrap(), "{expr}");
+        }
+    }
+
+    #[test]
+    fn percent_multiplies_by_its_ucum_factor() {
+        for expr in ["50 '%' * 2 'mg' = 1 'mg'", "50 '%' * 50 '%' = 25 '%'"] {
+            assert!(eval(expr).as_boolean().unwrap(), "{expr}");
+        }
+    }
+
+    #[test]
+    fn quantity_collections_match_across_units_in_any_order() {
+        assert!(is_equivalent(
+            quantities(&[(1, "g"), (2, "kg"), (3, "m")]),
+            quantities(&[(300, "cm"), (1000, "mg"), (2000, "g")])
+        ));
+        assert!(!is_equivalent(
+            quantities(&[(1, "g"), (2, "kg")]),
+            quantities(&[(1000, "mg"), (1000, "mg")])
+        ));
+        assert!(!is_equivalent(
+            quantities(&[(1, "g"), (2, "kg")]),
+            quantities(&[(1000, "mg")])
+        ));
+    }
 }
@@ -1,5 +1,6 @@
 use serde_json::json;
-use ferrum_fhirpath::{Context, Engine, Value};
+use ferrum_fhirpath::{Context, Engine, Result, TerminologyProvider, Value};
+use std::sync::{Arc, Mutex};
 
 #[path = "../test_support/mod.rs"]
 mod test_support;
@@ -70,3 +71,84 @@ fn resolves_root_resource_and_profile() {
         &Value::string("http://example.org/StructureDefinition/test")
     );
 }
+
+/// Records the codings `memberOf()` asks about; knows a single LOINC ValueSet
+#[derive(Default)]
+struct RecordingTerminology {
+    requests: Mutex<Vec<(Option<String>, String, String)>>,
+}
+
+impl TerminologyProvider for RecordingTerminology {
+    fn member_of(
+        &self,
+        system: Option<&str>,
+        code: &str,
+        value_set_url: &str,
+    ) -> Result<Option<bool>> {
+        self.requests.lock().unwrap().push((
+            system.map(str::to_string),
+            code.to_string(),
+            value_set_url.to_string(),
+        ));
+        if value_set_url != "http://example.org/ValueSet/blood-pressure" {
+            return Ok(None);
+        }
+        Ok(Some(system == Some("http://loinc.org") && code == "8480-6"))
+    }
+}
+
+#[test]
+fn terminology_constants_feed_member_of() {
+    let observation = Value::from_json(json!({
+        "resourceType": "Observation",
+        "status": "final",
+        "code": {
+            "coding": [
+                {"system": "http://snomed.info/sct", "code": "271649006"},
+                {"system": "http://loinc.org", "code": "8480-6"}
+            ]
+        }
+    }));
+    let ctx = Context::new(observation);
+    let terminology = Arc::new(RecordingTerminology::default());
+    let engine = Engine::new(test_support::context_r5().clone(), None)
+        .with_terminology_provider(terminology.clone());
+
+    let member = engine
+        .evaluate_expr(
+            "code.coding.where(system = %loinc).single()\
+                .memberOf('http://example.org/ValueSet/blood-pressure')",
+            &ctx,
+            None,
+        )
+        .expect("evaluation failed");
+    assert!(member.as_boolean().unwrap());
+
+    let not_member = engine
+        .evaluate_expr(
+            "code.coding.where(system = %sct).single()\
+                .memberOf('http://example.org/ValueSet/blood-pressure')",
+            &ctx,
+            None,
+        )
+        .expect("evaluation failed");
+    assert!(!not_member.as_boolean().unwrap());
+
+    // The provider saw the Codings selected through %loinc and %sct
+    let requests = terminology.requests.lock().unwrap();
+    assert_eq!(
+        requests.as_slice(),
+        &[
+            (
+                Some("http://loinc.org".to_string()),
+                "8480-6".to_string(),
+                "http://example.org/ValueSet/blood-pressure".to_string()
+            ),
+            (
+                Some("http://snomed.info/sct".to_string()),
+                "271649006".to_string(),
+                "http://example.org/ValueSet/blood-pressure".to_string()
+            ),
+        ]
+    );
+}
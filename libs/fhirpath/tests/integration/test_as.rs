@@ -169,3 +169,58 @@ fn test_ext1_choice_type_navigation() {
         "ext-1 should pass for extension with value"
     );
 }
+
+#[test]
+fn test_polymorphic_navigation_without_type_suffix() {
+    use serde_json::json;
+    use ferrum_fhirpath::{Context, Value};
+
+    let engine = test_support::engine_r5();
+
+    // value → valueQuantity
+    let ctx = Context::new(Value::from_json(json!({
+        "resourceType": "Observation",
+        "status": "final",
+        "valueQuantity": {"value": 185, "unit": "lbs"},
+        "component": [
+            {"code": {"text": "systolic"}, "valueQuantity": {"value": 120, "unit": "mmHg"}},
+            {"code": {"text": "note"}, "valueString": "ok"}
+        ]
+    })));
+    let result = engine.evaluate_expr("Observation.value", &ctx, None).unwrap();
+    assert_eq!(result.len(), 1);
+    let result = engine
+        .evaluate_expr("Observation.value.unit", &ctx, None)
+        .unwrap();
+    assert_eq!(result.as_string().unwrap().as_ref(), "lbs");
+    let result = engine
+        .evaluate_expr("Observation.value is Quantity", &ctx, None)
+        .unwrap();
+    assert_eq!(result.as_boolean().ok(), Some(true));
+
+    // Choice elements of backbone elements
+    let result = engine
+        .evaluate_expr("Observation.component.value", &ctx, None)
+        .unwrap();
+    assert_eq!(result.len(), 2);
+
+    // No value[x] present → empty
+    let ctx = Context::new(Value::from_json(json!({
+        "resourceType": "Observation",
+        "status": "final",
+        "code": {"text": "x"}
+    })));
+    let result = engine.evaluate_expr("Observation.value", &ctx, None).unwrap();
+    assert!(result.is_empty());
+
+    // Only declared choice elements expand: Patient has birthDate but no birth[x]
+    let ctx = Context::new(Value::from_json(json!({
+        "resourceType": "Patient",
+        "birthDate": "1970-01-01",
+        "deceasedBoolean": false
+    })));
+    let result = engine.evaluate_expr("Patient.birth", &ctx, None).unwrap();
+    assert!(result.is_empty());
+    let result = engine.evaluate_expr("Patient.deceased", &ctx, None).unwrap();
+    assert_eq!(result.as_boolean().ok(), Some(false));
+}
@@ -0,0 +1,80 @@
+//! Evaluation limits: runaway expressions fail with a bounded error instead of hanging
+
+use ferrum_context::DefaultFhirContext;
+use ferrum_fhirpath::{Context, Engine, Error, EvalLimits, Value};
+use serde_json::json;
+use std::sync::Arc;
+
+fn engine() -> Engine {
+    Engine::new(Arc::new(DefaultFhirContext::from_packages(vec![])), None)
+}
+
+fn evaluate(expr: &str, ctx: &Context) -> ferrum_fhirpath::Result<usize> {
+    engine()
+        .evaluate_expr(expr, ctx, None)
+        .map(|result| result.len())
+}
+
+fn assert_limit_error(result: ferrum_fhirpath::Result<usize>, needle: &str) {
+    match result {
+        Err(Error::InvalidOperation(message)) => {
+            assert!(message.contains(needle), "unexpected message: {message}")
+        }
+        other => panic!("expected an InvalidOperation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn unbounded_repeat_hits_iteration_limit() {
+    let ctx = Context::new(Value::empty()).with_limits(EvalLimits {
+        max_repeat_iterations: 50,
+        ..EvalLimits::default()
+    });
+
+    // Every projection yields a new integer, so only the limit stops it
+    assert_limit_error(evaluate("1.repeat($this + 1)", &ctx), "repeat()");
+}
+
+#[test]
+fn large_collections_hit_size_limit() {
+    let ctx = Context::new(Value::empty()).with_limits(EvalLimits {
+        max_collection_size: 4,
+        ..EvalLimits::default()
+    });
+
+    assert_eq!(evaluate("1 | 2 | 3 | 4", &ctx).unwrap(), 4);
+    assert_limit_error(evaluate("1 | 2 | 3 | 4 | 5", &ctx), "maximum size");
+}
+
+#[test]
+fn deep_descendants_hit_size_limit() {
+    let mut nested = json!({ "value": 0 });
+    for depth in 1..100 {
+        nested = json!({ "value": depth, "child": nested });
+    }
+    let ctx = Context::new(Value::from_json(nested)).with_limits(EvalLimits {
+        max_collection_size: 20,
+        ..EvalLimits::default()
+    });
+
+    assert_limit_error(evaluate("descendants()", &ctx), "descendants()");
+}
+
+#[test]
+fn nested_iteration_shares_step_budget() {
+    let limits = EvalLimits {
+        max_steps: 1_000,
+        ..EvalLimits::default()
+    };
+    let ctx = Context::new(Value::empty()).with_limits(limits);
+
+    // Each sub-expression stays small; together they exceed the budget
+    let expr = "(1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10).select( \
+                (1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10).select( \
+                (1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10).where($this > 5)))";
+    assert_limit_error(evaluate(expr, &ctx), "steps");
+
+    // The same expression completes within the default budget
+    let ctx = Context::new(Value::empty());
+    assert_eq!(evaluate(expr, &ctx).unwrap(), 500);
+}
@@ -444,6 +444,63 @@ fn test_nested_complex_type() {
     );
 }
 
+#[test]
+fn test_descendants_collect_nested_extensions() {
+    // Profile validation enumerates every extension in a resource, including nested ones
+    let engine = test_support::engine_r5();
+
+    let patient_json = json!({
+        "resourceType": "Patient",
+        "id": "example",
+        "extension": [{
+            "url": "http://example.org/fhir/StructureDefinition/nationality",
+            "extension": [
+                {"url": "code", "valueCodeableConcept": {"text": "Dutch"}},
+                {"url": "period", "valuePeriod": {"start": "2000-01-01"}}
+            ]
+        }],
+        "name": [{
+            "family": "Smith",
+            "extension": [{
+                "url": "http://example.org/fhir/StructureDefinition/own-name",
+                "valueString": "Smith"
+            }]
+        }],
+        // Attachment carries url and extension too, but is not an Extension itself
+        "photo": [{
+            "contentType": "image/png",
+            "url": "http://example.org/photo.png",
+            "extension": [{
+                "url": "http://example.org/fhir/StructureDefinition/photo-source",
+                "valueCode": "camera"
+            }]
+        }]
+    });
+    let patient = Value::from_json(patient_json);
+    let ctx = Context::new(patient);
+
+    let result = engine
+        .evaluate_expr(
+            "Patient.descendants().ofType(Extension).count()",
+            &ctx,
+            None,
+        )
+        .unwrap();
+    assert_eq!(result.as_integer().unwrap(), 5);
+
+    let result = engine
+        .evaluate_expr(
+            "Patient.descendants().ofType(Extension).where(url = 'period').exists()",
+            &ctx,
+            None,
+        )
+        .unwrap();
+    assert!(
+        result.as_boolean().unwrap(),
+        "Nested extension should be reachable"
+    );
+}
+
 #[test]
 fn test_quantity_specialization_age() {
     // Age inherits from Quantity
@@ -2,8 +2,9 @@
 
 use std::sync::Arc;
 use ferrum_fhirpath::context::Context;
-use ferrum_fhirpath::value::Value;
+use ferrum_fhirpath::value::{Collection, Value};
 use ferrum_fhirpath::vm::{Opcode, Plan, Vm};
+use ferrum_fhirpath::CompileOptions;
 
 mod test_support;
 
@@ -261,6 +262,71 @@ fn test_vm_navigation() {
     assert_eq!(result.as_string().unwrap().as_ref(), "John");
 }
 
+#[test]
+fn test_vm_navigation_flattens_nested_repeats() {
+    use serde_json::json;
+
+    // Bundle.entry (2) -> resource.name (2 + 1) -> given (2 + 1 + 2)
+    let bundle = json!({
+        "resourceType": "Bundle",
+        "entry": [
+            {"resource": {"resourceType": "Patient", "name": [
+                {"given": ["Ann", "Beth"]},
+                {"given": ["Cat"]}
+            ]}},
+            {"resource": {"resourceType": "Patient", "name": [
+                {"given": ["Dan", "Eve"]}
+            ]}}
+        ]
+    });
+
+    let plan = Plan {
+        opcodes: vec![
+            Opcode::LoadThis,
+            Opcode::Navigate(0),
+            Opcode::Navigate(1),
+            Opcode::Navigate(2),
+            Opcode::Navigate(3),
+            Opcode::Return,
+        ],
+        max_stack_depth: 64,
+        constants: vec![],
+        segments: vec![
+            Arc::from("entry"),
+            Arc::from("resource"),
+            Arc::from("name"),
+            Arc::from("given"),
+        ],
+        type_specifiers: vec![],
+        functions: vec![],
+        subplans: vec![],
+        variables: vec![],
+    };
+
+    let engine = test_support::engine_r5();
+    let expected = ["Ann", "Beth", "Cat", "Dan", "Eve"];
+
+    // Lazy JSON and materialized objects take different navigation paths
+    let lazy = Value::from_json(bundle);
+    for resource in [lazy.clone(), lazy.materialize()] {
+        let ctx = Context::new(resource);
+        let mut vm = Vm::new(&ctx, engine);
+        let result = vm.execute(&plan).unwrap();
+
+        assert_eq!(result.len(), expected.len());
+        let given: Vec<String> = result
+            .iter()
+            .map(|v| {
+                Collection::singleton(v.clone())
+                    .as_string()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(given, expected);
+    }
+}
+
 #[test]
 fn test_vm_index() {
     // Test Index with a multi-item collection by using Union to combine singletons
@@ -343,3 +409,31 @@ fn test_vm_binary_op_empty_collection() {
     // Binary op with empty collection should return empty
     assert_eq!(result.len(), 0);
 }
+
+#[test]
+fn test_vm_constant_folding_shrinks_plan() {
+    let engine = test_support::engine_r5();
+    let expr = "name.given.where(1 + 1 = 2).count() + (10 * 6 - 2) / 2";
+    let compile = |optimize| {
+        engine
+            .compile_with_options(
+                expr,
+                CompileOptions {
+                    optimize,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+    };
+
+    let unfolded = compile(false);
+    let folded = compile(true);
+
+    assert_eq!(unfolded.opcodes.len(), 14);
+    // The trivial where() is dropped and the arithmetic collapses into a single constant
+    assert_eq!(folded.opcodes.len(), 7);
+    assert!(!folded
+        .opcodes
+        .iter()
+        .any(|op| matches!(op, Opcode::Where(_))));
+}